@@ -10,6 +10,7 @@ bitflags! {
         const CALC_RESIST_RESIDUAL = CALC_RESIST_RESIDUAL;
         const CALC_REACT_RESIDUAL = CALC_REACT_RESIDUAL;
         const CALC_NOISE = CALC_NOISE;
+        const CALC_AC_STIM = CALC_AC_STIM;
         const ANALYSIS_DC = ANALYSIS_DC;
         const ANALYSIS_AC = ANALYSIS_AC;
         const ANALYSIS_STATIC = ANALYSIS_STATIC;
@@ -41,7 +42,7 @@ private_flags! {
     // pub(super) const NOISE_OP = Self::OP.0.bits | ANALYSIS_NOISE;
     // pub(super) const LARGE_SIGNAL_IC_OP = Self::OP.0.bits | ANALYSIS_TRAN | ANALYSIS_IC;
 
-    pub(super) const AC = CALC_RESIST_JACOBIAN | CALC_REACT_JACOBIAN | ANALYSIS_AC;
+    pub(super) const AC = CALC_RESIST_JACOBIAN | CALC_REACT_JACOBIAN | CALC_AC_STIM | ANALYSIS_AC;
     // pub(super) const NOISE = CALC_RESIST_JACOBIAN | CALC_REACT_JACOBIAN | CALC_NOISE | ANALYSIS_NOISE;
     // pub(super) const LARGE_SIGNAL = ANALYSIS_TRAN
     //     | CALC_RESIST_JACOBIAN
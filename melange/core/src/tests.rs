@@ -137,3 +137,42 @@ fn veriloga() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn ac_stim() -> Result<()> {
+    let mut arena = Arena::new();
+    let mut circ = Circuit::new("test_circ".to_owned(), &mut arena);
+
+    let gnd = circ.lookup_node("ground").expect("ground node");
+    let node_x = circ.node("X".to_owned());
+
+    let path = Utf8PathBuf::from_path_buf(project_root())
+        .expect("only utf8 paths are supported")
+        .join("openvaf")
+        .join("test_data")
+        .join("osdi")
+        .join("ac_stim.va");
+    circ.load_veriloga_file(path, &veriloga::Opts::default())?;
+
+    let (_, src1) =
+        circ.new_device_instance_by_name("src1".to_owned(), "ac_stim_src", vec![node_x, gnd])?;
+    circ.set_model_param(src1, "mag", 1e-3.into())?;
+    circ.set_model_param(src1, "phase", std::f64::consts::FRAC_PI_2.into())?;
+
+    let (res1, _) =
+        circ.new_device_instance_by_name("res1".to_owned(), "resistor", vec![node_x, gnd])?;
+    circ.set_instance_param(res1, "r", 1e3.into())?;
+
+    let mut ctx = ExprEvalCtx::new(&arena);
+    ctx.set_param(CircuitParam::TEMPERATURE, 300.0.into());
+    let mut sim = circ.prepare_simulation(ctx.borrow(), &arena, SimConfig::default())?;
+
+    // the stimulus is only active during the AC analysis
+    let solution = sim.dc_op()?;
+    assert_approx_eq!(solution[node_x], 0.0);
+    // the source drives its current out of X through the resistor
+    let solution = sim.ac()?;
+    assert_approx_eq_cmplx!(solution[node_x], 0.0 - j 1.0);
+
+    Ok(())
+}
@@ -14,7 +14,7 @@ use openvaf::{
 };
 pub(crate) use osdi_0_4::{
     ANALYSIS_AC, ANALYSIS_DC, ANALYSIS_IC, ANALYSIS_NOISE, ANALYSIS_STATIC, ANALYSIS_TRAN,
-    CALC_NOISE, CALC_REACT_JACOBIAN, CALC_REACT_RESIDUAL, CALC_RESIST_JACOBIAN,
+    CALC_AC_STIM, CALC_NOISE, CALC_REACT_JACOBIAN, CALC_REACT_RESIDUAL, CALC_RESIST_JACOBIAN,
     CALC_RESIST_RESIDUAL,
 };
 
@@ -78,6 +78,9 @@ pub fn compile_va(path: &Utf8Path, opts: &Opts) -> Result<Vec<Box<dyn DeviceImpl
         dump_unopt_mir: false,
         dump_ir: false,
         dump_unopt_ir: false,
+        sensitivities: Vec::new(),
+        derivative_order: 1,
+        frozen_params: Vec::new(),
     };
 
     let res = openvaf::compile(&openvaf_opts);
@@ -7,6 +7,7 @@ use std::rc::Rc;
 use std::{ptr, slice};
 
 use anyhow::{bail, Result};
+use num_complex::Complex64;
 use stdx::format_to;
 use stdx::iter::zip;
 use typed_index_collections::TiSlice;
@@ -47,6 +48,14 @@ impl OsdiDescriptor {
         unsafe { slice::from_raw_parts(self.jacobian_entries, self.num_jacobian_entries as usize) }
     }
 
+    /// The index of the `ac_stim` excitation of the AC analysis `analysis`.
+    fn ac_stim(&self, analysis: &str) -> Option<u32> {
+        // # SAFETY: OsdiDescriptor can only be constructed from FFI and is assumed to contain
+        // valid data
+        let names = unsafe { slice::from_raw_parts(self.ac_stim_names, self.num_ac_stim as usize) };
+        names.iter().position(|&name| unsafe { osdi_str(name) } == analysis).map(|i| i as u32)
+    }
+
     fn check_init_result(&self, res: OsdiInitInfo) -> Result<()> {
         if (res.flags & EVAL_RET_FLAG_FATAL) != 0 {
             bail!("Verilog-A $fatal was called")
@@ -519,6 +528,25 @@ impl InstanceImpl for OsdiInstance {
         self.descriptor.load_residual_resist(self.data, self.model_data, residual.as_mut_ptr())
     }
 
+    fn load_ac_residual(&self, _dc_solve: &TiSlice<Node, f64>, rhs: &mut TiSlice<Node, Complex64>) {
+        // melange only runs the default AC analysis
+        if let Some(analysis) = self.descriptor.ac_stim("ac") {
+            let mut re = vec![0f64; rhs.len()];
+            let mut im = vec![0f64; rhs.len()];
+            self.descriptor.load_ac_stim(
+                self.data,
+                self.model_data,
+                analysis,
+                re.as_mut_ptr(),
+                im.as_mut_ptr(),
+            );
+            // the excitation is loaded like the residual so it changes sign on the rhs
+            for (dst, (re, im)) in zip(rhs.iter_mut(), zip(re, im)) {
+                *dst -= Complex64::new(re, im);
+            }
+        }
+    }
+
     fn load_lead_current_resist(&self, _dc_solve: &TiSlice<Node, f64>, dst: &mut [f64]) {
         for (node, dst) in zip(self.descriptor.terminals(), dst) {
             unsafe {
@@ -529,6 +529,33 @@ impl Diagnostic for SyntaxError {
                     message: "unsupported net type".to_owned(),
                 }])
             }
+            SyntaxError::IllegalEventSeparator { range, .. } => {
+                let FileSpan { range, file: file_id } = parse.to_file_span(range, &sm);
+                Report::error().with_labels(vec![Label {
+                    style: LabelStyle::Primary,
+                    file_id,
+                    range: range.into(),
+                    message: "expected 'or'".to_owned(),
+                }])
+            }
+            SyntaxError::IllegalEvent { range } => {
+                let FileSpan { range, file: file_id } = parse.to_file_span(range, &sm);
+                Report::error().with_labels(vec![Label {
+                    style: LabelStyle::Primary,
+                    file_id,
+                    range: range.into(),
+                    message: "expected cross(..), above(..) or timer(..)".to_owned(),
+                }])
+            }
+            SyntaxError::EventArgCntMismatch { max, range, .. } => {
+                let FileSpan { range, file: file_id } = parse.to_file_span(range, &sm);
+                Report::error().with_labels(vec![Label {
+                    style: LabelStyle::Primary,
+                    file_id,
+                    range: range.into(),
+                    message: format!("expected 1 to {max} arguments"),
+                }])
+            }
             SyntaxError::RangeConstraintForNonNumericParameter { range, ty, .. } => {
                 let (file_id, [range, ty]) = text_ranges_to_unified_spans(&sm, &parse, [range, ty]);
                 Report::error().with_labels(vec![
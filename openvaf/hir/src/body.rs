@@ -2,8 +2,8 @@ use std::sync::Arc;
 
 use hir_def::db::HirDefDB;
 pub use hir_def::expr::Event;
-use hir_def::DefWithBodyId;
 pub use hir_def::{/*expr::CaseCond,*/ BuiltIn, Case, ExprId, Literal, ParamSysFun, StmtId, Type,};
+use hir_def::{DefWithBodyId, FunctionId, InstanceId, Lookup, NodeId, ParamId};
 use hir_ty::db::HirTyDB;
 use hir_ty::inference;
 use hir_ty::types::{Signature, Ty};
@@ -18,14 +18,49 @@ use crate::{
 pub struct Body {
     body: Arc<hir_def::body::Body>,
     infere: Arc<inference::InferenceResult>,
+    inst: Option<Arc<InstanceCtx>>,
 }
 impl Body {
     pub(crate) fn new(id: DefWithBodyId, db: &CompilationDB) -> Body {
-        Body { body: db.body(id), infere: db.inference_result(id) }
+        Body::new_in(id, None, db)
+    }
+
+    /// Creates a body that is evaluated within the module instance `inst`.
+    /// All items referenced by the body belong to that instance.
+    pub(crate) fn new_in(id: DefWithBodyId, inst: Option<InstanceId>, db: &CompilationDB) -> Body {
+        let inst = inst.map(|id| Arc::new(InstanceCtx::new(id, db)));
+        Body { body: db.body(id), infere: db.inference_result(id), inst }
     }
 
     pub fn borrow(&self) -> BodyRef<'_> {
-        BodyRef { body: &self.body, infere: &self.infere }
+        BodyRef { body: &self.body, infere: &self.infere, inst: self.inst.as_deref() }
+    }
+}
+
+/// The module instance a body is evaluated in
+#[derive(Debug)]
+struct InstanceCtx {
+    id: InstanceId,
+    /// The ports of the instantiated module and the node they are connected to (if any)
+    ports: Box<[(NodeId, Option<Node>)]>,
+}
+
+impl InstanceCtx {
+    fn new(id: InstanceId, db: &CompilationDB) -> InstanceCtx {
+        let loc = id.lookup(db);
+        let ports = match db.module_inst_data(loc.inst).module {
+            Some(module) => {
+                let connections = db.inference_result(loc.inst.into());
+                db.module_data(module)
+                    .ports
+                    .iter()
+                    .zip(&connections.port_connections)
+                    .map(|(&port, &node)| (port, node.map(|node| Node::new(db, node, loc.parent))))
+                    .collect()
+            }
+            None => Box::default(),
+        };
+        InstanceCtx { id, ports }
     }
 }
 
@@ -33,9 +68,53 @@ impl Body {
 pub struct BodyRef<'a> {
     body: &'a hir_def::body::Body,
     infere: &'a inference::InferenceResult,
+    inst: Option<&'a InstanceCtx>,
 }
 
 impl<'a> BodyRef<'a> {
+    fn inst(&self) -> Option<InstanceId> {
+        self.inst.map(|inst| inst.id)
+    }
+
+    fn node(&self, id: NodeId) -> Node {
+        if let Some(inst) = self.inst {
+            if let Some(&(_, Some(node))) = inst.ports.iter().find(|(port, _)| *port == id) {
+                return node;
+            }
+        }
+        Node { id, inst: self.inst() }
+    }
+
+    fn function(&self, id: FunctionId) -> Function {
+        Function { id, inst: self.inst() }
+    }
+
+    fn branch_write(&self, branch: inference::BranchWrite) -> BranchWrite {
+        match branch {
+            inference::BranchWrite::Named(id) => {
+                BranchWrite::Named(Branch { id, inst: self.inst() })
+            }
+            inference::BranchWrite::Unnamed { hi, lo } => {
+                BranchWrite::Unnamed { hi: self.node(hi), lo: lo.map(|lo| self.node(lo)) }
+            }
+        }
+    }
+
+    /// Returns whether the port `expr` is connected if this body is evaluated
+    /// within a module instance (where this is known at compile time).
+    pub fn port_connected(&self, expr: ExprId) -> Option<bool> {
+        let id = self.infere.expr_types[expr].unwrap_node();
+        let inst = self.inst?;
+        let &(_, node) = inst.ports.iter().find(|(port, _)| *port == id)?;
+        Some(node.is_some())
+    }
+
+    /// The parameters overwritten by a module instance or paramset
+    /// (see [`crate::Instance::param_overrides`] and [`crate::Module::param_overrides`])
+    pub(crate) fn param_overrides(&self) -> &'a [(ParamId, ExprId)] {
+        &self.infere.param_overrides
+    }
+
     pub fn entry(&self) -> &'a [StmtId] {
         &self.body.entry_stmts
     }
@@ -56,12 +135,12 @@ impl<'a> BodyRef<'a> {
 
     fn resolve_path(&self, expr: ExprId) -> Ref {
         match self.infere.expr_types[expr] {
-            Ty::Var(_, id) => Ref::Variable(Variable { id }),
-            Ty::Param(_, id) => Ref::Parameter(Parameter { id }),
+            Ty::Var(_, id) => Ref::Variable(Variable { id, inst: self.inst() }),
+            Ty::Param(_, id) => Ref::Parameter(Parameter { id, inst: self.inst() }),
             Ty::FunctionVar { fun, arg: Some(arg), .. } => {
                 Ref::FunctionArg(FunctionArg { fun_id: fun, arg_id: arg })
             }
-            Ty::FunctionVar { fun, .. } => Ref::FunctionReturn(Function { id: fun }),
+            Ty::FunctionVar { fun, .. } => Ref::FunctionReturn(self.function(fun)),
             Ty::NatureAttr(_, id) => Ref::NatureAttr(NatureAttribute { id }),
 
             ref it => {
@@ -120,24 +199,30 @@ impl<'a> BodyRef<'a> {
         }
     }
 
+    /// Returns the values of an array of numeric literals (see
+    /// [`hir_def::body::Body::as_real_literal_array`]).
+    pub fn as_real_literal_array(&self, expr: ExprId) -> Option<Vec<f64>> {
+        self.body.as_real_literal_array(expr)
+    }
+
     pub fn into_node(&self, expr: ExprId) -> Node {
         let id = self.infere.expr_types[expr].unwrap_node();
-        Node { id }
+        self.node(id)
     }
 
     pub fn into_port_flow(&self, expr: ExprId) -> Node {
         let id = self.infere.expr_types[expr].unwrap_port_flow();
-        Node { id }
+        Node { id, inst: self.inst() }
     }
 
     pub fn into_parameter(&self, expr: ExprId) -> Parameter {
         let id = self.infere.expr_types[expr].unwrap_param();
-        Parameter { id }
+        Parameter { id, inst: self.inst() }
     }
 
     pub fn into_branch(&self, expr: ExprId) -> Branch {
         let id = self.infere.expr_types[expr].unwrap_branch();
-        Branch { id }
+        Branch { id, inst: self.inst() }
     }
 
     pub fn get_expr(&self, expr: ExprId) -> Expr<'a> {
@@ -151,7 +236,7 @@ impl<'a> BodyRef<'a> {
             hir_def::Expr::Call { ref args, .. } => {
                 let fun = match self.infere.resolved_calls[&expr] {
                     inference::ResolvedFun::User { func, limit } => {
-                        ResolvedFun::User { func: Function { id: func }, limit }
+                        ResolvedFun::User { func: self.function(func), limit }
                     }
                     inference::ResolvedFun::BuiltIn(builtin) => ResolvedFun::BuiltIn(builtin),
                     // this is a special case, the VAMS standard allows these parameters
@@ -167,6 +252,7 @@ impl<'a> BodyRef<'a> {
                 Expr::Call { fun, args }
             }
             hir_def::Expr::Array(ref args) => Expr::Array(args),
+            hir_def::Expr::Index { base, index } => Expr::Index { base, index },
             hir_def::Expr::Literal(ref literal) => Expr::Literal(literal),
             _ => panic!("invalid HIR: {:?}", self.body.exprs[expr]),
         }
@@ -189,11 +275,19 @@ impl<'a> BodyRef<'a> {
             }
             hir_def::Stmt::Assignment { val, .. } => {
                 let stmt = match self.infere.assignment_destination[&stmnt] {
-                    inference::AssignDst::Var(id) => {
-                        Stmt::Assignment { lhs: AssignmentLhs::Variable(Variable { id }), rhs: val }
-                    }
+                    inference::AssignDst::Var(id) => Stmt::Assignment {
+                        lhs: AssignmentLhs::Variable(Variable { id, inst: self.inst() }),
+                        rhs: val,
+                    },
+                    inference::AssignDst::VarElement { var, index } => Stmt::Assignment {
+                        lhs: AssignmentLhs::VariableElement {
+                            var: Variable { id: var, inst: self.inst() },
+                            index,
+                        },
+                        rhs: val,
+                    },
                     inference::AssignDst::FunVar { fun, arg: None } => Stmt::Assignment {
-                        lhs: AssignmentLhs::FunctionReturn(Function { id: fun }),
+                        lhs: AssignmentLhs::FunctionReturn(self.function(fun)),
                         rhs: val,
                     },
                     inference::AssignDst::FunVar { fun, arg: Some(arg) } => Stmt::Assignment {
@@ -202,12 +296,12 @@ impl<'a> BodyRef<'a> {
                     },
                     inference::AssignDst::Flow(branch) => Stmt::Contribute {
                         kind: ContributeKind::Flow,
-                        branch: branch.into(),
+                        branch: self.branch_write(branch),
                         rhs: val,
                     },
                     inference::AssignDst::Potential(branch) => Stmt::Contribute {
                         kind: ContributeKind::Potential,
-                        branch: branch.into(),
+                        branch: self.branch_write(branch),
                         rhs: val,
                     },
                 };
@@ -229,6 +323,11 @@ impl<'a> BodyRef<'a> {
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub enum AssignmentLhs {
     Variable(Variable),
+    /// The element `var[index]` of an array variable
+    VariableElement {
+        var: Variable,
+        index: ExprId,
+    },
     FunctionReturn(Function),
     FunctionArg(FunctionArg),
 }
@@ -265,11 +364,30 @@ impl Stmt<'_> {
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Expr<'a> {
     Read(Ref),
-    BinaryOp { lhs: ExprId, rhs: ExprId, op: BinaryOp },
-    UnaryOp { expr: ExprId, op: UnaryOp },
-    Select { cond: ExprId, then_val: ExprId, else_val: ExprId },
-    Call { fun: ResolvedFun, args: &'a [ExprId] },
+    BinaryOp {
+        lhs: ExprId,
+        rhs: ExprId,
+        op: BinaryOp,
+    },
+    UnaryOp {
+        expr: ExprId,
+        op: UnaryOp,
+    },
+    Select {
+        cond: ExprId,
+        then_val: ExprId,
+        else_val: ExprId,
+    },
+    Call {
+        fun: ResolvedFun,
+        args: &'a [ExprId],
+    },
     Array(&'a [ExprId]),
+    /// An element `base[index]` of an array
+    Index {
+        base: ExprId,
+        index: ExprId,
+    },
     Literal(&'a Literal),
 }
 impl Expr<'_> {
@@ -4,7 +4,7 @@ pub use basedb::{BaseDB, FileId};
 use hir_def::db::HirDefDB;
 use hir_def::nameres::diagnostics::DefDiagnosticWrapped;
 use hir_def::nameres::{DefMap, LocalScopeId, ScopeDefItem, ScopeOrigin};
-use hir_def::DefWithBodyId;
+use hir_def::{DefWithBodyId, Lookup};
 use hir_ty::diagnostics::InferenceDiagnosticWrapped;
 use hir_ty::validation::{
     self, BodyValidationDiagnostic, BodyValidationDiagnosticWrapped,
@@ -65,8 +65,19 @@ pub(crate) fn collect(db: &CompilationDB, root_file: FileId, sink: &mut impl Dia
             )
         }
 
+        if let ScopeOrigin::ParamSet(paramset) = def_map[*child].origin {
+            collect_body_diagnostcs(db, sink, paramset.into(), &parse, &sm, root_file, &ast_id_map);
+        }
+
         collect_scope(db, &def_map, &parse, &sm, &ast_id_map, root_file, sink, *child)
     }
+
+    // overloaded paramsets are not declared in the root scope
+    for &paramset in def_map.paramset_overloads.values().flatten() {
+        collect_body_diagnostcs(db, sink, paramset.into(), &parse, &sm, root_file, &ast_id_map);
+        let scope = paramset.lookup(db).scope.local_scope;
+        collect_scope(db, &def_map, &parse, &sm, &ast_id_map, root_file, sink, scope)
+    }
 }
 
 // FIXME bundle required syntax info into struct in BaseDB
@@ -9,6 +9,7 @@
 //! They are written in "ECS" style, with relatively little abstractions.
 //! Many types are not self-contained, and explicitly use local indexes, arenas, etc.
 
+use std::fmt;
 use std::sync::Arc;
 
 use basedb::diagnostics::sink::Buffer;
@@ -17,18 +18,17 @@ pub use basedb::diagnostics::DiagnosticSink;
 use basedb::{BaseDB, FileId};
 pub use hir_def::body::{ConstraintValue, ParamConstraint};
 use hir_def::db::HirDefDB;
-pub use hir_def::expr::CaseCond;
+pub use hir_def::expr::{CaseCond, Event, GlobalEvent, MonitoredEvent, MonitoredEventKind};
 pub use hir_def::nameres::diagnostics::PathResolveError;
 use hir_def::nameres::{DefMap, LocalScopeId, ScopeDefItem};
 use hir_def::{
-    AliasParamId, BlockId, BlockLoc, BranchId, DefWithBodyId, DisciplineId, FunctionId,
-    LocalFunctionArgId, Lookup, ModuleId, ModuleLoc, NatureAttrId, NatureId, NodeId, ParamId,
-    VarId,
+    AliasParamId, BlockId, BlockLoc, BranchId, DefWithBodyId, DisciplineId, FunctionId, InstanceId,
+    InstanceLoc, Intern, LocalFunctionArgId, Lookup, ModuleId, ModuleInstId, ModuleLoc,
+    NatureAttrId, NatureId, NodeId, ParamId, ParamSetId, VarId,
 };
-pub use hir_def::{BuiltIn, Case, Literal, ParamSysFun, Path, Type};
+pub use hir_def::{ArrayRange, BuiltIn, Case, Literal, ParamSysFun, Path, Type};
 pub use hir_ty::builtin;
 use hir_ty::db::HirTyDB as HirDatabase;
-use hir_ty::inference;
 pub use rec_declarations::RecDeclarations;
 use salsa::InternKey;
 use smol_str::SmolStr;
@@ -54,6 +54,7 @@ pub mod signatures {
         IDTMOD_NO_IC, IDT_IC, IDT_IC_ASSERT, IDT_IC_ASSERT_NATURE, IDT_IC_ASSERT_TOL, IDT_NO_IC,
         LIMIT_BUILTIN_FUNCTION, MAX_INT, MAX_REAL, NATURE_ACCESS_BRANCH, NATURE_ACCESS_NODES,
         NATURE_ACCESS_NODE_GND, NATURE_ACCESS_PORT_FLOW, SIMPARAM_DEFAULT, SIMPARAM_NO_DEFAULT,
+        SIMPROBE_DEFAULT, SIMPROBE_NO_DEFAULT,
     };
     pub use hir_ty::types::{BOOL_EQ, INT_EQ, INT_OP, REAL_EQ, REAL_OP, STR_EQ};
 }
@@ -97,10 +98,14 @@ impl CompilationUnit {
             .declarations
             .iter()
             .filter_map(|(_, def)| {
-                if let ScopeDefItem::ModuleId(id) = *def {
-                    Some(Module { id })
-                } else {
-                    None
+                match *def {
+                    ScopeDefItem::ModuleId(id) => Some(Module { id, paramset: None }),
+                    // every paramset is compiled as an additional model of its base module
+                    ScopeDefItem::ParamSetId(paramset) => db
+                        .param_set_data(paramset)
+                        .module
+                        .map(|id| Module { id, paramset: Some(paramset) }),
+                    _ => None,
                 }
             })
             .collect()
@@ -115,24 +120,98 @@ impl CompilationUnit {
     }
 }
 
+/// A module that is compiled to a model. Paramsets are represented by their base
+/// module: They share its body but expose the parameters of the paramset instead.
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Module {
     id: ModuleId,
+    paramset: Option<ParamSetId>,
 }
 
-stdx::impl_debug! {
-    match Module{
-        Module{ id } => "{id:?}";
+impl fmt::Debug for Module {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.paramset {
+            Some(paramset) => write!(f, "{:?}({:?})", paramset, self.id),
+            None => write!(f, "{:?}", self.id),
+        }
     }
 }
 
 impl Module {
     pub fn name(self, db: &CompilationDB) -> String {
-        db.module_data(self.id).name.to_string()
+        match self.paramset {
+            Some(paramset) => db.param_set_data(paramset).name.to_string(),
+            None => db.module_data(self.id).name.to_string(),
+        }
     }
 
     pub fn uuid(self, _db: &CompilationDB) -> u32 {
-        self.id.as_intern_id().as_u32()
+        match self.paramset {
+            // count down from the top to avoid collisions with modules
+            Some(paramset) => u32::MAX - paramset.as_intern_id().as_u32(),
+            None => self.id.as_intern_id().as_u32(),
+        }
+    }
+
+    /// All parameters (including local parameters) of the base module of a paramset
+    /// in declaration order. These parameters are fixed to the value assigned by the
+    /// paramset (or their default value). Empty for normal modules.
+    pub fn base_parameters(self, db: &CompilationDB) -> Vec<Parameter> {
+        if self.paramset.is_none() {
+            return Vec::new();
+        }
+        let scope = self.lookup(db).scope;
+        scope.def_map(db)[scope.local_scope]
+            .declarations
+            .values()
+            .filter_map(|def| match *def {
+                ScopeDefItem::ParamId(id) => Some(Parameter { id, inst: None }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The parameters of the base module overwritten by a paramset.
+    /// The returned expressions belong to the returned body.
+    pub fn param_overrides(self, db: &CompilationDB) -> Option<(Body, Vec<(Parameter, ExprId)>)> {
+        let body = Body::new(self.paramset?.into(), db);
+        let overrides = body
+            .borrow()
+            .param_overrides()
+            .iter()
+            .map(|&(id, expr)| (Parameter { id, inst: None }, expr))
+            .collect();
+        Some((body, overrides))
+    }
+
+    /// The paramsets with the same name as the paramset of this model (in declaration order).
+    /// They are compiled into this model and selected by the ranges of their parameters.
+    /// Empty for normal modules and paramsets that are not overloaded.
+    pub fn paramset_overloads(self, db: &CompilationDB) -> Vec<Module> {
+        let Some(paramset) = self.paramset else {
+            return Vec::new();
+        };
+        let def_map = db.def_map(paramset.lookup(db).scope.root_file);
+        def_map
+            .paramset_overloads
+            .get(&paramset)
+            .into_iter()
+            .flatten()
+            .map(|&overload| Module { id: self.id, paramset: Some(overload) })
+            .collect()
+    }
+
+    /// The parameters declared by a paramset. Empty for normal modules.
+    pub fn paramset_parameters(self, db: &CompilationDB) -> Vec<Parameter> {
+        match self.paramset {
+            Some(paramset) => db
+                .param_set_data(paramset)
+                .params
+                .iter()
+                .map(|&id| Parameter { id, inst: None })
+                .collect(),
+            None => Vec::new(),
+        }
     }
 
     fn lookup(self, db: &CompilationDB) -> ModuleLoc {
@@ -150,15 +229,37 @@ impl Module {
     }
 
     pub fn internal_nodes(self, db: &CompilationDB) -> Vec<Node> {
-        db.module_data(self.id).internal_nodes.iter().map(|&id| Node { id }).collect()
+        db.module_data(self.id).internal_nodes.iter().map(|&id| Node { id, inst: None }).collect()
     }
 
     pub fn ports(self, db: &CompilationDB) -> Vec<Node> {
-        db.module_data(self.id).ports.iter().map(|&id| Node { id }).collect()
+        db.module_data(self.id).ports.iter().map(|&id| Node { id, inst: None }).collect()
+    }
+
+    /// The module instances declared within this module
+    pub fn instances(self, db: &CompilationDB) -> Vec<Instance> {
+        Instance::children(db, self.id, None)
+    }
+
+    /// All (nested) module instances of this module in depth-first order.
+    /// Each instance is always listed before the instances nested within it.
+    pub fn all_instances(self, db: &CompilationDB) -> Vec<Instance> {
+        fn collect(db: &CompilationDB, instances: Vec<Instance>, dst: &mut Vec<Instance>) {
+            for inst in instances {
+                dst.push(inst);
+                collect(db, inst.instances(db), dst);
+            }
+        }
+        let mut res = Vec::new();
+        collect(db, self.instances(db), &mut res);
+        res
     }
 
     pub fn rec_declarations(self, db: &CompilationDB) -> RecDeclarations<'_> {
-        RecDeclarations::new(Scope::Module(self), db)
+        match self.paramset {
+            Some(paramset) => RecDeclarations::new_paramset(self, paramset, db),
+            None => RecDeclarations::new(Scope::Module(self), db),
+        }
     }
 
     pub fn analog_initial_block(&self, db: &CompilationDB) -> Body {
@@ -176,7 +277,142 @@ impl Module {
         path: &Path,
     ) -> Result<Variable, PathResolveError> {
         let scope = self.id.lookup(db).scope;
-        scope.resolve_item_path(db, path).map(|id| Variable { id })
+        scope.resolve_item_path(db, path).map(|id| Variable { id, inst: None })
+    }
+}
+
+/// A (possibly nested) instance of a module that is flattened into the top-level module.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Instance {
+    id: InstanceId,
+}
+
+stdx::impl_debug! {
+    match Instance{
+        Instance{ id } => "{id:?}";
+    }
+}
+
+impl Instance {
+    fn children(db: &CompilationDB, module: ModuleId, parent: Option<InstanceId>) -> Vec<Instance> {
+        let scope = module.lookup(db).scope;
+        scope.def_map(db)[scope.local_scope]
+            .declarations
+            .values()
+            .filter_map(|def| match *def {
+                ScopeDefItem::ModuleInstId(inst) if db.module_inst_data(inst).module.is_some() => {
+                    Some(Instance { id: InstanceLoc { parent, inst }.intern(db) })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn module_inst(self, db: &CompilationDB) -> ModuleInstId {
+        self.id.lookup(db).inst
+    }
+
+    /// The hierarchical name of this instance (for example `x1.x2`)
+    pub fn name(self, db: &CompilationDB) -> String {
+        let loc = self.id.lookup(db);
+        let name = db.module_inst_data(loc.inst).name.to_string();
+        match loc.parent {
+            Some(parent) => format!("{}.{name}", Instance { id: parent }.name(db)),
+            None => name,
+        }
+    }
+
+    pub fn module(self, db: &CompilationDB) -> Module {
+        let id = db.module_inst_data(self.module_inst(db)).module.expect("module is resolved");
+        Module { id, paramset: None }
+    }
+
+    pub fn parent(self, db: &CompilationDB) -> Option<Instance> {
+        self.id.lookup(db).parent.map(|id| Instance { id })
+    }
+
+    /// The module instances nested within this instance
+    pub fn instances(self, db: &CompilationDB) -> Vec<Instance> {
+        Instance::children(db, self.module(db).id, Some(self.id))
+    }
+
+    /// All parameters (including local parameters) of the instantiated module
+    /// in declaration order.
+    pub fn parameters(self, db: &CompilationDB) -> Vec<Parameter> {
+        let scope = self.module(db).lookup(db).scope;
+        scope.def_map(db)[scope.local_scope]
+            .declarations
+            .values()
+            .filter_map(|def| match *def {
+                ScopeDefItem::ParamId(id) => Some(Parameter { id, inst: Some(self.id) }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The nodes that are added to the top-level module by this instance.
+    /// These are the internal nodes of the instantiated module and all unconnected ports.
+    pub fn internal_nodes(self, db: &CompilationDB) -> Vec<Node> {
+        let module = db.module_data(self.module(db).id);
+        let connections = db.inference_result(self.module_inst(db).into());
+        let unconnected_ports = module
+            .ports
+            .iter()
+            .zip(&connections.port_connections)
+            .filter(|(_, connection)| connection.is_none())
+            .map(|(&id, _)| id);
+        unconnected_ports
+            .chain(module.internal_nodes.iter().copied())
+            .map(|id| Node { id, inst: Some(self.id) })
+            .collect()
+    }
+
+    pub fn analog_initial_block(self, db: &CompilationDB) -> Body {
+        let id = DefWithBodyId::ModuleId { initial: true, module: self.module(db).id };
+        Body::new_in(id, Some(self.id), db)
+    }
+
+    pub fn analog_block(self, db: &CompilationDB) -> Body {
+        let id = DefWithBodyId::ModuleId { initial: false, module: self.module(db).id };
+        Body::new_in(id, Some(self.id), db)
+    }
+
+    /// The parameters overwritten by this instance. The returned expressions
+    /// belong to the returned body which must be evaluated within the parent.
+    pub fn param_overrides(self, db: &CompilationDB) -> (Body, Vec<(Parameter, ExprId)>) {
+        let loc = self.id.lookup(db);
+        let body = Body::new_in(loc.inst.into(), loc.parent, db);
+        let overrides = body
+            .borrow()
+            .param_overrides()
+            .iter()
+            .map(|&(id, expr)| (Parameter { id, inst: Some(self.id) }, expr))
+            .collect();
+        (body, overrides)
+    }
+}
+
+/// Prints the instance path (if any) followed by the id of a hir item
+macro_rules! impl_instance_debug {
+    ($($ty: ident),*) => {
+        $(impl fmt::Debug for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                if let Some(inst) = self.inst {
+                    write!(f, "{inst:?}.")?;
+                }
+                write!(f, "{:?}", self.id)
+            }
+        })*
+    };
+}
+
+impl_instance_debug!(Function, Node, Variable, Branch);
+
+/// Prefixes `name` with the hierarchical name of `inst`
+fn instance_name(db: &CompilationDB, inst: Option<InstanceId>, name: &str) -> String {
+    match inst {
+        Some(id) => format!("{}.{name}", Instance { id }.name(db)),
+        None => name.to_owned(),
     }
 }
 
@@ -204,12 +440,7 @@ impl Block {
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Function {
     id: FunctionId,
-}
-
-stdx::impl_debug! {
-    match Function{
-        Function{ id } => "{id:?}";
-    }
+    inst: Option<InstanceId>,
 }
 
 impl Function {
@@ -232,7 +463,7 @@ impl Function {
     }
 
     pub fn body(&self, db: &CompilationDB) -> Body {
-        Body::new(self.id.into(), db)
+        Body::new_in(self.id.into(), self.inst, db)
     }
 }
 
@@ -244,7 +475,7 @@ pub struct FunctionArg {
 
 impl FunctionArg {
     pub fn function(self) -> Function {
-        Function { id: self.fun_id }
+        Function { id: self.fun_id, inst: None }
     }
 
     pub fn name(self, db: &CompilationDB) -> String {
@@ -283,17 +514,6 @@ impl BranchWrite {
     }
 }
 
-impl From<inference::BranchWrite> for BranchWrite {
-    #[inline]
-    fn from(inner: inference::BranchWrite) -> Self {
-        match inner {
-            inference::BranchWrite::Named(branch) => BranchWrite::Named(Branch { id: branch }),
-            inference::BranchWrite::Unnamed { hi, lo } => {
-                BranchWrite::Unnamed { hi: Node { id: hi }, lo: lo.map(|id| Node { id }) }
-            }
-        }
-    }
-}
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Scope {
     Module(Module),
@@ -329,9 +549,16 @@ impl Scope {
                 hir_def::nameres::ScopeOrigin::Root => {
                     unreachable!("Root scope can not be a child scope")
                 }
-                hir_def::nameres::ScopeOrigin::Module(id) => Scope::Module(Module { id }),
+                hir_def::nameres::ScopeOrigin::Module(id) => {
+                    Scope::Module(Module { id, paramset: None })
+                }
                 hir_def::nameres::ScopeOrigin::Block(id) => Scope::Block(Block { id }),
-                hir_def::nameres::ScopeOrigin::Function(id) => Scope::Function(Function { id }),
+                hir_def::nameres::ScopeOrigin::Function(id) => {
+                    Scope::Function(Function { id, inst: None })
+                }
+                hir_def::nameres::ScopeOrigin::ParamSet(_) => {
+                    unreachable!("paramsets are only declared in the root scope")
+                }
             })
             .collect()
     }
@@ -344,16 +571,18 @@ impl Scope {
             .iter()
             .filter_map(|(name, &def)| {
                 let res = match def {
-                    ScopeDefItem::ModuleId(id) => ScopeDef::ModuleInstance(Module { id }),
+                    ScopeDefItem::ModuleId(id) => {
+                        ScopeDef::ModuleInstance(Module { id, paramset: None })
+                    }
                     ScopeDefItem::BlockId(id) => ScopeDef::Block(Block { id }),
-                    ScopeDefItem::NodeId(id) => ScopeDef::Node(Node { id }),
-                    ScopeDefItem::VarId(id) => ScopeDef::Variable(Variable { id }),
-                    ScopeDefItem::ParamId(id) => ScopeDef::Parameter(Parameter { id }),
+                    ScopeDefItem::NodeId(id) => ScopeDef::Node(Node { id, inst: None }),
+                    ScopeDefItem::VarId(id) => ScopeDef::Variable(Variable { id, inst: None }),
+                    ScopeDefItem::ParamId(id) => ScopeDef::Parameter(Parameter { id, inst: None }),
                     ScopeDefItem::AliasParamId(id) => {
                         ScopeDef::AliasParameter(AliasParameter { id })
                     }
-                    ScopeDefItem::BranchId(id) => ScopeDef::Branch(Branch { id }),
-                    ScopeDefItem::FunctionId(id) => ScopeDef::Function(Function { id }),
+                    ScopeDefItem::BranchId(id) => ScopeDef::Branch(Branch { id, inst: None }),
+                    ScopeDefItem::FunctionId(id) => ScopeDef::Function(Function { id, inst: None }),
                     // implementation details
                     ScopeDefItem::BuiltIn(_)
                     | ScopeDefItem::NatureId(_)
@@ -362,7 +591,10 @@ impl Scope {
                     | ScopeDefItem::ParamSysFun(_)
                     | ScopeDefItem::FunctionReturn(_)
                     | ScopeDefItem::FunctionArgId(_)
-                    | ScopeDefItem::NatureAttrId(_) => return None,
+                    | ScopeDefItem::NatureAttrId(_)
+                    | ScopeDefItem::ModuleInstId(_)
+                    | ScopeDefItem::ParamSetId(_)
+                    | ScopeDefItem::GenvarId(_) => return None,
                 };
                 Some((name.to_owned(), res))
             })
@@ -373,18 +605,34 @@ impl Scope {
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Node {
     id: NodeId,
+    /// The module instance this node belongs to, `None` for nodes of the top-level module
+    inst: Option<InstanceId>,
 }
 
-stdx::impl_debug! {
-    match Node{
-        Node{ id } => "{id:?}";
+impl Node {
+    /// Returns the node `id` within the module instance `inst`. Ports of
+    /// instantiated modules are replaced by the nodes they are connected to.
+    fn new(db: &CompilationDB, id: NodeId, inst: Option<InstanceId>) -> Node {
+        if let Some(inst_id) = inst {
+            let loc = inst_id.lookup(db);
+            if let Some(module) = db.module_inst_data(loc.inst).module {
+                let port = db.module_data(module).ports.iter().position(|&port| port == id);
+                let connections = db.inference_result(loc.inst.into());
+                if let Some(node) = port.and_then(|port| connections.port_connections[port]) {
+                    return Node::new(db, node, loc.parent);
+                }
+            }
+        }
+        Node { id, inst }
     }
-}
 
-impl Node {
     #[inline]
     pub fn name(self, db: &CompilationDB) -> SmolStr {
-        db.node_data(self.id).name.clone().into()
+        let data = db.node_data(self.id);
+        match self.inst {
+            Some(_) => instance_name(db, self.inst, &data.name).into(),
+            None => data.name.clone().into(),
+        }
     }
 
     #[inline]
@@ -395,62 +643,110 @@ impl Node {
 
     #[inline]
     pub fn is_input(self, db: &CompilationDB) -> bool {
-        db.node_data(self.id).is_input
+        self.inst.is_none() && db.node_data(self.id).is_input
     }
 
     #[inline]
     pub fn is_output(self, db: &CompilationDB) -> bool {
-        db.node_data(self.id).is_output
+        self.inst.is_none() && db.node_data(self.id).is_output
     }
 
     #[inline]
     pub fn is_port(self, db: &CompilationDB) -> bool {
-        db.node_data(self.id).is_port()
+        self.inst.is_none() && db.node_data(self.id).is_port()
     }
 
     #[inline]
     pub fn is_gnd(self, db: &CompilationDB) -> bool {
         db.node_data(self.id).is_gnd
     }
+
+    /// Resolves the target of `$analog_node_alias` (or `$analog_port_alias` if `port` is set)
+    /// relative to the module instance this node belongs to. Returns `None` if the target
+    /// is not a node of the compiled module.
+    pub fn resolve_alias(self, db: &CompilationDB, target: &str, port: bool) -> Option<Node> {
+        let module = self.id.lookup(db).module;
+        let target = module.resolve_node_alias(db, target, port)?;
+        let inst = target
+            .insts
+            .into_iter()
+            .fold(self.inst, |parent, inst| Some(InstanceLoc { parent, inst }.intern(db)));
+        Some(Node::new(db, target.node, inst))
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Variable {
     id: VarId,
-}
-
-stdx::impl_debug! {
-    match Variable{
-        Variable{ id } => "{id:?}";
-    }
+    inst: Option<InstanceId>,
 }
 
 impl Variable {
     pub fn name(self, db: &CompilationDB) -> SmolStr {
-        db.var_data(self.id).name.clone().into()
+        let data = db.var_data(self.id);
+        match self.inst {
+            Some(_) => instance_name(db, self.inst, &data.name).into(),
+            None => data.name.clone().into(),
+        }
     }
 
     pub fn ty(self, db: &CompilationDB) -> Type {
         db.var_data(self.id).ty.clone()
     }
 
+    /// The index range of array variables
+    pub fn array_range(self, db: &CompilationDB) -> Option<ArrayRange> {
+        match self.ty(db) {
+            Type::Array { len, .. } => {
+                Some(db.var_data(self.id).range.unwrap_or_else(|| ArrayRange::with_len(len)))
+            }
+            _ => None,
+        }
+    }
+
     pub fn init(self, db: &CompilationDB) -> Body {
-        Body::new(self.id.into(), db)
+        Body::new_in(self.id.into(), self.inst, db)
     }
 
     pub fn get_attr(&self, db: &CompilationDB, ast: &AstCache, name: &str) -> Option<ast::Attr> {
         ast.resolve_attribute(name, self.id.lookup(db).ast_id(db).erased())
     }
+
+    /// The nature declared with the `(* nature = "..." *)` attribute of this variable
+    pub fn nature(self, db: &CompilationDB) -> Option<Nature> {
+        let ast = db.compilation_unit().ast(db);
+        let attr = self.get_attr(db, &ast, "nature")?;
+        let name = attr.val()?.as_str_literal()?;
+        Nature::lookup(db, &name)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Parameter {
     id: ParamId,
+    inst: Option<InstanceId>,
+}
+
+impl fmt::Debug for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut res = f.debug_struct("Parameter");
+        res.field("id", &self.id);
+        if let Some(inst) = self.inst {
+            res.field("inst", &inst);
+        }
+        res.finish()
+    }
 }
 
 impl Parameter {
     pub fn name(self, db: &CompilationDB) -> String {
-        db.param_data(self.id).name.to_string()
+        instance_name(db, self.inst, &db.param_data(self.id).name)
+    }
+
+    /// The module instance this parameter belongs to. The values of these
+    /// parameters are fixed at compile time by the instance.
+    pub fn instance(self) -> Option<Instance> {
+        self.inst.map(|id| Instance { id })
     }
 
     pub fn default(self, db: &CompilationDB) -> ExprId {
@@ -461,14 +757,38 @@ impl Parameter {
         db.param_exprs(self.id).bounds
     }
 
+    /// Whether this parameter is declared by an overloaded paramset. The ranges of these
+    /// parameters select the paramset instead of restricting the value of the parameter.
+    pub fn selects_paramset(self, db: &CompilationDB) -> bool {
+        let scope = self.id.lookup(db).scope;
+        let def_map = scope.def_map(db);
+        match def_map[scope.local_scope].origin {
+            hir_def::nameres::ScopeOrigin::ParamSet(paramset) => def_map
+                .paramset_overloads
+                .iter()
+                .any(|(&first, overloads)| first == paramset || overloads.contains(&paramset)),
+            _ => false,
+        }
+    }
+
     pub fn init(self, db: &CompilationDB) -> Body {
-        Body::new(self.id.into(), db)
+        Body::new_in(self.id.into(), self.inst, db)
     }
 
     pub fn ty(self, db: &CompilationDB) -> Type {
         db.param_ty(self.id)
     }
 
+    /// The index range of array parameters
+    pub fn array_range(self, db: &CompilationDB) -> Option<ArrayRange> {
+        match self.ty(db) {
+            Type::Array { len, .. } => {
+                Some(db.param_data(self.id).range.unwrap_or_else(|| ArrayRange::with_len(len)))
+            }
+            _ => None,
+        }
+    }
+
     pub fn get_attr(&self, db: &CompilationDB, ast: &AstCache, name: &str) -> Option<ast::Attr> {
         ast.resolve_attribute(name, self.id.lookup(db).ast_id(db).erased())
     }
@@ -493,7 +813,7 @@ impl AliasParameter {
         db.resolve_alias(self.id).and_then(|alias| match alias {
             hir_ty::db::Alias::Cycel => None,
             hir_ty::db::Alias::Param(id) => {
-                Some(ResolvedAliasParameter::Parameter(Parameter { id }))
+                Some(ResolvedAliasParameter::Parameter(Parameter { id, inst: None }))
             }
             hir_ty::db::Alias::ParamSysFun(param) => {
                 Some(ResolvedAliasParameter::SystemParameter(param))
@@ -533,17 +853,12 @@ impl BranchKind {
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Branch {
     id: BranchId,
-}
-
-stdx::impl_debug! {
-    match Branch{
-        Branch{ id } => "{id:?}";
-    }
+    inst: Option<InstanceId>,
 }
 
 impl Branch {
     pub fn name(self, db: &CompilationDB) -> String {
-        db.branch_data(self.id).name.to_string()
+        instance_name(db, self.inst, &db.branch_data(self.id).name)
     }
 
     pub fn discipline(self, db: &CompilationDB) -> Discipline {
@@ -553,10 +868,14 @@ impl Branch {
 
     pub fn kind(self, db: &CompilationDB) -> BranchKind {
         match db.branch_info(self.id).unwrap().kind {
-            hir_ty::lower::BranchKind::PortFlow(node) => BranchKind::PortFlow(Node { id: node }),
-            hir_ty::lower::BranchKind::NodeGnd(node) => BranchKind::NodeGnd(Node { id: node }),
+            hir_ty::lower::BranchKind::PortFlow(node) => {
+                BranchKind::PortFlow(Node { id: node, inst: self.inst })
+            }
+            hir_ty::lower::BranchKind::NodeGnd(node) => {
+                BranchKind::NodeGnd(Node::new(db, node, self.inst))
+            }
             hir_ty::lower::BranchKind::Nodes(hi, lo) => {
-                BranchKind::Nodes(Node { id: hi }, Node { id: lo })
+                BranchKind::Nodes(Node::new(db, hi, self.inst), Node::new(db, lo, self.inst))
             }
         }
     }
@@ -598,6 +917,25 @@ impl Nature {
     pub fn units(self, db: &CompilationDB) -> String {
         db.nature_data(self.id).units.clone().unwrap_or_default()
     }
+
+    /// Looks up the nature called `name` declared in the root file
+    pub fn lookup(db: &CompilationDB, name: &str) -> Option<Nature> {
+        let def_map = db.def_map(db.compilation_unit().root_file);
+        let id = def_map.resolve_local_item_in_scope(def_map.root(), &Name::resolve(name)).ok()?;
+        Some(Nature { id })
+    }
+
+    /// The nature of the time derivative of a quantity with this nature (`ddt_nature`)
+    pub fn ddt_nature(self, db: &CompilationDB) -> Option<Nature> {
+        let id = db.nature_info(self.id).ddt_nature;
+        (id != self.id).then_some(Nature { id })
+    }
+
+    /// The nature of the time integral of a quantity with this nature (`idt_nature`)
+    pub fn idt_nature(self, db: &CompilationDB) -> Option<Nature> {
+        let id = db.nature_info(self.id).idt_nature;
+        (id != self.id).then_some(Nature { id })
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -4,6 +4,7 @@ use std::ops::Deref;
 use std::sync::Arc;
 
 use hir_def::nameres::{self, DefMap, LocalScopeId, ScopeDefItem};
+use hir_def::{Lookup, ParamSetId};
 use smol_str::SmolStr;
 use syntax::name::Name;
 
@@ -16,6 +17,8 @@ struct Scope {
     _def_map: Arc<DefMap>,
     iter: indexmap::map::Iter<'static, Name, nameres::ScopeDefItem>,
     def: Option<(Name, ScopeDef)>,
+    /// parameters of a base module are replaced by the parameters of a paramset
+    skip_params: bool,
 }
 impl Scope {
     fn new(def_map: Arc<DefMap>, scope: LocalScopeId, block: Option<(Name, ScopeDef)>) -> Scope {
@@ -23,7 +26,7 @@ impl Scope {
         let iter: indexmap::map::Iter<'_, Name, nameres::ScopeDefItem> =
             def_map[scope].declarations.iter();
         let iter = unsafe { transmute(iter) };
-        Scope { _def_map: def_map, iter, def: block }
+        Scope { _def_map: def_map, iter, def: block, skip_params: false }
     }
 }
 
@@ -39,6 +42,21 @@ impl<'a> RecDeclarations<'a> {
         RecDeclarations { path: Vec::new(), stack: vec![Scope::new(def_map, scope_id, None)], db }
     }
 
+    /// The declarations of the base module of a paramset where the parameters of the
+    /// base module are replaced by the parameters of the paramset (listed first).
+    pub(super) fn new_paramset(
+        module: Module,
+        paramset: ParamSetId,
+        db: &'a CompilationDB,
+    ) -> RecDeclarations<'a> {
+        let (scope_id, def_map) = super::Scope::Module(module).def_map_and_scope(db);
+        let mut base = Scope::new(def_map, scope_id, None);
+        base.skip_params = true;
+        let scope = paramset.lookup(db).scope;
+        let paramset = Scope::new(scope.def_map(db), scope.local_scope, None);
+        RecDeclarations { path: Vec::new(), stack: vec![base, paramset], db }
+    }
+
     /// crates a path in the current scope with the final
     /// component given by `name`
     pub fn to_path(&self, name: Name) -> SmolStr {
@@ -61,6 +79,11 @@ impl Iterator for RecDeclarations<'_> {
         loop {
             let scope = self.stack.last_mut()?;
             if let Some((name, &item)) = scope.iter.next() {
+                if scope.skip_params
+                    && matches!(item, ScopeDefItem::ParamId(_) | ScopeDefItem::AliasParamId(_))
+                {
+                    continue;
+                }
                 let def = match item {
                     ScopeDefItem::BlockId(id) => {
                         if let Some(def_map) = self.db.block_def_map(id) {
@@ -73,14 +96,16 @@ impl Iterator for RecDeclarations<'_> {
                         }
                         continue;
                     }
-                    ScopeDefItem::ModuleId(id) => ScopeDef::ModuleInstance(Module { id }),
-                    ScopeDefItem::NodeId(id) => ScopeDef::Node(Node { id }),
-                    ScopeDefItem::VarId(id) => ScopeDef::Variable(Variable { id }),
-                    ScopeDefItem::ParamId(id) => ScopeDef::Parameter(Parameter { id }),
+                    ScopeDefItem::ModuleId(id) => {
+                        ScopeDef::ModuleInstance(Module { id, paramset: None })
+                    }
+                    ScopeDefItem::NodeId(id) => ScopeDef::Node(Node { id, inst: None }),
+                    ScopeDefItem::VarId(id) => ScopeDef::Variable(Variable { id, inst: None }),
+                    ScopeDefItem::ParamId(id) => ScopeDef::Parameter(Parameter { id, inst: None }),
                     ScopeDefItem::AliasParamId(id) => {
                         ScopeDef::AliasParameter(AliasParameter { id })
                     }
-                    ScopeDefItem::BranchId(id) => ScopeDef::Branch(Branch { id }),
+                    ScopeDefItem::BranchId(id) => ScopeDef::Branch(Branch { id, inst: None }),
                     _ => continue,
                 };
                 return Some((name.clone(), def));
@@ -14,7 +14,8 @@ use crate::item_tree::{DisciplineAttr, ItemTreeId, ItemTreeNode, NatureAttr};
 use crate::nameres::{DefMapSource, LocalScopeId};
 use crate::{
     DefWithBodyId, DisciplineAttrLoc, DisciplineLoc, Expr, ExprId, FunctionLoc, Literal, Lookup,
-    ModuleLoc, NatureAttrLoc, NatureLoc, ParamId, ParamLoc, ScopeId, Stmt, StmtId, Type, VarLoc,
+    ModuleInstLoc, ModuleLoc, NatureAttrLoc, NatureLoc, ParamId, ParamLoc, ParamSetLoc, ScopeId,
+    Stmt, StmtId, Type, VarLoc,
 };
 
 mod lower;
@@ -81,6 +82,7 @@ impl Body {
                     ast_id_map: &ast_id_map,
                     curr_scope,
                     registry: &registry,
+                    genvars: Vec::new(),
                 };
                 body.entry_stmts = if initial {
                     ast.analog_initial_behaviour().map(|stmt| ctx.collect_stmt(stmt)).collect()
@@ -110,6 +112,7 @@ impl Body {
                     ast_id_map: &ast_id_map,
                     curr_scope,
                     registry: &registry,
+                    genvars: Vec::new(),
                 };
                 body.entry_stmts = ast.body().map(|stmt| ctx.collect_stmt(stmt)).collect();
             }
@@ -127,17 +130,31 @@ impl Body {
                     ast_id_map: &ast_id_map,
                     curr_scope,
                     registry: &registry,
+                    genvars: Vec::new(),
                 };
 
                 let expr = if let Some(expr) = ast.default() {
                     ctx.collect_expr(expr)
                 } else {
-                    let default_val = match db.var_data(var).ty {
+                    let ty = db.var_data(var).ty.clone();
+                    let default_val = match ty.base_type() {
                         Type::Real => Literal::Float(Ieee64::with_float(0.0)),
                         Type::Integer => Literal::Int(0),
-                        _ => unreachable!("invalid var type (TODO arrays)"),
+                        Type::String => Literal::String("".into()),
+                        _ => Literal::Int(0),
                     };
-                    ctx.alloc_expr_desugared(Expr::Literal(default_val))
+                    match ty {
+                        // arrays are initialized with zero elements
+                        Type::Array { len, .. } => {
+                            let vals = (0..len)
+                                .map(|_| {
+                                    ctx.alloc_expr_desugared(Expr::Literal(default_val.clone()))
+                                })
+                                .collect();
+                            ctx.alloc_expr_desugared(Expr::Array(vals))
+                        }
+                        _ => ctx.alloc_expr_desugared(Expr::Literal(default_val)),
+                    }
                 };
                 let stmt = ctx.alloc_stmt_desugared(Stmt::Expr(expr));
                 body.entry_stmts = vec![stmt].into_boxed_slice();
@@ -160,6 +177,7 @@ impl Body {
                     ast_id_map: &ast_id_map,
                     curr_scope,
                     registry: &registry,
+                    genvars: Vec::new(),
                 };
                 let expr = ctx.collect_opt_expr(ast.val());
                 let stmt = ctx.alloc_stmt_desugared(Stmt::Expr(expr));
@@ -182,11 +200,82 @@ impl Body {
                     ast_id_map: &ast_id_map,
                     curr_scope,
                     registry: &registry,
+                    genvars: Vec::new(),
                 };
                 let expr = ctx.collect_opt_expr(ast.val());
                 let stmt = ctx.alloc_stmt_desugared(Stmt::Expr(expr));
                 body.entry_stmts = vec![stmt].into_boxed_slice();
             }
+            DefWithBodyId::ModuleInstId(inst) => {
+                let ModuleInstLoc { scope, id: item_tree } = inst.lookup(db);
+
+                let ast_id = tree[item_tree].ast_id();
+                let ast = ast_id_map.get(ast_id).to_node(ast.syntax());
+                let curr_scope = (scope, ast_id.into());
+
+                let mut ctx = LowerCtx {
+                    db,
+                    source_map: &mut source_map,
+                    body: &mut body,
+                    ast_id_map: &ast_id_map,
+                    curr_scope,
+                    registry: &registry,
+                    genvars: Vec::new(),
+                };
+
+                // the parameter overrides are shared by all instances of a declaration
+                // the entry statements are the parameter overrides followed by the port connections
+                let param_overrides = ast
+                    .syntax()
+                    .parent()
+                    .and_then(ast::ModuleInstDecl::cast)
+                    .and_then(|decl| decl.param_overrides())
+                    .into_iter()
+                    .flat_map(|overrides| overrides.overrides())
+                    .map(|param| param.expr());
+                let port_connections = ast.connections().map(|port| port.expr());
+                body.entry_stmts = param_overrides
+                    .chain(port_connections)
+                    .map(|expr| {
+                        let expr = ctx.collect_opt_expr(expr);
+                        ctx.alloc_stmt_desugared(Stmt::Expr(expr))
+                    })
+                    .collect();
+            }
+            DefWithBodyId::ParamSetId(paramset) => {
+                let ParamSetLoc { scope, id: item_tree } = paramset.lookup(db);
+
+                let ast_id = tree[item_tree].ast_id();
+                let ast = ast_id_map.get(ast_id).to_node(ast.syntax());
+                let curr_scope = (scope, ast_id.into());
+
+                let mut ctx = LowerCtx {
+                    db,
+                    source_map: &mut source_map,
+                    body: &mut body,
+                    ast_id_map: &ast_id_map,
+                    curr_scope,
+                    registry: &registry,
+                    genvars: Vec::new(),
+                };
+
+                // the entry statements are the overrides of the base module parameters
+                body.entry_stmts = ast
+                    .param_set_items()
+                    .filter_map(|item| match item {
+                        ast::ParamSetItem::ParamSetAssign(assign)
+                            if assign.name_ref().is_some() =>
+                        {
+                            Some(assign.expr())
+                        }
+                        _ => None,
+                    })
+                    .map(|expr| {
+                        let expr = ctx.collect_opt_expr(expr);
+                        ctx.alloc_stmt_desugared(Stmt::Expr(expr))
+                    })
+                    .collect();
+            }
         }
 
         (Arc::new(body), Arc::new(source_map))
@@ -216,6 +305,7 @@ impl Body {
             ast_id_map: &ast_id_map,
             curr_scope: (scope, ast_id.into()),
             registry: &registry,
+            genvars: Vec::new(),
         };
 
         let default = ctx.collect_opt_expr(ast.default());
@@ -257,6 +347,36 @@ impl Body {
 
         (Arc::new(body), Arc::new(source_map), ParamExprs { default, bounds })
     }
+
+    /// Folds a numeric literal (optionally negated) into a real value.
+    pub fn as_real_literal(&self, expr: ExprId) -> Option<f64> {
+        match self.exprs[expr] {
+            Expr::Literal(Literal::Float(val)) => Some(val.into()),
+            Expr::Literal(Literal::Int(val)) => Some(val as f64),
+            Expr::UnaryOp { expr, op: ast::UnaryOp::Neg } => Some(-self.as_real_literal(expr)?),
+            Expr::UnaryOp { expr, op: ast::UnaryOp::Identity } => self.as_real_literal(expr),
+            _ => None,
+        }
+    }
+
+    /// Folds an array expression whose elements are all numeric literals
+    /// (like the inline table of `noise_table`). Nested arrays are flattened.
+    pub fn as_real_literal_array(&self, expr: ExprId) -> Option<Vec<f64>> {
+        fn collect(body: &Body, expr: ExprId, dst: &mut Vec<f64>) -> Option<()> {
+            if let Expr::Array(ref vals) = body.exprs[expr] {
+                for &val in vals {
+                    collect(body, val, dst)?;
+                }
+            } else {
+                dst.push(body.as_real_literal(expr)?);
+            }
+            Some(())
+        }
+
+        let mut res = Vec::new();
+        collect(self, expr, &mut res)?;
+        Some(res)
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -3,15 +3,19 @@ use std::mem;
 use basedb::lints::LintRegistry;
 use basedb::{AstIdMap, ErasedAstId, LintAttrs};
 use syntax::ast::{self, ArgListOwner, AttrIter, AttrsOwner, FunctionRef};
-use syntax::name::AsName;
+use syntax::name::{kw, AsIdent, AsName, Name};
 use syntax::AstPtr;
 
 // use tracing::debug;
 use super::{Body, BodySourceMap};
+use crate::const_eval::{node_array_element_name, ConstEval, MAX_ELABORATION_SIZE};
 use crate::db::HirDefDB;
-use crate::expr::{CaseCond, Event, GlobalEvent};
+use crate::expr::{CaseCond, Event, GlobalEvent, MonitoredEvent, MonitoredEventKind};
 use crate::nameres::DefMapSource;
-use crate::{BlockLoc, Case, Expr, ExprId, Intern, Literal, Path, ScopeId, Stmt, StmtId};
+use crate::{
+    BlockLoc, Case, Expr, ExprId, GenvarId, Intern, Literal, Lookup, ParamId, Path, ScopeId, Stmt,
+    StmtId,
+};
 
 pub(super) struct LowerCtx<'a> {
     pub(super) db: &'a dyn HirDefDB,
@@ -20,6 +24,8 @@ pub(super) struct LowerCtx<'a> {
     pub(super) ast_id_map: &'a AstIdMap,
     pub(super) curr_scope: (ScopeId, ErasedAstId),
     pub(super) registry: &'a LintRegistry,
+    /// The values of the genvars of the `for` loops that are currently being unrolled
+    pub(super) genvars: Vec<(Name, i32)>,
 }
 
 impl LowerCtx<'_> {
@@ -81,12 +87,25 @@ impl LowerCtx<'_> {
             // BLOCK
             ast::Expr::PathExpr(path) => {
                 if let Some(path) = path.path().and_then(Path::resolve) {
-                    Expr::Path { path, port: false }
+                    match self.genvar_value(&path) {
+                        Some(val) => Expr::Literal(Literal::Int(val)),
+                        None => Expr::Path { path, port: false },
+                    }
                 } else {
                     return self.missing_expr();
                 }
             }
 
+            ast::Expr::IndexExpr(e) => {
+                if let Some(path) = self.node_array_element(e) {
+                    Expr::Path { path, port: false }
+                } else {
+                    let base = self.collect_opt_expr(e.base());
+                    let index = self.collect_opt_expr(e.index());
+                    Expr::Index { base, index }
+                }
+            }
+
             ast::Expr::PortFlow(port_flow) => {
                 if let Some(path) = port_flow.port().and_then(Path::resolve) {
                     Expr::Path { path, port: true }
@@ -137,13 +156,16 @@ impl LowerCtx<'_> {
                 let body = self.collect_opt_stmt(stmt.body());
                 Stmt::WhileLoop { cond, body }
             }
-            ast::Stmt::ForStmt(stmt) => {
-                let cond = self.collect_opt_expr(stmt.condition());
-                let init = self.collect_opt_stmt(stmt.init());
-                let incr = self.collect_opt_stmt(stmt.incr());
-                let body = self.collect_opt_stmt(stmt.for_body());
-                Stmt::ForLoop { init, cond, incr, body }
-            }
+            ast::Stmt::ForStmt(stmt) => match self.unroll_genvar_loop(stmt) {
+                Some(unrolled) => unrolled,
+                None => {
+                    let cond = self.collect_opt_expr(stmt.condition());
+                    let init = self.collect_opt_stmt(stmt.init());
+                    let incr = self.collect_opt_stmt(stmt.incr());
+                    let body = self.collect_opt_stmt(stmt.for_body());
+                    Stmt::ForLoop { init, cond, incr, body }
+                }
+            },
             ast::Stmt::CaseStmt(stmt) => self.collect_case_stmt(stmt),
             ast::Stmt::EventStmt(stmt) => return self.collect_event_stmt(stmt),
             ast::Stmt::BlockStmt(stmt) => self.collect_block(stmt),
@@ -151,22 +173,149 @@ impl LowerCtx<'_> {
         self.alloc_stmt(s, AstPtr::new(&stmt), stmt.attrs())
     }
 
+    fn const_eval<T>(&self, f: impl FnOnce(&ConstEval) -> T) -> T {
+        let scope = self.curr_scope.0;
+        let resolve_param = |name: &Name| {
+            let path = Path::new_ident(name.clone());
+            scope.resolve_item_path::<ParamId>(self.db, &path).ok().map(|it| it.lookup(self.db).id)
+        };
+        f(&ConstEval {
+            db: self.db,
+            root_file: scope.root_file,
+            resolve_param: &resolve_param,
+            genvars: &self.genvars,
+        })
+    }
+
+    fn genvar_value(&self, path: &Path) -> Option<i32> {
+        let name = path.as_ident()?;
+        self.genvars.iter().rev().find(|(it, _)| *it == name).map(|(_, val)| *val)
+    }
+
+    /// Indexing a node array with a constant (`n[i+1]` inside a genvar loop)
+    /// refers to the corresponding element node. Only the elements of a node array
+    /// are declared (`n[0]`, `n[1]`, ...) so the base name itself can not be resolved.
+    fn node_array_element(&self, e: &ast::IndexExpr) -> Option<Path> {
+        let mut path = match e.base()? {
+            ast::Expr::PathExpr(path) => Path::resolve(path.path()?)?,
+            _ => return None,
+        };
+        if self.curr_scope.0.resolve_path(self.db, &path).is_ok() {
+            return None;
+        }
+        let index = self.const_eval(|eval| eval.eval(&e.index()?))?;
+        let name = path.segments.pop()?;
+        path.segments.push(node_array_element_name(&name, index));
+        Some(path)
+    }
+
+    /// `for` loops over a genvar are unrolled during elaboration. The body is lowered
+    /// once per iteration with the genvar replaced by its current value.
+    /// Returns `None` if `stmt` is not a genvar loop or if the number of iterations
+    /// is not known at compile time. Such loops are lowered as normal loops and
+    /// reported during type inference.
+    fn unroll_genvar_loop(&mut self, stmt: &ast::ForStmt) -> Option<Stmt> {
+        let (genvar, init) = self.genvar_assignment(stmt.init()?)?;
+        let (incr_genvar, incr) = self.genvar_assignment(stmt.incr()?)?;
+        if genvar != incr_genvar {
+            return None;
+        }
+        let cond = stmt.condition()?;
+
+        let mut iterations = Vec::new();
+        let mut val = self.const_eval(|eval| eval.eval(&init))?;
+        loop {
+            self.genvars.push((genvar.clone(), val));
+            let res = self.const_eval(|eval| {
+                if eval.eval(&cond)? == 0 {
+                    return Some(None);
+                }
+                eval.eval(&incr).map(Some)
+            });
+            self.genvars.pop();
+            match res? {
+                Some(next) => {
+                    iterations.push(val);
+                    val = next;
+                }
+                None => break,
+            }
+            if iterations.len() > MAX_ELABORATION_SIZE {
+                return None;
+            }
+        }
+
+        let body = iterations
+            .into_iter()
+            .map(|val| {
+                self.genvars.push((genvar.clone(), val));
+                let stmt = self.collect_opt_stmt(stmt.for_body());
+                self.genvars.pop();
+                stmt
+            })
+            .collect();
+        Some(Stmt::Block { body })
+    }
+
+    /// Matches `genvar = val` and returns the genvar and the assigned value.
+    fn genvar_assignment(&self, stmt: ast::Stmt) -> Option<(Name, ast::Expr)> {
+        let assign = match stmt {
+            ast::Stmt::AssignStmt(stmt) => stmt.assign()?,
+            _ => return None,
+        };
+        let path = Path::resolve(assign.lval()?.as_path()?)?;
+        self.curr_scope.0.resolve_item_path::<GenvarId>(self.db, &path).ok()?;
+        Some((path.as_ident()?, assign.rval()?))
+    }
+
     fn collect_event_stmt(&mut self, event_stmt: &ast::EventStmt) -> StmtId {
-        let kind = if event_stmt.initial_step_token().is_some() {
-            GlobalEvent::InitialStep
+        let event = if event_stmt.initial_step_token().is_some() {
+            self.collect_global_event(event_stmt, GlobalEvent::InitialStep)
         } else if event_stmt.final_step_token().is_some() {
-            GlobalEvent::FinalStep
+            self.collect_global_event(event_stmt, GlobalEvent::FinalStep)
         } else {
-            return self.collect_opt_stmt(event_stmt.stmt());
+            let events: Vec<_> =
+                event_stmt.exprs().filter_map(|expr| self.collect_monitored_event(expr)).collect();
+            // invalid events are reported during syntax validation
+            if events.is_empty() {
+                return self.collect_opt_stmt(event_stmt.stmt());
+            }
+            Event::Monitored(events)
         };
 
-        let phases = event_stmt.sim_phases().map(|lit| lit.unescaped_value()).collect();
-        let event = Event::Global { kind, phases };
         let stmt = Stmt::EventControl { event, body: self.collect_opt_stmt(event_stmt.stmt()) };
 
         self.alloc_stmt(stmt, AstPtr::new(event_stmt).cast().unwrap(), event_stmt.attrs())
     }
 
+    fn collect_global_event(&mut self, event_stmt: &ast::EventStmt, kind: GlobalEvent) -> Event {
+        let phases = event_stmt.sim_phases().map(|lit| lit.unescaped_value()).collect();
+        Event::Global { kind, phases }
+    }
+
+    fn collect_monitored_event(&mut self, expr: ast::Expr) -> Option<MonitoredEvent> {
+        let call = match expr {
+            ast::Expr::Call(call) => call,
+            _ => return None,
+        };
+        let name = match call.function_ref()? {
+            FunctionRef::Path(path) => Path::resolve(path)?.as_ident()?,
+            FunctionRef::SysFun(_) => return None,
+        };
+        let kind = match &*name {
+            kw::raw::cross => MonitoredEventKind::Cross,
+            kw::raw::above => MonitoredEventKind::Above,
+            kw::raw::timer => MonitoredEventKind::Timer,
+            _ => return None,
+        };
+        // a missing argument list is reported during syntax validation
+        let args: Vec<_> = call.arg_list()?.args().map(|arg| self.collect_expr(arg)).collect();
+        if args.is_empty() {
+            return None;
+        }
+        Some(MonitoredEvent { kind, args })
+    }
+
     fn collect_case_stmt(&mut self, case_stmt: &ast::CaseStmt) -> Stmt {
         let discr = self.collect_opt_expr(case_stmt.discriminant());
         let case_arms = case_stmt
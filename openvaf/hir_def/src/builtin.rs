@@ -96,33 +96,34 @@ pub enum BuiltIn {
     port_connected = 81u8,
     analog_node_alias = 82u8,
     analog_port_alias = 83u8,
-    test_plusargs = 84u8,
-    value_plusargs = 85u8,
-    bound_step = 86u8,
-    analysis = 87u8,
-    ac_stim = 88u8,
-    noise_table = 89u8,
-    noise_table_log = 90u8,
-    white_noise = 91u8,
-    flicker_noise = 92u8,
-    limit = 93u8,
-    absdelay = 94u8,
-    ddt = 95u8,
-    idt = 96u8,
-    idtmod = 97u8,
-    ddx = 98u8,
-    zi_nd = 99u8,
-    zi_np = 100u8,
-    zi_zd = 101u8,
-    zi_zp = 102u8,
-    laplace_nd = 103u8,
-    laplace_np = 104u8,
-    laplace_zd = 105u8,
-    laplace_zp = 106u8,
-    limexp = 107u8,
-    last_crossing = 108u8,
-    slew = 109u8,
-    transition = 110u8,
+    table_model = 84u8,
+    test_plusargs = 85u8,
+    value_plusargs = 86u8,
+    bound_step = 87u8,
+    analysis = 88u8,
+    ac_stim = 89u8,
+    noise_table = 90u8,
+    noise_table_log = 91u8,
+    white_noise = 92u8,
+    flicker_noise = 93u8,
+    limit = 94u8,
+    absdelay = 95u8,
+    ddt = 96u8,
+    idt = 97u8,
+    idtmod = 98u8,
+    ddx = 99u8,
+    zi_nd = 100u8,
+    zi_np = 101u8,
+    zi_zd = 102u8,
+    zi_zp = 103u8,
+    laplace_nd = 104u8,
+    laplace_np = 105u8,
+    laplace_zd = 106u8,
+    laplace_zp = 107u8,
+    limexp = 108u8,
+    last_crossing = 109u8,
+    slew = 110u8,
+    transition = 111u8,
 }
 #[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
 #[allow(nonstandard_style, unreachable_pub)]
@@ -174,56 +175,15 @@ impl BuiltIn {
     #[allow(clippy::match_like_matches_macro)]
     pub fn is_unsupported(self) -> bool {
         match self {
-            BuiltIn::simprobe
-            | BuiltIn::analog_node_alias
-            | BuiltIn::analog_port_alias
-            | BuiltIn::test_plusargs
-            | BuiltIn::value_plusargs
-            | BuiltIn::zi_nd
-            | BuiltIn::zi_np
-            | BuiltIn::zi_zd
-            | BuiltIn::zi_zp
-            | BuiltIn::laplace_nd
-            | BuiltIn::laplace_np
-            | BuiltIn::laplace_zd
-            | BuiltIn::laplace_zp
-            | BuiltIn::last_crossing
-            | BuiltIn::slew
-            | BuiltIn::transition
-            | BuiltIn::fclose
-            | BuiltIn::fopen
-            | BuiltIn::fdisplay
-            | BuiltIn::fwrite
-            | BuiltIn::fstrobe
-            | BuiltIn::fmonitor
-            | BuiltIn::fgets
+            BuiltIn::fgets
             | BuiltIn::fscanf
-            | BuiltIn::swrite
-            | BuiltIn::sformat
-            | BuiltIn::sscanf
             | BuiltIn::rewind
             | BuiltIn::fseek
             | BuiltIn::ftell
             | BuiltIn::fflush
             | BuiltIn::ferror
             | BuiltIn::feof
-            | BuiltIn::fdebug
-            | BuiltIn::dist_chi_square
-            | BuiltIn::dist_exponential
-            | BuiltIn::dist_poisson
-            | BuiltIn::dist_uniform
-            | BuiltIn::dist_erlang
-            | BuiltIn::dist_normal
-            | BuiltIn::dist_t
-            | BuiltIn::random
-            | BuiltIn::arandom
-            | BuiltIn::rdist_chi_square
-            | BuiltIn::rdist_exponential
-            | BuiltIn::rdist_poisson
-            | BuiltIn::rdist_uniform
-            | BuiltIn::rdist_erlang
-            | BuiltIn::rdist_normal
-            | BuiltIn::rdist_t => true,
+            | BuiltIn::fdebug => true,
             _ => false,
         }
     }
@@ -345,6 +305,7 @@ pub fn insert_builtin_scope(dst: &mut IndexMap<Name, ScopeDefItem, BuildHasherDe
     dst.insert(sysfun::port_connected, BuiltIn::port_connected.into());
     dst.insert(sysfun::analog_node_alias, BuiltIn::analog_node_alias.into());
     dst.insert(sysfun::analog_port_alias, BuiltIn::analog_port_alias.into());
+    dst.insert(sysfun::table_model, BuiltIn::table_model.into());
     dst.insert(sysfun::test_plusargs, BuiltIn::test_plusargs.into());
     dst.insert(sysfun::value_plusargs, BuiltIn::value_plusargs.into());
     dst.insert(sysfun::bound_step, BuiltIn::bound_step.into());
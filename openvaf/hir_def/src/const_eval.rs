@@ -0,0 +1,237 @@
+//! Evaluation of the integer expressions that must be known during elaboration:
+//! the bounds of (node) arrays and the iterations of `genvar` loops.
+//!
+//! This works directly on the AST because it runs before (and during) name resolution.
+//! Local parameters (`localparam`) are replaced with their default value. Parameters that
+//! can be overridden by the simulator are not constant: The topology of the compiled model
+//! can not change when such a parameter is overridden.
+
+use basedb::FileId;
+use syntax::ast::{self, BinaryOp, LiteralKind, UnaryOp};
+use syntax::name::{AsIdent, Name};
+use syntax::{AstNode, SyntaxNode};
+
+use crate::db::HirDefDB;
+use crate::item_tree::{ItemTree, ModuleItem, Net, Param};
+use crate::{ArrayRange, ItemTreeId, LocalNodeId, Lookup, ModuleId, ParamId, Path, ScopeId};
+
+/// Parameter defaults may refer to other parameters.
+/// The depth is limited to avoid running into cyclic definitions.
+const MAX_DEPTH: u32 = 32;
+
+pub(crate) struct ConstEval<'a> {
+    pub(crate) db: &'a dyn HirDefDB,
+    pub(crate) root_file: FileId,
+    pub(crate) resolve_param: &'a dyn Fn(&Name) -> Option<ItemTreeId<Param>>,
+    pub(crate) genvars: &'a [(Name, i32)],
+}
+
+impl ConstEval<'_> {
+    pub(crate) fn eval(&self, expr: &ast::Expr) -> Option<i32> {
+        self.eval_expr(expr, 0)
+    }
+
+    fn eval_expr(&self, expr: &ast::Expr, depth: u32) -> Option<i32> {
+        let res = match expr {
+            ast::Expr::Literal(lit) => match lit.kind() {
+                LiteralKind::IntNumber(lit) => lit.value(),
+                _ => return None,
+            },
+            ast::Expr::ParenExpr(e) => self.eval_expr(&e.expr()?, depth)?,
+            ast::Expr::PrefixExpr(e) => {
+                let val = self.eval_expr(&e.expr()?, depth)?;
+                match e.op_kind()? {
+                    UnaryOp::BitNegate => !val,
+                    UnaryOp::Not => (val == 0) as i32,
+                    UnaryOp::Neg => val.checked_neg()?,
+                    UnaryOp::Identity => val,
+                }
+            }
+            ast::Expr::BinExpr(e) => {
+                let lhs = self.eval_expr(&e.lhs()?, depth)?;
+                let rhs = self.eval_expr(&e.rhs()?, depth)?;
+                match e.op_kind()? {
+                    BinaryOp::BooleanOr => (lhs != 0 || rhs != 0) as i32,
+                    BinaryOp::BooleanAnd => (lhs != 0 && rhs != 0) as i32,
+                    BinaryOp::EqualityTest => (lhs == rhs) as i32,
+                    BinaryOp::NegatedEqualityTest => (lhs != rhs) as i32,
+                    BinaryOp::LesserEqualTest => (lhs <= rhs) as i32,
+                    BinaryOp::GreaterEqualTest => (lhs >= rhs) as i32,
+                    BinaryOp::LesserTest => (lhs < rhs) as i32,
+                    BinaryOp::GreaterTest => (lhs > rhs) as i32,
+                    BinaryOp::Addition => lhs.checked_add(rhs)?,
+                    BinaryOp::Multiplication => lhs.checked_mul(rhs)?,
+                    BinaryOp::Subtraction => lhs.checked_sub(rhs)?,
+                    BinaryOp::Division => lhs.checked_div(rhs)?,
+                    BinaryOp::Remainder => lhs.checked_rem(rhs)?,
+                    BinaryOp::LeftShift => lhs.checked_shl(rhs.try_into().ok()?)?,
+                    BinaryOp::RightShift => lhs.checked_shr(rhs.try_into().ok()?)?,
+                    BinaryOp::BitwiseXor => lhs ^ rhs,
+                    BinaryOp::BitwiseEq => !(lhs ^ rhs),
+                    BinaryOp::BitwiseOr => lhs | rhs,
+                    BinaryOp::BitwiseAnd => lhs & rhs,
+                    BinaryOp::Power => lhs.checked_pow(rhs.try_into().ok()?)?,
+                }
+            }
+            ast::Expr::SelectExpr(e) => {
+                if self.eval_expr(&e.condition()?, depth)? != 0 {
+                    self.eval_expr(&e.then_val()?, depth)?
+                } else {
+                    self.eval_expr(&e.else_val()?, depth)?
+                }
+            }
+            ast::Expr::PathExpr(e) => {
+                let name = Path::resolve(e.path()?)?.as_ident()?;
+                if let Some((_, val)) = self.genvars.iter().rev().find(|(it, _)| *it == name) {
+                    return Some(*val);
+                }
+                self.eval_param(&name, depth)?
+            }
+            _ => return None,
+        };
+        Some(res)
+    }
+
+    fn eval_param(&self, name: &Name, depth: u32) -> Option<i32> {
+        if depth >= MAX_DEPTH {
+            return None;
+        }
+        let param = (self.resolve_param)(name)?;
+        let tree = self.db.item_tree(self.root_file);
+        if !tree[param].is_local {
+            return None;
+        }
+        let default = param_default(self.db, self.root_file, &tree, param)?;
+        self.eval_expr(&default, depth + 1)
+    }
+
+    /// Returns the first parameter that can be overridden by the simulator referenced
+    /// by `expr` (directly or by the default value of a local parameter).
+    pub(crate) fn overridable_param(&self, expr: &ast::Expr) -> Option<Name> {
+        self.find_overridable_param(expr, 0)
+    }
+
+    fn find_overridable_param(&self, expr: &ast::Expr, depth: u32) -> Option<Name> {
+        if depth >= MAX_DEPTH {
+            return None;
+        }
+        let tree = self.db.item_tree(self.root_file);
+        expr.syntax().descendants().filter_map(ast::PathExpr::cast).find_map(|e| {
+            let name = Path::resolve(e.path()?)?.as_ident()?;
+            if self.genvars.iter().any(|(it, _)| *it == name) {
+                return None;
+            }
+            let param = (self.resolve_param)(&name)?;
+            if !tree[param].is_local {
+                return Some(name);
+            }
+            let default = param_default(self.db, self.root_file, &tree, param)?;
+            self.find_overridable_param(&default, depth + 1)
+        })
+    }
+}
+
+fn param_default(
+    db: &dyn HirDefDB,
+    root_file: FileId,
+    tree: &ItemTree,
+    param: ItemTreeId<Param>,
+) -> Option<ast::Expr> {
+    let ast_id_map = db.ast_id_map(root_file);
+    let root: SyntaxNode = db.parse(root_file).syntax_node();
+    ast_id_map.get(tree[param].ast_id).to_node(&root).default()
+}
+
+/// Evaluates the range of `node` if it was declared as a node array.
+/// Returns `None` for scalar nodes and `Some(Err((net, param)))` if the bounds of
+/// the declaration `net` are not constant (or too large) or if the node is a port.
+/// `param` is the overridable parameter the bounds depend on (if any).
+pub(crate) fn node_array_indices(
+    db: &dyn HirDefDB,
+    module: ModuleId,
+    node: LocalNodeId,
+) -> Option<Result<Vec<i32>, (ItemTreeId<Net>, Option<Name>)>> {
+    let loc = module.lookup(db);
+    let tree = loc.item_tree(db);
+    let module = &tree[loc.id];
+    let net = module.nodes[node].array_decl(&tree)?;
+    if module.nodes[node].is_port {
+        return Some(Err((net, None)));
+    }
+
+    let ast_id_map = db.ast_id_map(loc.scope.root_file);
+    let root: SyntaxNode = db.parse(loc.scope.root_file).syntax_node();
+    let range = ast_id_map.get(tree[net].ast_id).to_node(&root).range();
+
+    let resolve_param = |name: &Name| {
+        module.items.iter().find_map(|item| match *item {
+            ModuleItem::Parameter(param) if tree[param].name == *name => Some(param),
+            _ => None,
+        })
+    };
+    let eval = ConstEval {
+        db,
+        root_file: loc.scope.root_file,
+        resolve_param: &resolve_param,
+        genvars: &[],
+    };
+
+    let bounds = range.as_ref().and_then(|range| {
+        let start = eval.eval(&range.start()?)?;
+        let end = eval.eval(&range.end()?)?;
+        Some((start, end))
+    });
+
+    let res = match bounds {
+        Some((start, end)) if start.abs_diff(end) < MAX_ELABORATION_SIZE as u32 => {
+            Ok(range_indices(start, end).collect())
+        }
+        _ => {
+            let param = range.and_then(|range| {
+                let param = |expr: Option<ast::Expr>| eval.overridable_param(&expr?);
+                param(range.start()).or_else(|| param(range.end()))
+            });
+            Err((net, param))
+        }
+    };
+    Some(res)
+}
+
+/// Evaluates the bounds of an array variable or parameter declared in `scope`.
+/// Returns `None` if the bounds are not constant (or the array is too large).
+pub(crate) fn array_range(
+    db: &dyn HirDefDB,
+    scope: ScopeId,
+    range: &ast::Range,
+) -> Option<ArrayRange> {
+    let resolve_param = |name: &Name| {
+        let param: ParamId = scope.resolve_item_path(db, &Path::new_ident(name.clone())).ok()?;
+        Some(param.lookup(db).id)
+    };
+    let eval =
+        ConstEval { db, root_file: scope.root_file, resolve_param: &resolve_param, genvars: &[] };
+
+    let start = eval.eval(&range.start()?)?;
+    let end = eval.eval(&range.end()?)?;
+    if start.abs_diff(end) >= MAX_ELABORATION_SIZE as u32 {
+        return None;
+    }
+    Some(ArrayRange { start, end })
+}
+
+/// The name of the element `index` of the node array `base` (`base[index]`).
+pub(crate) fn node_array_element_name(base: &Name, index: i32) -> Name {
+    Name::resolve(&format!("{}[{}]", base, index))
+}
+
+/// The indices of the elements of a node array with the bounds `[start:end]`.
+/// Just like Verilog bit ranges the bounds may be given in descending order.
+pub(crate) fn range_indices(start: i32, end: i32) -> impl Iterator<Item = i32> {
+    let (lo, hi) = if start <= end { (start, end) } else { (end, start) };
+    let descending = start > end;
+    (lo..=hi).map(move |i| if descending { hi - (i - lo) } else { i })
+}
+
+/// Upper limit on the number of iterations of a `genvar` loop and the number of elements of
+/// a node array. This only protects against accidental endless loops during elaboration.
+pub(crate) const MAX_ELABORATION_SIZE: usize = 1 << 16;
@@ -5,11 +5,14 @@ use ordered_float::OrderedFloat;
 use syntax::name::Name;
 use typed_index_collections::TiSlice;
 
+use crate::const_eval::{array_range, node_array_element_name, node_array_indices};
 use crate::db::HirDefDB;
 use crate::item_tree::{self, BranchKind, DisciplineAttrKind, Domain, NatureRef};
+use crate::nameres::ScopeDefItem;
 use crate::{
-    AliasParamId, BranchId, DisciplineId, FunctionId, Intern, ItemTree, LocalFunctionArgId,
-    LocalNatureAttrId, Lookup, ModuleId, NatureId, NodeId, NodeLoc, ParamId, Path, Type, VarId,
+    AliasParamId, ArrayRange, BranchId, DisciplineId, FunctionId, Intern, ItemTree,
+    LocalFunctionArgId, LocalNatureAttrId, Lookup, ModuleId, ModuleInstId, NatureId, NodeId,
+    NodeLoc, ParamId, ParamLoc, ParamSetId, Path, Type, VarId,
 };
 
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
@@ -122,13 +125,23 @@ impl NatureData {
 pub struct VarData {
     pub name: Name,
     pub ty: Type,
+    /// The index range of array variables.
+    /// Arrays whose bounds are not constant have the type [`Type::Err`].
+    pub range: Option<ArrayRange>,
 }
 
 impl VarData {
     pub fn var_data_query(db: &dyn HirDefDB, id: VarId) -> Arc<VarData> {
         let loc = id.lookup(db);
         let var = &loc.item_tree(db)[loc.id];
-        Arc::new(VarData { name: var.name.clone(), ty: var.ty.clone() })
+        let (ty, range) = match loc.source(db).range() {
+            Some(range) => match array_range(db, loc.scope, &range) {
+                Some(range) => (var.ty.clone().to_dim(&[range.num_elements()]), Some(range)),
+                None => (Type::Err, None),
+            },
+            None => (var.ty.clone(), None),
+        };
+        Arc::new(VarData { name: var.name.clone(), ty, range })
     }
 }
 
@@ -136,13 +149,27 @@ impl VarData {
 pub struct ParamData {
     pub name: Name,
     pub ty: Option<Type>,
+    /// The index range of array parameters.
+    /// Arrays whose bounds are not constant have the type [`Type::Err`].
+    pub range: Option<ArrayRange>,
 }
 
 impl ParamData {
     pub fn param_data_query(db: &dyn HirDefDB, id: ParamId) -> Arc<ParamData> {
         let loc = id.lookup(db);
         let param = &loc.item_tree(db)[loc.id];
-        Arc::new(ParamData { name: param.name.clone(), ty: param.ty.clone() })
+        let (ty, range) = match loc.source(db).range() {
+            Some(range) => match array_range(db, loc.scope, &range) {
+                Some(range) => {
+                    // the element type of untyped parameters is inferred from their default
+                    let ty = param.ty.clone().map(|ty| ty.to_dim(&[range.num_elements()]));
+                    (ty, Some(range))
+                }
+                None => (Some(Type::Err), None),
+            },
+            None => (param.ty.clone(), None),
+        };
+        Arc::new(ParamData { name: param.name.clone(), ty, range })
     }
 }
 
@@ -162,9 +189,13 @@ impl NodeData {
         let tree = module.item_tree(db);
         let node = &tree[module.id].nodes[loc.id];
         let (is_input, is_output) = node.direction(&tree);
+        let name = match loc.index {
+            Some(index) => node_array_element_name(&node.name, index),
+            None => node.name.clone(),
+        };
 
         Arc::new(NodeData {
-            name: node.name.clone(),
+            name,
             discipline: node.discipline(&tree),
             is_input,
             is_output,
@@ -245,9 +276,128 @@ impl ModuleData {
         let item_tree = loc.item_tree(db);
         let num_ports = item_tree[loc.id].num_ports;
         let num_nodes = item_tree[loc.id].nodes.len() as u32;
-        let ports = (0..num_ports).map(|id| NodeLoc { module, id: id.into() }.intern(db)).collect();
-        let internal_nodes =
-            (num_ports..num_nodes).map(|id| NodeLoc { module, id: id.into() }.intern(db)).collect();
+        let ports = (0..num_ports)
+            .map(|id| NodeLoc { module, id: id.into(), index: None }.intern(db))
+            .collect();
+
+        // node arrays are expanded into their elements
+        let mut internal_nodes = Vec::with_capacity((num_nodes - num_ports) as usize);
+        for id in num_ports..num_nodes {
+            let id = id.into();
+            match node_array_indices(db, module, id) {
+                Some(Ok(indices)) => internal_nodes.extend(
+                    indices
+                        .into_iter()
+                        .map(|index| NodeLoc { module, id, index: Some(index) }.intern(db)),
+                ),
+                _ => internal_nodes.push(NodeLoc { module, id, index: None }.intern(db)),
+            }
+        }
         Arc::new(ModuleData { name: item_tree[loc.id].name.clone(), ports, internal_nodes })
     }
 }
+
+impl ModuleId {
+    /// Resolves the target of `$analog_node_alias` (or `$analog_port_alias` if `port` is set)
+    /// within this module. The target is the name of a node (like `b` or `b[1]`) optionally
+    /// prefixed by the names of the module instances it is declared in (like `x1.b`).
+    /// Port aliases must refer to a port. Returns `None` if the target is not declared
+    /// within this module, it then has to be resolved by the simulator.
+    pub fn resolve_node_alias(
+        self,
+        db: &dyn HirDefDB,
+        target: &str,
+        port: bool,
+    ) -> Option<NodeAliasTarget> {
+        let mut module = self;
+        let mut insts = Vec::new();
+        // the backslash of escaped identifiers is optional
+        let mut segments =
+            target.split('.').map(|segment| Name::resolve(segment.trim().trim_start_matches('\\')));
+        let mut name = segments.next()?;
+        for next in segments {
+            let scope = module.lookup(db).scope;
+            let inst = match scope.def_map(db)[scope.local_scope].declarations.get(&name) {
+                Some(&ScopeDefItem::ModuleInstId(inst)) => inst,
+                _ => return None,
+            };
+            module = db.module_inst_data(inst).module?;
+            insts.push(inst);
+            name = next;
+        }
+
+        let data = db.module_data(module);
+        let internal_nodes = if port { &[][..] } else { &data.internal_nodes[..] };
+        let node = data
+            .ports
+            .iter()
+            .chain(internal_nodes)
+            .copied()
+            .find(|&node| db.node_data(node).name == name)?;
+        Some(NodeAliasTarget { insts, node })
+    }
+}
+
+/// The node that is the target of `$analog_node_alias` or `$analog_port_alias`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeAliasTarget {
+    /// The module instances along the hierarchical name of the target (outermost first)
+    pub insts: Vec<ModuleInstId>,
+    pub node: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleInstData {
+    pub name: Name,
+    /// The instantiated module or `None` if it could not be resolved
+    pub module: Option<ModuleId>,
+    pub param_overrides: Vec<Option<Name>>,
+    pub port_connections: Vec<Option<Name>>,
+}
+
+impl ModuleInstData {
+    pub fn module_inst_data_query(db: &dyn HirDefDB, inst: ModuleInstId) -> Arc<ModuleInstData> {
+        let loc = inst.lookup(db);
+        let item_tree = loc.item_tree(db);
+        let inst = &item_tree[loc.id];
+        let def_map = db.def_map(loc.scope.root_file);
+        let module = def_map.resolve_local_item_in_scope(def_map.root(), &inst.module).ok();
+        Arc::new(ModuleInstData {
+            name: inst.name.clone(),
+            module,
+            param_overrides: inst.param_overrides.clone(),
+            port_connections: inst.port_connections.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamSetData {
+    pub name: Name,
+    /// The base module or `None` if it could not be resolved
+    pub module: Option<ModuleId>,
+    /// The parameters declared within the paramset
+    pub params: Vec<ParamId>,
+    pub overrides: Vec<Name>,
+}
+
+impl ParamSetData {
+    pub fn param_set_data_query(db: &dyn HirDefDB, paramset: ParamSetId) -> Arc<ParamSetData> {
+        let loc = paramset.lookup(db);
+        let item_tree = loc.item_tree(db);
+        let paramset = &item_tree[loc.id];
+        let def_map = db.def_map(loc.scope.root_file);
+        let module = def_map.resolve_local_item_in_scope(def_map.root(), &paramset.module).ok();
+        let params = paramset
+            .params
+            .iter()
+            .map(|&id| ParamLoc { scope: loc.scope, id }.intern(db))
+            .collect();
+        Arc::new(ParamSetData {
+            name: paramset.name.clone(),
+            module,
+            params,
+            overrides: paramset.overrides.clone(),
+        })
+    }
+}
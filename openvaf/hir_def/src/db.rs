@@ -5,8 +5,8 @@ use stdx::Upcast;
 
 use crate::body::{Body, BodySourceMap, ParamExprs};
 use crate::data::{
-    AliasParamData, BranchData, DisciplineData, FunctionData, ModuleData, NatureData, NodeData,
-    ParamData, VarData,
+    AliasParamData, BranchData, DisciplineData, FunctionData, ModuleData, ModuleInstData,
+    NatureData, NodeData, ParamData, ParamSetData, VarData,
 };
 use crate::item_tree::ndatable::NDATable;
 use crate::item_tree::ItemTree;
@@ -14,8 +14,9 @@ use crate::nameres::{DefMap, ScopeOrigin};
 use crate::{
     AliasParamId, AliasParamLoc, BlockId, BlockLoc, BranchId, BranchLoc, DefWithBodyId,
     DisciplineAttrId, DisciplineAttrLoc, DisciplineId, DisciplineLoc, FunctionArgId,
-    FunctionArgLoc, FunctionId, FunctionLoc, ModuleId, ModuleLoc, NatureAttrId, NatureAttrLoc,
-    NatureId, NatureLoc, NodeId, NodeLoc, ParamId, ParamLoc, VarId, VarLoc,
+    FunctionArgLoc, FunctionId, FunctionLoc, GenvarId, GenvarLoc, InstanceId, InstanceLoc,
+    ModuleId, ModuleInstId, ModuleInstLoc, ModuleLoc, NatureAttrId, NatureAttrLoc, NatureId,
+    NatureLoc, NodeId, NodeLoc, ParamId, ParamLoc, ParamSetId, ParamSetLoc, VarId, VarLoc,
 };
 
 #[salsa::query_group(InternDatabase)]
@@ -46,6 +47,14 @@ pub trait InternDB: BaseDB {
     fn intern_function_arg(&self, loc: FunctionArgLoc) -> FunctionArgId;
     #[salsa::interned]
     fn intern_alias_param(&self, loc: AliasParamLoc) -> AliasParamId;
+    #[salsa::interned]
+    fn intern_module_inst(&self, loc: ModuleInstLoc) -> ModuleInstId;
+    #[salsa::interned]
+    fn intern_instance(&self, loc: InstanceLoc) -> InstanceId;
+    #[salsa::interned]
+    fn intern_param_set(&self, loc: ParamSetLoc) -> ParamSetId;
+    #[salsa::interned]
+    fn intern_genvar(&self, loc: GenvarLoc) -> GenvarId;
 }
 
 #[salsa::query_group(HirDefDatabase)]
@@ -106,6 +115,12 @@ pub trait HirDefDB: InternDB + Upcast<dyn BaseDB> {
     #[salsa::invoke(ModuleData::module_data_query)]
     fn module_data(&self, module: ModuleId) -> Arc<ModuleData>;
 
+    #[salsa::invoke(ModuleInstData::module_inst_data_query)]
+    fn module_inst_data(&self, inst: ModuleInstId) -> Arc<ModuleInstData>;
+
+    #[salsa::invoke(ParamSetData::param_set_data_query)]
+    fn param_set_data(&self, paramset: ParamSetId) -> Arc<ParamSetData>;
+
     #[salsa::transparent]
     fn find_module(&self, root_file: FileId) -> ModuleId;
 }
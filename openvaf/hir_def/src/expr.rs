@@ -82,6 +82,12 @@ pub enum Expr {
         args: Vec<ExprId>,
     },
     Array(Vec<ExprId>),
+    /// `base[index]`. Constant indices into node arrays are resolved during lowering
+    /// so these only remain for indices that are not known during elaboration.
+    Index {
+        base: ExprId,
+        index: ExprId,
+    },
     Literal(Literal),
 }
 
@@ -100,6 +106,10 @@ impl Expr {
                 f(then_val);
                 f(else_val);
             }
+            Expr::Index { base, index } => {
+                f(base);
+                f(index);
+            }
             Expr::Call { args: ref exprs, .. } | Expr::Array(ref exprs) => {
                 for e in exprs {
                     f(*e)
@@ -139,11 +149,26 @@ pub enum GlobalEvent {
     FinalStep,
 }
 
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum MonitoredEventKind {
+    Cross,
+    Above,
+    Timer,
+}
+
+/// A `cross`, `above` or `timer` event. Multiple events may be combined with `or`.
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub struct MonitoredEvent {
+    pub kind: MonitoredEventKind,
+    pub args: Vec<ExprId>,
+}
+
 // non_exhaustive because currently the full standard is not implemented
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
 #[non_exhaustive]
 pub enum Event {
     Global { kind: GlobalEvent, phases: Vec<String> },
+    Monitored(Vec<MonitoredEvent>),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -162,7 +187,17 @@ impl Stmt {
     #[inline]
     pub fn walk_child_exprs(&self, mut f: impl FnMut(ExprId)) {
         match *self {
-            Stmt::Empty | Stmt::Missing | Stmt::Block { .. } | Stmt::EventControl { .. } => (),
+            Stmt::Empty
+            | Stmt::Missing
+            | Stmt::Block { .. }
+            | Stmt::EventControl { event: Event::Global { .. }, .. } => (),
+            Stmt::EventControl { event: Event::Monitored(ref events), .. } => {
+                for event in events {
+                    for arg in &event.args {
+                        f(*arg)
+                    }
+                }
+            }
             Stmt::If { cond: expr, .. }
             | Stmt::ForLoop { cond: expr, .. }
             | Stmt::WhileLoop { cond: expr, .. }
@@ -73,6 +73,9 @@ impl ItemTree {
             ports,
             branches,
             functions,
+            module_insts,
+            paramsets,
+            genvars,
         } = &mut self.data;
         modules.shrink_to_fit();
         disciplines.shrink_to_fit();
@@ -85,6 +88,9 @@ impl ItemTree {
         ports.shrink_to_fit();
         branches.shrink_to_fit();
         functions.shrink_to_fit();
+        module_insts.shrink_to_fit();
+        paramsets.shrink_to_fit();
+        genvars.shrink_to_fit();
         nature_attrs.shrink_to_fit();
         discipline_attrs.shrink_to_fit();
     }
@@ -109,6 +115,9 @@ pub struct ItemTreeData {
     pub ports: Arena<Port>,
     pub branches: Arena<Branch>,
     pub functions: Arena<Function>,
+    pub module_insts: Arena<ModuleInst>,
+    pub paramsets: Arena<ParamSet>,
+    pub genvars: Arena<Genvar>,
 }
 
 /// Trait implemented by all item nodes in the item tree.
@@ -136,12 +145,14 @@ pub enum RootItem {
     Module(ItemTreeId<Module>),
     Nature(ItemTreeId<Nature>),
     Discipline(ItemTreeId<Discipline>),
+    ParamSet(ItemTreeId<ParamSet>),
 }
 
 impl_from_typed! (
     Module(ItemTreeId<Module>),
     Nature(ItemTreeId<Nature>),
-    Discipline(ItemTreeId<Discipline>) for RootItem
+    Discipline(ItemTreeId<Discipline>),
+    ParamSet(ItemTreeId<ParamSet>) for RootItem
 );
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -220,6 +231,7 @@ item_tree_nodes! {
     Module in modules -> ast::ModuleDecl,
     Discipline in disciplines -> ast::DisciplineDecl,
     Nature in natures -> ast::NatureDecl,
+    ParamSet in paramsets -> ast::ParamSetDecl,
 
     Var in variables -> ast::Var,
     Param in parameters -> ast::Param,
@@ -228,6 +240,8 @@ item_tree_nodes! {
     Port in ports -> ast::PortDecl,
     Branch in branches -> ast::BranchDecl,
     Function in functions -> ast::Function,
+    ModuleInst in module_insts -> ast::ModuleInst,
+    Genvar in genvars -> ast::GenvarDecl,
     NatureAttr in nature_attrs -> ast::NatureAttr,
     DisciplineAttr in discipline_attrs -> ast::DisciplineAttr,
 }
@@ -250,6 +264,8 @@ pub enum ModuleItem {
     Branch(ItemTreeId<Branch>),
     Node(LocalNodeId),
     Function(ItemTreeId<Function>),
+    ModuleInst(ItemTreeId<ModuleInst>),
+    Genvar(ItemTreeId<Genvar>),
 }
 
 impl_from_typed! (
@@ -259,7 +275,9 @@ impl_from_typed! (
     Variable(ItemTreeId<Var>),
     Branch(ItemTreeId<Branch>),
     Node(LocalNodeId),
-    Function(ItemTreeId<Function>) for ModuleItem
+    Function(ItemTreeId<Function>),
+    ModuleInst(ItemTreeId<ModuleInst>),
+    Genvar(ItemTreeId<Genvar>) for ModuleItem
 );
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -279,6 +297,9 @@ pub struct Net {
     pub name: Name,
     pub discipline: Option<Name>,
     pub is_gnd: bool,
+    /// The net was declared with a range (`electrical [0:N-1] n;`).
+    /// The bounds are evaluated during name resolution.
+    pub is_array: bool,
 
     pub name_idx: usize,
     pub ast_id: AstId<ast::NetDecl>,
@@ -388,6 +409,38 @@ pub struct Branch {
     pub ast_id: AstId<ast::BranchDecl>,
 }
 
+/// An instance of another module (`res #(.r(1k)) r1(a, b);`).
+/// Overrides and connections are either named (`.name(expr)`) or ordered (`None`).
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ModuleInst {
+    pub name: Name,
+    pub module: Name,
+    pub param_overrides: Vec<Option<Name>>,
+    pub port_connections: Vec<Option<Name>>,
+    pub ast_id: AstId<ast::ModuleInst>,
+}
+
+/// A paramset (`paramset short nmos; ... endparamset`) that overrides the parameters
+/// of its base module. `overrides` lists the names of the base module parameters
+/// that are assigned (`.name = expr;`) in the order they appear in.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ParamSet {
+    pub name: Name,
+    pub module: Name,
+    pub params: Vec<ItemTreeId<Param>>,
+    pub overrides: Vec<Name>,
+    pub ast_id: AstId<ast::ParamSetDecl>,
+}
+
+/// A `genvar` declaration. Genvars may only be used as the loop variable of `for` loops
+/// that are unrolled during elaboration.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Genvar {
+    pub name: Name,
+    pub name_idx: usize,
+    pub ast_id: AstId<ast::GenvarDecl>,
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Block {
     pub name: Option<Name>,
@@ -467,6 +520,13 @@ impl NodeTypeDecl {
         }
     }
 
+    pub fn array_net(self, tree: &ItemTree) -> Option<ItemTreeId<Net>> {
+        match self {
+            NodeTypeDecl::Net(net) if tree[net].is_array => Some(net),
+            _ => None,
+        }
+    }
+
     pub fn direction(self, tree: &ItemTree) -> Option<(bool, bool)> {
         match self {
             NodeTypeDecl::Port(port) => Some((tree[port].is_input, tree[port].is_output)),
@@ -524,4 +584,9 @@ impl Node {
     pub fn discipline(&self, tree: &ItemTree) -> Option<Name> {
         self.decls.iter().find_map(|decl| decl.discipline(tree).clone())
     }
+
+    /// The declaration that turns this node into a node array (if any)
+    pub fn array_decl(&self, tree: &ItemTree) -> Option<ItemTreeId<Net>> {
+        self.decls.iter().find_map(|decl| decl.array_net(tree))
+    }
 }
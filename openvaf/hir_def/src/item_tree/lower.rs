@@ -11,8 +11,8 @@ use typed_index_collections::TiVec;
 
 use super::{
     Block, Branch, BranchKind, Discipline, DisciplineAttr, DisciplineAttrKind, Domain, Function,
-    FunctionArg, FunctionItem, ItemTree, ItemTreeId, Module, ModuleItem, Nature, NatureAttr,
-    NatureRef, NatureRefKind, Net, Node, Param, Port, RootItem, Var,
+    FunctionArg, FunctionItem, Genvar, ItemTree, ItemTreeId, Module, ModuleInst, ModuleItem,
+    Nature, NatureAttr, NatureRef, NatureRefKind, Net, Node, Param, ParamSet, Port, RootItem, Var,
 };
 // use tracing::trace;
 use crate::db::HirDefDB;
@@ -48,6 +48,7 @@ impl Ctx {
             ast::Item::DisciplineDecl(discipline) => self.lower_discipline(discipline)?.into(),
             ast::Item::NatureDecl(nature) => self.lower_nature(nature)?.into(),
             ast::Item::ModuleDecl(module) => self.lower_module(module)?.into(),
+            ast::Item::ParamSetDecl(paramset) => self.lower_paramset(paramset)?.into(),
         };
         Some(item)
     }
@@ -268,6 +269,28 @@ impl Ctx {
         Some(self.tree.data.modules.push_and_get_key(res))
     }
 
+    fn lower_paramset(&mut self, decl: ast::ParamSetDecl) -> Option<ItemTreeId<ParamSet>> {
+        let name = decl.name()?.as_name();
+        let module = decl.module()?.as_name();
+        let ast_id = self.source_ast_id_map.ast_id(&decl);
+
+        let mut params = Vec::new();
+        let mut overrides = Vec::new();
+        for item in decl.param_set_items() {
+            match item {
+                ast::ParamSetItem::ParamDecl(param) => self.lower_param(param, &mut params),
+                ast::ParamSetItem::ParamSetAssign(assign) => {
+                    if let Some(name) = assign.name_ref() {
+                        overrides.push(name.as_name())
+                    }
+                }
+            }
+        }
+
+        let res = ParamSet { name, module, params, overrides, ast_id };
+        Some(self.tree.data.paramsets.push_and_get_key(res))
+    }
+
     fn lower_module_items(
         &mut self,
         items: ast::AstChildren<ast::ModuleItem>,
@@ -300,6 +323,8 @@ impl Ctx {
                 }
                 ast::ModuleItem::BranchDecl(branch) => self.lower_branch(branch, dst),
                 ast::ModuleItem::AliasParam(alias) => self.lower_alias_param(alias, dst),
+                ast::ModuleItem::ModuleInstDecl(decl) => self.lower_module_inst_decl(decl, dst),
+                ast::ModuleItem::GenvarDecl(decl) => self.lower_genvar_decl(decl, dst),
             };
         }
     }
@@ -384,6 +409,46 @@ impl Ctx {
         }
     }
 
+    fn lower_module_inst_decl(&mut self, decl: ast::ModuleInstDecl, dst: &mut Vec<ModuleItem>) {
+        let module = match decl.module() {
+            Some(module) => module.as_name(),
+            None => return,
+        };
+        let param_overrides: Vec<_> = decl
+            .param_overrides()
+            .into_iter()
+            .flat_map(|overrides| overrides.overrides())
+            .map(|param| param.name_ref().map(|name| name.as_name()))
+            .collect();
+
+        for inst in decl.module_insts() {
+            let name = match inst.name() {
+                Some(name) => name.as_name(),
+                None => continue,
+            };
+            let port_connections =
+                inst.connections().map(|port| port.name_ref().map(|name| name.as_name())).collect();
+            let inst = ModuleInst {
+                name,
+                module: module.clone(),
+                param_overrides: param_overrides.clone(),
+                port_connections,
+                ast_id: self.source_ast_id_map.ast_id(&inst),
+            };
+            let inst = self.tree.data.module_insts.push_and_get_key(inst);
+            dst.push(inst.into())
+        }
+    }
+
+    fn lower_genvar_decl(&mut self, decl: ast::GenvarDecl, dst: &mut Vec<ModuleItem>) {
+        let ast_id = self.source_ast_id_map.ast_id(&decl);
+        for (name_idx, name) in decl.names().enumerate() {
+            let genvar = Genvar { name: name.as_name(), name_idx, ast_id };
+            let id = self.tree.data.genvars.push_and_get_key(genvar);
+            dst.push(id.into());
+        }
+    }
+
     fn lower_module_ports(
         &mut self,
         ports: ast::ModulePorts,
@@ -460,6 +525,7 @@ impl Ctx {
         let ast_id = self.source_ast_id_map.ast_id(&decl);
 
         let is_gnd = decl.net_type_token().map_or(false, |it| it.text() == kw::raw::ground);
+        let is_array = decl.range().is_some();
         for (name_idx, name) in decl.names().enumerate() {
             let name = name.as_name();
             let id = self.tree.data.nets.push_and_get_key(Net {
@@ -467,6 +533,7 @@ impl Ctx {
                 discipline: discipline.clone(),
                 ast_id,
                 is_gnd,
+                is_array,
                 name_idx,
             });
 
@@ -5,7 +5,7 @@ use syntax::ast;
 
 use super::{
     BlockScopeItem, Discipline, Function, FunctionItem, ItemTreeId, Module, ModuleItem, Nature,
-    Param, Var,
+    Param, ParamSet, Var,
 };
 use crate::ItemTree;
 
@@ -64,6 +64,20 @@ impl<'a> Printer<'a> {
             wln!(self, "module {}", module.name);
             self.indented(|s| s.print_module(module))
         }
+
+        for paramset in &self.tree.data.paramsets {
+            wln!(self, "paramset {} {}", paramset.name, paramset.module);
+            self.indented(|s| s.print_paramset(paramset))
+        }
+    }
+
+    fn print_paramset(&mut self, paramset: &ParamSet) {
+        for &param in &paramset.params {
+            self.print_parameter(param)
+        }
+        for name in &paramset.overrides {
+            wln!(self, ".{} = ...", name)
+        }
     }
 
     fn print_nature_attrs(&mut self, nature: &Nature) {
@@ -124,6 +138,20 @@ impl<'a> Printer<'a> {
                     let param = &self.tree[param];
                     wln!(self, "aliasparam {} = {:?}", param.name, param.src);
                 }
+                ModuleItem::ModuleInst(inst) => {
+                    let inst = &self.tree[inst];
+                    wln!(
+                        self,
+                        "{} #{:?} {} {:?}",
+                        inst.module,
+                        inst.param_overrides,
+                        inst.name,
+                        inst.port_connections
+                    );
+                }
+                ModuleItem::Genvar(genvar) => {
+                    wln!(self, "genvar {}", self.tree[genvar].name);
+                }
             }
         }
     }
@@ -1,5 +1,6 @@
 pub mod body;
 mod builtin;
+mod const_eval;
 mod data;
 pub mod db;
 pub mod expr;
@@ -25,16 +26,17 @@ use syntax::name::Name;
 use syntax::{AstNode, AstPtr};
 
 pub use crate::builtin::{BuiltIn, ParamSysFun};
-pub use crate::data::FunctionArg;
+pub use crate::data::{FunctionArg, NodeAliasTarget};
 use crate::db::HirDefDB;
 pub use crate::expr::{Case, Expr, ExprId, Literal, Stmt, StmtId};
 pub use crate::item_tree::{
-    AliasParam, Branch, BranchKind, Discipline, DisciplineAttr, Function, ItemTree, ItemTreeId,
-    ItemTreeNode, Module, Nature, NatureAttr, NatureRef, NatureRefKind, NodeTypeDecl, Param, Var,
+    AliasParam, Branch, BranchKind, Discipline, DisciplineAttr, Function, Genvar, ItemTree,
+    ItemTreeId, ItemTreeNode, Module, ModuleInst, Nature, NatureAttr, NatureRef, NatureRefKind,
+    NodeTypeDecl, Param, ParamSet, Var,
 };
 use crate::nameres::ScopeDefItem;
 pub use crate::path::Path;
-pub use crate::types::Type;
+pub use crate::types::{ArrayRange, Type};
 
 impl ParamSysFun {
     pub fn default_value(self) -> f64 {
@@ -294,10 +296,31 @@ impl_intern!(AliasParamId, AliasParamLoc, intern_alias_param, lookup_intern_alia
 pub type FunctionLoc = ItemLoc<Function>;
 impl_intern!(FunctionId, FunctionLoc, intern_function, lookup_intern_function);
 
+pub type ModuleInstLoc = ItemLoc<ModuleInst>;
+impl_intern!(ModuleInstId, ModuleInstLoc, intern_module_inst, lookup_intern_module_inst);
+
+/// A (possibly nested) module instance. Instances are identified by their path from the
+/// top-level module so that a module instantiated multiple times receives distinct ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InstanceLoc {
+    pub parent: Option<InstanceId>,
+    pub inst: ModuleInstId,
+}
+
+impl_intern!(InstanceId, InstanceLoc, intern_instance, lookup_intern_instance);
+
+pub type ParamSetLoc = ItemLoc<ParamSet>;
+impl_intern!(ParamSetId, ParamSetLoc, intern_param_set, lookup_intern_param_set);
+
+pub type GenvarLoc = ItemLoc<Genvar>;
+impl_intern!(GenvarId, GenvarLoc, intern_genvar, lookup_intern_genvar);
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct NodeLoc {
     pub module: ModuleId,
     pub id: LocalNodeId,
+    /// The index of this node within a node array (`n[3]`)
+    pub index: Option<i32>,
 }
 
 pub type LocalNodeId = Idx<Node>;
@@ -413,6 +436,8 @@ pub enum DefWithBodyId {
     VarId(VarId),
     NatureAttrId(NatureAttrId),
     DisciplineAttrId(DisciplineAttrId),
+    ModuleInstId(ModuleInstId),
+    ParamSetId(ParamSetId),
 }
 
 impl DefWithBodyId {
@@ -424,11 +449,13 @@ impl DefWithBodyId {
             DefWithBodyId::VarId(id) => id.lookup(db).scope.root_file,
             DefWithBodyId::NatureAttrId(id) => id.lookup(db).nature.lookup(db).root_file,
             DefWithBodyId::DisciplineAttrId(id) => id.lookup(db).discipline.lookup(db).root_file,
+            DefWithBodyId::ModuleInstId(id) => id.lookup(db).scope.root_file,
+            DefWithBodyId::ParamSetId(id) => id.lookup(db).scope.root_file,
         }
     }
 }
 
-impl_from!(ParamId, FunctionId,VarId,NatureAttrId,DisciplineAttrId for DefWithBodyId);
+impl_from!(ParamId, FunctionId,VarId,NatureAttrId,DisciplineAttrId,ModuleInstId,ParamSetId for DefWithBodyId);
 impl TryFrom<ScopeDefItem> for DefWithBodyId {
     type Error = ();
     fn try_from(src: ScopeDefItem) -> Result<DefWithBodyId, ()> {
@@ -437,6 +464,8 @@ impl TryFrom<ScopeDefItem> for DefWithBodyId {
             ScopeDefItem::ParamId(param) => param.into(),
             ScopeDefItem::FunctionId(fun) => fun.into(),
             ScopeDefItem::NatureAttrId(attr) => attr.into(),
+            ScopeDefItem::ModuleInstId(inst) => inst.into(),
+            ScopeDefItem::ParamSetId(paramset) => paramset.into(),
             _ => return Err(()),
         };
         Ok(res)
@@ -16,8 +16,8 @@ use crate::builtin::{insert_builtin_scope, BuiltIn, ParamSysFun};
 use crate::db::HirDefDB;
 use crate::nameres::diagnostics::PathResolveError;
 use crate::{
-    AliasParamId, BlockId, BranchId, DisciplineId, FunctionArgId, FunctionId, Lookup, ModuleId,
-    NatureAttrId, NatureId, NodeId, ParamId, VarId,
+    AliasParamId, BlockId, BranchId, DisciplineId, FunctionArgId, FunctionId, GenvarId, Lookup,
+    ModuleId, ModuleInstId, NatureAttrId, NatureId, NodeId, ParamId, ParamSetId, VarId,
 };
 
 mod collect;
@@ -37,6 +37,9 @@ pub struct DefMap {
     scopes: Arena<Scope>,
     root_scope: LocalScopeId,
     pub diagnostics: Vec<DefDiagnostic>,
+    /// Paramsets that share their name with an earlier paramset (in declaration order),
+    /// indexed by the first paramset with that name. Only the first paramset is declared.
+    pub paramset_overloads: IndexMap<ParamSetId, Vec<ParamSetId>, BuildHasherDefault<FxHasher>>,
 }
 
 impl Index<LocalScopeId> for DefMap {
@@ -91,6 +94,9 @@ pub enum ScopeDefItem {
     FunctionReturn(FunctionId),
     FunctionArgId(FunctionArgId),
     NatureAttrId(NatureAttrId),
+    ModuleInstId(ModuleInstId),
+    ParamSetId(ParamSetId),
+    GenvarId(GenvarId),
 }
 
 impl ScopeDefItem {
@@ -112,6 +118,9 @@ impl ScopeDefItem {
             ScopeDefItem::BuiltIn(_) | ScopeDefItem::ParamSysFun(_) => return None,
             ScopeDefItem::AliasParamId(id) => id.lookup(db).ast_id(db).into(),
             ScopeDefItem::NatureAttrId(id) => id.lookup(db).ast_id(db).into(),
+            ScopeDefItem::ModuleInstId(id) => id.lookup(db).ast_id(db).into(),
+            ScopeDefItem::ParamSetId(id) => id.lookup(db).ast_id(db).into(),
+            ScopeDefItem::GenvarId(id) => id.lookup(db).ast_id(db).into(),
         };
         Some(id)
     }
@@ -188,6 +197,29 @@ impl ScopeDefItem {
             ScopeDefItem::BuiltIn(_) | ScopeDefItem::ParamSysFun(_) => return None,
             ScopeDefItem::AliasParamId(id) => ast_id_map.get(id.lookup(db).ast_id(db)).range(),
             ScopeDefItem::NatureAttrId(id) => ast_id_map.get(id.lookup(db).ast_id(db)).range(),
+            ScopeDefItem::ModuleInstId(id) => ast_id_map
+                .get(id.lookup(db).ast_id(db))
+                .to_node(parse.tree().syntax())
+                .name()?
+                .syntax()
+                .text_range(),
+            ScopeDefItem::ParamSetId(id) => ast_id_map
+                .get(id.lookup(db).ast_id(db))
+                .to_node(parse.tree().syntax())
+                .name()?
+                .syntax()
+                .text_range(),
+            ScopeDefItem::GenvarId(genvar) => {
+                let genvar = genvar.lookup(db);
+                let pos = genvar.item_tree(db)[genvar.id].name_idx;
+                ast_id_map
+                    .get(genvar.ast_id(db))
+                    .to_node(parse.tree().syntax())
+                    .names()
+                    .nth(pos)?
+                    .syntax()
+                    .text_range()
+            }
         };
 
         Some(res)
@@ -210,7 +242,10 @@ impl_from! {
     ParamSysFun,
     // DisciplineAttrId,
     FunctionArgId,
-    BuiltIn
+    BuiltIn,
+    ModuleInstId,
+    ParamSetId,
+    GenvarId
 
     for ScopeDefItem
 }
@@ -249,7 +284,10 @@ scope_item_kinds! {
     BranchId => "branch",
     FunctionId => "function",
     BuiltIn => "function",
-    FunctionArgId => "function argument"
+    FunctionArgId => "function argument",
+    ModuleInstId => "module instance",
+    ParamSetId => "paramset",
+    GenvarId => "genvar"
 }
 
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
@@ -260,6 +298,7 @@ pub enum ScopeOrigin {
     // Discipline(DisciplineId),
     Block(BlockId),
     Function(FunctionId),
+    ParamSet(ParamSetId),
 }
 
 pub type LocalScopeId = Idx<Scope>;
@@ -269,7 +308,8 @@ impl_from_typed! {
     // Nature(NatureId),
     // Discipline(DisciplineId),
     Block(BlockId),
-    Function(FunctionId)
+    Function(FunctionId),
+    ParamSet(ParamSetId)
     for ScopeOrigin
 }
 
@@ -9,14 +9,15 @@ use syntax::name::Name;
 use super::diagnostics::DefDiagnostic;
 use super::{DefMap, DefMapSource, LocalScopeId, Scope, ScopeDefItem, ScopeOrigin};
 use crate::builtin::insert_module_builtin_scope;
+use crate::const_eval::{node_array_element_name, node_array_indices};
 use crate::db::HirDefDB;
 use crate::item_tree::{
     BlockScopeItem, Function, FunctionItem, ItemTree, ItemTreeId, ItemTreeNode, Module, ModuleItem,
-    RootItem,
+    ParamSet, RootItem,
 };
 use crate::{
     BlockId, BlockLoc, DisciplineLoc, FunctionArgLoc, FunctionId, FunctionLoc, Intern, ItemLoc,
-    Lookup, ModuleLoc, NatureAttrLoc, NatureLoc, NodeLoc, ScopeId,
+    Lookup, ModuleId, ModuleLoc, NatureAttrLoc, NatureLoc, NodeLoc, ParamSetLoc, ScopeId,
 };
 
 pub fn collect_root_def_map(db: &dyn HirDefDB, root_file: FileId) -> Arc<DefMap> {
@@ -30,6 +31,7 @@ pub fn collect_root_def_map(db: &dyn HirDefDB, root_file: FileId) -> Arc<DefMap>
             root_scope: LocalScopeId::from(0u32),
             src: DefMapSource::Root,
             diagnostics: Vec::new(),
+            paramset_overloads: IndexMap::default(),
         },
         tree,
         db,
@@ -52,6 +54,7 @@ pub fn collect_function_map(db: &dyn HirDefDB, function: FunctionId) -> Arc<DefM
             src: DefMapSource::Function(function),
             root_scope: LocalScopeId::from(0u32), // This will be changed once the scope has been created
             diagnostics: Vec::new(),
+            paramset_overloads: IndexMap::default(),
         },
         tree,
         db,
@@ -79,6 +82,7 @@ pub fn collect_block_map(db: &dyn HirDefDB, block: BlockId) -> Option<Arc<DefMap
             src: DefMapSource::Block(block),
             root_scope: LocalScopeId::from(0u32),
             diagnostics: Vec::new(),
+            paramset_overloads: IndexMap::default(),
         },
         tree,
         db,
@@ -225,6 +229,7 @@ impl DefCollector<'_> {
         for item in &*self.tree.top_level {
             match *item {
                 RootItem::Module(module) => self.collect_module(module, root_scope),
+                RootItem::ParamSet(paramset) => self.collect_paramset(paramset, root_scope),
                 RootItem::Nature(nature) => {
                     let id = NatureLoc { root_file: self.root_file, id: nature }.intern(self.db);
                     self.insert_decl(root_scope, self.tree[nature].name.clone(), id);
@@ -247,6 +252,9 @@ impl DefCollector<'_> {
                 }
             }
         }
+
+        self.check_module_insts(root_scope);
+        self.check_paramsets(root_scope);
     }
 
     fn collect_module(&mut self, item_tree: ItemTreeId<Module>, parent_scope: LocalScopeId) {
@@ -262,11 +270,29 @@ impl DefCollector<'_> {
             match *item {
                 ModuleItem::Scope(ast) => self.collect_block_scope(scope, ast),
 
-                ModuleItem::Node(id) => self.insert_decl(
-                    scope,
-                    module.nodes[id].name.clone(),
-                    NodeLoc { module: module_id, id }.intern(self.db),
-                ),
+                ModuleItem::Node(id) => {
+                    let name = &module.nodes[id].name;
+                    match node_array_indices(self.db, module_id, id) {
+                        Some(Ok(indices)) => {
+                            for index in indices {
+                                let node = NodeLoc { module: module_id, id, index: Some(index) };
+                                let name = node_array_element_name(name, index);
+                                self.insert_decl(scope, name, node.intern(self.db))
+                            }
+                        }
+                        res => {
+                            if let Some(Err((net, param))) = res {
+                                self.map.diagnostics.push(DefDiagnostic::InvalidNodeArrayRange {
+                                    decl: self.tree[net].ast_id,
+                                    name: name.clone(),
+                                    param,
+                                })
+                            }
+                            let node = NodeLoc { module: module_id, id, index: None };
+                            self.insert_decl(scope, name.clone(), node.intern(self.db))
+                        }
+                    }
+                }
                 ModuleItem::Branch(id) => {
                     self.insert_item_decl(scope, self.tree[id].name.clone(), id)
                 }
@@ -282,6 +308,120 @@ impl DefCollector<'_> {
                 ModuleItem::AliasParameter(id) => {
                     self.insert_item_decl(scope, self.tree[id].name.clone(), id)
                 }
+                ModuleItem::ModuleInst(id) => {
+                    self.insert_item_decl(scope, self.tree[id].name.clone(), id)
+                }
+                ModuleItem::Genvar(id) => {
+                    self.insert_item_decl(scope, self.tree[id].name.clone(), id)
+                }
+            }
+        }
+    }
+
+    fn collect_paramset(&mut self, item_tree: ItemTreeId<ParamSet>, parent_scope: LocalScopeId) {
+        let paramset_id = ParamSetLoc { id: item_tree, scope: self.next_scope() }.intern(self.db);
+
+        let scope = self.new_scope(ScopeOrigin::ParamSet(paramset_id), parent_scope);
+        let paramset = &self.tree[item_tree];
+
+        // paramsets with the same name overload the first paramset with that name:
+        // the simulator selects the first of them whose parameters are within their ranges
+        match self.map.scopes[parent_scope].declarations.get(&paramset.name) {
+            Some(&ScopeDefItem::ParamSetId(first)) => {
+                self.map.paramset_overloads.entry(first).or_default().push(paramset_id)
+            }
+            _ => self.insert_scope(parent_scope, scope, paramset.name.clone(), paramset_id),
+        }
+        for &param in &paramset.params {
+            self.insert_item_decl(scope, self.tree[param].name.clone(), param)
+        }
+    }
+
+    /// Resolves the base modules of all paramsets and ensures that overloaded
+    /// paramsets share the base module and parameters of the paramset they overload.
+    fn check_paramsets(&mut self, root_scope: LocalScopeId) {
+        let paramsets: Vec<_> = self.map[root_scope]
+            .declarations
+            .values()
+            .filter_map(|decl| match *decl {
+                ScopeDefItem::ParamSetId(paramset) => Some(paramset),
+                _ => None,
+            })
+            .collect();
+
+        for paramset in paramsets {
+            let first = &self.tree[paramset.lookup(self.db).id];
+            if let Err(err) =
+                self.map.resolve_local_item_in_scope::<ModuleId>(root_scope, &first.module)
+            {
+                self.map.diagnostics.push(DefDiagnostic::UnresolvedParamSetModule { paramset, err })
+            }
+
+            let params: Vec<_> = first.params.iter().map(|&param| &self.tree[param].name).collect();
+            for &overload in self.map.paramset_overloads.get(&paramset).into_iter().flatten() {
+                let overload_item = &self.tree[overload.lookup(self.db).id];
+                let same_params = overload_item.params.len() == params.len()
+                    && overload_item
+                        .params
+                        .iter()
+                        .all(|&param| params.contains(&&self.tree[param].name));
+                if overload_item.module != first.module || !same_params {
+                    self.map.diagnostics.push(DefDiagnostic::ParamSetOverloadMismatch {
+                        paramset: overload,
+                        name: first.name.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Resolves the modules of all module instances. Instances of unknown modules and
+    /// (indirectly) recursive instantiations can not be flattened and are reported here.
+    fn check_module_insts(&mut self, root_scope: LocalScopeId) {
+        let mut insts = Vec::new();
+        for &scope in self.map[root_scope].children.values() {
+            let parent = match self.map[scope].origin {
+                ScopeOrigin::Module(module) => module,
+                _ => continue,
+            };
+            for decl in self.map[scope].declarations.values() {
+                if let ScopeDefItem::ModuleInstId(inst) = *decl {
+                    insts.push((parent, inst));
+                }
+            }
+        }
+
+        let mut resolved = Vec::with_capacity(insts.len());
+        for (parent, inst) in insts {
+            let name = &self.tree[inst.lookup(self.db).id].module;
+            match self.map.resolve_local_item_in_scope::<ModuleId>(root_scope, name) {
+                Ok(module) => resolved.push((parent, inst, module)),
+                Err(err) => {
+                    self.map.diagnostics.push(DefDiagnostic::UnresolvedModule { inst, err })
+                }
+            }
+        }
+
+        for &(parent, inst, module) in &resolved {
+            let mut stack = vec![module];
+            let mut visited = Vec::new();
+            while let Some(module) = stack.pop() {
+                if module == parent {
+                    self.map.diagnostics.push(DefDiagnostic::RecursiveModuleInst {
+                        inst,
+                        module: self.tree[inst.lookup(self.db).id].module.clone(),
+                    });
+                    break;
+                }
+                if !visited.contains(&module) {
+                    visited.push(module);
+                    stack.extend(
+                        resolved
+                            .iter()
+                            .filter(|(parent, _, _)| *parent == module)
+                            .map(|(_, _, child)| *child),
+                    );
+                }
             }
         }
     }
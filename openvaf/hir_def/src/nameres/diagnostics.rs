@@ -1,14 +1,15 @@
 use std::ops::Deref;
 
 use basedb::diagnostics::{Diagnostic, Label, LabelStyle, Report};
-use basedb::{AstIdMap, BaseDB, FileId};
+use basedb::{AstId, AstIdMap, BaseDB, FileId};
 use stdx::{impl_display, pretty};
 use syntax::name::Name;
 use syntax::sourcemap::{FileSpan, SourceMap};
-use syntax::{Parse, SourceFile};
+use syntax::{ast, AstNode, Parse, SourceFile, TextRange};
 
 use super::{ResolvedPath, ScopeDefItem};
 use crate::db::HirDefDB;
+use crate::{Lookup, ModuleInstId, ParamSetId};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PathResolveError {
@@ -47,6 +48,11 @@ impl PathResolveError {
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum DefDiagnostic {
     AlreadyDeclared { old: ScopeDefItem, new: ScopeDefItem, name: Name },
+    UnresolvedModule { inst: ModuleInstId, err: PathResolveError },
+    RecursiveModuleInst { inst: ModuleInstId, module: Name },
+    UnresolvedParamSetModule { paramset: ParamSetId, err: PathResolveError },
+    ParamSetOverloadMismatch { paramset: ParamSetId, name: Name },
+    InvalidNodeArrayRange { decl: AstId<ast::NetDecl>, name: Name, param: Option<Name> },
 }
 
 pub struct DefDiagnosticWrapped<'a> {
@@ -86,6 +92,113 @@ impl Diagnostic for DefDiagnosticWrapped<'_> {
                     .with_message(format!("'{}' was already declared in this scope", name))
                     .with_labels(labels)
             }
+            DefDiagnostic::UnresolvedModule { inst, err } => {
+                let FileSpan { range, file } =
+                    self.parse.to_file_span(self.module_name_range(*inst), self.sm);
+                Report::error().with_message(err.to_string()).with_labels(vec![Label {
+                    style: LabelStyle::Primary,
+                    file_id: file,
+                    range: range.into(),
+                    message: err.message(),
+                }])
+            }
+            DefDiagnostic::RecursiveModuleInst { inst, module } => {
+                let FileSpan { range, file } =
+                    self.parse.to_file_span(self.module_name_range(*inst), self.sm);
+                Report::error()
+                    .with_message(format!("module '{}' is instantiated recursively", module))
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: file,
+                        range: range.into(),
+                        message: "recursive module instance".to_owned(),
+                    }])
+                    .with_notes(vec![
+                        "help: module instances are flattened into a single model".to_owned()
+                    ])
+            }
+            DefDiagnostic::UnresolvedParamSetModule { paramset, err } => {
+                let paramset = self.ast_id_map.get(paramset.lookup(self.db).ast_id(self.db));
+                let paramset = paramset.to_node(self.parse.tree().syntax());
+                let range = paramset
+                    .module()
+                    .map_or_else(|| paramset.syntax().text_range(), |it| it.syntax().text_range());
+                let FileSpan { range, file } = self.parse.to_file_span(range, self.sm);
+                Report::error().with_message(err.to_string()).with_labels(vec![Label {
+                    style: LabelStyle::Primary,
+                    file_id: file,
+                    range: range.into(),
+                    message: err.message(),
+                }])
+            }
+            DefDiagnostic::ParamSetOverloadMismatch { paramset, name } => {
+                let paramset = self.ast_id_map.get(paramset.lookup(self.db).ast_id(self.db));
+                let paramset = paramset.to_node(self.parse.tree().syntax());
+                let range = paramset
+                    .name()
+                    .map_or_else(|| paramset.syntax().text_range(), |it| it.syntax().text_range());
+                let FileSpan { range, file } = self.parse.to_file_span(range, self.sm);
+                Report::error()
+                    .with_message(format!(
+                        "overloaded paramset '{}' does not match its first declaration",
+                        name
+                    ))
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: file,
+                        range: range.into(),
+                        message: "overloaded here".to_owned(),
+                    }])
+                    .with_notes(vec![
+                        "help: all paramsets with the same name must use the same module and declare the same parameters"
+                            .to_owned(),
+                    ])
+            }
+            DefDiagnostic::InvalidNodeArrayRange { decl, name, param } => {
+                let decl = self.ast_id_map.get(*decl).to_node(self.parse.tree().syntax());
+                let range = decl
+                    .range()
+                    .map_or_else(|| decl.syntax().text_range(), |it| it.syntax().text_range());
+                let FileSpan { range, file } = self.parse.to_file_span(range, self.sm);
+                let message = match param {
+                    Some(param) => format!("depends on the parameter '{}'", param),
+                    None => "expected a constant integer range".to_owned(),
+                };
+                let mut notes = vec![
+                    "help: the bounds may only depend on integer literals and local parameters"
+                        .to_owned(),
+                ];
+                if param.is_some() {
+                    notes.push(
+                        "note: parameters can be overridden by the simulator but the nodes of a model are fixed\nhelp: declare the parameter with 'localparam'"
+                            .to_owned(),
+                    );
+                } else {
+                    notes.push("note: ports can not be declared as node arrays".to_owned());
+                }
+                Report::error()
+                    .with_message(format!("the node array '{}' could not be elaborated", name))
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: file,
+                        range: range.into(),
+                        message,
+                    }])
+                    .with_notes(notes)
+            }
         }
     }
 }
+
+impl DefDiagnosticWrapped<'_> {
+    /// The range of the module name in the declaration of `inst`
+    fn module_name_range(&self, inst: ModuleInstId) -> TextRange {
+        let inst = self.ast_id_map.get(inst.lookup(self.db).ast_id(self.db));
+        let inst = inst.to_node(self.parse.tree().syntax());
+        inst.syntax()
+            .parent()
+            .and_then(ast::ModuleInstDecl::cast)
+            .and_then(|decl| decl.module())
+            .map_or_else(|| inst.syntax().text_range(), |module| module.syntax().text_range())
+    }
+}
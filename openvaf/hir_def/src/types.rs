@@ -84,7 +84,7 @@ impl Type {
             _ if self == other => Some(self.clone()),
             _ if self.dim() == other.dim() => {
                 let base_type1 = self.base_type();
-                let base_type2 = self.base_type();
+                let base_type2 = other.base_type();
                 let ty = base_type1.union(base_type2)?;
                 debug_assert_ne!(self.dim(), &[]);
                 Some(ty.to_dim(&self.dim()))
@@ -95,7 +95,7 @@ impl Type {
 
     pub fn base_type(&self) -> &Type {
         let mut curr = self;
-        while let Type::Array { ty, .. } = self {
+        while let Type::Array { ty, .. } = curr {
             curr = ty
         }
         curr
@@ -136,6 +136,52 @@ impl Type {
     }
 }
 
+/// The declared index range `[start:end]` of an array variable or parameter.
+/// Just like Verilog bit ranges the bounds may be given in descending order.
+/// The first element of the array always corresponds to `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArrayRange {
+    pub start: i32,
+    pub end: i32,
+}
+
+impl ArrayRange {
+    /// The range `[0:len-1]` used for arrays without an explicit declaration (array literals)
+    pub fn with_len(len: u32) -> ArrayRange {
+        debug_assert_ne!(len, 0);
+        ArrayRange { start: 0, end: len as i32 - 1 }
+    }
+
+    pub fn num_elements(self) -> u32 {
+        self.start.abs_diff(self.end) + 1
+    }
+
+    /// The position of the element `index` within the array or `None` if `index` is out of bounds
+    pub fn position(self, index: i32) -> Option<u32> {
+        let (lo, hi) =
+            if self.start <= self.end { (self.start, self.end) } else { (self.end, self.start) };
+        if index < lo || index > hi {
+            return None;
+        }
+        Some(self.start.abs_diff(index))
+    }
+
+    /// The index of the element at position `pos` within the array
+    pub fn index(self, pos: u32) -> i32 {
+        if self.start <= self.end {
+            self.start + pos as i32
+        } else {
+            self.start - pos as i32
+        }
+    }
+}
+
+impl_display! {
+    match ArrayRange{
+        ArrayRange{start, end} => "[{}:{}]", start, end;
+    }
+}
+
 pub trait AsType {
     fn as_type(&self) -> Type;
 }
@@ -0,0 +1,161 @@
+//! Lowering of array variables and parameters.
+//!
+//! MIR only knows scalar values so arrays are lowered element-wise: every element of an
+//! array variable is a separate place ([`PlaceKind::VarElement`]) and every element of an
+//! array parameter is a separate parameter ([`ParamKind::ParamElement`]).
+//! Elements are identified by their position within the array (starting at zero) and not by
+//! their declared index. Constant indices select an element directly while dynamic indices
+//! are lowered to a chain of selects over all elements. Constant indices outside of the
+//! array are rejected by `hir_ty`. A dynamic index outside of the array is a fatal error
+//! (just like `$fatal`) that aborts the evaluation: reading such an index returns zero and
+//! writing to it has no effect.
+
+use hir::{ArrayRange, Expr, ExprId, Ref, Type, Variable};
+use mir::builder::InstBuilder;
+use mir::{Value, FALSE, F_ZERO, ZERO};
+
+use crate::body::BodyLoweringCtx;
+use crate::fmt::{DisplayKind, FmtArg};
+use crate::{CallBackKind, ParamKind, PlaceKind, RetFlag};
+
+impl BodyLoweringCtx<'_, '_, '_> {
+    /// Lowers an array valued expression to its elements (cast to `elem_ty`).
+    pub fn lower_array_elems(&mut self, expr: ExprId, elem_ty: &Type) -> Vec<Value> {
+        let ty = self.body.expr_type(expr);
+        let len = array_len(&ty);
+        let vals: Vec<_> = match self.body.get_expr(expr) {
+            Expr::Array(vals) => vals.iter().map(|&val| self.lower_expr(val)).collect(),
+            Expr::Read(Ref::Variable(var)) => {
+                (0..len).map(|pos| self.ctx.use_place(PlaceKind::VarElement(var, pos))).collect()
+            }
+            Expr::Read(Ref::Parameter(param)) => match self.ctx.fixed_param_elements(param) {
+                Some(vals) => vals,
+                None => (0..len)
+                    .map(|pos| self.ctx.use_param(ParamKind::ParamElement(param, pos)))
+                    .collect(),
+            },
+            expr => unreachable!("invalid array expression {expr:?}"),
+        };
+
+        let src = ty.base_type();
+        if vals.is_empty() || src == elem_ty {
+            return vals;
+        }
+        vals.into_iter().map(|val| self.ctx.insert_cast(val, src, elem_ty)).collect()
+    }
+
+    /// Lowers the array element `base[index]`.
+    pub(crate) fn lower_index(&mut self, expr: ExprId, base: ExprId, index: ExprId) -> Value {
+        let elem_ty = self.body.expr_type(expr);
+        let range = self.array_range(base);
+
+        if let Some(index) = self.body.as_literalsignedint(&index) {
+            let pos = range.position(index).expect("out of bounds indices are rejected by hir_ty");
+            return match self.body.get_expr(base) {
+                Expr::Read(Ref::Variable(var)) => {
+                    self.ctx.use_place(PlaceKind::VarElement(var, pos))
+                }
+                _ => self.lower_array_elems(base, &elem_ty)[pos as usize],
+            };
+        }
+
+        let index = self.lower_expr(index);
+        self.check_index(index, range);
+        let elems = self.lower_array_elems(base, &elem_ty);
+        let mut res = self.zero(&elem_ty);
+        for (pos, elem) in elems.into_iter().enumerate().rev() {
+            let cond = self.is_index(index, range, pos as u32);
+            let old = res;
+            res = self.ctx.make_select(cond, |_, is_elem| if is_elem { elem } else { old });
+        }
+        res
+    }
+
+    /// Lowers the assignment `var[index] = rhs`.
+    pub(crate) fn lower_element_assignment(&mut self, var: Variable, index: ExprId, rhs: ExprId) {
+        let val = self.lower_expr(rhs);
+        let range = var.array_range(self.ctx.db).expect("assignment to array element");
+
+        if let Some(index) = self.body.as_literalsignedint(&index) {
+            let pos = range.position(index).expect("out of bounds indices are rejected by hir_ty");
+            self.ctx.def_place(PlaceKind::VarElement(var, pos), val);
+            return;
+        }
+
+        let index = self.lower_expr(index);
+        self.check_index(index, range);
+        for pos in 0..range.num_elements() {
+            let cond = self.is_index(index, range, pos);
+            let place = PlaceKind::VarElement(var, pos);
+            let old = self.ctx.use_place(place);
+            let new = self.ctx.make_select(cond, |_, is_elem| if is_elem { val } else { old });
+            self.ctx.def_place(place, new);
+        }
+    }
+
+    /// Lowers the assignment of a whole array `var = rhs`.
+    pub(crate) fn lower_array_assignment(&mut self, var: Variable, rhs: ExprId) {
+        let elem_ty = var.ty(self.ctx.db).base_type().clone();
+        let vals = self.lower_array_elems(rhs, &elem_ty);
+        for (pos, val) in vals.into_iter().enumerate() {
+            self.ctx.def_place(PlaceKind::VarElement(var, pos as u32), val);
+        }
+    }
+
+    /// Reports a fatal error and aborts the evaluation if the dynamic
+    /// `index` is not an index of the array.
+    fn check_index(&mut self, index: Value, range: ArrayRange) {
+        let (lo, hi) = (range.start.min(range.end), range.start.max(range.end));
+        let lo = self.ctx.iconst(lo);
+        let hi = self.ctx.iconst(hi);
+        let above_lo = self.ctx.ins().ile(lo, index);
+        let below_hi = self.ctx.ins().ile(index, hi);
+        let in_bounds = self.ctx.make_select(above_lo, |_, ok| if ok { below_hi } else { FALSE });
+        let msg = format!("%s: array index %d is out of bounds [{}:{}]\n", range.start, range.end);
+        let path = self.path;
+        self.ctx.make_cond(in_bounds, |ctx, in_bounds| {
+            if !in_bounds {
+                let args = [ctx.sconst(&msg), ctx.sconst(path), index];
+                let arg_tys = vec![FmtArg::from(Type::String), FmtArg::from(Type::Integer)];
+                let kind = CallBackKind::Print {
+                    kind: DisplayKind::Fatal,
+                    arg_tys: arg_tys.into_boxed_slice(),
+                };
+                ctx.call(kind, &args);
+                ctx.call(CallBackKind::SetRetFlag(RetFlag::Abort), &[]);
+            }
+        });
+    }
+
+    fn is_index(&mut self, index: Value, range: ArrayRange, pos: u32) -> Value {
+        let expected = self.ctx.iconst(range.index(pos));
+        self.ctx.ins().ieq(index, expected)
+    }
+
+    fn array_range(&self, base: ExprId) -> ArrayRange {
+        let range = match self.body.get_expr(base) {
+            Expr::Read(Ref::Variable(var)) => var.array_range(self.ctx.db),
+            Expr::Read(Ref::Parameter(param)) => param.array_range(self.ctx.db),
+            _ => None,
+        };
+        range.unwrap_or_else(|| ArrayRange::with_len(array_len(&self.body.expr_type(base))))
+    }
+
+    fn zero(&mut self, ty: &Type) -> Value {
+        match ty {
+            Type::Real => F_ZERO,
+            Type::Integer => ZERO,
+            Type::Bool => FALSE,
+            Type::String => self.ctx.sconst(""),
+            ty => unreachable!("invalid array element type {ty}"),
+        }
+    }
+}
+
+pub(crate) fn array_len(ty: &Type) -> u32 {
+    match *ty {
+        Type::Array { len, .. } => len,
+        Type::EmptyArray => 0,
+        ref ty => unreachable!("{ty} is not an array"),
+    }
+}
@@ -1,4 +1,4 @@
-use hir::{BodyRef, ExprId, Node};
+use hir::{BodyRef, ExprId, Node, Type};
 use mir::builder::InstBuilder;
 use mir::{Block, Value};
 use stdx::iter::zip;
@@ -85,4 +85,10 @@ impl LoweringCtx<'_, '_> {
     pub fn lower_expr_body(&mut self, body: BodyRef, i: usize) -> Value {
         BodyLoweringCtx { ctx: self, body, path: "" }.lower_expr(body.get_entry_expr(i))
     }
+
+    /// Lowers a body that returns an array (element-wise)
+    pub fn lower_array_body(&mut self, body: BodyRef, i: usize, elem_ty: &Type) -> Vec<Value> {
+        BodyLoweringCtx { ctx: self, body, path: "" }
+            .lower_array_elems(body.get_entry_expr(i), elem_ty)
+    }
 }
@@ -1,12 +1,13 @@
 use std::fmt::Display;
 
-use hir::{Node, Parameter};
+use hir::builtin::TableDimControl;
+use hir::{Node, Parameter, Type};
 use lasso::Spur;
 use mir::{FunctionSignature, Param};
 use stdx::Ieee64;
 
 use crate::fmt::{DisplayKind, FmtArg};
-use crate::LimitState;
+use crate::{Delay, LimitState, PersistentState, TranState};
 
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 pub enum ParamInfoKind {
@@ -23,6 +24,9 @@ pub enum RetFlag {
     Finish,
     Stop,
     Limited,
+    Reject,
+    /// Set by `$discontinuity(n)`, the order `n` is passed as the only argument.
+    Discontinuity,
 }
 
 impl std::fmt::Display for RetFlag {
@@ -32,30 +36,112 @@ impl std::fmt::Display for RetFlag {
             Self::Finish => "finish",
             Self::Stop => "stop",
             Self::Limited => "limited",
+            Self::Reject => "reject",
+            Self::Discontinuity => "discontinuity",
         };
         write!(f, "{}", txt)
     }
 }
 
+/// The distributions of the random number functions. The discriminant identifies
+/// the distribution in the generated code.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum RandomDist {
+    Random = 0,
+    Uniform = 1,
+    Normal = 2,
+    Exponential = 3,
+    Poisson = 4,
+    ChiSquare = 5,
+    T = 6,
+    Erlang = 7,
+}
+
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub enum CallBackKind {
-    Print { kind: DisplayKind, arg_tys: Box<[FmtArg]> },
+    Print {
+        kind: DisplayKind,
+        arg_tys: Box<[FmtArg]>,
+    },
+    FileOpen,
+    FileClose,
+    FilePrint {
+        arg_tys: Box<[FmtArg]>,
+    },
+    /// `$swrite`/`$sformat`: returns the formatted string. The string is owned by the string
+    /// slot `slot` and stays valid until the callback is called again.
+    FormatString {
+        arg_tys: Box<[FmtArg]>,
+        slot: u32,
+    },
+    /// `$sscanf`: returns the number of outputs assigned by the format.
+    ScanCount,
+    /// `$sscanf`: returns the value (converted to `ty`) assigned to the output `arg` or the
+    /// previous value of the output (the last argument) if it is not assigned. Strings are
+    /// owned by the string slot `slot`.
+    ScanValue {
+        arg: u32,
+        ty: Type,
+        slot: Option<u32>,
+    },
     SimParam,
     SimParamOpt,
     SimParamStr,
+    SimProbe,
+    SimProbeOpt,
+    /// `$test$plusargs`/`$value$plusargs`: returns whether a plusarg starts with the text of
+    /// the format before the first conversion.
+    TestPlusArgs,
+    /// `$value$plusargs`: returns the plusarg found by [`CallBackKind::TestPlusArgs`] (an empty
+    /// string if there is none) so it can be scanned with the format.
+    PlusArg,
     Derivative(Param),
     NodeDerivative(Node),
     ParamInfo(ParamInfoKind, Parameter),
     CollapseHint(Node, Option<Node>),
     LimDiscontinuity,
     Analysis,
-    BuiltinLimit { name: Spur, num_args: u32 },
+    BuiltinLimit {
+        name: Spur,
+        num_args: u32,
+    },
     StoreLimit(LimitState),
     TimeDerivative,
-    WhiteNoise { name: Spur, idx: u32 },
-    FlickerNoise { name: Spur, idx: u32 },
+    WhiteNoise {
+        name: Spur,
+        idx: u32,
+    },
+    FlickerNoise {
+        name: Spur,
+        idx: u32,
+    },
     NoiseTable(Box<NoiseTable>),
+    /// `ac_stim`: a small signal source (magnitude, phase) that is only active during the AC
+    /// analysis `name`.
+    AcStim {
+        name: Spur,
+        idx: u32,
+    },
     SetRetFlag(RetFlag),
+    AbsDelay(Delay),
+    AbsDelayWeight(Delay),
+    StoreTranState(TranState),
+    StorePersistentState(PersistentState),
+    Random {
+        dist: RandomDist,
+        global: bool,
+    },
+    RandomNextSeed(RandomDist),
+    RandomStream {
+        dist: RandomDist,
+        global: bool,
+    },
+    /// Evaluates a partial derivative (of the order given for each input) of a table.
+    /// The value of the table is the partial derivative of order zero.
+    TableModel {
+        table: Box<TableModel>,
+        derivative: Box<[u8]>,
+    },
 }
 
 impl CallBackKind {
@@ -79,6 +165,30 @@ impl CallBackKind {
                 returns: 1,
                 has_sideeffects: false,
             },
+            CallBackKind::SimProbe => FunctionSignature {
+                name: "simprobe".to_owned(),
+                params: 2,
+                returns: 1,
+                has_sideeffects: false,
+            },
+            CallBackKind::SimProbeOpt => FunctionSignature {
+                name: "simprobe_opt".to_owned(),
+                params: 3,
+                returns: 1,
+                has_sideeffects: false,
+            },
+            CallBackKind::TestPlusArgs => FunctionSignature {
+                name: "test_plusargs".to_owned(),
+                params: 1,
+                returns: 1,
+                has_sideeffects: false,
+            },
+            CallBackKind::PlusArg => FunctionSignature {
+                name: "plusarg".to_owned(),
+                params: 1,
+                returns: 1,
+                has_sideeffects: false,
+            },
             CallBackKind::Derivative(param) => FunctionSignature {
                 name: format!("ddx_{}", param),
                 params: 1,
@@ -109,6 +219,42 @@ impl CallBackKind {
                 returns: 0,
                 has_sideeffects: true,
             },
+            CallBackKind::FileOpen => FunctionSignature {
+                name: "$fopen".to_owned(),
+                params: 2,
+                returns: 1,
+                has_sideeffects: true,
+            },
+            CallBackKind::FileClose => FunctionSignature {
+                name: "$fclose".to_owned(),
+                params: 1,
+                returns: 0,
+                has_sideeffects: true,
+            },
+            CallBackKind::FilePrint { arg_tys } => FunctionSignature {
+                name: "$fdisplay".to_owned(),
+                params: arg_tys.len() as u16 + 2,
+                returns: 0,
+                has_sideeffects: true,
+            },
+            CallBackKind::FormatString { arg_tys, slot } => FunctionSignature {
+                name: format!("$swrite[{slot}]"),
+                params: arg_tys.len() as u16 + 1,
+                returns: 1,
+                has_sideeffects: false,
+            },
+            CallBackKind::ScanCount => FunctionSignature {
+                name: "$sscanf".to_owned(),
+                params: 2,
+                returns: 1,
+                has_sideeffects: false,
+            },
+            CallBackKind::ScanValue { arg, ty, .. } => FunctionSignature {
+                name: format!("$sscanf[{arg}, {ty:?}]"),
+                params: 3,
+                returns: 1,
+                has_sideeffects: false,
+            },
             CallBackKind::BuiltinLimit { name, num_args } => FunctionSignature {
                 name: format!("$limit[{name:?}]"),
                 params: *num_args as u16,
@@ -154,7 +300,7 @@ impl CallBackKind {
             CallBackKind::NoiseTable(table) => FunctionSignature {
                 name: format!(
                     "table_noise{}({:?}, {:?})",
-                    if table.log { "lob" } else { "" },
+                    if table.log { "_log" } else { "" },
                     table.name,
                     &table.vals
                 ),
@@ -162,14 +308,95 @@ impl CallBackKind {
                 returns: 1,
                 has_sideeffects: false,
             },
+            CallBackKind::AcStim { name, .. } => FunctionSignature {
+                name: format!("ac_stim({name:?})"),
+                params: 2,
+                returns: 1,
+                has_sideeffects: false,
+            },
             CallBackKind::SetRetFlag(flag) => FunctionSignature {
                 name: format!("SetRetFlag[{}]", flag),
-                params: 0,
+                params: if *flag == RetFlag::Discontinuity { 1 } else { 0 },
                 returns: 0,
                 has_sideeffects: true,
             },
+            CallBackKind::StoreTranState(state) => FunctionSignature {
+                name: format!("$store[{state:?}]"),
+                params: 1,
+                returns: 1,
+                has_sideeffects: true,
+            },
+            CallBackKind::StorePersistentState(state) => FunctionSignature {
+                name: format!("$store_persistent[{state:?}]"),
+                params: 1,
+                returns: 1,
+                has_sideeffects: true,
+            },
+            CallBackKind::AbsDelay(delay) => FunctionSignature {
+                name: format!("absdelay[{delay:?}]"),
+                params: 1,
+                returns: 1,
+                has_sideeffects: false,
+            },
+            CallBackKind::Random { dist, global } => FunctionSignature {
+                name: format!("$random[{dist:?}, global={global}]"),
+                params: 3,
+                returns: 1,
+                has_sideeffects: false,
+            },
+            CallBackKind::RandomNextSeed(dist) => FunctionSignature {
+                name: format!("$random_next_seed[{dist:?}]"),
+                params: 3,
+                returns: 1,
+                has_sideeffects: false,
+            },
+            CallBackKind::RandomStream { dist, global } => FunctionSignature {
+                name: format!("$random_stream[{dist:?}, global={global}]"),
+                params: 2,
+                returns: 1,
+                has_sideeffects: true,
+            },
+            CallBackKind::AbsDelayWeight(delay) => FunctionSignature {
+                name: format!("absdelay_weight[{delay:?}]"),
+                params: 1,
+                returns: 1,
+                has_sideeffects: false,
+            },
+            CallBackKind::TableModel { table, derivative } => FunctionSignature {
+                name: format!("$table_model[{:?}, {derivative:?}]", table.src),
+                params: table.control.len() as u16,
+                returns: 1,
+                has_sideeffects: false,
+            },
+        }
+    }
+
+    /// The callbacks that calculate the partial derivatives by each argument (for callbacks
+    /// that are differentiable).
+    pub fn partial_derivatives(&self) -> Option<Vec<CallBackKind>> {
+        match self {
+            CallBackKind::TableModel { table, derivative } => {
+                let partials = (0..derivative.len())
+                    .map(|i| {
+                        let mut derivative = derivative.clone();
+                        derivative[i] += 1;
+                        CallBackKind::TableModel { table: table.clone(), derivative }
+                    })
+                    .collect();
+                Some(partials)
+            }
+            _ => None,
+        }
+    }
+    /// The string slot that owns the strings returned by this callback.
+    pub fn string_slot(&self) -> Option<u32> {
+        match *self {
+            CallBackKind::FormatString { slot, .. } => Some(slot),
+            CallBackKind::ScanValue { slot, .. } => slot,
+            _ => None,
         }
     }
+
     pub fn is_noise(&self) -> bool {
         matches!(
             self,
@@ -179,6 +406,12 @@ impl CallBackKind {
         )
     }
 
+    /// Whether this callback is a small signal source (noise or `ac_stim`) that is zero in
+    /// large signal analyses.
+    pub fn is_small_signal_src(&self) -> bool {
+        self.is_noise() || matches!(self, CallBackKind::AcStim { .. })
+    }
+
     pub fn op_dependent(&self) -> bool {
         matches!(
             self,
@@ -187,8 +420,15 @@ impl CallBackKind {
                 | CallBackKind::StoreLimit(_)
                 | CallBackKind::Analysis
                 | CallBackKind::SimParamStr
+                | CallBackKind::SimProbe
+                | CallBackKind::SimProbeOpt
                 | CallBackKind::LimDiscontinuity
                 | CallBackKind::BuiltinLimit { .. }
+                | CallBackKind::AbsDelay(_)
+                | CallBackKind::AbsDelayWeight(_)
+                | CallBackKind::StoreTranState(_)
+                | CallBackKind::StorePersistentState(_)
+                | CallBackKind::RandomStream { .. }
         )
     }
 
@@ -197,7 +437,13 @@ impl CallBackKind {
     }
 
     pub fn tracked(&self) -> bool {
-        !matches!(self, CallBackKind::Print { .. })
+        !matches!(
+            self,
+            CallBackKind::Print { .. }
+                | CallBackKind::FileOpen
+                | CallBackKind::FileClose
+                | CallBackKind::FilePrint { .. }
+        )
     }
 }
 
@@ -205,25 +451,39 @@ impl CallBackKind {
 pub struct NoiseTable {
     pub name: Spur,
     pub log: bool,
+    /// (frequency, power) pairs sorted by ascending frequency
     pub vals: Box<[(Ieee64, Ieee64)]>,
     idx: u32,
 }
 
 impl NoiseTable {
-    // TODO: read from disk
     pub fn new(
         vals: impl IntoIterator<Item = (f64, f64)>,
         log: bool,
         name: Spur,
         idx: u32,
     ) -> Self {
-        let mut vals: Vec<(Ieee64, Ieee64)> = if log {
-            vals.into_iter().map(|(f, pwr)| (f.into(), pwr.into())).collect()
-        } else {
-            vals.into_iter().map(|(f, pwr)| (f.log10().into(), pwr.into())).collect()
-        };
-        vals.sort_unstable_by(|(f1, _), (f2, _)| f1.partial_cmp(f2).unwrap());
+        let mut vals: Vec<(Ieee64, Ieee64)> =
+            vals.into_iter().map(|(f, pwr)| (f.into(), pwr.into())).collect();
+        // the standard requires ascending frequencies, the first entry wins for duplicates
+        vals.sort_by(|(f1, _), (f2, _)| f64::from(*f1).total_cmp(&f64::from(*f2)));
         vals.dedup_by_key(|(f, _)| *f);
         Self { name, log, vals: vals.into_boxed_slice(), idx }
     }
 }
+
+/// A table of `$table_model`. Tables are loaded and prepared for interpolation at setup time.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct TableModel {
+    pub src: TableSource,
+    /// The interpolation and extrapolation of each input
+    pub control: Box<[TableDimControl]>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum TableSource {
+    /// A file that contains one sample (the inputs followed by the output) per line
+    File(Box<str>),
+    /// The samples (the inputs followed by the output) of an inline table stored row by row
+    Inline(Box<[Ieee64]>),
+}
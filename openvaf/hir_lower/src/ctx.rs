@@ -1,15 +1,20 @@
-use ahash::AHashSet;
-use hir::{CompilationDB, Node, Type, Variable};
+use ahash::{AHashMap, AHashSet};
+use hir::{
+    Body, BodyRef, CompilationDB, ExprId, Instance, Module, Nature, Node, Parameter, Type, Variable,
+};
 use mir::builder::{InsertBuilder, InstBuilder};
 use mir::{
     Block, DataFlowGraph, FuncRef, Inst, Opcode, SourceLoc, Value, FALSE, F_ZERO, INFINITY, TRUE,
 };
 use mir_build::{FuncInstBuilder, FunctionBuilder, Place};
+use stdx::iter::zip;
 use typed_indexmap::TiSet;
 
+use crate::body::BodyLoweringCtx;
+use crate::fmt::DisplayKind;
 use crate::{
     CallBackKind, HirInterner, ImplicitEquation, ImplicitEquationKind, LimitState, ParamKind,
-    PlaceKind,
+    PersistentState, PlaceKind, RetFlag, StateOwner, TranState,
 };
 
 pub struct LoweringCtx<'a, 'c> {
@@ -25,6 +30,20 @@ pub struct LoweringCtx<'a, 'c> {
     /// but necessary to avoid accidental correlation/opimization.
     /// For example white_noise(x) - white_noise(x) is not zero.
     pub num_noise_sources: u32,
+    /// Strings created at runtime (by `$swrite` or `$sscanf`) are owned by a dedicated
+    /// slot for every call, the slot is freed when the call is evaluated again.
+    pub num_string_slots: u32,
+    /// The (compile time) values of the parameters of module instances
+    /// and the base module parameters of a paramset (array parameters are stored element-wise)
+    fixed_params: AHashMap<Parameter, Vec<Value>>,
+    /// Whether the base module parameters of a paramset were overwritten by the paramset
+    fixed_params_given: AHashMap<Parameter, Value>,
+    /// The nature of the branch quantity of the contribution that is currently lowered
+    /// (used as the nature of small signal sources)
+    pub contribute_nature: Option<Nature>,
+    /// Analog operator calls (see [`HirInterner::operator_unknowns`]) whose unknown has no
+    /// nature yet. They receive the nature of the first contribution that uses their result.
+    pub operators_without_nature: Vec<Inst>,
 }
 
 impl<'a, 'c> LoweringCtx<'a, 'c> {
@@ -43,6 +62,209 @@ impl<'a, 'c> LoweringCtx<'a, 'c> {
             inside_lim: false,
             intern,
             num_noise_sources: 0,
+            num_string_slots: 0,
+            fixed_params: AHashMap::default(),
+            fixed_params_given: AHashMap::default(),
+            contribute_nature: None,
+            operators_without_nature: Vec::new(),
+        }
+    }
+
+    /// Returns the value of a parameter that belongs to a module instance.
+    /// These parameters are either overwritten by the instance or use their default value.
+    /// Array parameters are returned element-wise, all other parameters have a single element.
+    pub fn instance_param(&mut self, param: Parameter) -> Vec<Value> {
+        if let Some(val) = self.fixed_params.get(&param) {
+            return val.clone();
+        }
+        let inst = param.instance().expect("parameter belongs to a module instance");
+        let (body, overrides) = inst.param_overrides(self.db);
+        match overrides.iter().find(|(overwritten, _)| *overwritten == param) {
+            Some(&(_, expr)) => self.lower_param_value(param, body.borrow(), expr),
+            None => {
+                let body = param.init(self.db);
+                self.lower_param_value(param, body.borrow(), body.borrow().get_entry_expr(0))
+            }
+        }
+    }
+
+    fn lower_param_value(&mut self, param: Parameter, body: BodyRef, expr: ExprId) -> Vec<Value> {
+        let ty = param.ty(self.db);
+        let mut ctx = BodyLoweringCtx { ctx: self, body, path: "" };
+        if matches!(ty, Type::Array { .. }) {
+            ctx.lower_array_elems(expr, ty.base_type())
+        } else {
+            vec![ctx.lower_expr(expr)]
+        }
+    }
+
+    /// Computes the values of all parameters of a module instance.
+    /// This must happen before any body of the instance is lowered and parent
+    /// instances must be lowered before the instances nested within them.
+    pub fn lower_instance_params(&mut self, inst: Instance) {
+        for param in inst.parameters(self.db) {
+            let val = self.instance_param(param);
+            self.fixed_params.insert(param, val);
+        }
+    }
+
+    /// Computes the values of all base module parameters of a paramset. The parameters
+    /// assigned by the paramset are computed from their assignment (which may depend on the
+    /// parameters of the paramset) and all other parameters use their default value.
+    ///
+    /// Overloaded paramsets (paramsets with the same name) are all compiled into the model
+    /// of the first paramset and share its parameters. The first paramset whose parameters
+    /// are all within their ranges (`from`/`exclude`) is selected. If no paramset matches,
+    /// a fatal error is reported during evaluation. Array parameters do not take part in
+    /// the selection.
+    pub fn lower_paramset_params(&mut self, module: Module) {
+        let Some(first) = module.param_overrides(self.db) else {
+            return;
+        };
+        let overloads = module.paramset_overloads(self.db);
+        let exposed = module.paramset_parameters(self.db);
+        let mut paramsets = vec![(exposed.clone(), first)];
+        for overload in overloads {
+            let params = overload.paramset_parameters(self.db);
+            // the parameters of an overload are provided by the parameter
+            // with the same name of the first paramset
+            for &param in &params {
+                let name = param.name(self.db);
+                let Some(&src) = exposed.iter().find(|it| it.name(self.db) == name) else {
+                    continue;
+                };
+                let vals = match src.array_range(self.db) {
+                    Some(range) => (0..range.num_elements())
+                        .map(|pos| self.use_param(ParamKind::ParamElement(src, pos)))
+                        .collect(),
+                    None => vec![self.use_param(ParamKind::Param(src))],
+                };
+                let given = self.use_param(ParamKind::ParamGiven { param: src });
+                self.fixed_params.insert(param, vals);
+                self.fixed_params_given.insert(param, given);
+            }
+            let overrides = overload.param_overrides(self.db).expect("overloads are paramsets");
+            paramsets.push((params, overrides));
+        }
+
+        let selected: Vec<_> = if paramsets.len() == 1 {
+            vec![TRUE]
+        } else {
+            paramsets.iter().map(|(params, _)| self.paramset_in_bounds(params)).collect()
+        };
+
+        if paramsets.len() != 1 && !self.no_equations {
+            let mut none = TRUE;
+            for &selected in &selected {
+                let prev = none;
+                none =
+                    self.make_select(selected, |_, selected| if selected { FALSE } else { prev });
+            }
+            let msg = format!(
+                "no paramset '{}' matches the parameters of the instance\n",
+                module.name(self.db)
+            );
+            self.make_cond(none, |ctx, none| {
+                if none {
+                    let msg = ctx.sconst(&msg);
+                    let arg_tys = Vec::new().into_boxed_slice();
+                    let kind = CallBackKind::Print { kind: DisplayKind::Fatal, arg_tys };
+                    ctx.call(kind, &[msg]);
+                    ctx.call(CallBackKind::SetRetFlag(RetFlag::Abort), &[]);
+                }
+            });
+        }
+
+        for param in module.base_parameters(self.db) {
+            let (val, given) = self.lower_paramset_param(param, &paramsets, &selected);
+            self.fixed_params.insert(param, val);
+            self.fixed_params_given.insert(param, given);
+        }
+    }
+
+    /// Returns whether all (scalar) parameters of a paramset are within their ranges.
+    fn paramset_in_bounds(&mut self, params: &[Parameter]) -> Value {
+        let mut res = TRUE;
+        for &param in params {
+            if param.array_range(self.db).is_some() {
+                continue;
+            }
+            let val = match self.fixed_param(param) {
+                Some(val) => val,
+                None => self.use_param(ParamKind::Param(param)),
+            };
+            let body = param.init(self.db);
+            let bounds = param.bounds(self.db);
+            let ty = param.ty(self.db);
+            let in_bounds = BodyLoweringCtx { ctx: self, body: body.borrow(), path: "" }
+                .param_in_bounds(val, &bounds, &ty);
+            res = self.make_select(res, |_, res| if res { in_bounds } else { FALSE });
+        }
+        res
+    }
+
+    /// Computes the value of a base module parameter (and whether it was overwritten)
+    /// for the first selected paramset. The last paramset is used if none is selected.
+    fn lower_paramset_param(
+        &mut self,
+        param: Parameter,
+        paramsets: &[(Vec<Parameter>, (Body, Vec<(Parameter, ExprId)>))],
+        selected: &[Value],
+    ) -> (Vec<Value>, Value) {
+        let (_, (body, overrides)) = &paramsets[0];
+        if paramsets.len() == 1 {
+            return match overrides.iter().find(|(overwritten, _)| *overwritten == param) {
+                Some(&(_, expr)) => (self.lower_param_value(param, body.borrow(), expr), TRUE),
+                None => {
+                    let body = param.init(self.db);
+                    let expr = body.borrow().get_entry_expr(0);
+                    (self.lower_param_value(param, body.borrow(), expr), FALSE)
+                }
+            };
+        }
+
+        let ((then_bb, (then_vals, then_given)), (else_bb, (else_vals, else_given))) = self
+            .make_cond(selected[0], |ctx, is_selected| {
+                if is_selected {
+                    ctx.lower_paramset_param(param, &paramsets[..1], &selected[..1])
+                } else {
+                    ctx.lower_paramset_param(param, &paramsets[1..], &selected[1..])
+                }
+            });
+        let vals = zip(then_vals, else_vals)
+            .map(|(then_val, else_val)| {
+                self.func.ins().phi(&[(then_bb, then_val), (else_bb, else_val)])
+            })
+            .collect();
+        let given = self.func.ins().phi(&[(then_bb, then_given), (else_bb, else_given)]);
+        (vals, given)
+    }
+
+    /// Returns the value of a parameter that is not provided by the simulator
+    /// (because it belongs to a module instance or the base module of a paramset).
+    pub fn fixed_param(&mut self, param: Parameter) -> Option<Value> {
+        self.fixed_param_elements(param).map(|val| val[0])
+    }
+
+    /// Returns the elements of an array parameter that is not provided by the simulator.
+    pub fn fixed_param_elements(&mut self, param: Parameter) -> Option<Vec<Value>> {
+        if param.instance().is_some() {
+            Some(self.instance_param(param))
+        } else {
+            self.fixed_params.get(&param).cloned()
+        }
+    }
+
+    /// Returns whether a parameter that is not provided by the simulator was
+    /// explicitly overwritten (by a module instance or paramset).
+    pub fn fixed_param_given(&self, param: Parameter) -> Option<Value> {
+        match param.instance() {
+            Some(inst) => {
+                let (_, overrides) = inst.param_overrides(self.db);
+                let given = overrides.iter().any(|(overwritten, _)| *overwritten == param);
+                Some(if given { TRUE } else { FALSE })
+            }
+            None => self.fixed_params_given.get(&param).copied(),
         }
     }
 
@@ -76,11 +298,18 @@ impl<'a, 'c> LoweringCtx<'a, 'c> {
                 PlaceKind::FunctionReturn { .. }
                 | PlaceKind::FunctionArg { .. }
                 | PlaceKind::Param(_)
+                | PlaceKind::ParamElement(..)
                 | PlaceKind::ParamMin(_)
                 | PlaceKind::ParamMax(_) => return place,
 
                 PlaceKind::Var(var) => self.use_param(ParamKind::HiddenState(var)),
-                PlaceKind::ImplicitResidual { .. } | PlaceKind::Contribute { .. } => F_ZERO,
+                PlaceKind::VarElement(var, pos) => {
+                    self.use_param(ParamKind::HiddenStateElement(var, pos))
+                }
+                PlaceKind::ImplicitResidual { .. }
+                | PlaceKind::Contribute { .. }
+                | PlaceKind::DelayInput(_)
+                | PlaceKind::DelayTime(_) => F_ZERO,
                 PlaceKind::CollapseImplicitEquation(_) => TRUE,
                 PlaceKind::IsVoltageSrc(_) => FALSE,
                 PlaceKind::BoundStep => INFINITY,
@@ -220,6 +449,42 @@ impl<'a, 'c> LoweringCtx<'a, 'c> {
         val
     }
 
+    /// Allocates a new transient state for the analog operator or event `owner` and
+    /// returns it together with its value at the last accepted timepoint.
+    pub fn tran_state(&mut self, owner: impl Into<StateOwner>) -> (TranState, Value) {
+        let state = self.intern.tran_states.push_and_get_key(owner.into());
+        let prev_val = self.use_param(ParamKind::PrevTranState(state));
+        (state, prev_val)
+    }
+
+    /// Writes the value of a transient state for the current timepoint.
+    /// The value is only committed by the simulator if the timepoint is accepted.
+    pub fn store_tran_state(&mut self, state: TranState, val: Value) {
+        self.call1(CallBackKind::StoreTranState(state), &[val]);
+    }
+
+    /// Allocates a new persistent state for the analog operator or event `owner` and
+    /// returns it together with its value at the last accepted timepoint.
+    pub fn persistent_state(&mut self, owner: impl Into<StateOwner>) -> (PersistentState, Value) {
+        let state = self.intern.persistent_states.push_and_get_key(owner.into());
+        let prev_val = self.use_param(ParamKind::PrevPersistentState(state));
+        (state, prev_val)
+    }
+
+    /// Writes the value of a persistent state for the current timepoint.
+    /// The value is only committed once the simulator accepts the timepoint.
+    pub fn store_persistent_state(&mut self, state: PersistentState, val: Value) {
+        self.call1(CallBackKind::StorePersistentState(state), &[val]);
+    }
+
+    /// Limits the next timestep to `step` unless a smaller bound was already requested.
+    pub fn bound_step(&mut self, step: Value) {
+        let old_step = self.use_place(PlaceKind::BoundStep);
+        let smaller = self.ins().flt(step, old_step);
+        let step = self.make_select(smaller, |_, smaller| if smaller { step } else { old_step });
+        self.def_place(PlaceKind::BoundStep, step);
+    }
+
     pub fn implicit_equation(&mut self, kind: ImplicitEquationKind) -> (ImplicitEquation, Value) {
         let equation = self.intern.implicit_equations.push_and_get_key(kind);
         let place = self.dec_place(PlaceKind::CollapseImplicitEquation(equation));
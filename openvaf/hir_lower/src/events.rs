@@ -0,0 +1,278 @@
+//! Lowering of monitored events (`@(cross(..))`, `@(above(..))` and `@(timer(..))`).
+//!
+//! Every event remembers the value of its expression (or the next time it fires) at the last
+//! accepted timepoint in a transient state. An event fires if a crossing happened between that
+//! timepoint and the current one. If the crossing lies further before the current timepoint
+//! than the time tolerance allows, the timestep is rejected with `EVAL_RET_FLAG_REJECT` and
+//! `$bound_step` is set so that the simulator retries with a step ending at the (linearly
+//! interpolated) crossing. Timers use `$bound_step` to place a breakpoint at the next time
+//! they fire.
+//!
+//! Outside of transient timestepping (including the operating point that precedes it)
+//! `cross` and `timer` never fire while `above` fires if its expression is not negative.
+//!
+//! `last_crossing` is lowered here as well. It does not control the timestep and instead
+//! keeps its history in persistent states so that the time of the last crossing survives
+//! across timepoints.
+
+use hir::{BuiltIn, ExprId, MonitoredEvent, MonitoredEventKind, StmtId};
+use mir::builder::InstBuilder;
+use mir::{Value, FALSE, F_ZERO, INFINITY, TRUE, ZERO};
+
+use crate::body::BodyLoweringCtx;
+use crate::{CallBackKind, ParamKind, RetFlag};
+
+/// Used if no time tolerance is specified for an event
+const DEFAULT_TIME_TOL: f64 = 1e-12;
+
+impl BodyLoweringCtx<'_, '_, '_> {
+    pub(crate) fn lower_monitored_events(&mut self, events: &[MonitoredEvent], body: StmtId) {
+        let mut fired = FALSE;
+        for event in events {
+            let event_fired = match event.kind {
+                MonitoredEventKind::Cross => self.lower_cross(&event.args),
+                MonitoredEventKind::Above => self.lower_above(&event.args),
+                MonitoredEventKind::Timer => self.lower_timer(&event.args),
+            };
+            fired = self.lower_select_with(fired, |_| TRUE, |_| event_fired);
+        }
+
+        self.ctx.make_cond(fired, |ctx, fired| {
+            if fired {
+                BodyLoweringCtx { body: self.body, path: self.path, ctx }.lower_stmt(body);
+            }
+        });
+    }
+
+    /// `cross(expr, dir, time_tol, expr_tol)`
+    fn lower_cross(&mut self, args: &[ExprId]) -> Value {
+        let val = self.lower_expr(args[0]);
+        let dir = args.get(1).map_or(F_ZERO, |arg| self.lower_expr(*arg));
+        let (time_tol, expr_tol) = self.lower_event_tols(args.get(2), args.get(3));
+        if self.ctx.no_equations {
+            return FALSE;
+        }
+
+        let (val_state, prev_val) = self.ctx.tran_state(MonitoredEventKind::Cross);
+        let (time_state, prev_time) = self.ctx.tran_state(MonitoredEventKind::Cross);
+        let time = self.ctx.use_param(ParamKind::Abstime);
+
+        let is_timestep = self.is_tran();
+        let fired = self.lower_select_with(
+            is_timestep,
+            |mut sel| {
+                let rising = sel.is_crossing(prev_val, val, true);
+                let falling = sel.is_crossing(prev_val, val, false);
+                let allow_rising = sel.ctx.ins().fge(dir, F_ZERO);
+                let rising = sel.lower_select_with(allow_rising, |_| rising, |_| FALSE);
+                let allow_falling = sel.ctx.ins().fle(dir, F_ZERO);
+                let falling = sel.lower_select_with(allow_falling, |_| falling, |_| FALSE);
+                let crossed = sel.lower_select_with(rising, |_| TRUE, |_| falling);
+                sel.lower_select_with(
+                    crossed,
+                    |mut sel| {
+                        sel.localize_crossing(prev_val, val, prev_time, time, time_tol, expr_tol)
+                    },
+                    |_| FALSE,
+                )
+            },
+            |_| FALSE,
+        );
+
+        self.ctx.store_tran_state(val_state, val);
+        self.ctx.store_tran_state(time_state, time);
+        fired
+    }
+
+    /// `above(expr, time_tol, expr_tol)`
+    fn lower_above(&mut self, args: &[ExprId]) -> Value {
+        let val = self.lower_expr(args[0]);
+        let (time_tol, expr_tol) = self.lower_event_tols(args.get(1), args.get(2));
+        if self.ctx.no_equations {
+            return self.ctx.ins().fge(val, F_ZERO);
+        }
+
+        let (val_state, prev_val) = self.ctx.tran_state(MonitoredEventKind::Above);
+        let (time_state, prev_time) = self.ctx.tran_state(MonitoredEventKind::Above);
+        let time = self.ctx.use_param(ParamKind::Abstime);
+
+        let is_timestep = self.is_tran();
+        let fired = self.lower_select_with(
+            is_timestep,
+            |mut sel| {
+                let rising = sel.is_crossing(prev_val, val, true);
+                sel.lower_select_with(
+                    rising,
+                    |mut sel| {
+                        sel.localize_crossing(prev_val, val, prev_time, time, time_tol, expr_tol)
+                    },
+                    |_| FALSE,
+                )
+            },
+            |mut sel| sel.ctx.ins().fge(val, F_ZERO),
+        );
+
+        self.ctx.store_tran_state(val_state, val);
+        self.ctx.store_tran_state(time_state, time);
+        fired
+    }
+
+    /// `timer(start, period, time_tol)`
+    fn lower_timer(&mut self, args: &[ExprId]) -> Value {
+        let start = self.lower_expr(args[0]);
+        let period = args.get(1).map_or(F_ZERO, |arg| self.lower_expr(*arg));
+        let time_tol = match args.get(2) {
+            Some(arg) => self.lower_expr(*arg),
+            None => self.ctx.fconst(DEFAULT_TIME_TOL),
+        };
+        if self.ctx.no_equations {
+            return FALSE;
+        }
+
+        let (next_state, prev_next) = self.ctx.tran_state(MonitoredEventKind::Timer);
+        let time = self.ctx.use_param(ParamKind::Abstime);
+
+        let is_timestep = self.is_tran();
+        let [fired, next, step] = self.lower_multi_select(is_timestep, |mut sel, timestep| {
+            if !timestep {
+                return [FALSE, start, INFINITY];
+            }
+
+            let latest = sel.ctx.ins().fadd(time, time_tol);
+            let fired = sel.ctx.ins().fge(latest, prev_next);
+            let next = sel.lower_select_with(
+                fired,
+                |mut sel| {
+                    let periodic = sel.ctx.ins().fgt(period, F_ZERO);
+                    sel.lower_select_with(
+                        periodic,
+                        |mut sel| sel.ctx.ins().fadd(prev_next, period),
+                        |_| INFINITY,
+                    )
+                },
+                |_| prev_next,
+            );
+            let step = sel.ctx.ins().fsub(next, time);
+            [fired, next, step]
+        });
+
+        self.ctx.store_tran_state(next_state, next);
+        self.ctx.bound_step(step);
+        fired
+    }
+
+    /// `last_crossing(expr, dir)`
+    ///
+    /// Returns the (linearly interpolated) time at which `expr` last crossed zero in the
+    /// direction `dir` (both directions if `dir` is zero or omitted). `-1.0` is
+    /// returned if no crossing happened yet.
+    pub(crate) fn lower_last_crossing(&mut self, args: &[ExprId]) -> Value {
+        let val = self.lower_expr(args[0]);
+        let dir = args.get(1).map_or(ZERO, |arg| self.lower_expr(*arg));
+        let no_crossing = self.ctx.fconst(-1.0);
+        if self.ctx.no_equations {
+            return no_crossing;
+        }
+
+        let (val_state, prev_val) = self.ctx.persistent_state(BuiltIn::last_crossing);
+        let (time_state, prev_time) = self.ctx.persistent_state(BuiltIn::last_crossing);
+        let (last_state, prev_last) = self.ctx.persistent_state(BuiltIn::last_crossing);
+        let time = self.ctx.use_param(ParamKind::Abstime);
+
+        let is_timestep = self.is_tran();
+        let last = self.lower_select_with(
+            is_timestep,
+            |mut sel| {
+                let rising = sel.is_crossing(prev_val, val, true);
+                let falling = sel.is_crossing(prev_val, val, false);
+                let allow_rising = sel.ctx.ins().ige(dir, ZERO);
+                let rising = sel.lower_select_with(allow_rising, |_| rising, |_| FALSE);
+                let allow_falling = sel.ctx.ins().ile(dir, ZERO);
+                let falling = sel.lower_select_with(allow_falling, |_| falling, |_| FALSE);
+                let crossed = sel.lower_select_with(rising, |_| TRUE, |_| falling);
+                sel.lower_select_with(
+                    crossed,
+                    |mut sel| {
+                        let dt = sel.ctx.ins().fsub(time, prev_time);
+                        let delta = sel.ctx.ins().fsub(prev_val, val);
+                        let frac = sel.ctx.ins().fdiv(prev_val, delta);
+                        let crossing_step = sel.ctx.ins().fmul(dt, frac);
+                        sel.ctx.ins().fadd(prev_time, crossing_step)
+                    },
+                    |_| prev_last,
+                )
+            },
+            |_| no_crossing,
+        );
+
+        self.ctx.store_persistent_state(val_state, val);
+        self.ctx.store_persistent_state(time_state, time);
+        self.ctx.store_persistent_state(last_state, last);
+        last
+    }
+
+    fn lower_event_tols(
+        &mut self,
+        time_tol: Option<&ExprId>,
+        expr_tol: Option<&ExprId>,
+    ) -> (Value, Value) {
+        let time_tol = match time_tol {
+            Some(arg) => self.lower_expr(*arg),
+            None => self.ctx.fconst(DEFAULT_TIME_TOL),
+        };
+        let expr_tol = expr_tol.map_or(F_ZERO, |arg| self.lower_expr(*arg));
+        (time_tol, expr_tol)
+    }
+
+    /// Whether `val` crossed zero in the given direction since the last accepted
+    /// timepoint (where it was `prev_val`)
+    fn is_crossing(&mut self, prev_val: Value, val: Value, rising: bool) -> Value {
+        if rising {
+            let was_below = self.ctx.ins().flt(prev_val, F_ZERO);
+            self.lower_select_with(was_below, |mut sel| sel.ctx.ins().fge(val, F_ZERO), |_| FALSE)
+        } else {
+            let was_above = self.ctx.ins().fgt(prev_val, F_ZERO);
+            self.lower_select_with(was_above, |mut sel| sel.ctx.ins().fle(val, F_ZERO), |_| FALSE)
+        }
+    }
+
+    /// Checks whether a crossing between the last accepted timepoint and the current
+    /// timepoint was hit within the tolerances. Otherwise the timestep is rejected and the
+    /// next step is bounded to end at the linearly interpolated crossing.
+    fn localize_crossing(
+        &mut self,
+        prev_val: Value,
+        val: Value,
+        prev_time: Value,
+        time: Value,
+        time_tol: Value,
+        expr_tol: Value,
+    ) -> Value {
+        let dt = self.ctx.ins().fsub(time, prev_time);
+        let delta = self.ctx.ins().fsub(prev_val, val);
+        let frac = self.ctx.ins().fdiv(prev_val, delta);
+        let crossing_step = self.ctx.ins().fmul(dt, frac);
+        let overshoot = self.ctx.ins().fsub(dt, crossing_step);
+
+        let in_time = self.ctx.ins().fle(overshoot, time_tol);
+        let hit = self.lower_select_with(
+            in_time,
+            |_| TRUE,
+            |mut sel| {
+                let neg_val = sel.ctx.ins().fneg(val);
+                let is_neg = sel.ctx.ins().flt(val, F_ZERO);
+                let abs_val = sel.lower_select_with(is_neg, |_| neg_val, |_| val);
+                sel.ctx.ins().fle(abs_val, expr_tol)
+            },
+        );
+
+        let step = self.lower_select_with(hit, |_| INFINITY, |_| crossing_step);
+        self.ctx.make_cond(hit, |ctx, hit| {
+            if !hit {
+                ctx.call(CallBackKind::SetRetFlag(RetFlag::Reject), &[]);
+            }
+        });
+        self.ctx.bound_step(step);
+        hit
+    }
+}
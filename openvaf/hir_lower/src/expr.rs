@@ -1,25 +1,26 @@
 use hir::builtin::{
-    FLICKER_NOISE_NAME, NOISE_TABLE_FILE_NAME, NOISE_TABLE_INLINE_NAME, WHITE_NOISE_NAME,
+    read_noise_table, FLICKER_NOISE_NAME, NOISE_TABLE_FILE_NAME, NOISE_TABLE_INLINE_NAME,
+    WHITE_NOISE_NAME,
 };
 use hir::signatures::{
-    ABS_INT, ABS_REAL, BOOL_EQ, DDX_POT, IDTMOD_IC, IDTMOD_IC_MODULUS, IDTMOD_IC_MODULUS_OFFSET,
-    IDTMOD_IC_MODULUS_OFFSET_NATURE, IDTMOD_IC_MODULUS_OFFSET_TOL, IDTMOD_NO_IC, IDT_IC,
-    IDT_IC_ASSERT, IDT_IC_ASSERT_NATURE, IDT_IC_ASSERT_TOL, IDT_NO_IC, INT_EQ, INT_OP,
-    LIMIT_BUILTIN_FUNCTION, MAX_INT, MAX_REAL, NATURE_ACCESS_BRANCH, NATURE_ACCESS_NODES,
-    NATURE_ACCESS_NODE_GND, NATURE_ACCESS_PORT_FLOW, REAL_EQ, REAL_OP, SIMPARAM_DEFAULT,
-    SIMPARAM_NO_DEFAULT, STR_EQ,
+    ABSDELAY_MAX, ABS_INT, ABS_REAL, BOOL_EQ, DDX_POT, IDTMOD_IC, IDTMOD_IC_MODULUS,
+    IDTMOD_IC_MODULUS_OFFSET, IDTMOD_IC_MODULUS_OFFSET_NATURE, IDTMOD_IC_MODULUS_OFFSET_TOL,
+    IDTMOD_NO_IC, IDT_IC, IDT_IC_ASSERT, IDT_IC_ASSERT_NATURE, IDT_IC_ASSERT_TOL, IDT_NO_IC,
+    INT_EQ, INT_OP, LIMIT_BUILTIN_FUNCTION, MAX_INT, MAX_REAL, NATURE_ACCESS_BRANCH,
+    NATURE_ACCESS_NODES, NATURE_ACCESS_NODE_GND, NATURE_ACCESS_PORT_FLOW, REAL_EQ, REAL_OP,
+    SIMPARAM_DEFAULT, SIMPARAM_NO_DEFAULT, SIMPROBE_DEFAULT, SIMPROBE_NO_DEFAULT, STR_EQ,
 };
 use hir::{Body, BuiltIn, Expr, ExprId, Literal, /*ParamSysFun,*/ Ref, ResolvedFun, Type};
 use mir::builder::InstBuilder;
-use mir::{Opcode, Value, FALSE, F_ZERO, GRAVESTONE, INFINITY, TRUE, ZERO};
+use mir::{Opcode, Value, FALSE, F_ONE, F_ZERO, GRAVESTONE, INFINITY, TRUE, ZERO};
 use stdx::iter::zip;
 use syntax::ast::{BinaryOp, UnaryOp};
 
 use crate::body::BodyLoweringCtx;
 use crate::fmt::DisplayKind;
 use crate::{
-    CallBackKind, CurrentKind, IdtKind, ImplicitEquationKind, NoiseTable, ParamKind, PlaceKind,
-    RetFlag,
+    CallBackKind, CurrentKind, IdtKind, ImplicitEquationKind, NodeAlias, NoiseTable, ParamKind,
+    PlaceKind, RetFlag,
 };
 
 impl BodyLoweringCtx<'_, '_, '_> {
@@ -32,7 +33,10 @@ impl BodyLoweringCtx<'_, '_, '_> {
             Expr::Read(Ref::ParamSysFun(param)) => {
                 self.ctx.use_param(ParamKind::ParamSysFun(param))
             }
-            Expr::Read(Ref::Parameter(param)) => self.ctx.use_param(ParamKind::Param(param)),
+            Expr::Read(Ref::Parameter(param)) => match self.ctx.fixed_param(param) {
+                Some(val) => val,
+                None => self.ctx.use_param(ParamKind::Param(param)),
+            },
             Expr::Read(Ref::FunctionReturn(fun)) => {
                 self.ctx.use_place(PlaceKind::FunctionReturn(fun))
             }
@@ -53,7 +57,8 @@ impl BodyLoweringCtx<'_, '_, '_> {
                 ResolvedFun::User { func, limit } => self.lower_user_fun(func, limit, args),
                 ResolvedFun::BuiltIn(builtin) => self.lower_builtin(expr, builtin, args),
             },
-            Expr::Array(vals) => self.lower_array(expr, vals),
+            Expr::Index { base, index } => self.lower_index(expr, base, index),
+            Expr::Array(_) => unreachable!("arrays are lowered element-wise"),
             Expr::Literal(lit) => match *lit {
                 Literal::String(ref str) => self.ctx.sconst(str),
                 Literal::Int(val) => self.ctx.iconst(val),
@@ -101,9 +106,6 @@ impl BodyLoweringCtx<'_, '_, '_> {
         }
     }
 
-    fn lower_array(&mut self, _expr: ExprId, _args: &[ExprId]) -> Value {
-        todo!("arrays")
-    }
     fn lower_bin_op(&mut self, expr: ExprId, lhs: ExprId, rhs: ExprId, op: BinaryOp) -> Value {
         let signature = self.body.get_call_signature(expr);
         let op = match op {
@@ -416,6 +418,56 @@ impl BodyLoweringCtx<'_, '_, '_> {
                 self.ins_display(DisplayKind::Display, true, args);
                 GRAVESTONE
             }
+            BuiltIn::random
+            | BuiltIn::arandom
+            | BuiltIn::rdist_uniform
+            | BuiltIn::rdist_normal
+            | BuiltIn::rdist_exponential
+            | BuiltIn::rdist_poisson
+            | BuiltIn::rdist_chi_square
+            | BuiltIn::rdist_t
+            | BuiltIn::rdist_erlang
+            | BuiltIn::dist_uniform
+            | BuiltIn::dist_normal
+            | BuiltIn::dist_exponential
+            | BuiltIn::dist_poisson
+            | BuiltIn::dist_chi_square
+            | BuiltIn::dist_t
+            | BuiltIn::dist_erlang => self.lower_random(builtin, args),
+
+            BuiltIn::table_model => self.lower_table_model(args),
+
+            BuiltIn::fwrite => {
+                self.ins_file_display(false, args);
+                GRAVESTONE
+            }
+            BuiltIn::fdisplay | BuiltIn::fstrobe | BuiltIn::fmonitor => {
+                self.ins_file_display(true, args);
+                GRAVESTONE
+            }
+            BuiltIn::fopen => {
+                let path = self.lower_expr(args[0]);
+                let mode = match args.get(1) {
+                    Some(&mode) => self.lower_expr(mode),
+                    None => self.ctx.sconst("w"),
+                };
+                self.ctx.call1(CallBackKind::FileOpen, &[path, mode])
+            }
+            BuiltIn::fclose => {
+                let fd = self.lower_expr(args[0]);
+                self.ctx.call(CallBackKind::FileClose, &[fd]);
+                ZERO
+            }
+            BuiltIn::swrite | BuiltIn::sformat => {
+                self.ins_string_write(args);
+                GRAVESTONE
+            }
+            BuiltIn::sscanf => self.lower_sscanf(args),
+            BuiltIn::test_plusargs => {
+                let fmt = self.lower_expr(args[0]);
+                self.ctx.call1(CallBackKind::TestPlusArgs, &[fmt])
+            }
+            BuiltIn::value_plusargs => self.lower_value_plusargs(args),
             BuiltIn::debug => {
                 self.ins_display(DisplayKind::Debug, true, args);
                 GRAVESTONE
@@ -480,7 +532,10 @@ impl BodyLoweringCtx<'_, '_, '_> {
                     self.ctx.func.interner.get_or_intern(name)
                 };
                 let pwr = self.lower_expr(args[0]);
-                self.ctx.call1(CallBackKind::WhiteNoise { name, idx }, &[pwr])
+                let res = self.ctx.call1(CallBackKind::WhiteNoise { name, idx }, &[pwr]);
+                let unknown = self.small_signal_unknown("white_noise", args.get(1).copied());
+                self.set_operator_unknown(res, unknown);
+                res
             }
             BuiltIn::flicker_noise => {
                 // see above
@@ -495,7 +550,10 @@ impl BodyLoweringCtx<'_, '_, '_> {
                 };
                 let pwr = self.lower_expr(args[0]);
                 let exp = self.lower_expr(args[1]);
-                self.ctx.call1(CallBackKind::FlickerNoise { name, idx }, &[pwr, exp])
+                let res = self.ctx.call1(CallBackKind::FlickerNoise { name, idx }, &[pwr, exp]);
+                let unknown = self.small_signal_unknown("flicker_noise", args.get(2).copied());
+                self.set_operator_unknown(res, unknown);
+                res
             }
             BuiltIn::noise_table | BuiltIn::noise_table_log => {
                 // see above
@@ -509,8 +567,43 @@ impl BodyLoweringCtx<'_, '_, '_> {
                     self.ctx.func.interner.get_or_intern(name)
                 };
                 let log = builtin == BuiltIn::noise_table_log;
-                let noise_table = NoiseTable::new([(0.0, 0.0)], log, name, idx);
-                self.ctx.call1(CallBackKind::NoiseTable(Box::new(noise_table)), &[])
+                let vals = match self.body.as_literal(args[0]) {
+                    Some(Literal::String(path)) => {
+                        // unreadable files have already been reported during validation
+                        let root_file = self.ctx.db.compilation_unit().root_file();
+                        read_noise_table(self.ctx.db, root_file, path).unwrap_or_default()
+                    }
+                    _ => self
+                        .body
+                        .as_real_literal_array(args[0])
+                        .unwrap_or_default()
+                        .chunks_exact(2)
+                        .map(|pair| (pair[0], pair[1]))
+                        .collect(),
+                };
+                let noise_table = NoiseTable::new(vals, log, name, idx);
+                let res = self.ctx.call1(CallBackKind::NoiseTable(Box::new(noise_table)), &[]);
+                let unknown =
+                    self.small_signal_unknown(&format!("{builtin:?}"), args.get(1).copied());
+                self.set_operator_unknown(res, unknown);
+                res
+            }
+
+            BuiltIn::ac_stim => {
+                // see above
+                let idx = self.ctx.num_noise_sources;
+                self.ctx.num_noise_sources += 1;
+                let name = match args.first() {
+                    Some(&name) => self.body.as_literal(name).unwrap().unwrap_str(),
+                    None => "ac",
+                };
+                let name = self.ctx.func.interner.get_or_intern(name);
+                let mag = args.get(1).map_or(F_ONE, |&mag| self.lower_expr(mag));
+                let phase = args.get(2).map_or(F_ZERO, |&phase| self.lower_expr(phase));
+                let res = self.ctx.call1(CallBackKind::AcStim { name, idx }, &[mag, phase]);
+                let unknown = self.small_signal_unknown("ac_stim", args.first().copied());
+                self.set_operator_unknown(res, unknown);
+                res
             }
 
             BuiltIn::abstime => self.ctx.use_param(ParamKind::Abstime),
@@ -520,7 +613,15 @@ impl BodyLoweringCtx<'_, '_, '_> {
                     return F_ZERO;
                 }
                 let arg = self.lower_expr(args[0]);
-                self.ctx.call1(CallBackKind::TimeDerivative, &[arg])
+                let res = self.ctx.call1(CallBackKind::TimeDerivative, &[arg]);
+                // operands without a ddt_nature (or nature) fall back to the contribution
+                let nature = self
+                    .operand_nature(args[0])
+                    .and_then(|it| it.ddt_nature(self.ctx.db))
+                    .or(self.ctx.contribute_nature);
+                let unknown = self.implicit_unknown("ddt", args[0], nature);
+                self.set_operator_unknown(res, unknown);
+                res
             }
 
             BuiltIn::idt | BuiltIn::idtmod if self.ctx.no_equations => {
@@ -539,7 +640,7 @@ impl BodyLoweringCtx<'_, '_, '_> {
                         IDT_IC_ASSERT | IDT_IC_ASSERT_TOL | IDT_IC_ASSERT_NATURE => IdtKind::Assert
                 };
 
-                self.lower_integral(kind, args)
+                self.lower_integral(builtin, kind, args)
             }
 
             BuiltIn::idtmod => {
@@ -554,7 +655,7 @@ impl BodyLoweringCtx<'_, '_, '_> {
                         | IDTMOD_IC_MODULUS_OFFSET_NATURE => IdtKind::ModulusOffset
                 };
 
-                self.lower_integral(kind, args)
+                self.lower_integral(builtin, kind, args)
             }
 
             BuiltIn::flow => {
@@ -630,11 +731,50 @@ impl BodyLoweringCtx<'_, '_, '_> {
                 let arg0 = self.lower_expr(args[0]);
                 self.ctx.call1(CallBackKind::SimParamStr, &[arg0])
             }
-            BuiltIn::param_given => self
-                .ctx
-                .use_param(ParamKind::ParamGiven { param: self.body.into_parameter(args[0]) }),
-            BuiltIn::port_connected => {
-                self.ctx.use_param(ParamKind::PortConnected { port: self.body.into_node(args[0]) })
+            BuiltIn::simprobe => {
+                let arg0 = self.lower_expr(args[0]);
+                let arg1 = self.lower_expr(args[1]);
+                match_signature! {signature:
+                    SIMPROBE_NO_DEFAULT => self.ctx.call1(CallBackKind::SimProbe, &[arg0, arg1]),
+                    SIMPROBE_DEFAULT => {
+                        let arg2 = self.lower_expr(args[2]);
+                        self.ctx.call1(CallBackKind::SimProbeOpt, &[arg0, arg1, arg2])
+                    }
+                }
+            }
+            BuiltIn::param_given => {
+                let param = self.body.into_parameter(args[0]);
+                match self.ctx.fixed_param_given(param) {
+                    Some(given) => given,
+                    None => self.ctx.use_param(ParamKind::ParamGiven { param }),
+                }
+            }
+            BuiltIn::port_connected => match self.body.port_connected(args[0]) {
+                Some(true) => TRUE,
+                Some(false) => FALSE,
+                None => self
+                    .ctx
+                    .use_param(ParamKind::PortConnected { port: self.body.into_node(args[0]) }),
+            },
+            BuiltIn::analog_node_alias | BuiltIn::analog_port_alias => {
+                let port = builtin == BuiltIn::analog_port_alias;
+                let node = self.body.into_node(args[0]);
+                let Some(Literal::String(target)) = self.body.as_literal(args[1]) else {
+                    unreachable!("alias targets are string literals")
+                };
+                match node.resolve_alias(self.ctx.db, target, port) {
+                    // the alias is collapsed into its target during setup_instance
+                    Some(dst) if dst != node => {
+                        let dst = (!dst.is_gnd(self.ctx.db)).then_some(dst);
+                        self.ctx.call(CallBackKind::CollapseHint(node, dst), &[]);
+                    }
+                    Some(_) => (),
+                    None => {
+                        let alias = NodeAlias { target: target.to_string(), port };
+                        self.ctx.intern.node_aliases.insert(node, alias);
+                    }
+                }
+                self.ctx.iconst(1)
             }
             BuiltIn::bound_step => {
                 let step_size = self.lower_expr(args[0]);
@@ -668,10 +808,17 @@ impl BodyLoweringCtx<'_, '_, '_> {
             BuiltIn::discontinuity => {
                 // AB: Negative literals are represented as UnaryOp::Neg(Literal)
                 //     We have a function for that now.
-                if self.ctx.inside_lim && Some(-1) == self.body.as_literalsignedint(&args[0]) {
-                    self.ctx.call(CallBackKind::LimDiscontinuity, &[]);
+                if Some(-1) == args.first().and_then(|arg| self.body.as_literalsignedint(arg)) {
+                    if self.ctx.inside_lim {
+                        self.ctx.call(CallBackKind::LimDiscontinuity, &[]);
+                    }
                 } else {
-                    // TODO implement support for discontinuity?
+                    // $discontinuity without an argument is a discontinuity of order 0
+                    let order = match args.first() {
+                        Some(&arg) => self.lower_expr(arg),
+                        None => self.ctx.iconst(0),
+                    };
+                    self.ctx.call(CallBackKind::SetRetFlag(RetFlag::Discontinuity), &[order]);
                 }
                 GRAVESTONE
             }
@@ -689,43 +836,64 @@ impl BodyLoweringCtx<'_, '_, '_> {
                 GRAVESTONE
             }
 
-            /* TODO: absdelay
+            BuiltIn::absdelay if self.ctx.no_equations => self.lower_expr(args[0]),
             BuiltIn::absdelay => {
-                let arg = self.lower_expr(args[0]);
+                let input = self.lower_expr(args[0]);
                 let mut delay = self.lower_expr(args[1]);
-                let (eq1, res) = self.ctx.implicit_equation(ImplicitEquationKind::Absdelay);
-                let (eq2, intermediate) = self.ctx.implicit_equation(ImplicitEquationKind::Absdelay);
                 if signature == ABSDELAY_MAX {
                     let max_delay = self.lower_expr(args[2]);
                     let use_delay = self.ctx.ins().fle(delay, max_delay);
                     delay = self.lower_select_with(use_delay, |_| delay, |_| max_delay);
-                } else {
-                    delay = self.ctx.call1(CallBackKind::StoreDelayTime(eq1), &[delay]);
                 }
 
-                let mut resist_val = self.ctx.ins().fsub(res, arg);
-                resist_val = self.ctx.ins().fdiv(resist_val, delay);
-                self.ctx.def_resist_residual(resist_val, eq1);
-                self.ctx.def_react_residual(intermediate, eq1);
-
-                let mut resist_val = self.ctx.ins().fsub(res, intermediate);
-                resist_val = self.ctx.ins().fdiv(resist_val, delay);
-                self.ctx.def_resist_residual(resist_val, eq2);
-                let react_val = self.ctx.ins().fdiv(res, F_THREE);
-                self.ctx.def_react_residual(react_val, eq2);
+                // The output is an implicit unknown so that simulators can apply the
+                // exact phase shift exp(-j*w*delay) to its equation during AC analysis
+                let id = self.ctx.intern.delays.next_key();
+                let (equation, res) =
+                    self.ctx.implicit_equation(ImplicitEquationKind::Absdelay(id));
+                self.ctx.intern.delays.push(equation);
+                let nature = self.operand_nature(args[0]);
+                let unknown = self.implicit_unknown("absdelay", args[0], nature);
+                self.ctx.intern.set_implicit_unknown(equation, unknown);
+                self.ctx.def_place(PlaceKind::DelayInput(id), input);
+                self.ctx.def_place(PlaceKind::DelayTime(id), delay);
+
+                // res = hist + weight * (input - hist)
+                let hist = self.ctx.call1(CallBackKind::AbsDelay(id), &[delay]);
+                let weight = self.ctx.call1(CallBackKind::AbsDelayWeight(id), &[delay]);
+                let diff = self.ctx.ins().fsub(input, hist);
+                let val = self.ctx.ins().fmul(weight, diff);
+                let val = self.ctx.ins().fadd(hist, val);
+                let residual = self.ctx.ins().fsub(res, val);
+                self.ctx.def_resist_residual(residual, equation);
 
                 res
-            }*/
-            BuiltIn::slew | BuiltIn::transition | BuiltIn::limit | BuiltIn::absdelay => {
-                self.lower_expr(args[0])
             }
+            BuiltIn::laplace_nd
+            | BuiltIn::laplace_np
+            | BuiltIn::laplace_zd
+            | BuiltIn::laplace_zp
+            | BuiltIn::zi_nd
+            | BuiltIn::zi_np
+            | BuiltIn::zi_zd
+            | BuiltIn::zi_zp => self.lower_filter(builtin, args),
+
+            BuiltIn::transition => self.lower_transition(args),
+            BuiltIn::slew => self.lower_slew(args),
+            BuiltIn::last_crossing => self.lower_last_crossing(args),
+            BuiltIn::limit => self.lower_expr(args[0]),
 
             _ => unreachable!(),
         }
     }
 
-    fn lower_integral(&mut self, kind: IdtKind, args: &[ExprId]) -> Value {
+    fn lower_integral(&mut self, builtin: BuiltIn, kind: IdtKind, args: &[ExprId]) -> Value {
         let (equation, val) = self.ctx.implicit_equation(ImplicitEquationKind::Idt(kind));
+        let operand_nature = self.operand_nature(args[0]);
+        let nature = operand_nature.and_then(|it| it.idt_nature(self.ctx.db));
+        let mut unknown = self.implicit_unknown(&format!("{builtin:?}"), args[0], nature);
+        unknown.residual_nature = operand_nature;
+        self.ctx.intern.set_implicit_unknown(equation, unknown);
 
         let mut enable_integral = self.ctx.use_param(ParamKind::EnableIntegration);
         let residual = if kind.has_ic() {
@@ -0,0 +1,342 @@
+//! Lowering of the `laplace_*` and `zi_*` analog filters.
+//!
+//! Every filter is first turned into a rational transfer function `N(s)/D(s)` (polynomials in
+//! `s` with coefficients in ascending order). That transfer function is then realized in
+//! controllable canonical form with one implicit equation per pole:
+//!
+//! ```text
+//! ddt(x_i) = x_{i+1}                                    for i < m - 1
+//! d_m * ddt(x_{m-1}) + d_{m-1} x_{m-1} + ... + d_0 x_0 = u
+//! y = n_0 x_0 + ... + n_{m-1} x_{m-1} + n_m ddt(x_{m-1}) + ...
+//! ```
+//!
+//! The z-domain filters are discrete-time filters: their input is sampled every `T` seconds
+//! (starting at `t0`) and the output is held between samples. Each sample is processed with
+//! the difference equation of `N(z^-1)/D(z^-1)` in direct form II. The delay line and the
+//! held output are transient states that are only updated at the sample times, which are
+//! hit exactly by requesting them with `$bound_step`. After a sample the output ramps
+//! linearly to the new value within the transition time `τ`. Outside of transient
+//! analysis the filter behaves like its DC gain `N(1)/D(1)`.
+
+use hir::{BuiltIn, ExprId, Type};
+use mir::builder::InstBuilder;
+use mir::{Value, F_ONE, F_ZERO, INFINITY};
+use stdx::iter::zip;
+
+use crate::body::BodyLoweringCtx;
+use crate::{CallBackKind, ImplicitEquationKind, ParamKind};
+
+/// Tolerance (in sample periods) for detecting that a sample time was reached
+const SAMPLE_TOL: f64 = 1e-9;
+
+/// A complex number, the roots of the filters are given as (real, imag) pairs.
+#[derive(Clone, Copy)]
+struct Complex {
+    re: Value,
+    im: Value,
+}
+
+impl Complex {
+    const ONE: Complex = Complex { re: F_ONE, im: F_ZERO };
+}
+
+impl BodyLoweringCtx<'_, '_, '_> {
+    pub(crate) fn lower_filter(&mut self, builtin: BuiltIn, args: &[ExprId]) -> Value {
+        let input = self.lower_expr(args[0]);
+        let (num, den) = match builtin {
+            BuiltIn::laplace_nd => (self.lower_coeffs(args[1]), self.lower_coeffs(args[2])),
+            BuiltIn::laplace_np => (self.lower_coeffs(args[1]), self.lower_laplace_roots(args[2])),
+            BuiltIn::laplace_zd => (self.lower_laplace_roots(args[1]), self.lower_coeffs(args[2])),
+            BuiltIn::laplace_zp => {
+                (self.lower_laplace_roots(args[1]), self.lower_laplace_roots(args[2]))
+            }
+            BuiltIn::zi_nd | BuiltIn::zi_np | BuiltIn::zi_zd | BuiltIn::zi_zp => {
+                return self.lower_zi_filter(builtin, input, args)
+            }
+            _ => unreachable!(),
+        };
+
+        if self.ctx.no_equations {
+            return self.dc_response(input, &num, &den);
+        }
+
+        // the states of the realization are named after the filter and their index
+        let first = self.ctx.intern.implicit_equations.next_key();
+        let res = self.realize_transfer_function(input, &num, &den);
+        let unknown = self.implicit_unknown(&format!("{builtin:?}"), args[0], None);
+        let last = self.ctx.intern.implicit_equations.next_key();
+        for (i, equation) in (u32::from(first)..u32::from(last)).enumerate() {
+            let mut unknown = unknown.clone();
+            unknown.name = format!("{}[{i}]", unknown.name);
+            self.ctx.intern.set_implicit_unknown(equation.into(), unknown);
+        }
+        res
+    }
+
+    /// Lowers a z-domain filter `zi_*(x, num, den, T [, τ [, t0]])` as a sampled filter
+    fn lower_zi_filter(&mut self, builtin: BuiltIn, input: Value, args: &[ExprId]) -> Value {
+        let mut num = if matches!(builtin, BuiltIn::zi_nd | BuiltIn::zi_np) {
+            self.lower_coeffs(args[1])
+        } else {
+            self.lower_zi_roots(args[1])
+        };
+        let mut den = if matches!(builtin, BuiltIn::zi_nd | BuiltIn::zi_zd) {
+            self.lower_coeffs(args[2])
+        } else {
+            self.lower_zi_roots(args[2])
+        };
+
+        // the steady state of the delay line for a constant input (z = 1)
+        let num_dc = self.poly_sum(&num);
+        let den_dc = self.poly_sum(&den);
+        let has_dc_pole = self.ctx.ins().feq(den_dc, F_ZERO);
+        let tap_dc = self.lower_select_with(
+            has_dc_pole,
+            |_| F_ZERO,
+            |mut sel| sel.ctx.ins().fdiv(input, den_dc),
+        );
+        let out_dc = self.ctx.ins().fmul(num_dc, tap_dc);
+        if self.ctx.no_equations {
+            return out_dc;
+        }
+
+        let period = self.lower_expr(args[3]);
+        let transition = args.get(4).map_or(F_ZERO, |arg| self.lower_expr(*arg));
+        let first_sample = args.get(5).map_or(F_ZERO, |arg| self.lower_expr(*arg));
+
+        let order = num.len().max(den.len()).saturating_sub(1);
+        num.resize(order + 1, F_ZERO);
+        den.resize(order + 1, F_ZERO);
+        // the number of samples taken so far, the output after the last sample, the output
+        // it ramps from and the delay line
+        let (states, prev): (Vec<_>, Vec<_>) =
+            (0..order + 3).map(|_| self.ctx.tran_state(builtin)).unzip();
+        let (prev_samples, prev_out, prev_from) = (prev[0], prev[1], prev[2]);
+        let taps = &prev[3..];
+
+        // a sample is due once the time reaches t0 + samples * T, the tolerance ensures that
+        // samples are not missed due to roundoff when the simulator steps onto a sample time
+        let time = self.ctx.use_param(ParamKind::Abstime);
+        let tol = self.ctx.fconst(SAMPLE_TOL);
+        let elapsed = self.ctx.ins().fsub(time, first_sample);
+        let elapsed = self.ctx.ins().fdiv(elapsed, period);
+        let elapsed = self.ctx.ins().fadd(elapsed, tol);
+        let elapsed = self.ctx.ins().floor(elapsed);
+        let due_samples = self.ctx.ins().fadd(elapsed, F_ONE);
+        let sampled = self.ctx.ins().fgt(due_samples, prev_samples);
+
+        // w_0 = (x - sum_{k>0} d_k w_k) / d_0 and y = sum_k n_k w_k
+        let mut feedback = input;
+        let mut out = F_ZERO;
+        for (k, &tap) in taps.iter().enumerate() {
+            let val = self.ctx.ins().fmul(den[k + 1], tap);
+            feedback = self.ctx.ins().fsub(feedback, val);
+            let val = self.ctx.ins().fmul(num[k + 1], tap);
+            out = self.ctx.ins().fadd(out, val);
+        }
+        let new_tap = self.ctx.ins().fdiv(feedback, den[0]);
+        let val = self.ctx.ins().fmul(num[0], new_tap);
+        let out = self.ctx.ins().fadd(out, val);
+
+        // a new sample interrupts the ramp towards the previous sample
+        let prev_start = self.sample_time(prev_samples, period, first_sample);
+        let prev_end = self.ctx.ins().fadd(prev_start, transition);
+        let from = self.ramp(prev_from, prev_out, prev_start, prev_end, time);
+
+        let mut next = vec![due_samples, out, from, new_tap];
+        next.extend_from_slice(&taps[..order.saturating_sub(1)]);
+        let next = self.select_vals(sampled, &next, &prev);
+        let (samples, out, from) = (next[0], next[1], next[2]);
+
+        let start = self.sample_time(samples, period, first_sample);
+        let end = self.ctx.ins().fadd(start, transition);
+        let res = self.ramp(from, out, start, end, time);
+        // the next step ends at the end of the ramp or at the next sample
+        let ramping = self.ctx.ins().flt(time, end);
+        let step = self.lower_select_with(
+            ramping,
+            |mut sel| sel.ctx.ins().fsub(end, time),
+            |mut sel| {
+                let next_sample = sel.ctx.ins().fadd(start, period);
+                sel.ctx.ins().fsub(next_sample, time)
+            },
+        );
+
+        let is_tran = self.is_tran();
+        let mut tran = vec![res, step];
+        tran.extend(next);
+        let mut dc = vec![out_dc, INFINITY, F_ZERO, out_dc, out_dc];
+        dc.resize(order + 5, tap_dc);
+        let vals = self.select_vals(is_tran, &tran, &dc);
+        for (state, val) in zip(states, &vals[2..]) {
+            self.ctx.store_tran_state(state, *val);
+        }
+        self.ctx.bound_step(vals[1]);
+        vals[0]
+    }
+
+    /// Selects between two lists of values that were computed before the branch
+    fn select_vals(&mut self, cond: Value, then_vals: &[Value], else_vals: &[Value]) -> Vec<Value> {
+        let ((then_bb, _), (else_bb, _)) = self.lower_cond_with(cond, |_, _| ());
+        zip(then_vals, else_vals)
+            .map(|(&then_val, &else_val)| {
+                self.ctx.ins().phi(&[(then_bb, then_val), (else_bb, else_val)])
+            })
+            .collect()
+    }
+
+    /// The time `t0 + (samples - 1) * T` at which the last of `samples` samples was taken
+    fn sample_time(&mut self, samples: Value, period: Value, first_sample: Value) -> Value {
+        let idx = self.ctx.ins().fsub(samples, F_ONE);
+        let offset = self.ctx.ins().fmul(idx, period);
+        self.ctx.ins().fadd(first_sample, offset)
+    }
+
+    fn poly_sum(&mut self, poly: &[Value]) -> Value {
+        poly.iter().fold(F_ZERO, |sum, &coeff| self.ctx.ins().fadd(sum, coeff))
+    }
+
+    /// Lowers the coefficients of a filter
+    fn lower_coeffs(&mut self, arr: ExprId) -> Vec<Value> {
+        self.lower_array_elems(arr, &Type::Real)
+    }
+
+    /// Lowers an array of (real, imag) pairs
+    fn lower_roots(&mut self, arr: ExprId) -> Vec<Complex> {
+        self.lower_coeffs(arr)
+            .chunks_exact(2)
+            .map(|root| Complex { re: root[0], im: root[1] })
+            .collect()
+    }
+
+    /// Computes the polynomial `prod_k (1 - s/r_k)` where roots at the origin contribute `s`
+    /// instead (as specified by the standard for `laplace_zp`).
+    fn lower_laplace_roots(&mut self, arr: ExprId) -> Vec<Value> {
+        let roots = self.lower_roots(arr);
+        let mut poly = vec![Complex::ONE];
+        for root in roots {
+            let re2 = self.ctx.ins().fmul(root.re, root.re);
+            let im2 = self.ctx.ins().fmul(root.im, root.im);
+            let mag2 = self.ctx.ins().fadd(re2, im2);
+            let at_origin = self.ctx.ins().feq(mag2, F_ZERO);
+            let [c0, c1_re, c1_im] = self.lower_multi_select(at_origin, |mut sel, at_origin| {
+                if at_origin {
+                    [F_ZERO, F_ONE, F_ZERO]
+                } else {
+                    // -1/r = -conj(r)/|r|^2
+                    let re = sel.ctx.ins().fneg(root.re);
+                    let re = sel.ctx.ins().fdiv(re, mag2);
+                    let im = sel.ctx.ins().fdiv(root.im, mag2);
+                    [F_ONE, re, im]
+                }
+            });
+            let factor = [Complex { re: c0, im: F_ZERO }, Complex { re: c1_re, im: c1_im }];
+            poly = self.complex_poly_mul(&poly, &factor);
+        }
+        // complex roots come in conjugate pairs so the imaginary parts cancel
+        poly.into_iter().map(|coeff| coeff.re).collect()
+    }
+
+    /// Computes the polynomial `prod_k (1 - r_k z^-1)` in `z^-1`
+    fn lower_zi_roots(&mut self, arr: ExprId) -> Vec<Value> {
+        let roots = self.lower_roots(arr);
+        let mut poly = vec![Complex::ONE];
+        for root in roots {
+            let re = self.ctx.ins().fneg(root.re);
+            let im = self.ctx.ins().fneg(root.im);
+            poly = self.complex_poly_mul(&poly, &[Complex::ONE, Complex { re, im }]);
+        }
+        poly.into_iter().map(|coeff| coeff.re).collect()
+    }
+
+    /// The response to a constant input `N(0)/D(0) * u`. Filters with poles at the origin
+    /// (integrators) have no DC response and return zero instead.
+    fn dc_response(&mut self, input: Value, num: &[Value], den: &[Value]) -> Value {
+        let num0 = num.first().copied().unwrap_or(F_ZERO);
+        let den0 = den.first().copied().unwrap_or(F_ZERO);
+        let has_dc_pole = self.ctx.ins().feq(den0, F_ZERO);
+        self.lower_select_with(
+            has_dc_pole,
+            |_| F_ZERO,
+            |mut sel| {
+                let gain = sel.ctx.ins().fdiv(num0, den0);
+                sel.ctx.ins().fmul(gain, input)
+            },
+        )
+    }
+
+    fn realize_transfer_function(&mut self, input: Value, num: &[Value], den: &[Value]) -> Value {
+        let order = den.len().saturating_sub(1);
+
+        if order == 0 {
+            // no poles: the output is a weighted sum of derivatives of the input
+            let den0 = den.first().copied().unwrap_or(F_ONE);
+            let mut res = F_ZERO;
+            let mut derivative = input;
+            for (k, &coeff) in num.iter().enumerate() {
+                if k != 0 {
+                    derivative = self.ctx.call1(CallBackKind::TimeDerivative, &[derivative]);
+                }
+                let val = self.ctx.ins().fmul(coeff, derivative);
+                res = self.ctx.ins().fadd(res, val);
+            }
+            return self.ctx.ins().fdiv(res, den0);
+        }
+
+        let states: Vec<_> =
+            (0..order).map(|_| self.ctx.implicit_equation(ImplicitEquationKind::Filter)).collect();
+
+        // ddt(x_i) - x_{i+1} = 0
+        for window in states.windows(2) {
+            let (equation, state) = window[0];
+            let (_, next_state) = window[1];
+            let resist = self.ctx.ins().fneg(next_state);
+            self.ctx.def_resist_residual(resist, equation);
+            self.ctx.def_react_residual(state, equation);
+        }
+
+        // d_m ddt(x_{m-1}) + sum_k d_k x_k - u = 0
+        let (equation, last_state) = states[order - 1];
+        let mut resist = self.ctx.ins().fneg(input);
+        for (&coeff, &(_, state)) in den.iter().zip(&states) {
+            let val = self.ctx.ins().fmul(coeff, state);
+            resist = self.ctx.ins().fadd(resist, val);
+        }
+        let react = self.ctx.ins().fmul(den[order], last_state);
+        self.ctx.def_resist_residual(resist, equation);
+        self.ctx.def_react_residual(react, equation);
+
+        // y = sum_k n_k s^k X
+        let mut res = F_ZERO;
+        let mut derivative = last_state;
+        for (k, &coeff) in num.iter().enumerate() {
+            let val = if k < order {
+                states[k].1
+            } else {
+                derivative = self.ctx.call1(CallBackKind::TimeDerivative, &[derivative]);
+                derivative
+            };
+            let val = self.ctx.ins().fmul(coeff, val);
+            res = self.ctx.ins().fadd(res, val);
+        }
+        res
+    }
+
+    fn complex_poly_mul(&mut self, lhs: &[Complex], rhs: &[Complex]) -> Vec<Complex> {
+        let mut res = vec![Complex { re: F_ZERO, im: F_ZERO }; lhs.len() + rhs.len() - 1];
+        for (i, lhs) in lhs.iter().enumerate() {
+            for (j, rhs) in rhs.iter().enumerate() {
+                let re1 = self.ctx.ins().fmul(lhs.re, rhs.re);
+                let re2 = self.ctx.ins().fmul(lhs.im, rhs.im);
+                let re = self.ctx.ins().fsub(re1, re2);
+                let im1 = self.ctx.ins().fmul(lhs.re, rhs.im);
+                let im2 = self.ctx.ins().fmul(lhs.im, rhs.re);
+                let im = self.ctx.ins().fadd(im1, im2);
+                let dst = &mut res[i + j];
+                dst.re = self.ctx.ins().fadd(dst.re, re);
+                dst.im = self.ctx.ins().fadd(dst.im, im);
+            }
+        }
+        res
+    }
+}
@@ -1,8 +1,9 @@
-use hir::{ExprId, Literal, Type};
-use mir::GRAVESTONE;
+use hir::{Expr, ExprId, Literal, Ref, Type};
+use mir::{Value, GRAVESTONE};
 
 use crate::body::BodyLoweringCtx;
 use crate::callbacks::CallBackKind;
+use crate::PlaceKind;
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq, Copy)]
 pub enum DisplayKind {
@@ -36,18 +37,94 @@ pub struct FmtArg {
 
 impl BodyLoweringCtx<'_, '_, '_> {
     pub fn ins_display(&mut self, kind: DisplayKind, newline: bool, args: &[ExprId]) {
+        // For $fatal skip the first argument
+        let args = if kind == DisplayKind::Fatal { args.get(1..).unwrap_or(&[]) } else { args };
+        let (call_args, arg_tys) = self.lower_fmt(newline, args, 0);
+        self.ctx
+            .call(CallBackKind::Print { kind, arg_tys: arg_tys.into_boxed_slice() }, &call_args);
+    }
+
+    /// Lowers `$fdisplay`/`$fwrite`/`$fstrobe`/`$fmonitor` where the first argument
+    /// is the file descriptor.
+    pub fn ins_file_display(&mut self, newline: bool, args: &[ExprId]) {
+        let fd = self.lower_expr(args[0]);
+        let (mut call_args, arg_tys) = self.lower_fmt(newline, &args[1..], 1);
+        call_args[0] = fd;
+        self.ctx.call(CallBackKind::FilePrint { arg_tys: arg_tys.into_boxed_slice() }, &call_args);
+    }
+
+    /// Lowers `$swrite`/`$sformat` where the first argument is the variable that receives
+    /// the formatted string.
+    pub fn ins_string_write(&mut self, args: &[ExprId]) {
+        let (call_args, arg_tys) = self.lower_fmt(false, &args[1..], 0);
+        let slot = self.ctx.num_string_slots;
+        self.ctx.num_string_slots += 1;
+        let kind = CallBackKind::FormatString { arg_tys: arg_tys.into_boxed_slice(), slot };
+        let val = self.ctx.call1(kind, &call_args);
+        let dst = self.body.get_expr(args[0]).as_assignment_lhs();
+        self.ctx.def_place(dst.into(), val);
+    }
+
+    /// Lowers `$sscanf`. Every output variable is assigned by a dedicated callback that
+    /// returns the previous value of the variable if the format does not assign it.
+    pub fn lower_sscanf(&mut self, args: &[ExprId]) -> Value {
+        let src = self.lower_expr(args[0]);
+        let fmt = self.lower_expr(args[1]);
+        for (arg, &expr) in args[2..].iter().enumerate() {
+            let var = match self.body.get_expr(expr) {
+                Expr::Read(Ref::Variable(var)) => var,
+                _ => unreachable!("$sscanf outputs are validated to be variables"),
+            };
+            let ty = var.ty(self.ctx.db);
+            let slot = (ty == Type::String).then(|| {
+                self.ctx.num_string_slots += 1;
+                self.ctx.num_string_slots - 1
+            });
+            let old = self.ctx.use_place(PlaceKind::Var(var));
+            let kind = CallBackKind::ScanValue { arg: arg as u32, ty, slot };
+            let val = self.ctx.call1(kind, &[src, fmt, old]);
+            self.ctx.def_place(PlaceKind::Var(var), val);
+        }
+        self.ctx.call1(CallBackKind::ScanCount, &[src, fmt])
+    }
+
+    /// `$value$plusargs` scans the plusarg that matches the format with the format (so the
+    /// text before the conversion matches the name of the plusarg).
+    pub fn lower_value_plusargs(&mut self, args: &[ExprId]) -> Value {
+        let fmt = self.lower_expr(args[0]);
+        let var = match self.body.get_expr(args[1]) {
+            Expr::Read(Ref::Variable(var)) => var,
+            _ => unreachable!("$value$plusargs outputs are validated to be variables"),
+        };
+        let ty = var.ty(self.ctx.db);
+        let slot = (ty == Type::String).then(|| {
+            self.ctx.num_string_slots += 1;
+            self.ctx.num_string_slots - 1
+        });
+        let src = self.ctx.call1(CallBackKind::PlusArg, &[fmt]);
+        let old = self.ctx.use_place(PlaceKind::Var(var));
+        let val = self.ctx.call1(CallBackKind::ScanValue { arg: 0, ty, slot }, &[src, fmt, old]);
+        self.ctx.def_place(PlaceKind::Var(var), val);
+        self.ctx.call1(CallBackKind::TestPlusArgs, &[fmt])
+    }
+
+    /// Lowers the arguments of a display-like task to a C format string followed by
+    /// the formatted values. The format string is placed after `offset` leading
+    /// (placeholder) arguments.
+    fn lower_fmt(
+        &mut self,
+        newline: bool,
+        args: &[ExprId],
+        offset: usize,
+    ) -> (Vec<Value>, Vec<FmtArg>) {
         let mut fmt_lit = String::new();
-        let mut call_args = vec![GRAVESTONE];
+        let mut call_args = vec![GRAVESTONE; offset + 1];
         let mut arg_tys = Vec::new();
 
         let mut i = 0;
 
         while let Some(&expr) = args.get(i) {
             i += 1;
-            // For $fatal skip the first argument
-            if i == 1 && kind == DisplayKind::Fatal {
-                continue;
-            }
             if let Some(Literal::String(ref lit)) = self.body.as_literal(expr) {
                 fmt_lit.reserve(lit.len());
                 let mut chars = lit.chars();
@@ -153,8 +230,7 @@ impl BodyLoweringCtx<'_, '_, '_> {
             fmt_lit.push('\n');
         }
 
-        call_args[0] = self.ctx.sconst(&fmt_lit);
-        self.ctx
-            .call(CallBackKind::Print { kind, arg_tys: arg_tys.into_boxed_slice() }, &call_args);
+        call_args[offset] = self.ctx.sconst(&fmt_lit);
+        (call_args, arg_tys)
     }
 }
@@ -0,0 +1,206 @@
+//! Readable names and natures of the unknowns of implicit equations.
+//!
+//! Every implicit equation is created by an analog operator (`ddt`, `idt`, `absdelay`, a noise
+//! source, ...). Its unknown is named after the operator and its operand (for example
+//! `idt(potential(a,b))`). The nature of the unknown is inferred from the operand: branch
+//! probes have the flow/potential nature of their discipline and variables can declare a
+//! nature with the `(* nature = "..." *)` attribute. `ddt` and `idt` map the nature of the
+//! operand to its `ddt_nature`/`idt_nature`. Small signal sources (noise and `ac_stim`)
+//! have the nature of the branch quantity they are contributed to. The same applies to `ddt`
+//! if the operand has no `ddt_nature`, also when the result is contributed through a variable.
+
+use std::fmt::Write;
+
+use ahash::AHashSet;
+use hir::signatures::{
+    NATURE_ACCESS_BRANCH, NATURE_ACCESS_NODES, NATURE_ACCESS_NODE_GND, NATURE_ACCESS_PORT_FLOW,
+};
+use hir::{BuiltIn, Discipline, Expr, ExprId, Literal, Nature, Ref, ResolvedFun};
+use mir::Value;
+
+use crate::body::BodyLoweringCtx;
+use crate::ImplicitUnknown;
+
+/// Operands with a longer description are not used for names
+const MAX_OPERAND_LEN: usize = 48;
+
+impl BodyLoweringCtx<'_, '_, '_> {
+    /// The nature of the value of `expr` (if it can be inferred)
+    pub(crate) fn operand_nature(&self, expr: ExprId) -> Option<Nature> {
+        let db = self.ctx.db;
+        match self.body.get_expr(expr) {
+            Expr::Read(Ref::Variable(var)) => var.nature(db),
+            Expr::Call { fun: ResolvedFun::BuiltIn(BuiltIn::potential), args } => {
+                self.probe_discipline(expr, args).potential(db)
+            }
+            Expr::Call { fun: ResolvedFun::BuiltIn(BuiltIn::flow), args } => {
+                self.probe_discipline(expr, args).flow(db)
+            }
+            _ => None,
+        }
+    }
+
+    /// The unknown of an equation created by the analog operator `name` with the operand
+    /// `operand`. The residual of the equation has the same nature as the unknown.
+    pub(crate) fn implicit_unknown(
+        &self,
+        name: &str,
+        operand: ExprId,
+        nature: Option<Nature>,
+    ) -> ImplicitUnknown {
+        let name = match self.describe_operand(operand) {
+            Some(operand) => format!("{name}({operand})"),
+            None => name.to_owned(),
+        };
+        ImplicitUnknown { name, nature, residual_nature: nature }
+    }
+
+    /// The unknown of a small signal source (noise or `ac_stim`) created by the operator `name`.
+    /// The optional `label` argument is the name of the source.
+    pub(crate) fn small_signal_unknown(
+        &self,
+        name: &str,
+        label: Option<ExprId>,
+    ) -> ImplicitUnknown {
+        let name = match label.and_then(|label| self.body.as_literal(label)) {
+            Some(Literal::String(label)) => format!("{name}({label})"),
+            _ => name.to_owned(),
+        };
+        let nature = self.ctx.contribute_nature;
+        ImplicitUnknown { name, nature, residual_nature: nature }
+    }
+
+    /// Remembers `unknown` for the analog operator call that produced `res`. The unknown is
+    /// used if the call is later turned into an implicit equation.
+    pub(crate) fn set_operator_unknown(&mut self, res: Value, unknown: ImplicitUnknown) {
+        if let Some(inst) = self.ctx.func.dfg.value_def(res).inst() {
+            if unknown.nature.is_none() {
+                self.ctx.operators_without_nature.push(inst);
+            }
+            self.ctx.intern.operator_unknowns.insert(inst, unknown);
+        }
+    }
+
+    /// Assigns `nature` to the unknowns of the analog operator calls without a nature that
+    /// `contribution` depends on. This covers operators whose result is assigned to a variable
+    /// before it is contributed (`x = ddt(q); I(a) <+ x;`).
+    pub(crate) fn infer_operator_natures(&mut self, contribution: Value, nature: Nature) {
+        if self.ctx.operators_without_nature.is_empty() {
+            return;
+        }
+
+        let dfg = &self.ctx.func.dfg;
+        let mut visited = AHashSet::new();
+        let mut stack = vec![contribution];
+        while let Some(val) = stack.pop() {
+            if let Some(inst) = dfg.value_def(val).inst() {
+                if visited.insert(inst) {
+                    stack.extend_from_slice(dfg.instr_args(inst));
+                }
+            }
+        }
+
+        let operator_unknowns = &mut self.ctx.intern.operator_unknowns;
+        self.ctx.operators_without_nature.retain(|inst| {
+            if !visited.contains(inst) {
+                return true;
+            }
+            if let Some(unknown) = operator_unknowns.get_mut(inst) {
+                unknown.nature = Some(nature);
+                unknown.residual_nature = Some(nature);
+            }
+            false
+        });
+    }
+
+    /// A source like description of `expr`, `None` if the expression is too complex to be
+    /// used as a name.
+    fn describe_operand(&self, expr: ExprId) -> Option<String> {
+        let mut dst = String::new();
+        self.write_operand(expr, &mut dst)?;
+        Some(dst)
+    }
+
+    fn write_operand(&self, expr: ExprId, dst: &mut String) -> Option<()> {
+        let db = self.ctx.db;
+        match self.body.get_expr(expr) {
+            Expr::Read(Ref::Variable(var)) => dst.push_str(&var.name(db)),
+            Expr::Read(Ref::Parameter(param)) => dst.push_str(&param.name(db)),
+            Expr::Read(Ref::FunctionArg(arg)) => dst.push_str(&arg.name(db)),
+            Expr::Read(Ref::ParamSysFun(param)) => write!(dst, "${param:?}").unwrap(),
+            Expr::Literal(Literal::Int(val)) => write!(dst, "{val}").unwrap(),
+            Expr::Literal(&Literal::Float(val)) => write!(dst, "{:?}", f64::from(val)).unwrap(),
+            Expr::UnaryOp { expr, op } => {
+                write!(dst, "{op}").unwrap();
+                self.write_nested_operand(expr, dst)?;
+            }
+            Expr::BinaryOp { lhs, rhs, op } => {
+                self.write_nested_operand(lhs, dst)?;
+                write!(dst, "{op}").unwrap();
+                self.write_nested_operand(rhs, dst)?;
+            }
+            Expr::Call {
+                fun: ResolvedFun::BuiltIn(builtin @ (BuiltIn::potential | BuiltIn::flow)),
+                args,
+            } => {
+                write!(dst, "{builtin:?}(").unwrap();
+                let signature = self.body.get_call_signature(expr);
+                match signature {
+                    NATURE_ACCESS_NODES | NATURE_ACCESS_NODE_GND => {
+                        dst.push_str(&self.body.into_node(args[0]).name(db));
+                        if let Some(&lo) = args.get(1) {
+                            write!(dst, ",{}", self.body.into_node(lo).name(db)).unwrap();
+                        }
+                    }
+                    NATURE_ACCESS_BRANCH => dst.push_str(&self.body.into_branch(args[0]).name(db)),
+                    NATURE_ACCESS_PORT_FLOW => {
+                        write!(dst, "<{}>", self.body.into_port_flow(args[0]).name(db)).unwrap()
+                    }
+                    _ => return None,
+                }
+                dst.push(')');
+            }
+            Expr::Call { fun, args } => {
+                match fun {
+                    ResolvedFun::BuiltIn(builtin) => write!(dst, "{builtin:?}(").unwrap(),
+                    ResolvedFun::User { func, .. } => write!(dst, "{}(", func.name(db)).unwrap(),
+                }
+                for (i, &arg) in args.iter().enumerate() {
+                    if i != 0 {
+                        dst.push(',');
+                    }
+                    self.write_operand(arg, dst)?;
+                }
+                dst.push(')');
+            }
+            _ => return None,
+        }
+        (dst.len() <= MAX_OPERAND_LEN).then_some(())
+    }
+
+    /// Writes `expr` and wraps binary operators in parentheses
+    fn write_nested_operand(&self, expr: ExprId, dst: &mut String) -> Option<()> {
+        if matches!(self.body.get_expr(expr), Expr::BinaryOp { .. }) {
+            dst.push('(');
+            self.write_operand(expr, dst)?;
+            dst.push(')');
+        } else {
+            self.write_operand(expr, dst)?;
+        }
+        Some(())
+    }
+
+    /// The discipline of the branch accessed by the probe `expr`
+    fn probe_discipline(&self, expr: ExprId, args: &[ExprId]) -> Discipline {
+        let db = self.ctx.db;
+        let signature = self.body.get_call_signature(expr);
+        match_signature! {
+            signature:
+                NATURE_ACCESS_NODES | NATURE_ACCESS_NODE_GND => {
+                    self.body.into_node(args[0]).discipline(db)
+                },
+                NATURE_ACCESS_BRANCH => self.body.into_branch(args[0]).discipline(db),
+                NATURE_ACCESS_PORT_FLOW => self.body.into_port_flow(args[0]).discipline(db)
+        }
+    }
+}
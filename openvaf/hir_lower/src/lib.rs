@@ -3,9 +3,12 @@ use std::iter::FilterMap;
 
 use ahash::{AHashMap, AHashSet};
 use bitset::HybridBitSet;
-pub use callbacks::{CallBackKind, NoiseTable, ParamInfoKind, RetFlag};
+pub use callbacks::{
+    CallBackKind, NoiseTable, ParamInfoKind, RandomDist, RetFlag, TableModel, TableSource,
+};
 use hir::{
-    Branch, BranchWrite, CompilationDB, Module, Node, ParamSysFun, Parameter, Type, Variable,
+    Branch, BranchWrite, BuiltIn, CompilationDB, Module, MonitoredEventKind, Nature, Node,
+    ParamSysFun, Parameter, Type, Variable,
 };
 use indexmap::IndexMap;
 use lasso::Rodeo;
@@ -14,7 +17,7 @@ use mir::{DataFlowGraph, FuncRef, Function, Inst, KnownDerivatives, Param, Unkno
 use mir_build::{FunctionBuilder, FunctionBuilderContext, RetBuilder};
 use rustc_hash::FxHasher;
 use stdx::packed_option::PackedOption;
-use stdx::{impl_debug_display, impl_idx_from};
+use stdx::{impl_debug_display, impl_from_typed, impl_idx_from};
 use typed_index_collections::TiVec;
 use typed_indexmap::{map, TiMap, TiSet};
 
@@ -31,20 +34,33 @@ macro_rules! match_signature {
     };
 }
 
+mod array;
 mod body;
 mod callbacks;
 mod ctx;
+mod events;
 mod expr;
+mod filter;
 pub mod fmt;
+mod implicit;
 mod parameters;
+mod random;
 mod state;
 mod stmt;
+mod table;
+mod transition;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ImplicitEquationKind {
     Ddt,
     NoiseSrc,
+    /// A correlation network for an `ac_stim` source
+    AcStimSrc,
     Idt(IdtKind),
+    /// A state of a `laplace_*` filter
+    Filter,
+    /// The output of an `absdelay` operator
+    Absdelay(Delay),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -82,13 +98,26 @@ pub enum ParamKind {
     EnableLim,
     PrevState(LimitState),
     NewState(LimitState),
-    Voltage { hi: Node, lo: Option<Node> },
+    PrevTranState(TranState),
+    PrevPersistentState(PersistentState),
+    Voltage {
+        hi: Node,
+        lo: Option<Node>,
+    },
     Current(CurrentKind),
     Temperature,
-    ParamGiven { param: Parameter },
-    PortConnected { port: Node },
+    ParamGiven {
+        param: Parameter,
+    },
+    PortConnected {
+        port: Node,
+    },
     ParamSysFun(ParamSysFun),
     HiddenState(Variable),
+    /// The element at (zero based) position `pos` of an array parameter
+    ParamElement(Parameter, u32),
+    /// The element at (zero based) position `pos` of an array variable
+    HiddenStateElement(Variable, u32),
     ImplicitUnknown(ImplicitEquation),
 }
 
@@ -109,8 +138,11 @@ impl ParamKind {
                 | ParamKind::Abstime
                 | ParamKind::EnableIntegration
                 | ParamKind::HiddenState(_)
+                | ParamKind::HiddenStateElement(..)
                 | ParamKind::PrevState(_)
                 | ParamKind::NewState(_)
+                | ParamKind::PrevTranState(_)
+                | ParamKind::PrevPersistentState(_)
                 | ParamKind::EnableLim
         )
     }
@@ -154,6 +186,9 @@ impl IdtKind {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum PlaceKind {
     Var(Variable),
+    /// The element at (zero based) position `pos` of an array variable.
+    /// This is the position within the array and not the declared index.
+    VarElement(Variable, u32),
     FunctionReturn(hir::Function),
     FunctionArg(hir::FunctionArg),
     Contribute {
@@ -169,21 +204,30 @@ pub enum PlaceKind {
     IsVoltageSrc(BranchWrite),
     /// A parameter during param initiliztion is mutable (write default in case its not given)
     Param(Parameter),
+    ParamElement(Parameter, u32),
     ParamMin(Parameter),
     ParamMax(Parameter),
     BoundStep,
+    /// The value delayed by an `absdelay` operator
+    DelayInput(Delay),
+    /// The delay time of an `absdelay` operator
+    DelayTime(Delay),
 }
 
 impl PlaceKind {
     pub fn ty(&self, db: &CompilationDB) -> Type {
         match *self {
             PlaceKind::Var(var) => var.ty(db),
+            PlaceKind::VarElement(var, _) => var.ty(db).base_type().clone(),
+            PlaceKind::ParamElement(param, _) => param.ty(db).base_type().clone(),
             PlaceKind::FunctionReturn(fun) => fun.return_ty(db),
             PlaceKind::FunctionArg(arg) => arg.ty(db),
 
             PlaceKind::ImplicitResidual { .. }
             | PlaceKind::Contribute { .. }
-            | PlaceKind::BoundStep => Type::Real,
+            | PlaceKind::BoundStep
+            | PlaceKind::DelayInput(_)
+            | PlaceKind::DelayTime(_) => Type::Real,
             PlaceKind::ParamMin(param) | PlaceKind::ParamMax(param) | PlaceKind::Param(param) => {
                 param.ty(db)
             }
@@ -202,6 +246,9 @@ impl From<hir::AssignmentLhs> for PlaceKind {
             hir::AssignmentLhs::Variable(var) => PlaceKind::Var(var),
             hir::AssignmentLhs::FunctionReturn(fun) => PlaceKind::FunctionReturn(fun),
             hir::AssignmentLhs::FunctionArg(arg) => PlaceKind::FunctionArg(arg),
+            hir::AssignmentLhs::VariableElement { .. } => {
+                unreachable!("array elements are lowered with BodyLoweringCtx::assign")
+            }
         }
     }
 }
@@ -220,6 +267,59 @@ impl_debug_display! {
     match LimitState {LimitState(i) => "lim_state{}", i;}
 }
 
+/// A state of an analog operator that depends on the history of a transient simulation
+/// (like `transition`). These states are stored in the state vector of the simulator after
+/// the limiting states.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TranState(u32);
+impl_idx_from!(TranState(u32));
+impl_debug_display! {
+    match TranState {TranState(i) => "tran_state{}", i;}
+}
+
+/// A state of an analog operator that is stored in the instance data and persists across
+/// `eval` calls. Values written during `eval` are only committed once the simulator accepts
+/// the timestep (see `accept_timestep` in the OSDI descriptor).
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PersistentState(u32);
+impl_idx_from!(PersistentState(u32));
+impl_debug_display! {
+    match PersistentState {PersistentState(i) => "persistent_state{}", i;}
+}
+
+/// The analog operator or monitored event that owns a [`TranState`] or [`PersistentState`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StateOwner {
+    Operator(BuiltIn),
+    Event(MonitoredEventKind),
+}
+impl_from_typed!(Operator(BuiltIn), Event(MonitoredEventKind) for StateOwner);
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Delay(u32);
+impl_idx_from!(Delay(u32));
+impl_debug_display! {
+    match Delay {Delay(i) => "delay{}", i;}
+}
+
+/// The target of a `$analog_node_alias` or `$analog_port_alias` call that is not a node of
+/// the compiled module and must be resolved by the simulator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeAlias {
+    pub target: String,
+    pub port: bool,
+}
+
+/// The readable name and the natures of the unknown of an implicit equation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImplicitUnknown {
+    /// Derived from the analog operator that created the equation and its operand
+    /// (for example `ddt(qb)`)
+    pub name: String,
+    pub nature: Option<Nature>,
+    pub residual_nature: Option<Nature>,
+}
+
 /// A mapping between abstractions used in the MIR and the corresponding
 /// information from the HIR. This allows the MIR to remain independent of the frontend/HIR
 #[derive(Debug, PartialEq, Clone)]
@@ -231,6 +331,20 @@ pub struct HirInterner {
     pub tagged_reads: IndexMap<Value, Variable, BuildHasherDefault<FxHasher>>,
     pub implicit_equations: TiVec<ImplicitEquation, ImplicitEquationKind>,
     pub lim_state: TiMap<LimitState, Value, Vec<(Value, bool)>>,
+    /// The implicit equation that holds the output of each `absdelay`
+    pub delays: TiVec<Delay, ImplicitEquation>,
+    /// The analog operator or event that owns each transient state
+    pub tran_states: TiVec<TranState, StateOwner>,
+    /// The analog operator or event that owns each persistent state
+    pub persistent_states: TiVec<PersistentState, StateOwner>,
+    /// The nodes that are aliases of a node outside of the compiled module
+    pub node_aliases: IndexMap<Node, NodeAlias, BuildHasherDefault<FxHasher>>,
+    /// The name and natures of the unknowns of implicit equations
+    pub implicit_unknowns:
+        IndexMap<ImplicitEquation, ImplicitUnknown, BuildHasherDefault<FxHasher>>,
+    /// The name and natures of the unknown that replaces an analog operator call (`ddt`,
+    /// noise sources and `ac_stim`) if the call is turned into an implicit equation
+    pub operator_unknowns: AHashMap<Inst, ImplicitUnknown>,
 }
 
 pub type LiveParams<'a> = FilterMap<
@@ -248,11 +362,40 @@ impl Default for HirInterner {
             tagged_reads: IndexMap::with_hasher(BuildHasherDefault::<FxHasher>::default()),
             implicit_equations: TiVec::default(),
             lim_state: TiMap::default(),
+            delays: TiVec::default(),
+            tran_states: TiVec::default(),
+            persistent_states: TiVec::default(),
+            node_aliases: IndexMap::with_hasher(BuildHasherDefault::<FxHasher>::default()),
+            implicit_unknowns: IndexMap::with_hasher(BuildHasherDefault::<FxHasher>::default()),
+            operator_unknowns: AHashMap::new(),
         }
     }
 }
 
 impl HirInterner {
+    /// The number of slots in the state vector of the simulator (limiting and transient states)
+    pub fn num_states(&self) -> usize {
+        self.lim_state.len() + self.tran_states.len()
+    }
+
+    /// Sets the name and natures of the unknown of `equation`. A name that is already used by
+    /// another implicit unknown is made unique by appending the index of the equation.
+    pub fn set_implicit_unknown(
+        &mut self,
+        equation: ImplicitEquation,
+        mut unknown: ImplicitUnknown,
+    ) {
+        if self.implicit_unknowns.values().any(|it| it.name == unknown.name) {
+            unknown.name = format!("{}#{}", unknown.name, u32::from(equation));
+        }
+        self.implicit_unknowns.insert(equation, unknown);
+    }
+
+    /// The slot of a transient state in the state vector of the simulator
+    pub fn tran_state_slot(&self, state: TranState) -> usize {
+        self.lim_state.len() + usize::from(state)
+    }
+
     fn contains_ddx(
         ddx_calls: &mut AHashMap<FuncRef, (HybridBitSet<Unknown>, HybridBitSet<Unknown>)>,
         func: &Function,
@@ -357,7 +500,17 @@ impl HirInterner {
             }
         }
 
-        KnownDerivatives { unknowns, ddx_calls }
+        let call_derivatives = self
+            .callbacks
+            .iter_enumerated()
+            .filter_map(|(func, kind)| {
+                let partials = kind.partial_derivatives()?;
+                let partials = partials.iter().map(|partial| self.callbacks.index(partial));
+                Some((func, partials.collect::<Option<_>>()?))
+            })
+            .collect();
+
+        KnownDerivatives { unknowns, ddx_calls, call_derivatives }
     }
 
     pub fn is_param_live(&self, func: impl AsRef<Function>, kind: &ParamKind) -> bool {
@@ -496,17 +649,41 @@ impl<'a> MirBuilder<'a> {
         let path = self.module.name(self.db);
         let analog_initial_body = self.module.analog_initial_block(self.db);
         let analog_body = self.module.analog_block(self.db);
+        // module instances are flattened into the top-level module
+        let instances: Vec<_> = self
+            .module
+            .all_instances(self.db)
+            .into_iter()
+            .map(|inst| {
+                let inst_path = format!("{path}.{}", inst.name(self.db));
+                (inst, inst_path, inst.analog_initial_block(self.db), inst.analog_block(self.db))
+            })
+            .collect();
 
         let mut ctx = LoweringCtx::new(self.db, builder, !self.lower_equations, &mut interner)
             .with_tagged_vars(self.tagged_reads);
-        let mut body_ctx =
-            BodyLoweringCtx { ctx: &mut ctx, body: analog_initial_body.borrow(), path: &path };
+
+        // the base module parameters of paramsets and the parameters of module instances
+        // are fixed and computed upfront
+        ctx.lower_paramset_params(self.module);
+        for &(inst, ..) in &instances {
+            ctx.lower_instance_params(inst);
+        }
 
         // lower analog initial blocks first
-        body_ctx.lower_entry_stmts();
+        BodyLoweringCtx { ctx: &mut ctx, body: analog_initial_body.borrow(), path: &path }
+            .lower_entry_stmts();
+        for (_, inst_path, analog_initial_body, _) in &instances {
+            BodyLoweringCtx { ctx: &mut ctx, body: analog_initial_body.borrow(), path: inst_path }
+                .lower_entry_stmts();
+        }
         // ... and normal analog blocks afterwards
-        body_ctx.body = analog_body.borrow();
-        body_ctx.lower_entry_stmts();
+        BodyLoweringCtx { ctx: &mut ctx, body: analog_body.borrow(), path: &path }
+            .lower_entry_stmts();
+        for (_, inst_path, _, analog_body) in &instances {
+            BodyLoweringCtx { ctx: &mut ctx, body: analog_body.borrow(), path: inst_path }
+                .lower_entry_stmts();
+        }
 
         for var in self.required_vars {
             ctx.dec_place(PlaceKind::Var(var));
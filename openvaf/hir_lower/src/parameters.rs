@@ -1,11 +1,14 @@
 use std::f64::NEG_INFINITY;
 use std::mem::replace;
+use std::sync::Arc;
 
+use ahash::AHashMap;
 use hir::{CompilationDB, ConstraintValue, ParamConstraint, Parameter, Type};
 use lasso::Rodeo;
 use mir::builder::InstBuilder;
-use mir::{Block, FuncRef, Function, Opcode, Value, FALSE, GRAVESTONE, INFINITY};
+use mir::{Block, FuncRef, Function, Opcode, Value, FALSE, GRAVESTONE, INFINITY, TRUE};
 use mir_build::{FunctionBuilder, FunctionBuilderContext};
+use stdx::iter::zip;
 use stdx::packed_option::ReservedValue;
 use syntax::ast::ConstraintKind;
 
@@ -58,6 +61,7 @@ impl HirInterner {
         params: &[Parameter],
     ) {
         let mut default_vals = if build_stores { vec![GRAVESTONE; params.len()] } else { vec![] };
+        let mut array_default_vals: AHashMap<Parameter, Vec<Value>> = AHashMap::new();
 
         let f_neg_inf = func.dfg.fconst(NEG_INFINITY.into());
         let f_inf = INFINITY;
@@ -69,6 +73,62 @@ impl HirInterner {
         let mut ctx = LoweringCtx::new(db, builder, true, self);
 
         for (i, param) in params.iter().copied().enumerate() {
+            if let Some(range) = param.array_range(db) {
+                // array parameters are initialized element-wise
+                let param_given = ctx.use_param(ParamKind::ParamGiven { param });
+                let body = param.init(db);
+                let ty = param.ty(db);
+                let bounds = param_bounds(db, param);
+                let ops = CmpOps::from_ty(&ty);
+                let invalid =
+                    ctx.dec_callback(CallBackKind::ParamInfo(ParamInfoKind::Invalid, param));
+                let elem_ty = ty.base_type().clone();
+                let (param_vals, new_vals): (Vec<_>, Vec<_>) = (0..range.num_elements())
+                    .map(|pos| {
+                        let param_val = ctx.use_param(ParamKind::ParamElement(param, pos));
+                        let new_val = ctx.func.make_param(0u32.into());
+                        ctx.dfg_mut().replace_uses(param_val, new_val);
+                        (param_val, new_val)
+                    })
+                    .unzip();
+
+                let (then_src, else_src) = ctx.make_cond(param_given, |ctx, param_given| {
+                    let vals = if param_given {
+                        param_vals.clone()
+                    } else {
+                        ctx.lower_array_body(body.borrow(), 0, &elem_ty)
+                    };
+                    if build_stores {
+                        let mut ctx = BodyLoweringCtx { ctx, body: body.borrow(), path: "" };
+                        ctx.check_array_param(&vals, &bounds, ops, invalid);
+                        if !param_given {
+                            let defaults =
+                                vals.iter().map(|&val| ctx.ctx.ins().optbarrier(val)).collect();
+                            array_default_vals.insert(param, defaults);
+                        }
+                    }
+                    vals
+                });
+
+                for (pos, (&param_val, &new_val)) in zip(&param_vals, &new_vals).enumerate() {
+                    let pos = pos as u32;
+                    ctx.ins().with_result(new_val).phi(&[
+                        (then_src.0, then_src.1[pos as usize]),
+                        (else_src.0, else_src.1[pos as usize]),
+                    ]);
+                    // see below
+                    ctx.def_param(ParamKind::ParamElement(param, pos), new_val);
+                    ctx.def_output(PlaceKind::ParamElement(param, pos), param_val);
+                }
+
+                // min/max are not supported for arrays but the bounds apply to each element
+                if build_min_max {
+                    let mut ctx = BodyLoweringCtx { ctx: &mut ctx, body: body.borrow(), path: "" };
+                    ctx.check_array_param(&new_vals, &bounds, ops, invalid);
+                }
+                continue;
+            }
+
             let mut param_val = ctx.use_param(ParamKind::Param(param));
             let param_given = ctx.use_param(ParamKind::ParamGiven { param });
 
@@ -78,7 +138,7 @@ impl HirInterner {
 
             let body = param.init(db);
             let ty = param.ty(db);
-            let bounds = param.bounds(db);
+            let bounds = param_bounds(db, param);
 
             let ops = CmpOps::from_ty(&ty);
             let invalid = ctx.dec_callback(CallBackKind::ParamInfo(ParamInfoKind::Invalid, param));
@@ -310,6 +370,16 @@ impl HirInterner {
         ctx.func.func.layout.append_inst_to_bb(term, ctx.current_block());
 
         for (i, param) in params.iter().copied().enumerate() {
+            if let Some(range) = param.array_range(db) {
+                for pos in 0..range.num_elements() {
+                    let val = &mut self.params.raw[&ParamKind::ParamElement(param, pos)];
+                    let output_val =
+                        if build_stores { array_default_vals[&param][pos as usize] } else { *val };
+                    let output = &mut self.outputs[&PlaceKind::ParamElement(param, pos)];
+                    *val = replace(output, Some(output_val).into()).unwrap_unchecked();
+                }
+                continue;
+            }
             let val = &mut self.params.raw[&ParamKind::Param(param)];
             let output_val = if build_stores { default_vals[i] } else { *val };
             *val = replace(&mut self.outputs[&PlaceKind::Param(param)], Some(output_val).into())
@@ -318,7 +388,77 @@ impl HirInterner {
     }
 }
 
+/// The ranges of the parameters of overloaded paramsets select the paramset
+/// (see [`LoweringCtx::lower_paramset_params`]) instead of restricting their values.
+fn param_bounds(db: &CompilationDB, param: Parameter) -> Arc<[ParamConstraint]> {
+    if param.selects_paramset(db) {
+        Arc::from(Vec::new())
+    } else {
+        param.bounds(db)
+    }
+}
+
 impl BodyLoweringCtx<'_, '_, '_> {
+    /// Returns whether `val` satisfies the `from` and `exclude` constraints of a parameter.
+    pub(crate) fn param_in_bounds(
+        &mut self,
+        val: Value,
+        bounds: &[ParamConstraint],
+        ty: &Type,
+    ) -> Value {
+        let ops = CmpOps::from_ty(ty);
+        let mut from = None;
+        let mut excluded = FALSE;
+        for bound in bounds {
+            let matches = match bound.val {
+                ConstraintValue::Value(expr) => {
+                    let expr = self.lower_expr(expr);
+                    self.ctx.ins().binary1(ops.eq, expr, val)
+                }
+                ConstraintValue::Range(range) => {
+                    let start = self.lower_expr(range.start);
+                    let end = self.lower_expr(range.end);
+                    let is_lo_ok =
+                        self.ctx.ins().binary1(ops.in_bound(range.start_inclusive), start, val);
+                    let is_hi_ok =
+                        self.ctx.ins().binary1(ops.in_bound(range.end_inclusive), val, end);
+                    self.ctx.make_select(is_lo_ok, |_, ok| if ok { is_hi_ok } else { FALSE })
+                }
+            };
+            let any = match bound.kind {
+                ConstraintKind::From => from.get_or_insert(FALSE),
+                ConstraintKind::Exclude => &mut excluded,
+            };
+            let prev = *any;
+            *any = self.ctx.make_select(matches, |_, matches| if matches { TRUE } else { prev });
+        }
+
+        let from = from.unwrap_or(TRUE);
+        self.ctx.make_select(from, |ctx, from| {
+            if from {
+                ctx.make_select(excluded, |_, excluded| if excluded { FALSE } else { TRUE })
+            } else {
+                FALSE
+            }
+        })
+    }
+
+    /// Checks the bounds of every element of an array parameter
+    fn check_array_param(
+        &mut self,
+        vals: &[Value],
+        bounds: &[ParamConstraint],
+        ops: CmpOps,
+        invalid: FuncRef,
+    ) {
+        for &val in vals {
+            let exit = self.ctx.create_block();
+            self.check_param(val, bounds, &[], ConstraintKind::From, ops, invalid, exit);
+            self.check_param(val, bounds, &[], ConstraintKind::Exclude, ops, invalid, exit);
+            self.ctx.switch_to_block(exit);
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn check_param(
         &mut self,
@@ -0,0 +1,68 @@
+//! Lowering of the random number functions (`$random`, `$arandom`, `$rdist_*` and `$dist_*`).
+//!
+//! Values are drawn by callbacks that implement the distributions. If the seed is a variable
+//! the next seed is computed by a separate callback and written back to the variable. Calls
+//! without a seed draw from a stream that is stored in the instance data. The optional
+//! `"global"`/`"instance"` argument selects whether the global or the instance seed (set by
+//! the simulator) is mixed into the drawn values.
+
+use hir::{BuiltIn, Expr, ExprId, Literal, Ref, Type};
+use mir::{Value, F_ZERO};
+use stdx::iter::zip;
+
+use crate::body::BodyLoweringCtx;
+use crate::{CallBackKind, PlaceKind, RandomDist};
+
+impl BodyLoweringCtx<'_, '_, '_> {
+    pub(crate) fn lower_random(&mut self, builtin: BuiltIn, mut args: &[ExprId]) -> Value {
+        let dist = match builtin {
+            BuiltIn::random | BuiltIn::arandom => RandomDist::Random,
+            BuiltIn::rdist_uniform | BuiltIn::dist_uniform => RandomDist::Uniform,
+            BuiltIn::rdist_normal | BuiltIn::dist_normal => RandomDist::Normal,
+            BuiltIn::rdist_exponential | BuiltIn::dist_exponential => RandomDist::Exponential,
+            BuiltIn::rdist_poisson | BuiltIn::dist_poisson => RandomDist::Poisson,
+            BuiltIn::rdist_chi_square | BuiltIn::dist_chi_square => RandomDist::ChiSquare,
+            BuiltIn::rdist_t | BuiltIn::dist_t => RandomDist::T,
+            BuiltIn::rdist_erlang | BuiltIn::dist_erlang => RandomDist::Erlang,
+            _ => unreachable!("{builtin:?} is not a random number function"),
+        };
+
+        let mut global = true;
+        if let Some((&last, rem)) = args.split_last() {
+            if let Some(Literal::String(kind)) = self.body.as_literal(last) {
+                global = &**kind != "instance";
+                args = rem;
+            }
+        }
+
+        // distributions take at most two arguments, unused arguments are zero
+        let mut dist_args = [F_ZERO; 2];
+        for (dst, &arg) in zip(&mut dist_args, args.iter().skip(1)) {
+            *dst = self.lower_expr(arg);
+            let ty = self.resolved_ty(arg);
+            if ty != Type::Real {
+                *dst = self.ctx.insert_cast(*dst, &ty, &Type::Real);
+            }
+        }
+
+        let val = match args.first() {
+            Some(&seed_expr) => {
+                let seed = self.lower_expr(seed_expr);
+                let call_args = [seed, dist_args[0], dist_args[1]];
+                let val = self.ctx.call1(CallBackKind::Random { dist, global }, &call_args);
+                if let Expr::Read(Ref::Variable(var)) = self.body.get_expr(seed_expr) {
+                    let next = self.ctx.call1(CallBackKind::RandomNextSeed(dist), &call_args);
+                    self.ctx.def_place(PlaceKind::Var(var), next);
+                }
+                val
+            }
+            None => self.ctx.call1(CallBackKind::RandomStream { dist, global }, &dist_args),
+        };
+
+        if dist == RandomDist::Random {
+            self.ctx.insert_cast(val, &Type::Real, &Type::Integer)
+        } else {
+            val
+        }
+    }
+}
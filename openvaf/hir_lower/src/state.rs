@@ -1,6 +1,7 @@
-use hir::CompilationDB;
+use ahash::AHashMap;
+use hir::{CompilationDB, Module, Variable};
 use lasso::Rodeo;
-use mir::Function;
+use mir::{Function, Value};
 use mir_build::{FunctionBuilder, FunctionBuilderContext};
 
 use crate::ctx::LoweringCtx;
@@ -10,19 +11,38 @@ impl HirInterner {
     pub fn insert_var_init(
         &mut self,
         db: &CompilationDB,
+        module: Module,
         func: &mut Function,
         literals: &mut Rodeo,
     ) {
         let mut ctx = FunctionBuilderContext::default();
         let (builder, term) = FunctionBuilder::edit(func, literals, &mut ctx, false);
         let mut ctx = LoweringCtx::new(db, builder, true, self);
+        // initial values may depend on the (fixed) base module parameters of a paramset
+        ctx.lower_paramset_params(module);
+        // the initial value of an array is lowered once for all of its elements
+        let mut array_inits: AHashMap<Variable, Vec<Value>> = AHashMap::new();
         for (kind, param) in ctx.intern.params.clone().iter() {
-            if let ParamKind::HiddenState(var) = *kind {
-                if ctx.dfg().value_dead(*param) {
-                    continue;
+            match *kind {
+                ParamKind::HiddenState(var) => {
+                    if !is_used(&ctx, *param) {
+                        continue;
+                    }
+                    let val = ctx.lower_expr_body(var.init(db).borrow(), 0);
+                    replace_hidden_state(&mut ctx, *param, val);
                 }
-                let val = ctx.lower_expr_body(var.init(db).borrow(), 0);
-                ctx.dfg_mut().replace_uses(*param, val);
+                ParamKind::HiddenStateElement(var, pos) => {
+                    if !is_used(&ctx, *param) {
+                        continue;
+                    }
+                    let vals = array_inits.entry(var).or_insert_with(|| {
+                        let elem_ty = var.ty(db).base_type().clone();
+                        ctx.lower_array_body(var.init(db).borrow(), 0, &elem_ty)
+                    });
+                    let val = vals[pos as usize];
+                    replace_hidden_state(&mut ctx, *param, val);
+                }
+                _ => (),
             }
         }
 
@@ -30,3 +50,20 @@ impl HirInterner {
         ctx.func.func.layout.append_inst_to_bb(term, ctx.current_block())
     }
 }
+
+/// Variables that are never assigned are output with their hidden state
+/// so the hidden state may be used by an output without being read by any instruction.
+fn is_used(ctx: &LoweringCtx<'_, '_>, param: Value) -> bool {
+    !ctx.dfg().value_dead(param)
+        || ctx.intern.outputs.values().any(|val| val.expand() == Some(param))
+}
+
+/// Replaces all uses of the hidden state `param` (including outputs) with `val`.
+fn replace_hidden_state(ctx: &mut LoweringCtx<'_, '_>, param: Value, val: Value) {
+    ctx.dfg_mut().replace_uses(param, val);
+    for output in ctx.intern.outputs.values_mut() {
+        if output.expand() == Some(param) {
+            *output = val.into();
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use hir::{BranchWrite, Case, CaseCond, ContributeKind, ExprId, Node, Stmt, StmtId, Type};
+use hir::{
+    AssignmentLhs, BranchWrite, Case, CaseCond, ContributeKind, Event, ExprId, Node, Stmt, StmtId,
+    Type,
+};
 use mir::builder::InstBuilder;
 use mir::{Opcode, F_ZERO};
 
@@ -17,10 +20,21 @@ impl BodyLoweringCtx<'_, '_, '_> {
             Stmt::Expr(expr) => {
                 self.lower_expr(expr);
             }
+            Stmt::EventControl { event: Event::Monitored(events), body } => {
+                self.lower_monitored_events(events, body)
+            }
             Stmt::EventControl { body, .. } => {
                 // TODO handle porperly
                 self.lower_stmt(body);
             }
+            Stmt::Assignment { lhs: AssignmentLhs::VariableElement { var, index }, rhs } => {
+                self.lower_element_assignment(var, index, rhs)
+            }
+            Stmt::Assignment { lhs: AssignmentLhs::Variable(var), rhs }
+                if var.array_range(self.ctx.db).is_some() =>
+            {
+                self.lower_array_assignment(var, rhs)
+            }
             Stmt::Assignment { lhs, rhs } => {
                 let val_ = self.lower_expr(rhs);
                 self.ctx.def_place(lhs.into(), val_);
@@ -55,15 +69,23 @@ impl BodyLoweringCtx<'_, '_, '_> {
     }
 
     fn lower_case(&mut self, discr: ExprId, case_arms: &[Case]) {
-        let discr_op = match self.body.expr_type(discr) {
+        let discr_ty = self.body.expr_type(discr);
+        let discr_op = match discr_ty.base_type() {
             Type::Real => Opcode::Feq,
             Type::Integer => Opcode::Ieq,
             Type::Bool => Opcode::Beq,
             Type::String => Opcode::Seq,
-            Type::Array { .. } => todo!(),
+            // empty arrays are always equal
+            Type::EmptyArray => Opcode::Ieq,
             ty => unreachable!("Invalid type {}", ty),
         };
-        let discr = self.lower_expr(discr);
+        // arrays are compared element-wise
+        let is_array = matches!(discr_ty, Type::Array { .. } | Type::EmptyArray);
+        let discr = if is_array {
+            self.lower_array_elems(discr, discr_ty.base_type())
+        } else {
+            vec![self.lower_expr(discr)]
+        };
         let end = self.ctx.create_block();
 
         for Case { cond, body } in case_arms {
@@ -82,17 +104,34 @@ impl BodyLoweringCtx<'_, '_, '_> {
                 self.ctx.ensured_sealed();
 
                 // Lower the condition (val == discriminant)
-                let val_ = self.lower_expr(*val);
+                let val_ = if is_array {
+                    self.lower_array_elems(*val, discr_ty.base_type())
+                } else {
+                    vec![self.lower_expr(*val)]
+                };
+
+                // Create the next block
+                let next_block = self.ctx.create_block();
 
                 let old_loc = self.ctx.get_srcloc();
                 self.ctx.set_srcloc(mir::SourceLoc::new(u32::from(*val) as i32 + 1));
-                let cond = self.ctx.ins().binary1(discr_op, val_, discr);
+                if discr.is_empty() {
+                    self.ctx.ins().jump(body_head);
+                }
+                for (i, (&val_, &discr_)) in val_.iter().zip(&discr).enumerate() {
+                    let cond = self.ctx.ins().binary1(discr_op, val_, discr_);
+                    if i + 1 == discr.len() {
+                        self.ctx.ins().branch(cond, body_head, next_block, false);
+                    } else {
+                        // compare the next element
+                        let next_elem = self.ctx.create_block();
+                        self.ctx.ins().branch(cond, next_elem, next_block, false);
+                        self.ctx.seal_block(next_elem);
+                        self.ctx.switch_to_block(next_elem);
+                    }
+                }
                 self.ctx.set_srcloc(old_loc);
 
-                // Create the next block
-                let next_block = self.ctx.create_block();
-                self.ctx.ins().branch(cond, body_head, next_block, false);
-
                 self.ctx.switch_to_block(next_block);
             }
 
@@ -163,7 +202,19 @@ impl BodyLoweringCtx<'_, '_, '_> {
             F_ZERO,
         );
 
+        let discipline = match write {
+            BranchWrite::Named(branch) => branch.discipline(self.ctx.db),
+            BranchWrite::Unnamed { hi, .. } => hi.discipline(self.ctx.db),
+        };
+        self.ctx.contribute_nature = if voltage_src {
+            discipline.potential(self.ctx.db)
+        } else {
+            discipline.flow(self.ctx.db)
+        };
         let rhs = self.lower_expr(rhs);
+        if let Some(nature) = self.ctx.contribute_nature.take() {
+            self.infer_operator_natures(rhs, nature);
+        }
         if rhs == F_ZERO {
             return;
         }
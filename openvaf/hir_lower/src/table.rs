@@ -0,0 +1,81 @@
+//! Lowering of `$table_model`.
+//!
+//! Tables are evaluated by callbacks that return a partial derivative of the interpolated
+//! table. The callbacks for all partial derivatives up to the second order are declared
+//! together with the table so that `mir_autodiff` can differentiate the table exactly
+//! (see [`CallBackKind::partial_derivatives`]).
+
+use hir::builtin::{parse_table_control, TableDimControl};
+use hir::{ExprId, Literal, Type};
+use mir::Value;
+
+use crate::body::BodyLoweringCtx;
+use crate::{CallBackKind, TableModel, TableSource};
+
+impl BodyLoweringCtx<'_, '_, '_> {
+    pub(crate) fn lower_table_model(&mut self, args: &[ExprId]) -> Value {
+        let dims = args
+            .iter()
+            .position(|&arg| {
+                !matches!(self.body.expr_type(arg), Type::Real | Type::Integer | Type::Bool)
+            })
+            .unwrap_or(args.len());
+        let (inputs, tables) = args.split_at(dims);
+
+        // invalid tables and control strings are reported by the body validation
+        // (`TableModelError`), the lowering falls back to an empty table/default controls
+        let (src, ctrl) = match tables.first().and_then(|&table| self.body.as_literal(table)) {
+            Some(Literal::String(path)) => (TableSource::File(path.clone()), &tables[1..]),
+            _ if tables.len() <= dims => (TableSource::Inline(Box::default()), &[][..]),
+            _ => {
+                let columns: Vec<_> = tables[..=dims]
+                    .iter()
+                    .map(|&array| self.body.as_real_literal_array(array).unwrap_or_default())
+                    .collect();
+                let len = columns.iter().map(Vec::len).min().unwrap_or(0);
+                let samples = (0..len)
+                    .flat_map(|row| columns.iter().map(move |column| column[row].into()))
+                    .collect();
+                (TableSource::Inline(samples), &tables[dims + 1..])
+            }
+        };
+
+        let control = match ctrl.first().and_then(|&ctrl| self.body.as_literal(ctrl)) {
+            Some(Literal::String(ctrl)) => parse_table_control(ctrl, dims)
+                .unwrap_or_else(|_| vec![TableDimControl::default(); dims]),
+            _ => vec![TableDimControl::default(); dims],
+        };
+
+        let inputs: Vec<_> = inputs
+            .iter()
+            .map(|&arg| {
+                let val = self.lower_expr(arg);
+                let ty = self.resolved_ty(arg);
+                if ty == Type::Real {
+                    val
+                } else {
+                    self.ctx.insert_cast(val, &ty, &Type::Real)
+                }
+            })
+            .collect();
+
+        let table = Box::new(TableModel { src, control: control.into_boxed_slice() });
+        let kind = |derivative: Vec<u8>| CallBackKind::TableModel {
+            table: table.clone(),
+            derivative: derivative.into_boxed_slice(),
+        };
+        for i in 0..dims {
+            for j in i..dims {
+                let mut derivative = vec![0; dims];
+                derivative[i] += 1;
+                derivative[j] += 1;
+                self.ctx.dec_callback(kind(derivative));
+            }
+            let mut derivative = vec![0; dims];
+            derivative[i] = 1;
+            self.ctx.dec_callback(kind(derivative));
+        }
+
+        self.ctx.call1(kind(vec![0; dims]), &inputs)
+    }
+}
@@ -0,0 +1,207 @@
+//! Lowering of the `transition` and `slew` analog operators.
+//!
+//! Both operators depend on the history of a transient simulation. Their state is kept in
+//! the state vector of the simulator (after the states of limiting functions) so that it is
+//! only committed once a timepoint is accepted. The corners of the output waveform are
+//! reported to the simulator with `$bound_step` so that they are hit exactly.
+//!
+//! Outside of transient analysis (and during the initial operating point of a transient
+//! analysis) both operators pass their input through unchanged.
+
+use hir::{BuiltIn, ExprId};
+use mir::builder::InstBuilder;
+use mir::{Value, FALSE, F_ZERO, INFINITY, ZERO};
+
+use crate::body::BodyLoweringCtx;
+use crate::{CallBackKind, ParamKind};
+
+impl BodyLoweringCtx<'_, '_, '_> {
+    /// `transition(x, td, rise, fall, tol)`
+    ///
+    /// Whenever the input changes a linear ramp from the current output value to the new
+    /// input value is scheduled to start after `td`. The tolerance is not used since the
+    /// start and end of each ramp are always hit exactly.
+    pub(crate) fn lower_transition(&mut self, args: &[ExprId]) -> Value {
+        let input = self.lower_expr(args[0]);
+        if self.ctx.no_equations {
+            return input;
+        }
+
+        let delay = args.get(1).map_or(F_ZERO, |arg| self.lower_expr(*arg));
+        let rise = args.get(2).map_or(F_ZERO, |arg| self.lower_expr(*arg));
+        let fall = args.get(3).map_or(rise, |arg| self.lower_expr(*arg));
+
+        let (target_state, prev_target) = self.ctx.tran_state(BuiltIn::transition);
+        let (from_state, prev_from) = self.ctx.tran_state(BuiltIn::transition);
+        let (start_state, prev_start) = self.ctx.tran_state(BuiltIn::transition);
+        let (end_state, prev_end) = self.ctx.tran_state(BuiltIn::transition);
+
+        let time = self.ctx.use_param(ParamKind::Abstime);
+        let is_tran = self.is_tran();
+        let [res, from, start, end, step] = self.lower_multi_select(is_tran, |mut sel, tran| {
+            if !tran {
+                return [input, input, time, time, INFINITY];
+            }
+
+            let changed = sel.ctx.ins().fne(input, prev_target);
+            let [from, start, end] = sel.lower_multi_select(changed, |mut sel, changed| {
+                if !changed {
+                    return [prev_from, prev_start, prev_end];
+                }
+                // a new transition interrupts the previous one at the time it starts
+                let start = sel.ctx.ins().fadd(time, delay);
+                let from = sel.ramp(prev_from, prev_target, prev_start, prev_end, start);
+                let rising = sel.ctx.ins().fgt(input, from);
+                let duration = sel.lower_select_with(rising, |_| rise, |_| fall);
+                let end = sel.ctx.ins().fadd(start, duration);
+                [from, start, end]
+            });
+
+            let res = sel.ramp(from, input, start, end, time);
+            let before_start = sel.ctx.ins().flt(time, start);
+            let step = sel.lower_select_with(
+                before_start,
+                |mut sel| sel.ctx.ins().fsub(start, time),
+                |mut sel| {
+                    let before_end = sel.ctx.ins().flt(time, end);
+                    sel.lower_select_with(
+                        before_end,
+                        |mut sel| sel.ctx.ins().fsub(end, time),
+                        |_| INFINITY,
+                    )
+                },
+            );
+            [res, from, start, end, step]
+        });
+
+        self.ctx.store_tran_state(target_state, input);
+        self.ctx.store_tran_state(from_state, from);
+        self.ctx.store_tran_state(start_state, start);
+        self.ctx.store_tran_state(end_state, end);
+        self.ctx.bound_step(step);
+        res
+    }
+
+    /// `slew(x, max_pos_slew_rate, max_neg_slew_rate)`
+    ///
+    /// The output follows the input but its slope is limited to the given rates.
+    /// Without any rates (or with a rate of zero) the output is not limited.
+    pub(crate) fn lower_slew(&mut self, args: &[ExprId]) -> Value {
+        let input = self.lower_expr(args[0]);
+        if self.ctx.no_equations || args.len() == 1 {
+            return input;
+        }
+
+        let pos_rate = self.lower_expr(args[1]);
+        let neg_rate = match args.get(2) {
+            Some(arg) => self.lower_expr(*arg),
+            None => self.ctx.ins().fneg(pos_rate),
+        };
+
+        let (val_state, prev_val) = self.ctx.tran_state(BuiltIn::slew);
+        let (time_state, prev_time) = self.ctx.tran_state(BuiltIn::slew);
+
+        let time = self.ctx.use_param(ParamKind::Abstime);
+        let is_tran = self.is_tran();
+        let [res, step] = self.lower_multi_select(is_tran, |mut sel, tran| {
+            if !tran {
+                return [input, INFINITY];
+            }
+
+            // a rate that is zero (or has the wrong sign) does not limit the output
+            let dt = sel.ctx.ins().fsub(time, prev_time);
+            let limit_rise = sel.ctx.ins().fgt(pos_rate, F_ZERO);
+            let max = sel.lower_select_with(
+                limit_rise,
+                |mut sel| {
+                    let max_rise = sel.ctx.ins().fmul(pos_rate, dt);
+                    sel.ctx.ins().fadd(prev_val, max_rise)
+                },
+                |_| INFINITY,
+            );
+            let limit_fall = sel.ctx.ins().flt(neg_rate, F_ZERO);
+            let min = sel.lower_select_with(
+                limit_fall,
+                |mut sel| {
+                    let max_fall = sel.ctx.ins().fmul(neg_rate, dt);
+                    sel.ctx.ins().fadd(prev_val, max_fall)
+                },
+                |mut sel| sel.ctx.fconst(f64::NEG_INFINITY),
+            );
+
+            // while slewing the next step ends when the output reaches the input
+            let above_max = sel.ctx.ins().fgt(input, max);
+            sel.lower_multi_select(above_max, |mut sel, above_max| {
+                if above_max {
+                    let remaining = sel.ctx.ins().fsub(input, max);
+                    let step = sel.ctx.ins().fdiv(remaining, pos_rate);
+                    return [max, step];
+                }
+                let below_min = sel.ctx.ins().flt(input, min);
+                sel.lower_multi_select(below_min, |mut sel, below_min| {
+                    if below_min {
+                        let remaining = sel.ctx.ins().fsub(input, min);
+                        let step = sel.ctx.ins().fdiv(remaining, neg_rate);
+                        [min, step]
+                    } else {
+                        [input, INFINITY]
+                    }
+                })
+            })
+        });
+
+        self.ctx.store_tran_state(val_state, res);
+        self.ctx.store_tran_state(time_state, time);
+        self.ctx.bound_step(step);
+        res
+    }
+
+    /// The value of a linear ramp from `from` at `start` to `to` at `end` at time `time`
+    pub(crate) fn ramp(
+        &mut self,
+        from: Value,
+        to: Value,
+        start: Value,
+        end: Value,
+        time: Value,
+    ) -> Value {
+        let before_start = self.ctx.ins().fle(time, start);
+        self.lower_select_with(
+            before_start,
+            |_| from,
+            |mut sel| {
+                let after_end = sel.ctx.ins().fge(time, end);
+                sel.lower_select_with(
+                    after_end,
+                    |_| to,
+                    |mut sel| {
+                        let delta = sel.ctx.ins().fsub(to, from);
+                        let elapsed = sel.ctx.ins().fsub(time, start);
+                        let duration = sel.ctx.ins().fsub(end, start);
+                        let frac = sel.ctx.ins().fdiv(elapsed, duration);
+                        let delta = sel.ctx.ins().fmul(delta, frac);
+                        sel.ctx.ins().fadd(from, delta)
+                    },
+                )
+            },
+        )
+    }
+
+    /// Whether the operators follow the history of a transient analysis. The initial
+    /// operating point (`analysis("ic")`) of a transient analysis is excluded so that the
+    /// states are initialized from the operating point instead.
+    pub(crate) fn is_tran(&mut self) -> Value {
+        let tran = self.ctx.sconst("tran");
+        let is_tran = self.ctx.call1(CallBackKind::Analysis, &[tran]);
+        let is_tran = self.ctx.ins().ine(is_tran, ZERO);
+        self.lower_select_with(
+            is_tran,
+            |mut sel| {
+                let ic = sel.ctx.sconst("ic");
+                let is_ic = sel.ctx.call1(CallBackKind::Analysis, &[ic]);
+                sel.ctx.ins().ieq(is_ic, ZERO)
+            },
+            |_| FALSE,
+        )
+    }
+}
@@ -1,8 +1,14 @@
 #[rustfmt::skip]
 mod generated;
+mod noise_table;
+mod table_model;
 
 use generated::builtin_info;
 use hir_def::{BuiltIn, Type};
+pub use noise_table::{parse_noise_table, read_noise_table, NoiseTableError};
+pub use table_model::{
+    parse_table_control, TableControlError, TableDimControl, TableExtrapolation, TableInterpolation,
+};
 
 use crate::types::{BuiltinInfo, Signature, SignatureData, TyRequirement};
 
@@ -263,11 +269,11 @@ bultins! {
 
 
     TRANSITION = const {
-        fn TRANSITION_NO_ARGS(Val(Integer)) -> Real;
-        fn TRANSITION_DELAY(Val(Integer),Val(Real)) -> Real;
-        fn TRANSITION_DELAY_RISET(Val(Integer),Val(Real)) -> Real;
-        fn TRANSITION_DELAY_RISET_FALLT(Val(Integer),Val(Real),Val(Real)) -> Real;
-        fn TRANSITION_DELAY_RISET_FALLT_TOL(Val(Integer),Val(Real),Val(Real), Val(Real)) -> Real;
+        fn TRANSITION_NO_ARGS(Val(Real)) -> Real;
+        fn TRANSITION_DELAY(Val(Real),Val(Real)) -> Real;
+        fn TRANSITION_DELAY_RISET(Val(Real),Val(Real),Val(Real)) -> Real;
+        fn TRANSITION_DELAY_RISET_FALLT(Val(Real),Val(Real),Val(Real),Val(Real)) -> Real;
+        fn TRANSITION_DELAY_RISET_FALLT_TOL(Val(Real),Val(Real),Val(Real),Val(Real),Val(Real)) -> Real;
     }
 
 
@@ -353,8 +359,12 @@ bultins! {
         fn SIMPROBE_DEFAULT(Val(String),Val(String),Val(Real))->Real;
     }
 
-    const fn TEST_PLUSARGS(Val(String)) -> Bool;
-    const fn VALUE_PLUSARGS(Val(String),Val(String))->Bool;
+    const fn TEST_PLUSARGS(Val(String)) -> Integer;
+    VALUE_PLUSARGS = const {
+        fn VALUE_PLUSARGS_REAL(Val(String),Var(Real)) -> Integer;
+        fn VALUE_PLUSARGS_INT(Val(String),Var(Integer)) -> Integer;
+        fn VALUE_PLUSARGS_STR(Val(String),Var(String)) -> Integer;
+    }
 
     fn ANALOG_NODE_ALIAS(Node,Val(String)) -> Integer;
 
@@ -407,6 +417,17 @@ const SFORMAT: BuiltinInfo = BuiltinInfo::varargs(
     &[SignatureData { args: Cow::Borrowed(&[Var(String), Val(String)]), return_ty: Type::Void }],
     true,
 );
+/// The output variables after the format string are checked during validation.
+const SSCANF: BuiltinInfo = BuiltinInfo::varargs(
+    &[SignatureData { args: Cow::Borrowed(&[Val(String), Val(String)]), return_ty: Type::Integer }],
+    true,
+);
+/// The arguments after the first input are checked during validation because their meaning
+/// depends on the number of inputs.
+const TABLE_MODEL: BuiltinInfo = BuiltinInfo::varargs(
+    &[SignatureData { args: Cow::Borrowed(&[Val(Real), AnyVal]), return_ty: Type::Real }],
+    false,
+);
 const FATAL: BuiltinInfo = BuiltinInfo::varargs(
     &[SignatureData { args: Cow::Borrowed(&[Val(Integer)]), return_ty: Type::Void }],
     true,
@@ -474,7 +495,6 @@ copied_builtins! {
     FMONITOR = FDISPLAY_FUN
     FWRITE = FDISPLAY_FUN
     FDEBUG = FDISPLAY_FUN
-    SSCANF = FDISPLAY_FUN
     FSCANF = FDISPLAY_FUN
 
     REWIND = BASIC_IO
@@ -4,7 +4,7 @@ use hir_def::BuiltIn;
 
 use crate::builtin::*;
 
-const BUILTIN_INFO: [BuiltinInfo; 111usize] = [
+const BUILTIN_INFO: [BuiltinInfo; 112usize] = [
     ABS,
     ACOS,
     ACOSH,
@@ -89,6 +89,7 @@ const BUILTIN_INFO: [BuiltinInfo; 111usize] = [
     PORT_CONNECTED,
     ANALOG_NODE_ALIAS,
     ANALOG_PORT_ALIAS,
+    TABLE_MODEL,
     TEST_PLUSARGS,
     VALUE_PLUSARGS,
     BOUND_STEP,
@@ -0,0 +1,87 @@
+//! The file form of `$table_noise`/`$table_noise_log`.
+//!
+//! Every non-empty line that does not start with a comment (`#`) contains a frequency
+//! followed by the noise power, separated by whitespace or a comma. Relative paths are
+//! resolved relative to the directory of the compiled Verilog-A file.
+
+use std::fmt;
+
+use basedb::{BaseDB, FileId, FileReadError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoiseTableError {
+    /// the file could not be found or read
+    Unreadable,
+    /// the file is not valid UTF-8 text
+    InvalidText,
+    /// the line (starting at 1) does not contain two numbers
+    MalformedLine(usize),
+    /// the line (starting at 1) contains a non-finite (nan or inf) value
+    NonFinite(usize),
+    /// the file contains no entries
+    Empty,
+}
+
+impl fmt::Display for NoiseTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            NoiseTableError::Unreadable => write!(f, "file could not be read"),
+            NoiseTableError::InvalidText => write!(f, "file is not valid UTF-8 text"),
+            NoiseTableError::MalformedLine(line) => {
+                write!(f, "line {line} does not contain a frequency and a noise power")
+            }
+            NoiseTableError::NonFinite(line) => {
+                write!(f, "line {line} contains a value that is not finite")
+            }
+            NoiseTableError::Empty => write!(f, "file contains no entries"),
+        }
+    }
+}
+
+pub fn parse_noise_table(src: &str) -> Result<Vec<(f64, f64)>, NoiseTableError> {
+    let vals = src
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_nr, line)| {
+            let cols: Vec<_> = line
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|col| !col.is_empty())
+                .collect();
+            let (freq, pwr) = match *cols {
+                [freq, pwr] => match (freq.parse::<f64>(), pwr.parse::<f64>()) {
+                    (Ok(freq), Ok(pwr)) => (freq, pwr),
+                    _ => return Err(NoiseTableError::MalformedLine(line_nr)),
+                },
+                _ => return Err(NoiseTableError::MalformedLine(line_nr)),
+            };
+            if !freq.is_finite() || !pwr.is_finite() {
+                return Err(NoiseTableError::NonFinite(line_nr));
+            }
+            Ok((freq, pwr))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if vals.is_empty() {
+        return Err(NoiseTableError::Empty);
+    }
+    Ok(vals)
+}
+
+pub fn read_noise_table(
+    db: &dyn BaseDB,
+    root_file: FileId,
+    path: &str,
+) -> Result<Vec<(f64, f64)>, NoiseTableError> {
+    let path = db
+        .file_path(root_file)
+        .parent()
+        .and_then(|dir| dir.join(path))
+        .ok_or(NoiseTableError::Unreadable)?;
+    let file = db.file_id(path);
+    match db.file_text(file) {
+        Ok(src) => parse_noise_table(&src),
+        Err(FileReadError::Io(_)) => Err(NoiseTableError::Unreadable),
+        Err(FileReadError::InvalidTextFormat(_)) => Err(NoiseTableError::InvalidText),
+    }
+}
@@ -0,0 +1,122 @@
+//! The control string of `$table_model`.
+//!
+//! The control string contains one comma separated entry for every input of the table.
+//! Each entry consists of an interpolation character optionally followed by up to two
+//! extrapolation characters (for the lower and upper end of the table). A single
+//! extrapolation character applies to both ends. Empty entries (and a missing control
+//! string) select the default `1LL`: linear interpolation and linear extrapolation.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TableInterpolation {
+    /// `I`: the input (and the corresponding column of the data) is ignored
+    Ignore,
+    /// `D`: the value of the closest sample point is used
+    Discrete,
+    /// `1`: linear interpolation
+    Linear,
+    /// `2`: quadratic interpolation through the three closest sample points
+    Quadratic,
+    /// `3`: natural cubic spline interpolation
+    CubicSpline,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TableExtrapolation {
+    /// `C`: the value at the end of the table is used
+    Clamp,
+    /// `L`: the table is continued with the slope at the end of the table
+    Linear,
+    /// `E`: extrapolation is an error
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TableDimControl {
+    pub interpolation: TableInterpolation,
+    pub lower: TableExtrapolation,
+    pub upper: TableExtrapolation,
+}
+
+impl Default for TableDimControl {
+    fn default() -> Self {
+        TableDimControl {
+            interpolation: TableInterpolation::Linear,
+            lower: TableExtrapolation::Linear,
+            upper: TableExtrapolation::Linear,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableControlError {
+    InvalidChar { entry: usize, found: char },
+    TooManyChars { entry: usize },
+    WrongEntryCount { expected: usize, found: usize },
+}
+
+impl fmt::Display for TableControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TableControlError::InvalidChar { entry, found } => {
+                write!(f, "invalid character '{found}' in entry {}", entry + 1)
+            }
+            TableControlError::TooManyChars { entry } => {
+                write!(f, "entry {} has more than three characters", entry + 1)
+            }
+            TableControlError::WrongEntryCount { expected, found } => {
+                write!(f, "expected {expected} entries but found {found}")
+            }
+        }
+    }
+}
+
+/// Parses the control string of a table with `dims` inputs.
+pub fn parse_table_control(
+    ctrl: &str,
+    dims: usize,
+) -> Result<Vec<TableDimControl>, TableControlError> {
+    if ctrl.trim().is_empty() {
+        return Ok(vec![TableDimControl::default(); dims]);
+    }
+
+    let entries: Vec<_> = ctrl.split(',').map(str::trim).collect();
+    if entries.len() != dims {
+        return Err(TableControlError::WrongEntryCount { expected: dims, found: entries.len() });
+    }
+
+    entries
+        .into_iter()
+        .enumerate()
+        .map(|(entry, src)| {
+            let mut res = TableDimControl::default();
+            let mut chars = src.chars();
+            if let Some(c) = chars.next() {
+                res.interpolation = match c {
+                    'I' => TableInterpolation::Ignore,
+                    'D' => TableInterpolation::Discrete,
+                    '1' => TableInterpolation::Linear,
+                    '2' => TableInterpolation::Quadratic,
+                    '3' => TableInterpolation::CubicSpline,
+                    found => return Err(TableControlError::InvalidChar { entry, found }),
+                };
+            }
+
+            let mut extrapolation = chars.map(|c| match c {
+                'C' => Ok(TableExtrapolation::Clamp),
+                'L' => Ok(TableExtrapolation::Linear),
+                'E' => Ok(TableExtrapolation::Error),
+                found => Err(TableControlError::InvalidChar { entry, found }),
+            });
+            if let Some(lower) = extrapolation.next() {
+                res.lower = lower?;
+                res.upper = extrapolation.next().transpose()?.unwrap_or(res.lower);
+            }
+            if extrapolation.next().is_some() {
+                return Err(TableControlError::TooManyChars { entry });
+            }
+            Ok(res)
+        })
+        .collect()
+}
@@ -8,13 +8,13 @@ use basedb::lints::builtin::non_standard_code;
 use basedb::lints::{Lint, LintSrc};
 use basedb::{BaseDB, FileId};
 use hir_def::body::BodySourceMap;
-use hir_def::{ExprId, FunctionId, Lookup, Type};
+use hir_def::{DefWithBodyId, ExprId, FunctionId, Lookup, ModuleInstId, Type};
 use stdx::iter::zip;
 use stdx::pretty::List;
 use stdx::{impl_display, pretty};
 use syntax::ast::{self, AssignOp};
 use syntax::sourcemap::{FileSpan, SourceMap};
-use syntax::{Parse, SourceFile, TextSize};
+use syntax::{AstNode, Parse, SourceFile, TextSize};
 use typed_index_collections::TiSlice;
 
 use crate::db::HirTyDB;
@@ -58,6 +58,36 @@ pub struct InferenceDiagnosticWrapped<'a> {
     pub sm: &'a SourceMap,
 }
 
+impl InferenceDiagnosticWrapped<'_> {
+    /// Reports an error for an entry of a module instance. Unconnected entries (`.p()`)
+    /// have no expression so the name of the instance is highlighted instead.
+    fn module_inst_report(
+        &self,
+        inst: ModuleInstId,
+        expr: ExprId,
+        message: String,
+        label: String,
+    ) -> Report {
+        let range = match &self.body_sm.expr_map_back[expr] {
+            Some(expr) => expr.range(),
+            None => {
+                let db = self.db.upcast();
+                let inst = inst.lookup(db);
+                let ast = inst.source(db);
+                ast.name()
+                    .map_or_else(|| ast.syntax().text_range(), |name| name.syntax().text_range())
+            }
+        };
+        let src = self.parse.to_file_span(range, self.sm);
+        Report::error().with_message(message).with_labels(vec![Label {
+            style: LabelStyle::Primary,
+            file_id: src.file,
+            range: src.range.into(),
+            message: label,
+        }])
+    }
+}
+
 impl Diagnostic for InferenceDiagnosticWrapped<'_> {
     fn build_report(&self, _root_file: FileId, _db: &dyn BaseDB) -> Report {
         match *self.diag {
@@ -292,6 +322,144 @@ impl Diagnostic for InferenceDiagnosticWrapped<'_> {
                         "help: expected one of the following\nbranch current access: I(branch), I(a,b)\nnode voltage: V(x)".to_owned(),
                     ])
             }
+            InferenceDiagnostic::UnknownModuleInstParam { inst, expr, ref name } => {
+                let module = self.db.module_inst_data(inst).module;
+                let module = module.map(|module| self.db.module_data(module).name.clone());
+                let (message, label) = match name {
+                    Some(name) => (
+                        format!("module '{}' has no parameter '{}'", module.unwrap(), name),
+                        "unknown parameter".to_owned(),
+                    ),
+                    None => (
+                        format!("too many parameter overrides for module '{}'", module.unwrap()),
+                        "no matching parameter".to_owned(),
+                    ),
+                };
+                self.module_inst_report(inst, expr, message, label)
+            }
+            InferenceDiagnostic::UnknownModuleInstPort { inst, expr, ref name } => {
+                let module = self.db.module_inst_data(inst).module;
+                let module = module.map(|module| self.db.module_data(module).name.clone());
+                let (message, label) = match name {
+                    Some(name) => (
+                        format!("module '{}' has no port '{}'", module.unwrap(), name),
+                        "unknown port".to_owned(),
+                    ),
+                    None => (
+                        format!("too many port connections for module '{}'", module.unwrap()),
+                        "no matching port".to_owned(),
+                    ),
+                };
+                self.module_inst_report(inst, expr, message, label)
+            }
+            InferenceDiagnostic::UnknownParamSetParam { paramset, expr, ref name } => {
+                let module = self.db.param_set_data(paramset).module;
+                let module = self.db.module_data(module.unwrap()).name.clone();
+                let src = self.parse.to_file_span(
+                    self.body_sm.expr_map_back[expr].as_ref().unwrap().range(),
+                    self.sm,
+                );
+                Report::error()
+                    .with_message(format!("module '{}' has no parameter '{}'", module, name))
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: src.file,
+                        range: src.range.into(),
+                        message: "unknown parameter".to_owned(),
+                    }])
+            }
+            InferenceDiagnostic::InvalidGenvarUse { expr } => {
+                let src = self.parse.to_file_span(
+                    self.body_sm.expr_map_back[expr].as_ref().unwrap().range(),
+                    self.sm,
+                );
+                Report::error()
+                    .with_message("genvars can only be used inside loops that are unrolled during elaboration")
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: src.file,
+                        range: src.range.into(),
+                        message: "invalid genvar use".to_owned(),
+                    }])
+                    .with_notes(vec![
+                        "help: the loop must assign the genvar in its initializer and increment\nand its bounds may only depend on integer literals and local parameters (localparam)".to_owned(),
+                    ])
+            }
+            InferenceDiagnostic::NonConstNodeIndex { expr } => {
+                let src = self.parse.to_file_span(
+                    self.body_sm.expr_map_back[expr].as_ref().unwrap().range(),
+                    self.sm,
+                );
+                Report::error()
+                    .with_message("node arrays can only be indexed with constants")
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: src.file,
+                        range: src.range.into(),
+                        message: "index is not known during elaboration".to_owned(),
+                    }])
+                    .with_notes(vec![
+                        "help: use a genvar loop to iterate over the elements of a node array"
+                            .to_owned(),
+                    ])
+            }
+            InferenceDiagnostic::NotAnArray { expr, ref found_ty } => {
+                let src = self.parse.to_file_span(
+                    self.body_sm.expr_map_back[expr].as_ref().unwrap().range(),
+                    self.sm,
+                );
+                Report::error()
+                    .with_message(format!("expected an array but found {}", found_ty))
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: src.file,
+                        range: src.range.into(),
+                        message: "can not be indexed".to_owned(),
+                    }])
+            }
+            InferenceDiagnostic::IndexOutOfBounds { expr, index, range } => {
+                let src = self.parse.to_file_span(
+                    self.body_sm.expr_map_back[expr].as_ref().unwrap().range(),
+                    self.sm,
+                );
+                Report::error()
+                    .with_message(format!(
+                        "index {} is out of bounds for array range {}",
+                        index, range
+                    ))
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: src.file,
+                        range: src.range.into(),
+                        message: "index out of bounds".to_owned(),
+                    }])
+            }
+            InferenceDiagnostic::InvalidArrayRange { decl } => {
+                let (name, range) = match decl {
+                    DefWithBodyId::VarId(var) => {
+                        let var = var.lookup(self.db.upcast());
+                        (var.name(self.db.upcast()), var.source(self.db.upcast()).range())
+                    }
+                    DefWithBodyId::ParamId(param) => {
+                        let param = param.lookup(self.db.upcast());
+                        (param.name(self.db.upcast()), param.source(self.db.upcast()).range())
+                    }
+                    _ => unreachable!(),
+                };
+                let src = self.parse.to_file_span(range.unwrap().syntax().text_range(), self.sm);
+                Report::error()
+                    .with_message(format!("the array '{}' could not be elaborated", name))
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: src.file,
+                        range: src.range.into(),
+                        message: "expected a constant integer range".to_owned(),
+                    }])
+                    .with_notes(vec![
+                        "help: the bounds may only depend on integer literals and local parameters (localparam)"
+                            .to_owned(),
+                    ])
+            }
             InferenceDiagnostic::ExpectedProbe { e } => {
                 let src = self
                     .parse
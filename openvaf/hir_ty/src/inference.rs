@@ -6,16 +6,18 @@ use ahash::AHashMap;
 use arena::ArenaMap;
 use hir_def::body::Body;
 use hir_def::db::HirDefDB;
-use hir_def::expr::{CaseCond, Literal};
+use hir_def::expr::{CaseCond, Event, Literal};
 use hir_def::nameres::diagnostics::PathResolveError;
 use hir_def::nameres::{NatureAccess, ResolvedPath, ScopeDefItem, ScopeDefItemKind};
 use hir_def::{
-    BranchId, BuiltIn, DefWithBodyId, Expr, ExprId, FunctionArgLoc, FunctionId, LocalFunctionArgId,
-    Lookup, NatureId, NodeId, ParamSysFun, Path, Stmt, StmtId, Type, VarId,
+    ArrayRange, BranchId, BuiltIn, DefWithBodyId, Expr, ExprId, FunctionArgLoc, FunctionId,
+    LocalFunctionArgId, Lookup, ModuleId, ModuleInstId, NatureId, NodeId, ParamId, ParamSetId,
+    ParamSysFun, Path, Stmt, StmtId, Type, VarId,
 };
 use stdx::impl_from;
 use stdx::iter::zip;
 use syntax::ast::{self, BinaryOp, UnaryOp};
+use syntax::name::Name;
 use syntax::{TextRange, TextSize};
 use typed_index_collections::{TiSlice, TiVec};
 
@@ -42,7 +44,15 @@ pub enum ResolvedFun {
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub enum AssignDst {
     Var(VarId),
-    FunVar { fun: FunctionId, arg: Option<LocalFunctionArgId> },
+    /// An element `var[index]` of an array variable
+    VarElement {
+        var: VarId,
+        index: ExprId,
+    },
+    FunVar {
+        fun: FunctionId,
+        arg: Option<LocalFunctionArgId>,
+    },
     Flow(BranchWrite),
     Potential(BranchWrite),
 }
@@ -55,12 +65,10 @@ pub enum BranchWrite {
 
 impl AssignDst {
     pub fn ty(&self, db: &dyn HirDefDB) -> Type {
-        if let AssignDst::Var(var) = *self {
-            let var = var.lookup(db);
-            let tree = var.item_tree(db);
-            tree[var.id].ty.clone()
-        } else {
-            Type::Real
+        match *self {
+            AssignDst::Var(var) => db.var_data(var).ty.clone(),
+            AssignDst::VarElement { var, .. } => db.var_data(var).ty.base_type().clone(),
+            _ => Type::Real,
         }
     }
 }
@@ -72,6 +80,12 @@ pub struct InferenceResult {
     pub resolved_signatures: AHashMap<ExprId, Signature>,
     pub assignment_destination: AHashMap<StmtId, AssignDst>,
     pub casts: AHashMap<ExprId, Type>,
+    /// The parameters of the instantiated module that are overwritten by a module instance
+    /// (or the parameters of the base module that are overwritten by a paramset)
+    pub param_overrides: Vec<(ParamId, ExprId)>,
+    /// The nodes connected to the ports of the instantiated module (in port order).
+    /// `None` for ports that are left unconnected.
+    pub port_connections: Vec<Option<NodeId>>,
     pub diagnostics: Vec<InferenceDiagnostic>,
 }
 
@@ -86,13 +100,38 @@ impl InferenceResult {
         let mut ctx = Ctx { result, body: &body, db, expr_stmt_ty: None };
         ctx.expr_stmt_ty = match id {
             DefWithBodyId::ParamId(param) => match &db.param_data(param).ty {
+                Some(Type::Err)
+                    if param.lookup(db.upcast()).source(db.upcast()).range().is_some() =>
+                {
+                    ctx.result
+                        .diagnostics
+                        .push(InferenceDiagnostic::InvalidArrayRange { decl: id });
+                    Some(Type::Err)
+                }
                 Some(ty) => Some(ty.clone()),
                 // parameter type is inferred if omitted
                 None => ctx
                     .infere_expr(body.entry_stmts[0], db.param_exprs(param).default)
                     .and_then(|ty| ty.to_value()),
             },
-            DefWithBodyId::VarId(var) => Some(db.var_data(var).ty.clone()),
+            DefWithBodyId::VarId(var) => {
+                let ty = db.var_data(var).ty.clone();
+                if ty == Type::Err && var.lookup(db.upcast()).source(db.upcast()).range().is_some()
+                {
+                    ctx.result
+                        .diagnostics
+                        .push(InferenceDiagnostic::InvalidArrayRange { decl: id });
+                }
+                Some(ty)
+            }
+            DefWithBodyId::ModuleInstId(inst) => {
+                ctx.infere_module_inst(inst);
+                return Arc::new(ctx.result);
+            }
+            DefWithBodyId::ParamSetId(paramset) => {
+                ctx.infere_param_set(paramset);
+                return Arc::new(ctx.result);
+            }
             _ => None,
         };
 
@@ -130,6 +169,21 @@ impl Ctx<'_> {
                 self.infere_cond(stmt, cond)
             }
 
+            Stmt::EventControl { event: Event::Monitored(ref events), .. } => {
+                for event in events {
+                    for arg in &event.args {
+                        if let Some(ty) = self.infere_expr(stmt, *arg) {
+                            self.expect::<false>(
+                                *arg,
+                                None,
+                                ty,
+                                Cow::Borrowed(&[TyRequirement::Val(Type::Real)]),
+                            );
+                        }
+                    }
+                }
+            }
+
             Stmt::Case { discr, ref case_arms } => {
                 if let Some(ty) = self.infere_expr(stmt, discr) {
                     let req = ty.to_value().map_or(TyRequirement::AnyVal, TyRequirement::Val);
@@ -155,6 +209,140 @@ impl Ctx<'_> {
         self.body.stmts[stmt].walk_child_stmts(|stmt| self.infere_stmt(stmt));
     }
 
+    fn infere_module_inst(&mut self, inst: ModuleInstId) {
+        let data = self.db.module_inst_data(inst);
+        let (params, ports) = self.body.entry_stmts.split_at(data.param_overrides.len());
+        let module = data.module;
+        let module_params = self.module_params(module);
+
+        for (pos, (&stmt, name)) in zip(params, &data.param_overrides).enumerate() {
+            let expr = self.entry_expr(stmt);
+            let param = match name {
+                Some(name) => module_params
+                    .iter()
+                    .copied()
+                    .find(|param| self.db.param_data(*param).name == *name),
+                None => module_params.get(pos).copied(),
+            };
+            match param {
+                Some(param) => {
+                    self.infere_assignment(stmt, expr, Some(self.db.param_ty(param)));
+                    self.result.param_overrides.push((param, expr));
+                }
+                None => {
+                    self.infere_assignment(stmt, expr, None);
+                    if module.is_some() {
+                        self.result.diagnostics.push(InferenceDiagnostic::UnknownModuleInstParam {
+                            inst,
+                            expr,
+                            name: name.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        let module_ports = match module {
+            Some(module) => self.db.module_data(module).ports.clone(),
+            None => Vec::new(),
+        };
+        self.result.port_connections = vec![None; module_ports.len()];
+        for (pos, (&stmt, name)) in zip(ports, &data.port_connections).enumerate() {
+            let expr = self.entry_expr(stmt);
+            let port = match name {
+                Some(name) => {
+                    module_ports.iter().position(|port| self.db.node_data(*port).name == *name)
+                }
+                None => (pos < module_ports.len()).then_some(pos),
+            };
+            let node = match self.infere_expr(stmt, expr) {
+                Some(Ty::Node(node)) => Some(node),
+                Some(ty) => {
+                    self.result.diagnostics.push(
+                        TypeMismatch {
+                            expected: Cow::Borrowed(&[TyRequirement::Node]),
+                            found_ty: ty,
+                            expr,
+                        }
+                        .into(),
+                    );
+                    None
+                }
+                None => None,
+            };
+            match port {
+                Some(port) => self.result.port_connections[port] = node,
+                None if module.is_some() => {
+                    self.result.diagnostics.push(InferenceDiagnostic::UnknownModuleInstPort {
+                        inst,
+                        expr,
+                        name: name.clone(),
+                    });
+                }
+                None => (),
+            }
+        }
+    }
+
+    fn infere_param_set(&mut self, paramset: ParamSetId) {
+        let data = self.db.param_set_data(paramset);
+        let module_params = self.module_params(data.module);
+
+        for (&stmt, name) in zip(&*self.body.entry_stmts, &data.overrides) {
+            let expr = self.entry_expr(stmt);
+            let param = module_params
+                .iter()
+                .copied()
+                .find(|param| self.db.param_data(*param).name == *name);
+            match param {
+                Some(param) => {
+                    self.infere_assignment(stmt, expr, Some(self.db.param_ty(param)));
+                    self.result.param_overrides.push((param, expr));
+                }
+                None => {
+                    self.infere_assignment(stmt, expr, None);
+                    if data.module.is_some() {
+                        self.result.diagnostics.push(InferenceDiagnostic::UnknownParamSetParam {
+                            paramset,
+                            expr,
+                            name: name.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// The (non-local) parameters of `module` that can be overwritten
+    fn module_params(&self, module: Option<ModuleId>) -> Vec<ParamId> {
+        match module {
+            Some(module) => {
+                let scope = module.lookup(self.db.upcast()).scope;
+                scope.def_map(self.db.upcast())[scope.local_scope]
+                    .declarations
+                    .values()
+                    .filter_map(|decl| match *decl {
+                        ScopeDefItem::ParamId(param) if !self.is_local_param(param) => Some(param),
+                        _ => None,
+                    })
+                    .collect()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn is_local_param(&self, param: ParamId) -> bool {
+        let loc = param.lookup(self.db.upcast());
+        loc.item_tree(self.db.upcast())[loc.id].is_local
+    }
+
+    fn entry_expr(&self, stmt: StmtId) -> ExprId {
+        match self.body.stmts[stmt] {
+            Stmt::Expr(expr) => expr,
+            _ => unreachable!("entry statements of expression bodies are expressions"),
+        }
+    }
+
     fn infere_assignment(&mut self, stmt: StmtId, val: ExprId, dst_ty: Option<Type>) {
         if let Some(val_ty) = self.infere_expr(stmt, val) {
             if let Some(value_ty) = val_ty.to_value() {
@@ -198,6 +386,10 @@ impl Ctx<'_> {
 
         let (dst, ty) = match e? {
             Ty::Var(ty, var) => (AssignDst::Var(var), ty),
+            Ty::Val(ty) if self.as_var_element(expr).is_some() => {
+                let (var, index) = self.as_var_element(expr).unwrap();
+                (AssignDst::VarElement { var, index }, ty)
+            }
             Ty::FunctionVar { fun, ty, arg } => (AssignDst::FunVar { fun, arg }, ty),
             Ty::Val(Type::Real)
                 if matches!(
@@ -248,7 +440,10 @@ impl Ctx<'_> {
 
         // check that the correct operator is used
         match (&dst, assignment_kind) {
-            (AssignDst::Var(_) | AssignDst::FunVar { .. }, ast::AssignOp::Contribute) => {
+            (
+                AssignDst::Var(_) | AssignDst::VarElement { .. } | AssignDst::FunVar { .. },
+                ast::AssignOp::Contribute,
+            ) => {
                 self.result.diagnostics.push(InferenceDiagnostic::InvalidAssignDst {
                     e: expr,
                     maybe_different_operand: Some(ast::AssignOp::Assign),
@@ -284,7 +479,10 @@ impl Ctx<'_> {
             }
 
             Expr::Path { ref path, port: false } => match self.resolve_path(stmt, expr, path)? {
-                ScopeDefItem::BlockId(_) | ScopeDefItem::ModuleId(_) => Ty::Scope,
+                ScopeDefItem::BlockId(_)
+                | ScopeDefItem::ModuleId(_)
+                | ScopeDefItem::ModuleInstId(_)
+                | ScopeDefItem::ParamSetId(_) => Ty::Scope,
                 ScopeDefItem::NatureId(nature) => Ty::Nature(nature),
                 ScopeDefItem::DisciplineId(discipline) => Ty::Discipline(discipline),
                 ScopeDefItem::NodeId(node) => Ty::Node(node),
@@ -300,6 +498,11 @@ impl Ctx<'_> {
                 },
                 ScopeDefItem::BranchId(branch) => Ty::Branch(branch),
                 ScopeDefItem::BuiltIn(_) | ScopeDefItem::NatureAccess(_) => Ty::BuiltInFunction,
+                // genvars are replaced with literals when their loop is unrolled
+                ScopeDefItem::GenvarId(_) => {
+                    self.result.diagnostics.push(InferenceDiagnostic::InvalidGenvarUse { expr });
+                    return None;
+                }
 
                 ScopeDefItem::FunctionId(fun) => Ty::UserFunction(fun),
                 ScopeDefItem::FunctionReturn(fun) => Ty::FunctionVar {
@@ -391,6 +594,7 @@ impl Ctx<'_> {
             }
             Expr::Array(ref args) if args.is_empty() => Ty::Val(Type::EmptyArray),
             Expr::Array(ref args) => self.infere_array(stmt, args)?,
+            Expr::Index { base, index } => self.infere_index(stmt, expr, base, index)?,
             Expr::Literal(Literal::Float(_)) => Ty::Literal(Type::Real),
             Expr::Literal(Literal::Int(_)) => Ty::Literal(Type::Integer),
             // +/- inf can only appear in param bounds.
@@ -635,6 +839,7 @@ impl Ctx<'_> {
             | BuiltIn::error
             | BuiltIn::info
             | BuiltIn::fatal => self.infere_display(stmt, args),
+            BuiltIn::swrite | BuiltIn::sformat => self.infere_display(stmt, &args[1..]),
 
             _ => (),
         }
@@ -907,6 +1112,78 @@ impl Ctx<'_> {
         }
     }
 
+    fn infere_index(
+        &mut self,
+        stmt: StmtId,
+        expr: ExprId,
+        base: ExprId,
+        index: ExprId,
+    ) -> Option<Ty> {
+        let base_ty = self.infere_expr(stmt, base);
+        if let Some(index_ty) = self.infere_expr(stmt, index) {
+            self.expect::<false>(
+                index,
+                None,
+                index_ty,
+                Cow::Borrowed(&[TyRequirement::Val(Type::Integer)]),
+            );
+        }
+
+        let (ty, range) = match base_ty? {
+            // constant indices of node arrays are resolved during elaboration
+            Ty::Node(_) => {
+                self.result.diagnostics.push(InferenceDiagnostic::NonConstNodeIndex { expr });
+                return None;
+            }
+            Ty::Var(Type::Array { ty, len }, var) => {
+                (*ty, self.db.var_data(var).range.unwrap_or_else(|| ArrayRange::with_len(len)))
+            }
+            Ty::Param(Type::Array { ty, len }, param) => {
+                (*ty, self.db.param_data(param).range.unwrap_or_else(|| ArrayRange::with_len(len)))
+            }
+            Ty::Val(Type::Array { ty, len }) => (*ty, ArrayRange::with_len(len)),
+            Ty::Var(Type::Err, _) | Ty::Param(Type::Err, _) | Ty::Val(Type::Err) => return None,
+            found_ty => {
+                self.result
+                    .diagnostics
+                    .push(InferenceDiagnostic::NotAnArray { expr: base, found_ty });
+                return None;
+            }
+        };
+
+        if let Some(val) = self.as_int_literal(index) {
+            if range.position(val).is_none() {
+                self.result.diagnostics.push(InferenceDiagnostic::IndexOutOfBounds {
+                    expr: index,
+                    index: val,
+                    range,
+                });
+            }
+        }
+
+        Some(Ty::Val(ty))
+    }
+
+    /// Returns the (possibly negative) value of an integer literal
+    fn as_int_literal(&self, expr: ExprId) -> Option<i32> {
+        match self.body.exprs[expr] {
+            Expr::Literal(Literal::Int(val)) => Some(val),
+            Expr::UnaryOp { expr, op: UnaryOp::Neg } => self.as_int_literal(expr)?.checked_neg(),
+            _ => None,
+        }
+    }
+
+    /// Returns the variable and index if `expr` is an element of an array variable (`var[index]`)
+    fn as_var_element(&self, expr: ExprId) -> Option<(VarId, ExprId)> {
+        match self.body.exprs[expr] {
+            Expr::Index { base, index } => match self.result.expr_types[base] {
+                Ty::Var(Type::Array { .. }, var) => Some((var, index)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     fn infere_array(&mut self, stmt: StmtId, args: &[ExprId]) -> Option<Ty> {
         let infere_value_ty = |sel: &mut Self, arg| -> Option<Type> {
             sel.infere_expr(stmt, arg).and_then(|ty| {
@@ -967,7 +1244,7 @@ impl Ctx<'_> {
             }
         }
 
-        Some(Ty::Val(ty))
+        Some(Ty::Val(ty.to_dim(&[args.len() as u32])))
     }
 
     fn infere_bin_op(
@@ -1265,6 +1542,39 @@ pub enum InferenceDiagnostic {
         e: ExprId,
         stmt: StmtId,
     },
+    UnknownModuleInstParam {
+        inst: ModuleInstId,
+        expr: ExprId,
+        name: Option<Name>,
+    },
+    UnknownModuleInstPort {
+        inst: ModuleInstId,
+        expr: ExprId,
+        name: Option<Name>,
+    },
+    UnknownParamSetParam {
+        paramset: ParamSetId,
+        expr: ExprId,
+        name: Name,
+    },
+    InvalidGenvarUse {
+        expr: ExprId,
+    },
+    NonConstNodeIndex {
+        expr: ExprId,
+    },
+    NotAnArray {
+        expr: ExprId,
+        found_ty: Ty,
+    },
+    IndexOutOfBounds {
+        expr: ExprId,
+        index: i32,
+        range: ArrayRange,
+    },
+    InvalidArrayRange {
+        decl: DefWithBodyId,
+    },
 }
 
 impl_from!(TypeMismatch,SignatureMismatch, ArrayTypeMismatch for InferenceDiagnostic);
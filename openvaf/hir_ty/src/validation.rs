@@ -2,7 +2,7 @@ use basedb::diagnostics::{Diagnostic, Label, LabelStyle, Report};
 use basedb::lints::builtin::{const_simparam, trivial_probe, variant_const_simparam};
 use basedb::lints::{self, Lint, LintSrc};
 use basedb::{AstIdMap, BaseDB, FileId};
-pub use body::BodyValidationDiagnostic;
+pub use body::{BodyValidationDiagnostic, TableModelError};
 use hir_def::body::BodySourceMap;
 use hir_def::{
     DisciplineAttr, ExprId, ItemLoc, ItemTree, ItemTreeNode, Lookup, NatureAttr, NodeId,
@@ -455,6 +455,178 @@ impl Diagnostic for BodyValidationDiagnosticWrapped<'_> {
                     .with_message(format!("'{name}' access of branch without {name}"))
                     .with_notes(vec![format!("help: this branches nodes have a discipline without the '{name}' attribute")])
             }
+            BodyValidationDiagnostic::InvalidNoiseTable { expr, len } => {
+                let FileSpan { range, file } = self.expr_src(expr);
+                let (message, note) = match len {
+                    Some(len) => (
+                        format!("noise table with {len} entries"),
+                        "help: the table must contain (frequency, power) pairs",
+                    ),
+                    None => (
+                        "table contains non-literal values".to_owned(),
+                        "help: only numeric literals are currently supported in noise tables",
+                    ),
+                };
+                Report::error()
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: file,
+                        range: range.into(),
+                        message,
+                    }])
+                    .with_message("invalid inline noise table".to_owned())
+                    .with_notes(vec![note.to_owned()])
+            }
+            BodyValidationDiagnostic::InvalidNoiseTableFile { expr, ref err } => {
+                let FileSpan { range, file } = self.expr_src(expr);
+                let (message, note) = match err {
+                    Some(err) => (
+                        err.to_string(),
+                        "help: relative paths are resolved relative to the directory of the compiled file",
+                    ),
+                    None => (
+                        "expected a string literal".to_owned(),
+                        "help: noise table files are read when the model is compiled",
+                    ),
+                };
+                Report::error()
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: file,
+                        range: range.into(),
+                        message,
+                    }])
+                    .with_message("invalid noise table file".to_owned())
+                    .with_notes(vec![note.to_owned()])
+            }
+            BodyValidationDiagnostic::InvalidFilterCoefficients { expr, len, roots } => {
+                let FileSpan { range, file } = self.expr_src(expr);
+                let (message, note) = match len {
+                    Some(len) if roots => (
+                        format!("array of roots with {len} entries"),
+                        "help: roots are specified as (real, imaginary) pairs",
+                    ),
+                    Some(_) => (
+                        "empty array of coefficients".to_owned(),
+                        "help: at least one coefficient is required",
+                    ),
+                    None => (
+                        "expected an array literal".to_owned(),
+                        "help: the number of filter coefficients must be known at compile time",
+                    ),
+                };
+                Report::error()
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: file,
+                        range: range.into(),
+                        message,
+                    }])
+                    .with_message("invalid filter coefficients".to_owned())
+                    .with_notes(vec![note.to_owned()])
+            }
+            BodyValidationDiagnostic::InvalidTableModel { expr, err } => {
+                let FileSpan { range, file } = self.expr_src(expr);
+                let (message, note) = match err {
+                    TableModelError::MissingTable => (
+                        "no table was provided".to_owned(),
+                        "help: the inputs must be followed by a file name or one array for each input and the output".to_owned(),
+                    ),
+                    TableModelError::ArrayCount { expected, found } => (
+                        format!("expected {expected} arrays but found {found}"),
+                        "help: an inline table consists of one array for each input and one array for the output".to_owned(),
+                    ),
+                    TableModelError::ArrayLength { expected, found } => (
+                        format!("array with {found} entries"),
+                        format!("help: all arrays of the table must have the same (non-zero) length {expected}"),
+                    ),
+                    TableModelError::NonLiteralArray => (
+                        "table contains non-literal values".to_owned(),
+                        "help: only numeric literals are currently supported in inline tables".to_owned(),
+                    ),
+                    TableModelError::NonLiteralString => (
+                        "expected a string literal".to_owned(),
+                        "help: file names and control strings must be known at compile time".to_owned(),
+                    ),
+                    TableModelError::UnexpectedArg => (
+                        "unexpected argument".to_owned(),
+                        "help: only a control string may follow the table".to_owned(),
+                    ),
+                    TableModelError::Control(err) => (
+                        format!("invalid control string: {err}"),
+                        "help: each input is controlled by one entry of the form [I|D|1|2|3][C|L|E][C|L|E]".to_owned(),
+                    ),
+                };
+                Report::error()
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: file,
+                        range: range.into(),
+                        message,
+                    }])
+                    .with_message("invalid $table_model call".to_owned())
+                    .with_notes(vec![note])
+            }
+            BodyValidationDiagnostic::NonLiteralFormat { expr } => {
+                let FileSpan { range, file } = self.expr_src(expr);
+                Report::error()
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: file,
+                        range: range.into(),
+                        message: "expected a string literal".to_owned(),
+                    }])
+                    .with_message("invalid $sformat format string".to_owned())
+                    .with_notes(vec![
+                        "help: the format string must be known at compile time".to_owned()
+                    ])
+            }
+            BodyValidationDiagnostic::NonLiteralNodeAlias { expr, port } => {
+                let FileSpan { range, file } = self.expr_src(expr);
+                let func = if port { "$analog_port_alias" } else { "$analog_node_alias" };
+                Report::error()
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: file,
+                        range: range.into(),
+                        message: "expected a string literal".to_owned(),
+                    }])
+                    .with_message(format!("invalid {func} target"))
+                    .with_notes(vec![
+                        "help: aliases are resolved when the model is compiled".to_owned()
+                    ])
+            }
+            BodyValidationDiagnostic::NonLiteralAnalysisName { expr } => {
+                let FileSpan { range, file } = self.expr_src(expr);
+                Report::error()
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: file,
+                        range: range.into(),
+                        message: "expected a string literal".to_owned(),
+                    }])
+                    .with_message("invalid ac_stim analysis name".to_owned())
+                    .with_notes(vec![
+                        "help: AC stimuli are assigned to analyses when the model is compiled"
+                            .to_owned(),
+                    ])
+            }
+            BodyValidationDiagnostic::InvalidScanOutput { expr, plusargs } => {
+                let FileSpan { range, file } = self.expr_src(expr);
+                let func = if plusargs { "$value$plusargs" } else { "$sscanf" };
+                Report::error()
+                    .with_labels(vec![Label {
+                        style: LabelStyle::Primary,
+                        file_id: file,
+                        range: range.into(),
+                        message: "expected a variable".to_owned(),
+                    }])
+                    .with_message(format!("invalid {func} output"))
+                    .with_notes(vec![
+                        "help: converted values are assigned to real, integer or string variables"
+                            .to_owned(),
+                    ])
+            }
             BodyValidationDiagnostic::IncompatibleImplicitBranch { access, node1, node2 } => {
                 let node1_ = self.db.node_data(node1);
                 let node2_ = self.db.node_data(node2);
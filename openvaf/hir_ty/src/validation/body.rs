@@ -2,18 +2,21 @@ use std::mem::replace;
 
 use ahash::{HashMap, HashSet};
 use hir_def::body::Body;
+use hir_def::db::HirDefDB;
+use hir_def::expr::Event;
 use hir_def::{
     BranchId, BuiltIn, DefWithBodyId, DisciplineId, Expr, ExprId, FunctionArgLoc, Literal, Lookup,
-    NatureId, NodeId, ParamId, Path, Stmt, StmtId, VarId,
+    NatureId, NodeId, ParamId, Path, Stmt, StmtId, Type, VarId,
 };
 use stdx::impl_display;
 use syntax::ast::AssignOp;
 use syntax::name::{AsIdent, Name};
 
 use crate::builtin::{
-    ABSDELAY_MAX, DDT_TOL, IDT_IC_ASSERT_TOL, NATURE_ACCESS_BRANCH, NATURE_ACCESS_NODES,
-    NATURE_ACCESS_NODE_GND, NATURE_ACCESS_PORT_FLOW, NOISE_TABLE_INLINE, NOISE_TABLE_INLINE_NAME,
-    TRANSITION_DELAY_RISET_FALLT_TOL,
+    parse_table_control, read_noise_table, NoiseTableError, TableControlError, ABSDELAY_MAX,
+    DDT_TOL, IDT_IC_ASSERT_TOL, NATURE_ACCESS_BRANCH, NATURE_ACCESS_NODES, NATURE_ACCESS_NODE_GND,
+    NATURE_ACCESS_PORT_FLOW, NOISE_TABLE_FILE, NOISE_TABLE_FILE_NAME, NOISE_TABLE_INLINE,
+    NOISE_TABLE_INLINE_NAME, TRANSITION_DELAY_RISET_FALLT_TOL,
 };
 use crate::db::HirTyDB;
 use crate::inference::{BranchWrite, InferenceResult, ResolvedFun};
@@ -96,6 +99,57 @@ pub enum BodyValidationDiagnostic {
         node1: NodeId,
         node2: NodeId,
     },
+
+    InvalidNoiseTable {
+        expr: ExprId,
+        len: Option<usize>,
+    },
+
+    /// `err` is `None` if the path is not a string literal
+    InvalidNoiseTableFile {
+        expr: ExprId,
+        err: Option<NoiseTableError>,
+    },
+
+    InvalidFilterCoefficients {
+        expr: ExprId,
+        len: Option<usize>,
+        roots: bool,
+    },
+
+    InvalidTableModel {
+        expr: ExprId,
+        err: TableModelError,
+    },
+
+    NonLiteralFormat {
+        expr: ExprId,
+    },
+
+    InvalidScanOutput {
+        expr: ExprId,
+        plusargs: bool,
+    },
+
+    NonLiteralNodeAlias {
+        expr: ExprId,
+        port: bool,
+    },
+
+    NonLiteralAnalysisName {
+        expr: ExprId,
+    },
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum TableModelError {
+    MissingTable,
+    ArrayCount { expected: usize, found: usize },
+    ArrayLength { expected: usize, found: usize },
+    NonLiteralArray,
+    NonLiteralString,
+    UnexpectedArg,
+    Control(TableControlError),
 }
 
 impl BodyValidationDiagnostic {
@@ -214,7 +268,14 @@ impl BodyValidator<'_> {
 
                 return;
             }
-            Stmt::EventControl { body, .. } => {
+            Stmt::EventControl { ref event, body } => {
+                if let Event::Monitored(events) = event {
+                    for event in events {
+                        for arg in &event.args {
+                            self.validate_expr(*arg, stmt);
+                        }
+                    }
+                }
                 let old = replace(&mut self.ctx, BodyCtx::EventControl);
                 self.validate_stmt(body);
                 self.ctx = old;
@@ -682,7 +743,79 @@ impl ExprValidator<'_, '_> {
             (
                 BuiltIn::noise_table | BuiltIn::noise_table_log,
                 Some(NOISE_TABLE_INLINE | NOISE_TABLE_INLINE_NAME),
-            ) => self.validate_const_expr(args[0]),
+            ) => {
+                self.validate_const_expr(args[0]);
+                match self.parent.body.as_real_literal_array(args[0]) {
+                    Some(vals) if !vals.is_empty() && vals.len() % 2 == 0 => (),
+                    vals => self.report(BodyValidationDiagnostic::InvalidNoiseTable {
+                        expr: args[0],
+                        len: vals.map(|vals| vals.len()),
+                    }),
+                }
+            }
+            (
+                BuiltIn::noise_table | BuiltIn::noise_table_log,
+                Some(NOISE_TABLE_FILE | NOISE_TABLE_FILE_NAME),
+            ) => match self.parent.body.as_literal(args[0]) {
+                Some(Literal::String(path)) => {
+                    let db: &dyn HirDefDB = self.parent.db.upcast();
+                    let root_file = self.parent.owner.file(db);
+                    if let Err(err) = read_noise_table(db.upcast(), root_file, path) {
+                        self.report(BodyValidationDiagnostic::InvalidNoiseTableFile {
+                            expr: args[0],
+                            err: Some(err),
+                        })
+                    }
+                }
+                _ => self.report(BodyValidationDiagnostic::InvalidNoiseTableFile {
+                    expr: args[0],
+                    err: None,
+                }),
+            },
+            (BuiltIn::table_model, _) => {
+                let dims = self.validate_table_model(expr, args);
+                args = &args[..dims];
+            }
+            (BuiltIn::sformat, _) => {
+                if !matches!(self.parent.body.exprs[args[1]], Expr::Literal(Literal::String(_))) {
+                    self.report(BodyValidationDiagnostic::NonLiteralFormat { expr: args[1] })
+                }
+            }
+            (func @ (BuiltIn::analog_node_alias | BuiltIn::analog_port_alias), _) => {
+                if !matches!(self.parent.body.exprs[args[1]], Expr::Literal(Literal::String(_))) {
+                    self.report(BodyValidationDiagnostic::NonLiteralNodeAlias {
+                        expr: args[1],
+                        port: func == BuiltIn::analog_port_alias,
+                    })
+                }
+            }
+            (BuiltIn::ac_stim, _) if !args.is_empty() => {
+                if !matches!(self.parent.body.exprs[args[0]], Expr::Literal(Literal::String(_))) {
+                    self.report(BodyValidationDiagnostic::NonLiteralAnalysisName { expr: args[0] })
+                }
+            }
+            (BuiltIn::sscanf, _) => {
+                for &arg in &args[2..] {
+                    if !matches!(
+                        self.parent.infer.expr_types[arg],
+                        Ty::Var(Type::Real | Type::Integer | Type::String, _)
+                    ) {
+                        self.report(BodyValidationDiagnostic::InvalidScanOutput {
+                            expr: arg,
+                            plusargs: false,
+                        })
+                    }
+                }
+            }
+            (BuiltIn::value_plusargs, _) => {
+                // the signature also accepts function arguments
+                if !matches!(self.parent.infer.expr_types[args[1]], Ty::Var(_, _)) {
+                    self.report(BodyValidationDiagnostic::InvalidScanOutput {
+                        expr: args[1],
+                        plusargs: true,
+                    })
+                }
+            }
             (func @ (BuiltIn::simparam | BuiltIn::simparam_str), _) => {
                 if self.parent.ctx == BodyCtx::Const {
                     let known = if let Expr::Literal(Literal::String(name)) =
@@ -742,6 +875,14 @@ impl ExprValidator<'_, '_> {
                     for arg in const_args {
                         self.validate_const_expr(*arg)
                     }
+                    let (num_roots, den_roots) = match call {
+                        BuiltIn::laplace_nd | BuiltIn::zi_nd => (false, false),
+                        BuiltIn::laplace_np | BuiltIn::zi_np => (false, true),
+                        BuiltIn::laplace_zd | BuiltIn::zi_zd => (true, false),
+                        _ => (true, true),
+                    };
+                    self.validate_filter_coeffs(const_args[0], num_roots);
+                    self.validate_filter_coeffs(const_args[1], den_roots);
                 }
             }
 
@@ -753,6 +894,109 @@ impl ExprValidator<'_, '_> {
         }
     }
 
+    /// Filters are realized at compile time so the number of coefficients must be known
+    fn validate_filter_coeffs(&mut self, expr: ExprId, roots: bool) {
+        let len = match &self.parent.body.exprs[expr] {
+            Expr::Array(vals) => vals.len(),
+            _ => {
+                self.report(BodyValidationDiagnostic::InvalidFilterCoefficients {
+                    expr,
+                    len: None,
+                    roots,
+                });
+                return;
+            }
+        };
+        // roots are (real, imag) pairs, an empty list of roots is allowed
+        let invalid = if roots { len % 2 != 0 } else { len == 0 };
+        if invalid {
+            self.report(BodyValidationDiagnostic::InvalidFilterCoefficients {
+                expr,
+                len: Some(len),
+                roots,
+            })
+        }
+    }
+
+    /// Validates the table and control string of a `$table_model` call and returns the
+    /// number of inputs. The table and control string are read at compile time.
+    fn validate_table_model(&mut self, expr: ExprId, args: &[ExprId]) -> usize {
+        let body = self.parent.body;
+        let infer = self.parent.infer;
+        let is_table = |arg: &ExprId| {
+            !matches!(
+                infer.expr_types[*arg].to_value(),
+                Some(Type::Real | Type::Integer | Type::Bool)
+            )
+        };
+        let dims = args.iter().position(is_table).unwrap_or(args.len());
+        let tables = &args[dims..];
+        for arg in tables {
+            self.validate_const_expr(*arg)
+        }
+
+        let as_str = |arg: ExprId| match &body.exprs[arg] {
+            Expr::Literal(Literal::String(val)) => Some(&**val),
+            _ => None,
+        };
+        let is_str = |arg: &ExprId| infer.expr_types[*arg].to_value() == Some(Type::String);
+
+        let ctrl = match tables {
+            [] => Err((expr, TableModelError::MissingTable)),
+            [path, ctrl @ ..] if is_str(path) => {
+                if as_str(*path).is_none() {
+                    Err((*path, TableModelError::NonLiteralString))
+                } else {
+                    Ok(ctrl)
+                }
+            }
+            _ => {
+                let num_arrays = tables.iter().position(is_str).unwrap_or(tables.len());
+                let (arrays, ctrl) = tables.split_at(num_arrays);
+                if arrays.len() != dims + 1 {
+                    let err =
+                        TableModelError::ArrayCount { expected: dims + 1, found: arrays.len() };
+                    Err((expr, err))
+                } else {
+                    let mut res = Ok(ctrl);
+                    let mut len = None;
+                    for &array in arrays {
+                        let found = match body.as_real_literal_array(array) {
+                            Some(vals) => vals.len(),
+                            None => {
+                                res = Err((array, TableModelError::NonLiteralArray));
+                                break;
+                            }
+                        };
+                        let expected = *len.get_or_insert(found);
+                        if found != expected || found == 0 {
+                            res = Err((array, TableModelError::ArrayLength { expected, found }));
+                            break;
+                        }
+                    }
+                    res
+                }
+            }
+        };
+
+        let res = ctrl.and_then(|ctrl| match *ctrl {
+            [] => Ok(()),
+            [ctrl] => {
+                let src = as_str(ctrl).ok_or((ctrl, TableModelError::NonLiteralString))?;
+                parse_table_control(src, dims)
+                    .map(drop)
+                    .map_err(|err| (ctrl, TableModelError::Control(err)))
+            }
+            [_, unexpected, ..] => Err((unexpected, TableModelError::UnexpectedArg)),
+        });
+
+        if let Err((expr, err)) = res {
+            self.report(BodyValidationDiagnostic::InvalidTableModel { expr, err })
+        }
+
+        dims
+    }
+
     fn validate_const_expr(&mut self, expr: ExprId) {
         let old = replace(&mut self.parent.ctx, BodyCtx::Const);
         let sink = self.cond_diagnostic_sink.take();
@@ -267,10 +267,13 @@ impl Function {
 pub struct KnownDerivatives {
     pub unknowns: TiSet<Unknown, Value>,
     pub ddx_calls: AHashMap<FuncRef, (HybridBitSet<Unknown>, HybridBitSet<Unknown>)>,
+    /// Calls that are differentiable. Maps the callback to the callbacks that calculate
+    /// the partial derivative by each argument (called with the same arguments).
+    pub call_derivatives: AHashMap<FuncRef, Box<[FuncRef]>>,
     // pub standin_calls: AHashMap<FuncRef, u32>,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[repr(transparent)]
 pub struct Unknown(pub u32);
 impl_idx_from!(Unknown(u32));
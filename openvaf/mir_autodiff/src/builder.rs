@@ -7,8 +7,8 @@ use arena::{Arena, IdxRange};
 use bitset::{BitSet, HybridBitSet};
 use mir::builder::{InsertBuilder, InstBuilder, InstInserterBase};
 use mir::{
-    Block, Function, Inst, InstructionData, Opcode, SourceLoc, Unknown, Value, F_LOG10_E, F_ONE,
-    F_TWO, F_ZERO,
+    Block, FuncRef, Function, Inst, InstructionData, Opcode, SourceLoc, Unknown, Value, F_LOG10_E,
+    F_ONE, F_TWO, F_ZERO,
 };
 use rustc_hash::FxHasher;
 use stdx::iter::zip;
@@ -215,6 +215,11 @@ impl<'a, 'u> DerivativeBuilder<'a, 'u> {
                     }
 
                     debug_assert!(self.live_derivatives.conversions.get(&inst).is_none());
+                } else if self.intern.call_derivatives.contains_key(&func_ref) {
+                    if let Some(derivatives) = derivatives {
+                        self.build_normal_inst_derivatives(bcache, derivatives);
+                        self.insert_conversions(inst);
+                    }
                 }
             }
 
@@ -688,6 +693,15 @@ impl<'a, 'u> DerivativeBuilder<'a, 'u> {
         let res = self.func.dfg.first_result(inst);
         let op = self.func.dfg.insts[inst].opcode();
 
+        if let InstructionData::Call { func_ref, .. } = self.func.dfg.insts[inst] {
+            let call_derivatives = self.intern.call_derivatives;
+            if let Some(partials) = call_derivatives.get(&func_ref) {
+                let val = self.call_derivative(inst, partials, unknown);
+                self.insert_derivative(res, unknown, val);
+                return;
+            }
+        }
+
         let args = self.func.dfg.instr_args(inst);
         let arg0 = args.get(0).copied().unwrap_or_else(Value::reserved_value);
         let arg1 = args.get(1).copied().unwrap_or_else(Value::reserved_value);
@@ -922,6 +936,25 @@ impl<'a, 'u> DerivativeBuilder<'a, 'u> {
         self.insert_derivative(res, unknown, val)
     }
 
+    /// Chain rule for calls with known partial derivatives: sum(df/dx_i * dx_i)
+    fn call_derivative(&mut self, inst: Inst, partials: &[FuncRef], unknown: Unknown) -> Value {
+        let args = self.func.dfg.instr_args(inst).to_vec();
+        let mut res = F_ZERO;
+        for (&arg, &partial) in zip(&args, partials) {
+            let darg = self.derivative_of_1(arg, unknown);
+            if darg == F_ZERO {
+                continue;
+            }
+            let call = self.ins().call(partial, &args);
+            let mut val = self.func.dfg.first_result(call);
+            if darg != F_ONE {
+                val = self.ins().fmul(darg, val);
+            }
+            res = if res == F_ZERO { val } else { self.ins().fadd(res, val) };
+        }
+        res
+    }
+
     fn simplified_mul(&mut self, lhs: Value, dlhs: Value, rhs: Value, res: Value) -> Value {
         // make sure that x = A * exp(C) is derived as
         // A' * exp(C) + x*C' instead of A'*B + A*(exp(C)*C')
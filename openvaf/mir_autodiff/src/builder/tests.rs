@@ -1,12 +1,12 @@
 use bitset::HybridBitSet;
 use expect_test::{expect, Expect};
 use float_cmp::{ApproxEq, F64Margin};
-use mir::{ControlFlowGraph, DominatorTree, KnownDerivatives};
+use mir::{ControlFlowGraph, DominatorTree, KnownDerivatives, Unknown};
 use mir_interpret::{Data, Interpreter};
 use mir_reader::parse_function;
 use typed_index_collections::TiSlice;
 
-use crate::auto_diff;
+use crate::{auto_diff, higher_order_derivative, HigherOrderDerivatives};
 
 fn check_simple(src: &str, data_flow_result: Expect) {
     let (mut func, _) = parse_function(src).unwrap();
@@ -31,9 +31,9 @@ fn check_simple(src: &str, data_flow_result: Expect) {
     .into_iter()
     .collect();
 
-    let unknowns = KnownDerivatives { unknowns, ddx_calls };
+    let unknowns = KnownDerivatives { unknowns, ddx_calls, ..KnownDerivatives::default() };
 
-    auto_diff(&mut func, &dom_tree, &unknowns, &[]);
+    auto_diff(&mut func, &dom_tree, &unknowns, &[], None);
     data_flow_result.assert_eq(&func.to_debug_string());
 }
 
@@ -55,11 +55,11 @@ fn check_num(src: &str, data_flow_result: Expect, args: &[f64], num: f64) {
     .into_iter()
     .collect();
 
-    let unknowns = KnownDerivatives { unknowns, ddx_calls };
+    let unknowns = KnownDerivatives { unknowns, ddx_calls, ..KnownDerivatives::default() };
 
     let mut dom_tree = DominatorTree::default();
     dom_tree.compute(&func, &cfg, true, false, true);
-    auto_diff(&mut func, &dom_tree, &unknowns, &[]);
+    auto_diff(&mut func, &dom_tree, &unknowns, &[], None);
     let mut interpret = Interpreter::new(
         &func,
         TiSlice::from_ref(&[]),
@@ -996,3 +996,52 @@ fn subgraph() {
 
     check_simple(src, expect);
 }
+
+#[test]
+fn higher_order_exp_mul() {
+    let src = r##"
+        function %bar(v10, v11) {
+        block0:
+            v12 = fmul v10, v11
+            v13 = exp v12
+            v100 = optbarrier v13
+        }"##;
+    let (mut func, _) = parse_function(src).unwrap();
+    let mut cfg = ControlFlowGraph::new();
+    cfg.compute(&func);
+    let mut dom_tree = DominatorTree::default();
+    dom_tree.compute(&func, &cfg, true, false, true);
+
+    let unknowns = [10u32.into(), 11u32.into()].into_iter().collect();
+    let unknowns = KnownDerivatives { unknowns, ..KnownDerivatives::default() };
+    let x = 0u32.into();
+    let y = 1u32.into();
+    let higher_order =
+        HigherOrderDerivatives { vals: &[13u32.into()], unknowns: &[x, y], order: 3 };
+    let derivatives = auto_diff(&mut func, &dom_tree, &unknowns, &[], Some(&higher_order));
+
+    let (v10, v11) = (0.5f64, 1.5f64);
+    let mut interpret = Interpreter::new(
+        &func,
+        TiSlice::from_ref(&[]),
+        TiSlice::from_ref(Data::from_f64_slice(&[v10, v11])),
+    );
+    interpret.run();
+
+    let f = (v10 * v11).exp();
+    let expected: [(&[Unknown], f64); 7] = [
+        (&[x, x], v11 * v11 * f),
+        (&[x, y], f * (1.0 + v10 * v11)),
+        (&[y, y], v10 * v10 * f),
+        (&[x, x, x], v11 * v11 * v11 * f),
+        (&[x, x, y], v11 * f * (2.0 + v10 * v11)),
+        (&[x, y, y], v10 * f * (2.0 + v10 * v11)),
+        (&[y, y, y], v10 * v10 * v10 * f),
+    ];
+    let margin = F64Margin::default().epsilon(10f64 * f64::EPSILON);
+    for (unknowns, num) in expected {
+        let val = higher_order_derivative(&derivatives, 13u32.into(), unknowns);
+        let val: f64 = interpret.state.read(val);
+        assert!(val.approx_eq(num, margin), "{unknowns:?}: {val} != {num}");
+    }
+}
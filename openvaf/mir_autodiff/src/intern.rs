@@ -32,6 +32,7 @@ pub struct DerivativeInfo {
 pub struct DerivativeIntern<'a> {
     pub unknowns: TiSet<Unknown, Value>,
     pub ddx_calls: &'a AHashMap<FuncRef, (HybridBitSet<Unknown>, HybridBitSet<Unknown>)>,
+    pub call_derivatives: &'a AHashMap<FuncRef, Box<[FuncRef]>>,
     pub derivatives: TiSet<Derivative, DerivativeInfo>,
     buf: Vec<Unknown>,
 }
@@ -61,6 +62,7 @@ impl<'a> DerivativeIntern<'a> {
         Self {
             unknowns: known.unknowns.clone(),
             ddx_calls: &known.ddx_calls,
+            call_derivatives: &known.call_derivatives,
             derivatives,
             // standin_calls: &info.standin_calls,
             // don't expect more than 8. th order derivative in most code
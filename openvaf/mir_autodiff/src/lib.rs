@@ -11,26 +11,53 @@ pub use builder::build_derivatives;
 pub use live_derivatives::LiveDerivatives;
 use mir::{
     DataFlowGraph, DominatorTree, Function, Inst, InstructionData, KnownDerivatives, Opcode, Value,
+    F_ZERO,
 };
 use rustc_hash::FxHasher;
 
 use crate::intern::{Derivative, DerivativeIntern};
 
+/// Requests all mixed derivatives of `vals` with respect to `unknowns` up to (and including)
+/// `order`. First order derivatives must be requested with `extra_derivatives` instead.
+#[derive(Debug, Clone, Copy)]
+pub struct HigherOrderDerivatives<'a> {
+    pub vals: &'a [Value],
+    pub unknowns: &'a [mir::Unknown],
+    pub order: u32,
+}
+
 pub fn auto_diff(
     mut func: impl AsMut<Function>,
     dom_tree: &DominatorTree,
     derivatives: &KnownDerivatives,
     extra_derivatives: &[(Value, mir::Unknown)],
+    higher_order: Option<&HigherOrderDerivatives>,
 ) -> HashMap<(Value, mir::Unknown), Value, BuildHasherDefault<FxHasher>> {
     let func = func.as_mut();
     let mut intern = DerivativeIntern::new(derivatives);
-    let live_derivative = LiveDerivatives::build(func, &mut intern, extra_derivatives, dom_tree);
+    let live_derivative =
+        LiveDerivatives::build(func, &mut intern, extra_derivatives, higher_order, dom_tree);
     build_derivatives(func, &mut intern, &live_derivative, dom_tree.cfg_postorder())
 }
 
+/// Looks up the mixed derivative of `val` with respect to `unknowns` in the map returned by
+/// [`auto_diff`]. `unknowns` must be sorted in ascending order. Derivatives that are
+/// (trivially) zero are not stored in the map and are returned as `F_ZERO`.
+pub fn higher_order_derivative(
+    derivatives: &HashMap<(Value, mir::Unknown), Value, BuildHasherDefault<FxHasher>>,
+    val: Value,
+    unknowns: &[mir::Unknown],
+) -> Value {
+    unknowns
+        .iter()
+        .try_fold(val, |val, unknown| derivatives.get(&(val, *unknown)).copied())
+        .unwrap_or(F_ZERO)
+}
+
 fn is_zero_call(dfg: &DataFlowGraph, inst: Inst, intern: &DerivativeIntern) -> bool {
     if let InstructionData::Call { func_ref, .. } = dfg.insts[inst] {
         !intern.ddx_calls.contains_key(&func_ref)
+            && !intern.call_derivatives.contains_key(&func_ref)
     } else {
         false
     }
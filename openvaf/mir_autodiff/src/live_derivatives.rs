@@ -7,7 +7,7 @@ use workqueue::WorkQueue;
 
 use crate::intern::{Derivative, DerivativeIntern};
 use crate::postorder::{Postorder, PostorderParts};
-use crate::ChainRule;
+use crate::{ChainRule, HigherOrderDerivatives};
 
 #[cfg(test)]
 mod tests;
@@ -106,6 +106,12 @@ impl<'a, 'b> LiveDerivativeBuilder<'a, 'b> {
                     post_order.transverse_inst(inst);
                     continue;
                 }
+                InstructionData::Call { func_ref, .. }
+                    if self.intern.call_derivatives.contains_key(&func_ref) =>
+                {
+                    post_order.transverse_inst(inst);
+                    continue;
+                }
                 _ => continue,
             };
 
@@ -204,8 +210,8 @@ impl<'a, 'b> LiveDerivativeBuilder<'a, 'b> {
                             self.intern.num_derivatives(),
                         );
                     }
-                } else {
-                    continue; // TODO call derivatives
+                } else if !self.intern.call_derivatives.contains_key(&func_ref) {
+                    continue;
                 }
             }
 
@@ -249,6 +255,64 @@ impl<'a, 'b> LiveDerivativeBuilder<'a, 'b> {
         }
     }
 
+    /// Marks all mixed derivatives of `higher_order.vals` up to `higher_order.order` as live.
+    /// Each combination of unknowns is only generated once: the unknowns of a derivative are
+    /// sorted in ascending order from the innermost to the outermost derivative. Derivatives
+    /// that are not reachable at a value are zero and therefore skipped.
+    fn insert_higher_order_derivatives(&mut self, higher_order: &HigherOrderDerivatives) {
+        for val in higher_order.vals {
+            let inst = match self.func.dfg.value_def(*val) {
+                ValueDef::Result(inst, _) => inst,
+                _ => continue,
+            };
+
+            let unknowns: Vec<_> = higher_order
+                .unknowns
+                .iter()
+                .copied()
+                .filter(|unknown| {
+                    self.reachable_derivatives.contains(inst, self.intern.to_derivative(*unknown))
+                })
+                .collect();
+            let mut curr: Vec<_> =
+                unknowns.iter().map(|unknown| self.intern.to_derivative(*unknown)).collect();
+            for derivative in &curr {
+                self.live_derivatives.mat.insert(inst, *derivative);
+            }
+
+            for _ in 1..higher_order.order {
+                let mut next = Vec::new();
+                for derivative in curr {
+                    let innermost = self.intern.unknowns_rev(derivative).next().unwrap();
+                    for unknown in unknowns.iter().filter(|it| **it <= innermost) {
+                        let res = self.intern.raise_order_with(derivative, *unknown, |it| {
+                            self.reachable_derivatives.contains(inst, it)
+                        });
+                        let Some((raised, created)) = res else { continue };
+                        if created {
+                            self.populate_reachable(raised);
+                        }
+                        if !self.reachable_derivatives.contains(inst, raised) {
+                            continue;
+                        }
+
+                        // the builder computes a derivative from its previous order so these
+                        // have to be live too
+                        let num_derivatives = self.intern.num_derivatives();
+                        let row = self.live_derivatives.mat.ensure_row(inst);
+                        let mut prev_order = Some(raised);
+                        while let Some(derivative) = prev_order {
+                            row.insert_growable(derivative, num_derivatives);
+                            prev_order = self.intern.previous_order(derivative);
+                        }
+                        next.push(raised);
+                    }
+                }
+                curr = next;
+            }
+        }
+    }
+
     pub fn finish(mut self) -> (LiveDerivatives, BitSet<Inst>) {
         self.visited.clear();
         (self.live_derivatives, self.visited)
@@ -278,6 +342,7 @@ impl LiveDerivatives {
         func: &Function,
         intern: &mut DerivativeIntern,
         extra_derivatives: &[(Value, mir::Unknown)],
+        higher_order: Option<&HigherOrderDerivatives>,
         dom_tree: &DominatorTree,
     ) -> LiveDerivatives {
         let mut builder = LiveDerivativeBuilder::new(func, intern);
@@ -287,6 +352,9 @@ impl LiveDerivatives {
 
         // Add extra derivatives to live_derivatives
         builder.insert_extra_derivative(extra_derivatives);
+        if let Some(higher_order) = higher_order {
+            builder.insert_higher_order_derivatives(higher_order);
+        }
 
         // Solve for all possible live_derivatives
         let mut workqueue = builder.initial_live_derivative_workque();
@@ -297,8 +365,11 @@ impl LiveDerivatives {
 
         let (mut res, buf) = builder.finish();
 
-        // Optimize common subgraphs
-        res.run_subgraph_opt(func, intern, extra_derivatives, dom_tree, buf);
+        // Optimize common subgraphs. The subgraph optimization introduces new unknowns for the
+        // subgraphs which only works for first order derivatives of the outputs.
+        if higher_order.is_none() {
+            res.run_subgraph_opt(func, intern, extra_derivatives, dom_tree, buf);
+        }
 
         res
     }
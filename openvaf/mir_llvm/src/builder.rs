@@ -45,6 +45,20 @@ impl<'ll> MemLoc<'ll> {
             indices: vec![cx.const_unsigned_int(0), cx.const_unsigned_int(idx)].into_boxed_slice(),
         }
     }
+
+    /// Construct a MemLoc for the element `idx` of the array at this MemLoc.
+    /// The element type is elem_ty.
+    pub fn array_elem(
+        self,
+        idx: u32,
+        elem_ty: &'ll llvm_sys::LLVMType,
+        cx: &CodegenCx<'_, 'll>,
+    ) -> MemLoc<'ll> {
+        let mut indices = self.indices.into_vec();
+        indices.push(cx.const_unsigned_int(idx));
+        MemLoc { ptr: self.ptr, ptr_ty: self.ptr_ty, ty: elem_ty, indices: indices.into() }
+    }
+
     /// # Safety
     ///
     /// ptr_ty, ty and indices must be valid for ptr
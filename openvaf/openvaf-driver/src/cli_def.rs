@@ -36,6 +36,9 @@ pub fn main_command() -> Command {
             supported_targets(),
             target_cpu(),
             codegen_opts(),
+            sensitivity(),
+            derivative_order(),
+            param(),
             interface(),
             expand(),
             dump_json(),
@@ -65,6 +68,9 @@ pub const OPT_LVL: &str = "opt_lvl";
 pub const DEFINE: &str = "define";
 pub const PRINT_EXPANSION: &str = "print-expansion";
 pub const DUMP_JSON: &str = "dump-json";
+pub const SENSITIVITY: &str = "sensitivity";
+pub const DERIVATIVE_ORDER: &str = "derivative-order";
+pub const PARAM: &str = "param";
 pub const ALLOW: &str = "allow";
 pub const WARN: &str = "warn";
 pub const DENY: &str = "deny";
@@ -173,6 +179,41 @@ fn codegen_opts() -> Arg {
         .value_hint(ValueHint::Other)
 }
 
+fn sensitivity() -> Arg {
+    Arg::new(SENSITIVITY)
+        .long(SENSITIVITY)
+        .help("Generate derivatives by this parameter.")
+        .long_help("Generate the derivatives of the residuals and operating point variables by this real parameter.\nEquivalent to marking the parameter with the (* sensitivity *) attribute.")
+        .value_name("PARAM")
+        .action(ArgAction::Append)
+        .required(false)
+        .value_hint(ValueHint::Other)
+}
+
+fn derivative_order() -> Arg {
+    Arg::new(DERIVATIVE_ORDER)
+        .long(DERIVATIVE_ORDER)
+        .help("Highest order of the derivatives of the residuals.")
+        .long_help("Highest order of the derivatives of the residuals by the unknowns.\nOrders above one additionally generate the Taylor coefficients of the residuals required for distortion and harmonic balance analysis.\n\npossible values\n\n1 - jacobian only\n2 - second order Taylor coefficients\n3 - second and third order Taylor coefficients")
+        .value_name("ORDER")
+        .value_hint(ValueHint::Other)
+        .value_parser(["1","2","3"])
+        .hide_possible_values(true)
+        .default_value("1").required(false)
+}
+
+fn param() -> Arg {
+    Arg::new(PARAM)
+        .long(PARAM)
+        .short('P')
+        .help("Replace a parameter with a constant.")
+        .long_help("Replace a parameter with a constant at compile time.\nThe parameter is removed from the parameter table of the compiled model and all code that only depends on it is optimized away.\nThe value must lie within the range of the parameter (from/exclude).\nUseful for selector parameters (like version or mode flags) that are fixed for a PDK.")
+        .value_name("PARAM=VALUE")
+        .action(ArgAction::Append)
+        .required(false)
+        .value_hint(ValueHint::Other)
+}
+
 fn input() -> Arg {
     input_file_path_arg(INPUT)
         .help("The root Verilog-A file.")
@@ -10,9 +10,9 @@ use openvaf::{
 use termcolor::{Color, ColorChoice, ColorSpec, WriteColor};
 
 use crate::cli_def::{
-    ALLOW, BATCHMODE, CACHE_DIR, CODEGEN, DEFINE, DENY, DRYRUN, DUMPIR, DUMPMIR, DUMPUNOPTIR,
-    DUMPUNOPTMIR, INCLUDE, INPUT, LINTS, OPT_LVL, OUTPUT, SUPPORTED_TARGETS, TARGET, TARGET_CPU,
-    WARN,
+    ALLOW, BATCHMODE, CACHE_DIR, CODEGEN, DEFINE, DENY, DERIVATIVE_ORDER, DRYRUN, DUMPIR, DUMPMIR,
+    DUMPUNOPTIR, DUMPUNOPTMIR, INCLUDE, INPUT, LINTS, OPT_LVL, OUTPUT, PARAM, SENSITIVITY,
+    SUPPORTED_TARGETS, TARGET, TARGET_CPU, WARN,
 };
 use crate::{CompilationDestination, Opts};
 
@@ -122,6 +122,13 @@ pub fn matches_to_opts(matches: ArgMatches) -> Result<Opts> {
         dump_ir: matches.get_flag(DUMPIR),
         dump_unopt_ir: matches.get_flag(DUMPUNOPTIR),
         dry_run: matches.get_flag(DRYRUN),
+        sensitivities: matches
+            .get_many::<String>(SENSITIVITY)
+            .map_or_else(Vec::new, |values| values.cloned().collect()),
+        derivative_order: matches.get_one::<String>(DERIVATIVE_ORDER).unwrap().parse()?,
+        frozen_params: matches
+            .get_many::<String>(PARAM)
+            .map_or_else(Vec::new, |values| values.cloned().collect()),
     })
 }
 
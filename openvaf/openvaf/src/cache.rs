@@ -8,7 +8,13 @@ use hir::CompilationDB;
 use crate::Opts;
 
 // TODO: use high level hir API instead of low leve database API
-fn hash(db: &CompilationDB, defines: &[String]) -> md5::Digest {
+fn hash(
+    db: &CompilationDB,
+    defines: &[String],
+    sensitivities: &[String],
+    derivative_order: u32,
+    frozen_params: &[String],
+) -> md5::Digest {
     let mut hash_builder = md5::Context::new();
     let cu = db.compilation_unit();
 
@@ -20,6 +26,16 @@ fn hash(db: &CompilationDB, defines: &[String]) -> md5::Digest {
         hash_builder.consume(def)
     }
 
+    hash_builder.consume(sensitivities.len().to_ne_bytes());
+    for param in sensitivities {
+        hash_builder.consume(param)
+    }
+    hash_builder.consume(derivative_order.to_ne_bytes());
+    hash_builder.consume(frozen_params.len().to_ne_bytes());
+    for param in frozen_params {
+        hash_builder.consume(param)
+    }
+
     hash_builder.consume(env!("CARGO_PKG_VERSION"));
     let lints = db.global_lint_overwrites(cu.root_file());
     if cfg!(debug_assertions) && !lints.is_empty() {
@@ -49,7 +65,13 @@ fn hash(db: &CompilationDB, defines: &[String]) -> md5::Digest {
 }
 
 pub fn file_name(db: &CompilationDB, opts: &Opts) -> String {
-    let hash = u128::from_ne_bytes(*hash(db, &opts.defines));
+    let hash = u128::from_ne_bytes(*hash(
+        db,
+        &opts.defines,
+        &opts.sensitivities,
+        opts.derivative_order,
+        &opts.frozen_params,
+    ));
     let hash = base_n::encode(hash, base_n::CASE_INSENSITIVE);
     format!("{}.osdi", hash)
 }
@@ -12,7 +12,7 @@ use std::fs::{create_dir_all, remove_file};
 use std::io::Write;
 use std::time::Instant;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use basedb::diagnostics::{ConsoleSink, DiagnosticSink};
 pub use basedb::lints::{builtin as builtin_lints, LintLevel};
 use basedb::BaseDB;
@@ -22,7 +22,7 @@ use linker::link;
 pub use llvm_sys::target_machine::LLVMCodeGenOptLevel;
 use mir_llvm::LLVMBackend;
 pub use paths::AbsPathBuf;
-use sim_back::{collect_modules, print_intern, print_module};
+use sim_back::{collect_modules, print_intern, print_module, FreezeError};
 pub use target::host_triple;
 pub use target::spec::{get_target_names, Target};
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
@@ -56,6 +56,12 @@ pub struct Opts {
     pub dump_unopt_mir: bool,
     pub dump_ir: bool,
     pub dump_unopt_ir: bool,
+    /// Parameters for which the derivatives of the residuals and opvars are generated
+    pub sensitivities: Vec<String>,
+    /// The highest order of the derivatives of the residuals (see `ModuleInfo::derivative_order`)
+    pub derivative_order: u32,
+    /// Parameters (`NAME=VALUE`) that are replaced with a constant at compile time
+    pub frozen_params: Vec<String>,
 }
 // pub fn dump_json(opts: &Opts) -> Result<CompilationTermination> {
 //     let input =
@@ -185,12 +191,55 @@ pub fn compile(opts: &Opts) -> Result<CompilationTermination> {
     };
 
     // Lowering of natures from AST into HIR happens here
-    let modules = if let Some(modules) = collect_modules(&db, false, &mut ConsoleSink::new(&db)) {
-        modules
-    } else {
+    let Some(mut modules) = collect_modules(&db, false, &mut ConsoleSink::new(&db)) else {
         return Ok(CompilationTermination::FatalDiagnostic);
     };
 
+    for name in &opts.sensitivities {
+        let mut found = false;
+        for module in &mut modules {
+            found |= module.mark_sensitivity(&db, name);
+        }
+        if !found {
+            bail!("--sensitivity {name}: no module has a real parameter called '{name}'");
+        }
+    }
+
+    if !(1..=3).contains(&opts.derivative_order) {
+        bail!("--derivative-order {}: only orders 1 to 3 are supported", opts.derivative_order);
+    }
+    for module in &mut modules {
+        module.derivative_order = opts.derivative_order;
+    }
+
+    for frozen in &opts.frozen_params {
+        let Some((name, value)) = frozen.split_once('=') else {
+            bail!("-P {frozen}: expected PARAM=VALUE");
+        };
+        let mut found = false;
+        for module in &mut modules {
+            match module.freeze_param(&db, name, value) {
+                Ok(()) => found = true,
+                Err(FreezeError::UnknownParam) => (),
+                Err(FreezeError::InvalidValue(ty)) => {
+                    bail!("-P {frozen}: '{value}' is not a valid {ty} value for '{name}'")
+                }
+                Err(FreezeError::OutOfBounds) => {
+                    bail!("-P {frozen}: '{value}' is outside of the range allowed for '{name}'")
+                }
+                Err(FreezeError::UnknownBounds) => {
+                    bail!(
+                        "-P {frozen}: the range of '{name}' depends on parameters that are not \
+                         frozen (freeze them first)"
+                    )
+                }
+            }
+        }
+        if !found {
+            bail!("-P {frozen}: no module has a parameter called '{name}'");
+        }
+    }
+
     let back = LLVMBackend::new(&opts.codegen_opts, &opts.target, opts.target_cpu.clone(), &[]);
     if opts.dry_run {
         return Ok(CompilationTermination::Compiled { lib_file });
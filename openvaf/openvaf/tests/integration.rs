@@ -1,5 +1,5 @@
 use std::f64::consts;
-use std::ffi::OsStr;
+use std::ffi::{CStr, OsStr};
 use std::path::Path;
 
 use camino::Utf8Path;
@@ -10,14 +10,33 @@ use openvaf::{CompilationDestination, CompilationTermination, LLVMCodeGenOptLeve
 use stdx::{ignore_dev_tests, openvaf_test_data, project_root};
 use target::spec::Target;
 
-use crate::load::{load_osdi_lib, EvalFlags, OsdiDescriptor};
+use crate::load::{
+    load_osdi_lib, EvalFlags, EvalRetFlags, OsdiDescriptor, OsdiInstance, OsdiNode,
+    NODE_FLAG_ALIAS, NODE_FLAG_DELAY,
+};
 use crate::mock_sim::{MockSimulation, ALPHA};
 
 mod load;
 mod mock_sim;
 
 fn compile_and_load(root_file: &Utf8Path) -> &'static OsdiDescriptor {
-    let openvaf_opts = openvaf::Opts {
+    compile_and_load_with(root_file, |_| ())
+}
+
+fn compile_and_load_with(
+    root_file: &Utf8Path,
+    configure: impl FnOnce(&mut openvaf::Opts),
+) -> &'static OsdiDescriptor {
+    let libs = compile_and_load_all(root_file, configure);
+    assert_eq!(libs.len(), 1);
+    &libs[0]
+}
+
+fn compile_and_load_all(
+    root_file: &Utf8Path,
+    configure: impl FnOnce(&mut openvaf::Opts),
+) -> &'static [OsdiDescriptor] {
+    let mut openvaf_opts = openvaf::Opts {
         defines: Vec::new(),
         codegen_opts: Vec::new(),
         lints: Vec::new(),
@@ -35,7 +54,11 @@ fn compile_and_load(root_file: &Utf8Path) -> &'static OsdiDescriptor {
         dump_unopt_mir: false,
         dump_ir: false,
         dump_unopt_ir: false,
+        sensitivities: Vec::new(),
+        derivative_order: 1,
+        frozen_params: Vec::new(),
     };
+    configure(&mut openvaf_opts);
 
     let res = openvaf::compile(&openvaf_opts).unwrap();
     let lib_file = match res {
@@ -44,9 +67,7 @@ fn compile_and_load(root_file: &Utf8Path) -> &'static OsdiDescriptor {
             panic!("openvaf: compilation of {root_file} failed");
         }
     };
-    let libs = unsafe { load_osdi_lib(&lib_file).unwrap() };
-    assert_eq!(libs.len(), 1);
-    &libs[0]
+    unsafe { load_osdi_lib(&lib_file).unwrap() }
 }
 
 // fn integration_test(dir: &str) -> Result {
@@ -206,6 +227,970 @@ fn test_limit() -> Result<()> {
     Ok(())
 }
 
+fn test_simprobe() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    const GTH: f64 = 2.0;
+    const ITH: f64 = 0.5;
+    const V: f64 = 3.0;
+
+    // compile model and setup simulation
+    let main_file = openvaf_test_data("osdi").join("simprobe.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+
+    // x1.ith is missing and replaced by its default
+    sim.set_probe("x1", "gth", GTH);
+    sim.set_voltage("a", V);
+    let flags = instance.eval(&model, &mut sim, EvalFlags::empty());
+    assert_eq!(flags, EvalRetFlags::empty());
+    instance.load_dae(&model, &mut sim);
+    assert_approx_eq!(sim.read_jacobian("a", "a"), GTH, 0.0);
+    assert_approx_eq!(sim.read_jacobian("a", "c"), -GTH, 0.0);
+    assert_approx_eq!(sim.read_residual("a"), GTH * V + ITH, 0.0);
+
+    // x1.gth has no default
+    sim.probes.clear();
+    sim.next_iter();
+    let flags = instance.eval(&model, &mut sim, EvalFlags::empty());
+    assert!(flags.contains(EvalRetFlags::EVAL_RET_FLAG_FATAL));
+    Ok(())
+}
+
+fn test_plusargs() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    let main_file = openvaf_test_data("osdi").join("plusargs.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    let conductance = |sim: &mut MockSimulation| {
+        sim.next_iter();
+        let flags = instance.eval(&model, sim, EvalFlags::empty());
+        assert_eq!(flags, EvalRetFlags::empty());
+        instance.load_dae(&model, sim);
+        sim.read_jacobian("a", "a")
+    };
+
+    // plusargs provided by the simulator
+    sim.plusargs = Some(CString::new("+debug +gain=3").unwrap());
+    assert_approx_eq!(conductance(&mut sim), 14.0, 0.0);
+    sim.plusargs = Some(CString::new("").unwrap());
+    assert_approx_eq!(conductance(&mut sim), 1.0, 0.0);
+
+    // fallback to the environment
+    sim.plusargs = None;
+    std::env::set_var("OSDI_PLUSARGS", "+gain=5");
+    let gain = conductance(&mut sim);
+    std::env::remove_var("OSDI_PLUSARGS");
+    assert_approx_eq!(gain, 6.0, 0.0);
+    Ok(())
+}
+
+fn test_ac_stim() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    const MAG: f64 = 2e-3;
+    const PHASE: f64 = 0.5;
+
+    let main_file = openvaf_test_data("osdi").join("ac_stim.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let names: Vec<_> = desc.ac_stim_names().iter().map(|name| name.to_str().unwrap()).collect();
+    assert_eq!(names, ["ac", "ac2"]);
+
+    let model = desc.new_model();
+    model.set_real_param(1, MAG);
+    model.set_real_param(2, PHASE);
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    let flags = instance.eval(&model, &mut sim, EvalFlags::empty());
+    assert_eq!(flags, EvalRetFlags::empty());
+
+    // the stimuli are small signal sources that never contribute to the residual
+    instance.load_dae(&model, &mut sim);
+    assert_approx_eq!(sim.read_residual("p"), 0.0, 0.0);
+
+    let p = sim.nodes.get_index_of("p").unwrap();
+    let n = sim.nodes.get_index_of("n").unwrap();
+    let excitation = instance.load_ac_stim(&model, &sim, "ac");
+    assert_approx_eq!(excitation[p], MAG * PHASE.cos(), MAG * PHASE.sin());
+    assert_approx_eq!(excitation[n], -MAG * PHASE.cos(), -MAG * PHASE.sin());
+    let excitation = instance.load_ac_stim(&model, &sim, "ac2");
+    assert_approx_eq!(excitation[p], 2.0 * MAG, 0.0);
+    let excitation = instance.load_ac_stim(&model, &sim, "noise");
+    assert_approx_eq!(excitation[p], 0.0, 0.0);
+    Ok(())
+}
+
+fn test_discontinuity() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    let main_file = openvaf_test_data("osdi").join("discontinuity.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    let mut order = |sim: &mut MockSimulation, voltage: f64| {
+        sim.next_iter();
+        sim.set_voltage("a", voltage);
+        instance.eval(&model, sim, EvalFlags::ANALYSIS_TRAN).discontinuity_order()
+    };
+
+    assert_eq!(order(&mut sim, 0.0), None);
+    // the lowest order wins
+    assert_eq!(order(&mut sim, 0.6), Some(2));
+    assert_eq!(order(&mut sim, 1.2), Some(0));
+    // $discontinuity(-1) outside of a limiting function is ignored
+    assert_eq!(order(&mut sim, -1.0), None);
+    Ok(())
+}
+
+fn test_param_sensitivity() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    const R: f64 = 2e3;
+    const V: f64 = 0.5;
+
+    let main_file = openvaf_test_data("osdi").join("sensitivity.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    assert_eq!(desc.sensitivity_params(), [1, 2]);
+
+    let model = desc.new_model();
+    model.set_real_param(1, R);
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    sim.set_voltage("p", V);
+    instance.eval(&model, &mut sim, EvalFlags::empty());
+
+    let p = sim.nodes.get_index_of("p").unwrap();
+    let n = sim.nodes.get_index_of("n").unwrap();
+    let (residual, op_vars) = instance.load_param_sensitivity(&model, &sim, 1);
+    assert_approx_eq!(residual[p], -V / (R * R), 0.0);
+    assert_approx_eq!(residual[n], V / (R * R), 0.0);
+    float_cmp::assert_approx_eq!(f64, op_vars[0], -V / (R * R), epsilon = 1e-15);
+
+    let (residual, op_vars) = instance.load_param_sensitivity(&model, &sim, 2);
+    assert_approx_eq!(residual[p], 0.0, V);
+    assert_approx_eq!(residual[n], 0.0, -V);
+    float_cmp::assert_approx_eq!(f64, op_vars[0], 0.0, epsilon = 1e-15);
+
+    // parameters that are not marked for sensitivity analysis have no derivatives
+    let (residual, _) = instance.load_param_sensitivity(&model, &sim, 0);
+    assert_approx_eq!(residual[p], 0.0, 0.0);
+    Ok(())
+}
+
+fn test_implicit_natures() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    let main_file = openvaf_test_data("osdi").join("implicit_natures.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let node = |name: &str| {
+        desc.nodes()
+            .iter()
+            .find(|node| unsafe { CStr::from_ptr(node.name) }.to_str() == Ok(name))
+            .unwrap_or_else(|| panic!("no unknown named {name}"))
+    };
+    let units = |node: &OsdiNode| unsafe {
+        (
+            CStr::from_ptr(node.units).to_str().unwrap(),
+            CStr::from_ptr(node.residual_units).to_str().unwrap(),
+        )
+    };
+
+    // the unknown of idt has the idt_nature of the integrand
+    assert_eq!(units(node("idt(potential(br))")), ("Wb", "V"));
+    // variables declare their nature with an attribute
+    assert_eq!(units(node("ddt(q)")), ("A", "A"));
+    assert_eq!(units(node("absdelay(potential(a))")), ("V", "V"));
+    // otherwise ddt has the nature of the branch its result is contributed to
+    assert_eq!(units(node("ddt(qc)")), ("A", "A"));
+    Ok(())
+}
+
+fn test_higher_order() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    const K: f64 = 2e-3;
+    const C: f64 = 1e-12;
+    const V: f64 = 0.5;
+
+    let main_file = openvaf_test_data("osdi").join("higher_order.va");
+    let desc = compile_and_load_with(main_file.as_path().try_into().unwrap(), |opts| {
+        opts.derivative_order = 3
+    });
+
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    sim.set_voltage("p", V);
+    instance.eval(&model, &mut sim, EvalFlags::empty());
+
+    let p = sim.nodes.get_index_of("p").unwrap() as u32;
+    let n = sim.nodes.get_index_of("n").unwrap() as u32;
+    let vals = instance.load_higher_order(&model);
+    let coefficient = |residual: u32, unknowns: &[u32]| {
+        let pos = desc.higher_order_entries().iter().position(|entry| {
+            let entry_unknowns = [entry.unknown_1, entry.unknown_2, entry.unknown_3];
+            entry.residual == residual
+                && entry.order as usize == unknowns.len()
+                && entry_unknowns[..unknowns.len()] == *unknowns
+        });
+        pos.map_or((0.0, 0.0), |pos| vals[pos])
+    };
+
+    // I = K*V^3 and Q = C*V^2 with V = V(p) - V(n)
+    let expected: [(&[u32], f64, f64); 7] = [
+        (&[p, p], 3.0 * K * V, C),
+        (&[p, n], -6.0 * K * V, -2.0 * C),
+        (&[n, n], 3.0 * K * V, C),
+        (&[p, p, p], K, 0.0),
+        (&[p, p, n], -3.0 * K, 0.0),
+        (&[p, n, n], 3.0 * K, 0.0),
+        (&[n, n, n], -K, 0.0),
+    ];
+    for (unknowns, resist, react) in expected {
+        let (resist_p, react_p) = coefficient(p, unknowns);
+        float_cmp::assert_approx_eq!(f64, resist_p, resist, epsilon = 1e-15);
+        float_cmp::assert_approx_eq!(f64, react_p, react, epsilon = 1e-25);
+        let (resist_n, react_n) = coefficient(n, unknowns);
+        float_cmp::assert_approx_eq!(f64, resist_n, -resist, epsilon = 1e-15);
+        float_cmp::assert_approx_eq!(f64, react_n, -react, epsilon = 1e-25);
+    }
+    Ok(())
+}
+
+fn test_eval_batch() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    const R_A: f64 = 500.0;
+    const R_B: f64 = 2e3;
+
+    let main_file = openvaf_test_data("osdi").join("eval_batch.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance_a = model.new_instance();
+    instance_a.set_real_param(&model, 1, R_A);
+    let mut sim_a = instance_a.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    let mut instance_b = model.new_instance();
+    instance_b.set_real_param(&model, 1, R_B);
+    // both simulations have the same nodes so the instances can share the solution of sim_a
+    let mut sim_b = instance_b.mock_simulation(&model, desc.num_terminals, 300.0)?;
+
+    let mut eval = |instances: &[&OsdiInstance], voltage: f64| {
+        sim_a.next_iter();
+        sim_a.set_voltage("p", voltage);
+        let (flags, inst_flags) =
+            OsdiInstance::eval_batch(instances, &model, &mut sim_a, EvalFlags::empty());
+        let inst_orders: Vec<_> =
+            inst_flags.iter().map(|flags| flags.discontinuity_order()).collect();
+        (flags.discontinuity_order(), inst_orders)
+    };
+
+    assert_eq!(eval(&[], 0.8), (None, vec![]));
+    assert_eq!(eval(&[&instance_b], 0.2), (None, vec![None]));
+    assert_eq!(eval(&[&instance_b], 0.8), (Some(2), vec![Some(2)]));
+    // batches larger than the number of lanes are split into chunks
+    let instances = (0..75)
+        .map(|i| {
+            let mut instance = model.new_instance();
+            instance.set_real_param(&model, 1, if i % 3 == 0 { R_A } else { R_B });
+            let sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+            Ok((instance, sim))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let instance_refs: Vec<_> = instances.iter().map(|(instance, _)| instance).collect();
+    let (order, inst_orders) = eval(&instance_refs, 0.8);
+    assert_eq!(order, Some(0));
+    for (i, order) in inst_orders.into_iter().enumerate() {
+        assert_eq!(order, Some(if i % 3 == 0 { 0 } else { 2 }), "instance {i}");
+    }
+
+    // the lowest order of all instances wins but every instance reports its own order
+    assert_eq!(eval(&[&instance_a, &instance_b], 0.8), (Some(0), vec![Some(0), Some(2)]));
+
+    // every instance stores its own results
+    instance_a.load_dae(&model, &mut sim_a);
+    instance_b.load_dae(&model, &mut sim_b);
+    assert_approx_eq!(sim_a.read_residual("p"), 0.8 / R_A, 0.0);
+    assert_approx_eq!(sim_b.read_residual("p"), 0.8 / R_B, 0.0);
+
+    // the instances of the last (partial) chunk store their results too
+    for (i, (instance, sim)) in instances.iter_mut().enumerate().skip(64) {
+        let r = if i % 3 == 0 { R_A } else { R_B };
+        instance.load_dae(&model, sim);
+        assert_approx_eq!(sim.read_residual("p"), 0.8 / r, 0.0);
+    }
+
+    // eval_batch matches eval for a single instance
+    sim_a.clear();
+    let flags = instance_a.eval(&model, &mut sim_a, EvalFlags::empty());
+    assert_eq!(flags.discontinuity_order(), Some(0));
+    instance_a.load_dae(&model, &mut sim_a);
+    assert_approx_eq!(sim_a.read_residual("p"), 0.8 / R_A, 0.0);
+    Ok(())
+}
+
+fn test_frozen_params() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    const R: f64 = 1e3;
+    const V: f64 = 0.5;
+
+    let main_file = openvaf_test_data("osdi").join("frozen_params.va");
+    let desc = compile_and_load_with(main_file.as_path().try_into().unwrap(), |opts| {
+        opts.frozen_params = vec!["mode=1".to_owned()]
+    });
+
+    // frozen parameters are removed from the parameter table
+    let params: Vec<_> = desc
+        .params()
+        .iter()
+        .map(|param| unsafe { CStr::from_ptr(*param.name) }.to_str().unwrap())
+        .collect();
+    assert_eq!(params, ["$mfactor", "r"]);
+
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    sim.set_voltage("p", V);
+    instance.eval(&model, &mut sim, EvalFlags::empty());
+    instance.load_dae(&model, &mut sim);
+    assert_approx_eq!(sim.read_residual("p"), 2.0 * V / R, 0.0);
+    Ok(())
+}
+
+fn test_filters() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    const TAU: f64 = 1e-3;
+    const TAU2: f64 = 2.5e-4;
+    const PERIOD: f64 = 1e-3;
+    const STEP: f64 = 1e-6;
+
+    let main_file = openvaf_test_data("osdi").join("filters.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    let voltage =
+        |sim: &MockSimulation, node: &str| sim.solve[sim.nodes.get_index_of(node).unwrap()];
+
+    // the input steps from 0 to 1 at t = 0
+    instance.solve_op(&model, &mut sim);
+    for node in ["lnd", "lnp", "lzd", "lzp", "znd", "znp", "zzd", "zzp"] {
+        float_cmp::assert_approx_eq!(f64, voltage(&sim, node), 0.0, epsilon = 1e-12);
+    }
+    sim.set_voltage("inp", 1.0);
+
+    // the samples of the step responses of the zi filters
+    let znd = |k: i32| 2.0 * (1.0 - 0.5f64.powi(k + 1));
+    let znp = |k: i32| if k < 0 { 0.0 } else { 2.0 * (1.0 - 0.5f64.powi(k)) };
+    let zzd = |k: i32| if k < 0 { 0.0 } else { 4.0 - 3.0 * 0.5f64.powi(k) };
+
+    for i in 1..=3000 {
+        let t = i as f64 * STEP;
+        instance.tran_step(&model, &mut sim, t, STEP);
+
+        // backward Euler is accurate to about STEP / TAU2
+        let lnd = 1.0 - f64::exp(-t / TAU);
+        let lnp = 1.0 - (TAU * f64::exp(-t / TAU) - TAU2 * f64::exp(-t / TAU2)) / (TAU - TAU2);
+        let lz = 1.0 + (TAU2 - TAU) / TAU * f64::exp(-t / TAU);
+        float_cmp::assert_approx_eq!(f64, voltage(&sim, "lnd"), lnd, epsilon = 5e-3);
+        float_cmp::assert_approx_eq!(f64, voltage(&sim, "lnp"), lnp, epsilon = 5e-3);
+        float_cmp::assert_approx_eq!(f64, voltage(&sim, "lzd"), lz, epsilon = 5e-3);
+        float_cmp::assert_approx_eq!(f64, voltage(&sim, "lzp"), lz, epsilon = 5e-3);
+
+        // the output is held between the samples, zi_zp ramps to each new sample within PERIOD / 4
+        let k = (t / PERIOD).floor() as i32;
+        let frac = t / PERIOD - k as f64;
+        if (0.1..0.9).contains(&frac) {
+            float_cmp::assert_approx_eq!(f64, voltage(&sim, "znd"), znd(k), epsilon = 1e-12);
+            float_cmp::assert_approx_eq!(f64, voltage(&sim, "zzd"), zzd(k), epsilon = 1e-12);
+            let zzp =
+                if frac < 0.25 { zzd(k - 1) + (zzd(k) - zzd(k - 1)) * frac / 0.25 } else { zzd(k) };
+            float_cmp::assert_approx_eq!(f64, voltage(&sim, "zzp"), zzp, epsilon = 1e-9);
+        }
+        // zi_np samples at PERIOD / 2 + k * PERIOD
+        let k = (t / PERIOD - 0.5).floor() as i32;
+        let frac = t / PERIOD - 0.5 - k as f64;
+        if (0.1..0.9).contains(&frac) {
+            float_cmp::assert_approx_eq!(f64, voltage(&sim, "znp"), znp(k), epsilon = 1e-12);
+        }
+
+        // the next sample time of zi_np or the end of the ramp of zi_zp is the closest
+        if i == 1100 {
+            float_cmp::assert_approx_eq!(
+                f64,
+                instance.bound_step(),
+                0.15 * PERIOD,
+                epsilon = 1e-12
+            );
+        }
+        if i == 1300 {
+            float_cmp::assert_approx_eq!(f64, instance.bound_step(), 0.2 * PERIOD, epsilon = 1e-12);
+        }
+    }
+    Ok(())
+}
+
+fn test_transition_slew() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    const TD: f64 = 1e-6;
+    const TR: f64 = 2e-6;
+    const TF: f64 = 4e-6;
+    const RATE: f64 = 1e5;
+    const STEP: f64 = 5e-7;
+
+    let main_file = openvaf_test_data("osdi").join("transition.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    let voltage =
+        |sim: &MockSimulation, node: &str| sim.solve[sim.nodes.get_index_of(node).unwrap()];
+    let ramp = |t: f64, start: f64, end: f64, from: f64, to: f64| {
+        from + (to - from) * ((t - start) / (end - start)).clamp(0.0, 1.0)
+    };
+
+    // the operators start at the initial operating point instead of zero
+    sim.set_voltage("inp", 1.0);
+    instance.solve_tran_op(&model, &mut sim);
+    for node in ["trans", "slewed", "unlimited"] {
+        float_cmp::assert_approx_eq!(f64, voltage(&sim, node), 1.0, epsilon = 1e-12);
+    }
+
+    // the input falls to 0 at the first step and rises to 2 at the 21st step
+    sim.set_voltage("inp", 0.0);
+    for i in 1..=40 {
+        let t = i as f64 * STEP;
+        if i == 21 {
+            sim.set_voltage("inp", 2.0);
+        }
+        instance.tran_step(&model, &mut sim, t, STEP);
+
+        let (trans, slewed, unlimited) = if i <= 20 {
+            (ramp(t, STEP + TD, STEP + TD + TF, 1.0, 0.0), f64::max(1.0 - 2.0 * RATE * t, 0.0), 0.0)
+        } else {
+            let change = 21.0 * STEP;
+            (ramp(t, change + TD, change + TD + TR, 0.0, 2.0), RATE * (t - 20.0 * STEP), 2.0)
+        };
+        float_cmp::assert_approx_eq!(f64, voltage(&sim, "trans"), trans, epsilon = 1e-9);
+        float_cmp::assert_approx_eq!(f64, voltage(&sim, "slewed"), slewed, epsilon = 1e-9);
+        float_cmp::assert_approx_eq!(f64, voltage(&sim, "unlimited"), unlimited, epsilon = 1e-12);
+
+        // the start of the falling ramp is closer than the end of the slewing
+        if i == 1 {
+            float_cmp::assert_approx_eq!(f64, instance.bound_step(), TD, epsilon = 1e-12);
+        }
+        // the outputs have settled
+        if i == 12 {
+            assert_eq!(instance.bound_step(), f64::INFINITY);
+        }
+        // the slewing ends when the output reaches the input
+        if i == 30 {
+            float_cmp::assert_approx_eq!(
+                f64,
+                instance.bound_step(),
+                (2.0 - slewed) / RATE,
+                epsilon = 1e-12
+            );
+        }
+    }
+    Ok(())
+}
+
+fn test_events() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    const LEVEL: f64 = 0.55;
+    const PERIOD: f64 = 1e-6;
+    const STEP: f64 = 1e-7;
+
+    let main_file = openvaf_test_data("osdi").join("events.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    let voltage =
+        |sim: &MockSimulation, node: &str| sim.solve[sim.nodes.get_index_of(node).unwrap()];
+    let assert_fired = |sim: &MockSimulation, crossed: f64, above: f64, timer: f64| {
+        float_cmp::assert_approx_eq!(f64, voltage(sim, "crossed"), crossed, epsilon = 1e-12);
+        float_cmp::assert_approx_eq!(f64, voltage(sim, "above_level"), above, epsilon = 1e-12);
+        float_cmp::assert_approx_eq!(f64, voltage(sim, "timer_fired"), timer, epsilon = 1e-12);
+    };
+
+    // at the operating point only above fires (if its expression is not negative)
+    sim.set_voltage("inp", 1.0);
+    instance.solve_tran_op(&model, &mut sim);
+    assert_fired(&sim, 0.0, 1.0, 0.0);
+    float_cmp::assert_approx_eq!(f64, voltage(&sim, "last"), -1.0, epsilon = 1e-12);
+    sim.set_voltage("inp", 0.0);
+    instance.solve_tran_op(&model, &mut sim);
+    assert_fired(&sim, 0.0, 0.0, 0.0);
+
+    // the input rises with 1V/us, no crossing happened yet
+    for i in 1..=5 {
+        sim.set_voltage("inp", i as f64 * 0.1);
+        let flags = instance.tran_step(&model, &mut sim, i as f64 * STEP, STEP);
+        assert!(flags.is_empty());
+        assert_fired(&sim, 0.0, 0.0, 0.0);
+        float_cmp::assert_approx_eq!(f64, voltage(&sim, "last"), -1.0, epsilon = 1e-12);
+        if i == 1 {
+            // the timer requests a breakpoint at the time it fires
+            float_cmp::assert_approx_eq!(
+                f64,
+                instance.bound_step(),
+                PERIOD - STEP,
+                epsilon = 1e-18
+            );
+        }
+    }
+
+    // the step overshoots the crossing at 0.55us and is rejected
+    sim.set_voltage("inp", 0.6);
+    let flags = instance.tran_step(&model, &mut sim, 6.0 * STEP, STEP);
+    assert!(flags.contains(EvalRetFlags::EVAL_RET_FLAG_REJECT));
+    float_cmp::assert_approx_eq!(f64, instance.bound_step(), 0.5 * STEP, epsilon = 1e-18);
+
+    // the retry hits the crossing, rejected timesteps do not affect last_crossing
+    sim.set_voltage("inp", LEVEL);
+    let flags = instance.tran_step(&model, &mut sim, 5.5 * STEP, 0.5 * STEP);
+    assert!(flags.is_empty());
+    assert_fired(&sim, 1.0, 1.0, 0.0);
+    float_cmp::assert_approx_eq!(f64, voltage(&sim, "last"), 5.5 * STEP, epsilon = 1e-18);
+
+    for i in 6..=10 {
+        sim.set_voltage("inp", i as f64 * 0.1);
+        let flags = instance.tran_step(&model, &mut sim, i as f64 * STEP, STEP);
+        assert!(flags.is_empty());
+        assert_fired(&sim, 0.0, 0.0, if i == 10 { 1.0 } else { 0.0 });
+        float_cmp::assert_approx_eq!(f64, voltage(&sim, "last"), 5.5 * STEP, epsilon = 1e-18);
+    }
+    // the next breakpoint of the timer is one period later
+    float_cmp::assert_approx_eq!(f64, instance.bound_step(), PERIOD, epsilon = 1e-18);
+
+    // only rising crossings are monitored, last_crossing detects both directions
+    sim.set_voltage("inp", 0.0);
+    let flags = instance.tran_step(&model, &mut sim, 11.0 * STEP, STEP);
+    assert!(flags.is_empty());
+    assert_fired(&sim, 0.0, 0.0, 0.0);
+    let crossing = 10.0 * STEP + STEP * (1.0 - LEVEL);
+    float_cmp::assert_approx_eq!(f64, voltage(&sim, "last"), crossing, epsilon = 1e-18);
+    Ok(())
+}
+
+fn test_instances() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    let main_file = openvaf_test_data("osdi").join("instances.va");
+    let libs = compile_and_load_all(main_file.as_path().try_into().unwrap(), |_| ());
+    let names: Vec<_> =
+        libs.iter().map(|desc| unsafe { CStr::from_ptr(desc.name) }.to_str().unwrap()).collect();
+    // instantiated modules are still compiled on their own
+    assert_eq!(names, ["res", "divider"]);
+
+    let res = &libs[0];
+    let model = res.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, res.num_terminals, 300.0)?;
+    sim.set_voltage("a", 1.0);
+    instance.solve_op(&model, &mut sim);
+    assert_approx_eq!(sim.read_residual("a"), 1.0, 0.0);
+
+    // the instances are flattened into the divider, the parameter overrides are fixed
+    let divider = &libs[1];
+    let params: Vec<_> = divider
+        .params()
+        .iter()
+        .map(|param| unsafe { CStr::from_ptr(*param.name) }.to_str().unwrap())
+        .collect();
+    assert_eq!(params, ["$mfactor"]);
+    let model = divider.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, divider.num_terminals, 300.0)?;
+    sim.set_voltage("p", 1.0);
+    instance.solve_op(&model, &mut sim);
+    let mid = sim.solve[sim.nodes.get_index_of("mid").unwrap()];
+    float_cmp::assert_approx_eq!(f64, mid, 0.75, epsilon = 1e-12);
+    assert_approx_eq!(sim.read_residual("p"), 0.25e-3, 0.0);
+    Ok(())
+}
+
+fn test_paramset_binning() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    let main_file = openvaf_test_data("osdi").join("paramset_binning.va");
+    let libs = compile_and_load_all(main_file.as_path().try_into().unwrap(), |_| ());
+    let names: Vec<_> =
+        libs.iter().map(|desc| unsafe { CStr::from_ptr(desc.name) }.to_str().unwrap()).collect();
+    // both overloads are compiled into a single descriptor
+    assert_eq!(names, ["res", "res_bin"]);
+
+    let desc = &libs[1];
+    let params: Vec<_> = desc
+        .params()
+        .iter()
+        .map(|param| unsafe { CStr::from_ptr(*param.name) }.to_str().unwrap())
+        .collect();
+    assert_eq!(params, ["$mfactor", "l"]);
+
+    let resistance = |l: f64| -> Result<Option<f64>> {
+        let model = desc.new_model();
+        model.set_real_param(1, l);
+        // the ranges of the paramset parameters only select the overload
+        model.process_params()?;
+        let mut instance = model.new_instance();
+        let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+        sim.set_voltage("a", 1.0);
+        let flags = instance.solve_op(&model, &mut sim);
+        if flags.contains(EvalRetFlags::EVAL_RET_FLAG_FATAL) {
+            return Ok(None);
+        }
+        Ok(Some(1.0 / sim.read_residual("a").0))
+    };
+
+    let r = resistance(5e-6)?.expect("the first paramset is selected");
+    float_cmp::assert_approx_eq!(f64, r, 5e3, epsilon = 1e-9);
+    // the upper bound of the first paramset is exclusive
+    let r = resistance(10e-6)?.expect("the second paramset is selected");
+    float_cmp::assert_approx_eq!(f64, r, 20e3, epsilon = 1e-9);
+    let r = resistance(50e-6)?.expect("the second paramset is selected");
+    float_cmp::assert_approx_eq!(f64, r, 100e3, epsilon = 1e-9);
+    // no paramset matches
+    assert_eq!(resistance(200e-6)?, None);
+    Ok(())
+}
+
+fn test_genvar_ladder() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    const R: f64 = 1e3;
+
+    let main_file = openvaf_test_data("osdi").join("genvar_ladder.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    // the node array is elaborated with the localparam N = 4
+    let nodes: Vec<_> = desc
+        .nodes()
+        .iter()
+        .map(|node| unsafe { CStr::from_ptr(node.name) }.to_str().unwrap())
+        .collect();
+    assert_eq!(nodes.len(), 5);
+    assert!(["x[1]", "x[2]", "x[3]"].iter().all(|node| nodes.contains(node)));
+
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    sim.set_voltage("p", 1.0);
+    instance.solve_op(&model, &mut sim);
+    for (i, node) in ["x[1]", "x[2]", "x[3]"].into_iter().enumerate() {
+        let val = sim.solve[sim.nodes.get_index_of(node).unwrap()];
+        float_cmp::assert_approx_eq!(f64, val, 1.0 - (i + 1) as f64 / 4.0, epsilon = 1e-12);
+    }
+    assert_approx_eq!(sim.read_residual("p"), 1.0 / (4.0 * R), 0.0);
+    Ok(())
+}
+
+fn test_arrays() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    let main_file = openvaf_test_data("osdi").join("arrays.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let params: Vec<_> = desc
+        .params()
+        .iter()
+        .map(|param| unsafe { CStr::from_ptr(*param.name) }.to_str().unwrap())
+        .collect();
+    assert_eq!(params, ["$mfactor", "g", "sel"]);
+    assert_eq!(desc.params()[1].len, 3);
+
+    let conductance = |sel: i32| -> Result<Option<f64>> {
+        let model = desc.new_model();
+        model.set_int_param(2, sel);
+        model.process_params()?;
+        let mut instance = model.new_instance();
+        let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+        sim.set_voltage("a", 1.0);
+        let flags = instance.solve_op(&model, &mut sim);
+        if flags.contains(EvalRetFlags::EVAL_RET_FLAG_FATAL) {
+            return Ok(None);
+        }
+        Ok(Some(sim.read_residual("a").0))
+    };
+
+    // the uninitialized elements of hist and the unassigned opvar keep their initial values
+    float_cmp::assert_approx_eq!(f64, conductance(2)?.unwrap(), 2e-3, epsilon = 1e-15);
+    float_cmp::assert_approx_eq!(f64, conductance(3)?.unwrap(), 3e-3, epsilon = 1e-15);
+    // dynamic indices outside of the declared range abort the evaluation
+    assert_eq!(conductance(4)?, None);
+    assert_eq!(conductance(0)?, None);
+    Ok(())
+}
+
+fn test_file_io() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    let main_file = openvaf_test_data("osdi").join("file_io.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let dir = std::env::temp_dir().join("openvaf_file_io");
+    std::fs::create_dir_all(&dir)?;
+    let out = dir.join("file_io.txt");
+    let _ = std::fs::remove_file(&out);
+
+    mock_sim::set_file_dir(Some(&dir));
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    sim.set_voltage("a", 1.0);
+    instance.solve_op(&model, &mut sim);
+    mock_sim::set_file_dir(None);
+
+    // every evaluation truncates the file, both descriptors write to the same file and the
+    // file is flushed once the last descriptor is closed
+    assert_eq!(std::fs::read_to_string(&out)?, "r=1000\ni=0.001\n");
+    Ok(())
+}
+
+fn test_random() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    // the linear congruential generator of the Verilog reference implementation
+    fn unit(seed: &mut i32) -> f64 {
+        let next = (*seed as u32).wrapping_mul(69069).wrapping_add(1);
+        *seed = next as i32;
+        (next as f64 + 0.5) / 4294967296.0
+    }
+
+    const SEED: i32 = 7;
+    const GLOBAL: i32 = 11;
+
+    let main_file = openvaf_test_data("osdi").join("random.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let model = desc.new_model();
+    model.process_params()?;
+
+    let draw = |instance_seed: i32| -> Result<(f64, f64)> {
+        let mut instance = model.new_instance();
+        instance.set_random_seeds(GLOBAL, instance_seed);
+        let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+        instance.solve_op(&model, &mut sim);
+        Ok((sim.read_residual("a").0, sim.read_residual("b").0))
+    };
+
+    let mut seed = SEED ^ GLOBAL;
+    let uniform = 1.0 + 2.0 * unit(&mut seed);
+    let normal = |instance_seed: i32| {
+        let mut seed = SEED ^ instance_seed;
+        let (u1, u2) = (unit(&mut seed), unit(&mut seed));
+        f64::sqrt(-2.0 * u1.ln()) * f64::cos(2.0 * consts::PI * u2)
+    };
+
+    // constant seeds are not advanced, every evaluation (and every instance with the same
+    // seeds) draws the same values
+    let (u, n) = draw(42)?;
+    float_cmp::assert_approx_eq!(f64, u, uniform, epsilon = 1e-12);
+    float_cmp::assert_approx_eq!(f64, n, normal(42), epsilon = 1e-12);
+    assert_eq!(draw(42)?, (u, n));
+
+    // only the values drawn with the "instance" seed depend on the instance seed
+    let (u2, n2) = draw(43)?;
+    assert_eq!(u2, u);
+    float_cmp::assert_approx_eq!(f64, n2, normal(43), epsilon = 1e-12);
+    assert_ne!(n2, n);
+    Ok(())
+}
+
+fn test_table_model() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    let main_file = openvaf_test_data("osdi").join("table_model.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+
+    // a table that fails to load evaluates to zero and is not cached
+    mock_sim::set_file_dir(Some(openvaf_test_data("osdi").join("missing").as_path()));
+    let model = desc.new_model();
+    model.process_params()?;
+    mock_sim::set_file_dir(None);
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    sim.set_voltage("x", 1.4);
+    instance.solve_op(&model, &mut sim);
+    let (resist, _) = sim.read_residual("file");
+    float_cmp::assert_approx_eq!(f64, resist, 0.0, epsilon = 1e-12);
+
+    // the data file is read by setup_model (again)
+    mock_sim::set_file_dir(Some(openvaf_test_data("osdi").as_path()));
+    let model = desc.new_model();
+    model.process_params()?;
+    mock_sim::set_file_dir(None);
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+
+    let outputs = ["lin", "clamp", "discrete", "quad", "spline", "err", "file"];
+    let expected = [
+        // inside of the table
+        (1.4, [2.2, 2.2, 1.0, 1.96, 1.912, 2.2, 6.4]),
+        // above the table, "E" clamps the value (and reports an error)
+        (4.0, [14.0, 9.0, 9.0, 15.0, 9.0, 9.0, 9.0]),
+        // below the table
+        (-1.0, [-1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 4.0]),
+    ];
+    for (x, vals) in expected {
+        sim.set_voltage("x", x);
+        instance.solve_op(&model, &mut sim);
+        for (output, val) in outputs.into_iter().zip(vals) {
+            let (resist, _) = sim.read_residual(output);
+            float_cmp::assert_approx_eq!(f64, resist, val, epsilon = 1e-12);
+        }
+    }
+    Ok(())
+}
+
+fn test_string_fmt() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    let main_file = openvaf_test_data("osdi").join("string_fmt.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    instance.solve_op(&model, &mut sim);
+
+    // $sscanf returns the number of assigned arguments
+    assert_approx_eq!(sim.read_residual("cnt"), 2.0, 0.0);
+    assert_approx_eq!(sim.read_residual("val"), 2504.0, 0.0);
+    // the formatted strings are compared with the expected strings by the model
+    assert_approx_eq!(sim.read_residual("swrite"), 1.0, 0.0);
+    assert_approx_eq!(sim.read_residual("sformat"), 1.0, 0.0);
+    Ok(())
+}
+
+fn test_absdelay_ac() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    const TD: f64 = 1e-6;
+
+    let main_file = openvaf_test_data("osdi").join("absdelay_ac.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    assert_eq!(desc.delays().len(), 1);
+    let unknown = desc.delays()[0].unknown;
+    assert_ne!(unknown, u32::MAX);
+    // the simulator must apply the phase shift to the row of the delayed unknown
+    assert_eq!(desc.node_aliases()[unknown as usize].0, NODE_FLAG_DELAY);
+
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    instance.solve_op(&model, &mut sim);
+
+    // the delayed output is the input shifted by the phase -omega * td
+    let out = sim.nodes.get_index_of("out").unwrap();
+    for omega in [1e4, 1e6, 2e6, 5e6] {
+        let (re, im) = instance.solve_ac(&sim, omega, "inp")[out];
+        float_cmp::assert_approx_eq!(f64, re, f64::cos(omega * TD), epsilon = 1e-12);
+        float_cmp::assert_approx_eq!(f64, im, -f64::sin(omega * TD), epsilon = 1e-12);
+    }
+    Ok(())
+}
+
+fn test_node_alias() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    let main_file = openvaf_test_data("osdi").join("node_alias.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let node_idx = |name: &str| {
+        desc.nodes()
+            .iter()
+            .position(|node| unsafe { CStr::from_ptr(node.name) }.to_str() == Ok(name))
+            .unwrap() as u32
+    };
+    let (a, c, sub, t) = (node_idx("a"), node_idx("c"), node_idx("sub"), node_idx("t"));
+
+    // sub is bound to a node outside of the model, the simulator must connect it
+    let aliases = desc.node_aliases();
+    assert_eq!(aliases[sub as usize].0, NODE_FLAG_ALIAS);
+    assert_eq!(aliases[sub as usize].1.to_str(), Ok("substrate"));
+    for node in [a, c, t] {
+        assert_eq!(aliases[node as usize].0, 0);
+        assert_eq!(aliases[node as usize].1.to_bytes(), b"");
+    }
+
+    // t is resolved at compile time and collapsed into c
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    instance.process_params(&model, desc.num_terminals, 300.0)?;
+    let node_mapping = instance.node_mapping();
+    assert_eq!(node_mapping[t as usize].get(), node_mapping[c as usize].get());
+    assert_ne!(node_mapping[sub as usize].get(), u32::MAX);
+    Ok(())
+}
+
 macro_rules! assert_approx_eq {
     ($val: expr, $expect: expr) => {
         let resist = $val;
@@ -255,6 +1240,38 @@ fn test_noise() -> Result<()> {
     Ok(())
 }
 
+fn test_noise_table() -> Result<()> {
+    if stdx::IS_CI && cfg!(windows) {
+        return Ok(());
+    }
+
+    // the table file is resolved relative to noise_table.va
+    let main_file = openvaf_test_data("osdi").join("noise_table.va");
+    let desc = compile_and_load(main_file.as_path().try_into().unwrap());
+    let model = desc.new_model();
+    model.process_params()?;
+    let mut instance = model.new_instance();
+    let mut sim = instance.mock_simulation(&model, desc.num_terminals, 300.0)?;
+    instance.eval(&model, &mut sim, EvalFlags::empty());
+
+    let log_mid = f64::sqrt(2.0 * 4.0);
+    for (freq, lin, log) in [
+        (0.5, 2.0, 2.0),
+        (1.0, 2.0, 2.0),
+        (5.5, 3.0, 2.0 * f64::powf(2.0, f64::log10(5.5))),
+        (f64::sqrt(10.0), 2.0 + 2.0 * (f64::sqrt(10.0) - 1.0) / 9.0, log_mid),
+        (10.0, 4.0, 4.0),
+        (1e3, 1.0, 1.0),
+    ] {
+        instance.load_noise(&model, &mut sim, freq);
+        assert_approx_eq!(sim.read_noise(0), lin);
+        // the file form matches the inline form
+        assert_approx_eq!(sim.read_noise(1), lin);
+        assert_approx_eq!(sim.read_noise(2), log);
+    }
+    Ok(())
+}
+
 harness! {
     // TODO: run this in CI, somehow this test is flakey tough regarding the linker invocation (and really slow)
     Test::from_dir("integration", &integration_test, &ignore_dev_tests, &project_root().join("integration_tests")),
@@ -264,5 +1281,5 @@ harness! {
     Test::from_dir_filtered("vacask_spice", &vacask_spice_test, &is_va_file, &ignore_dev_tests, &vacask_devices().join("spice")),
     // VACASK simplified SPICE models
     Test::from_dir_filtered("vacask_spice_sn", &vacask_spice_sn_test, &is_va_file, &ignore_dev_tests, &vacask_devices().join("spice/sn")),
-    [Test::new("$limit", &test_limit),Test::new("noise", &test_noise)]
+    [Test::new("$limit", &test_limit),Test::new("noise", &test_noise),Test::new("noise_table", &test_noise_table),Test::new("$simprobe", &test_simprobe),Test::new("$analog_node_alias", &test_node_alias),Test::new("plusargs", &test_plusargs),Test::new("ac_stim", &test_ac_stim),Test::new("$discontinuity", &test_discontinuity),Test::new("sensitivity", &test_param_sensitivity),Test::new("implicit_natures", &test_implicit_natures),Test::new("higher_order", &test_higher_order),Test::new("eval_batch", &test_eval_batch),Test::new("frozen_params", &test_frozen_params),Test::new("filters", &test_filters),Test::new("absdelay_ac", &test_absdelay_ac),Test::new("transition_slew", &test_transition_slew),Test::new("events", &test_events),Test::new("instances", &test_instances),Test::new("paramset_binning", &test_paramset_binning),Test::new("genvar_ladder", &test_genvar_ladder),Test::new("arrays", &test_arrays),Test::new("file_io", &test_file_io),Test::new("random", &test_random),Test::new("table_model", &test_table_model),Test::new("string_fmt", &test_string_fmt)]
 }
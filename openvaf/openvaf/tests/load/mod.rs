@@ -27,6 +27,37 @@ impl OsdiDescriptor {
         unsafe { slice::from_raw_parts(self.nodes, self.num_nodes as usize) }
     }
 
+    pub fn node_aliases(&self) -> Vec<(u32, &CStr)> {
+        // SAFETY: self.data is a valid allocation and the descriptor is assumed valid
+        unsafe {
+            let flags = slice::from_raw_parts(self.node_flags, self.num_nodes as usize);
+            let aliases = slice::from_raw_parts(self.node_alias, self.num_nodes as usize);
+            zip(flags, aliases).map(|(&flags, &alias)| (flags, CStr::from_ptr(alias))).collect()
+        }
+    }
+
+    pub fn ac_stim_names(&self) -> Vec<&CStr> {
+        // SAFETY: self.data is a valid allocation and the descriptor is assumed valid
+        unsafe {
+            let names = slice::from_raw_parts(self.ac_stim_names, self.num_ac_stim as usize);
+            names.iter().map(|&name| CStr::from_ptr(name)).collect()
+        }
+    }
+
+    pub fn sensitivity_params(&self) -> &[u32] {
+        // SAFETY: self.data is a valid allocation and the descriptor is assumed valid
+        unsafe {
+            slice::from_raw_parts(self.sensitivity_params, self.num_sensitivity_params as usize)
+        }
+    }
+
+    pub fn higher_order_entries(&self) -> &[OsdiHigherOrderEntry] {
+        // SAFETY: self.data is a valid allocation and the descriptor is assumed valid
+        unsafe {
+            slice::from_raw_parts(self.higher_order_entries, self.num_higher_order_entries as usize)
+        }
+    }
+
     pub fn params(&self) -> &[OsdiParamOpvar] {
         // # SAFETY: OsdiDescriptor can only be constructed from FFI and is assumed to contain
         // valid data
@@ -43,6 +74,11 @@ impl OsdiDescriptor {
         unsafe { slice::from_raw_parts(self.noise_sources, self.num_noise_src as usize) }
     }
 
+    pub fn delays(&self) -> &[OsdiDelay] {
+        // SAFETY: self.data is a valid allocation and the descriptor is assumed valid
+        unsafe { slice::from_raw_parts(self.delays, self.num_delays as usize) }
+    }
+
     pub fn matrix_entries(&self) -> &[OsdiJacobianEntry] {
         // SAFETY: self.data is a valid allocation and the descriptor is assumed valid
         unsafe { slice::from_raw_parts(self.jacobian_entries, self.num_jacobian_entries as usize) }
@@ -176,6 +212,15 @@ impl OsdiModel {
         unsafe { ptr.write(val) };
     }
 
+    pub fn set_int_param(&self, param: u32, val: i32) {
+        let ptr = self.descriptor.access(ptr::null_mut(), self.data, param, ACCESS_FLAG_SET);
+        let ptr = ptr as *mut i32;
+        if ptr.is_null() {
+            unreachable!("invalid parameter access")
+        }
+        unsafe { ptr.write(val) };
+    }
+
     pub fn new_instance(&self) -> OsdiInstance {
         OsdiInstance {
             descriptor: self.descriptor,
@@ -310,15 +355,31 @@ impl OsdiInstance {
         Ok(internal_nodes)
     }
 
-    // pub fn set_real_param(&mut self, param: u32, val: f64) {
-    //     let ptr =
-    //         unsafe { self.descriptor.access(ptr::null_mut(), self.data, param, ACCESS_FLAG_SET) };
-    //     let ptr = ptr as *mut f64;
-    //     if ptr.is_null() {
-    //         unreachable!("invalid parameter access")
-    //     }
-    //     unsafe { ptr.write(val) };
-    // }
+    /// Writes the global and instance seed of the random number generator, must be called
+    /// before `setup_instance`.
+    pub fn set_random_seeds(&self, global: i32, instance: i32) {
+        let offset = self.descriptor.random_seed_offset;
+        assert_ne!(offset, u32::MAX, "the model does not use random numbers");
+        unsafe {
+            let seeds = self.data.add(offset as usize) as *mut i32;
+            seeds.write(global);
+            seeds.add(1).write(instance);
+        }
+    }
+
+    pub fn set_real_param(&self, model: &OsdiModel, param: u32, val: f64) {
+        let ptr = self.descriptor.access(
+            self.data,
+            model.data,
+            param,
+            ACCESS_FLAG_SET | ACCESS_FLAG_INSTANCE,
+        );
+        let ptr = ptr as *mut f64;
+        if ptr.is_null() {
+            unreachable!("invalid parameter access")
+        }
+        unsafe { ptr.write(val) };
+    }
 }
 
 pub unsafe fn load_osdi_lib(path: &Utf8Path) -> Result<&'static [OsdiDescriptor]> {
@@ -343,6 +404,23 @@ pub unsafe fn load_osdi_lib(path: &Utf8Path) -> Result<&'static [OsdiDescriptor]
     {
         osdi_log_ptr.write(osdi_log)
     }
+    if let Ok(osdi_simprobe_ptr) = lib.get::<*mut unsafe extern "C" fn(
+        *mut c_void,
+        *const c_char,
+        *const c_char,
+        *mut f64,
+    ) -> i32>(b"osdi_simprobe\0")
+    {
+        osdi_simprobe_ptr.write(crate::mock_sim::osdi_simprobe)
+    }
+    if let Ok(osdi_file_path_ptr) = lib.get::<*mut unsafe extern "C" fn(
+        *mut c_void,
+        *const c_char,
+        *const c_char,
+    ) -> *mut c_char>(b"osdi_file_path\0")
+    {
+        osdi_file_path_ptr.write(crate::mock_sim::osdi_file_path)
+    }
     if let Ok(osdi_lim_table) = lib.get(b"OSDI_LIM_TABLE\0") {
         let lim_table_base: *mut OsdiLimFunction = *osdi_lim_table;
         let lim_table_len: &u32 = *lib.get(b"OSDI_LIM_TABLE_LEN\0")?;
@@ -535,6 +613,9 @@ bitflags! {
         const ANALYSIS_IC = ANALYSIS_IC;
         const ANALYSIS_STATIC = ANALYSIS_STATIC;
         const ANALYSIS_NODESET = ANALYSIS_NODESET;
+        const CALC_AC_STIM = CALC_AC_STIM;
+        const CALC_PARAM_SENSITIVITY = CALC_PARAM_SENSITIVITY;
+        const CALC_HIGHER_ORDER = CALC_HIGHER_ORDER;
     }
 }
 
@@ -545,5 +626,15 @@ bitflags! {
         const EVAL_RET_FLAG_FATAL = EVAL_RET_FLAG_FATAL;
         const EVAL_RET_FLAG_FINISH = EVAL_RET_FLAG_FINISH;
         const EVAL_RET_FLAG_STOP = EVAL_RET_FLAG_STOP;
+        const EVAL_RET_FLAG_REJECT = EVAL_RET_FLAG_REJECT;
+        const EVAL_RET_FLAG_DISCONTINUITY = EVAL_RET_FLAG_DISCONTINUITY;
+    }
+}
+
+impl EvalRetFlags {
+    /// The lowest order of all `$discontinuity` calls during the last eval call
+    pub fn discontinuity_order(self) -> Option<u32> {
+        self.contains(Self::EVAL_RET_FLAG_DISCONTINUITY)
+            .then(|| self.bits() >> EVAL_RET_DISCONTINUITY_ORDER_SHIFT)
     }
 }
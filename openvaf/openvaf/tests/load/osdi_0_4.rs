@@ -36,10 +36,16 @@ pub const ANALYSIS_TRAN: u32 = 8192;
 pub const ANALYSIS_IC: u32 = 16384;
 pub const ANALYSIS_STATIC: u32 = 32768;
 pub const ANALYSIS_NODESET: u32 = 65536;
+pub const CALC_AC_STIM: u32 = 131072;
+pub const CALC_PARAM_SENSITIVITY: u32 = 262144;
+pub const CALC_HIGHER_ORDER: u32 = 524288;
 pub const EVAL_RET_FLAG_LIM: u32 = 1;
 pub const EVAL_RET_FLAG_FATAL: u32 = 2;
 pub const EVAL_RET_FLAG_FINISH: u32 = 4;
 pub const EVAL_RET_FLAG_STOP: u32 = 8;
+pub const EVAL_RET_FLAG_REJECT: u32 = 16;
+pub const EVAL_RET_FLAG_DISCONTINUITY: u32 = 32;
+pub const EVAL_RET_DISCONTINUITY_ORDER_SHIFT: u32 = 16;
 pub const LOG_LVL_MASK: u32 = 7;
 pub const LOG_LVL_DEBUG: u32 = 0;
 pub const LOG_LVL_DISPLAY: u32 = 1;
@@ -62,7 +68,11 @@ pub const DOMAIN_CONTINUOUS: u32 = 2;
 pub const NOISE_TYPE_WHITE: u32 = 0;
 pub const NOISE_TYPE_FLICKER: u32 = 1;
 pub const NOISE_TYPE_TABLE: u32 = 2;
+pub const NOISE_TYPE_TABLE_LOG: u32 = 3;
 pub const MODULEFLAG_ABSTIME: u32 = 1;
+pub const NODE_FLAG_ALIAS: u32 = 1;
+pub const NODE_FLAG_PORT_ALIAS: u32 = 2;
+pub const NODE_FLAG_DELAY: u32 = 4;
 
 #[repr(C)]
 pub struct OsdiLimFunction {
@@ -113,6 +123,15 @@ pub struct OsdiJacobianEntry {
     pub flags: u32,
 }
 #[repr(C)]
+pub struct OsdiHigherOrderEntry {
+    pub residual: u32,
+    pub order: u32,
+    pub unknown_1: u32,
+    pub unknown_2: u32,
+    pub unknown_3: u32,
+    pub flags: u32,
+}
+#[repr(C)]
 pub struct OsdiNode {
     pub name: *mut c_char,
     pub units: *mut c_char,
@@ -143,6 +162,25 @@ pub struct OsdiNatureRef {
     pub index: u32,
 }
 #[repr(C)]
+pub struct OsdiNoiseTable {
+    pub num_points: u32,
+    pub freq: *mut f64,
+    pub pwr: *mut f64,
+}
+#[repr(C)]
+pub struct OsdiDelayHistory {
+    pub len: u32,
+    pub time: *mut f64,
+    pub val: *mut f64,
+}
+#[repr(C)]
+pub struct OsdiDelay {
+    pub unknown: u32,
+    pub input_offset: u32,
+    pub delay_offset: u32,
+    pub history_offset: u32,
+}
+#[repr(C)]
 #[non_exhaustive]
 pub struct OsdiDescriptor {
     pub name: *mut c_char,
@@ -197,6 +235,29 @@ pub struct OsdiDescriptor {
     pub noise_source_type: *mut u32,
     pub load_noise_params: fn(*mut c_void, *mut c_void, *mut f64, *mut f64),
     pub module_flags: u32,
+    pub noise_tables: *mut OsdiNoiseTable,
+    pub num_delays: u32,
+    pub delays: *mut OsdiDelay,
+    pub num_persistent_states: u32,
+    pub accept_timestep: fn(*mut c_void, *mut c_void),
+    pub reject_timestep: fn(*mut c_void, *mut c_void),
+    pub random_seed_offset: u32,
+    pub node_flags: *mut u32,
+    pub node_alias: *mut *mut c_char,
+    pub num_ac_stim: u32,
+    pub ac_stim_names: *mut *mut c_char,
+    pub load_ac_stim: fn(*mut c_void, *mut c_void, u32, *mut f64, *mut f64),
+    pub num_sensitivity_params: u32,
+    pub sensitivity_params: *mut u32,
+    pub load_param_sensitivity_resist: fn(*mut c_void, *mut c_void, u32, *mut f64),
+    pub load_param_sensitivity_react: fn(*mut c_void, *mut c_void, u32, *mut f64),
+    pub load_opvar_sensitivity: fn(*mut c_void, *mut c_void, u32, *mut f64),
+    pub num_higher_order_entries: u32,
+    pub higher_order_entries: *mut OsdiHigherOrderEntry,
+    pub load_higher_order_resist: fn(*mut c_void, *mut c_void, *mut f64),
+    pub load_higher_order_react: fn(*mut c_void, *mut c_void, *mut f64),
+    pub eval_batch:
+        fn(*mut c_void, *mut *mut c_void, *mut c_void, *mut OsdiSimInfo, u32, *mut u32) -> u32,
 }
 impl OsdiDescriptor {
     pub fn access(
@@ -334,6 +395,66 @@ impl OsdiDescriptor {
     ) {
         (self.load_noise_params)(inst, model, power, exponent)
     }
+    pub fn accept_timestep(&self, inst: *mut c_void, model: *mut c_void) {
+        (self.accept_timestep)(inst, model)
+    }
+    pub fn reject_timestep(&self, inst: *mut c_void, model: *mut c_void) {
+        (self.reject_timestep)(inst, model)
+    }
+    pub fn load_ac_stim(
+        &self,
+        inst: *mut c_void,
+        model: *mut c_void,
+        analysis: u32,
+        re: *mut f64,
+        im: *mut f64,
+    ) {
+        (self.load_ac_stim)(inst, model, analysis, re, im)
+    }
+    pub fn load_param_sensitivity_resist(
+        &self,
+        inst: *mut c_void,
+        model: *mut c_void,
+        param: u32,
+        dst: *mut f64,
+    ) {
+        (self.load_param_sensitivity_resist)(inst, model, param, dst)
+    }
+    pub fn load_param_sensitivity_react(
+        &self,
+        inst: *mut c_void,
+        model: *mut c_void,
+        param: u32,
+        dst: *mut f64,
+    ) {
+        (self.load_param_sensitivity_react)(inst, model, param, dst)
+    }
+    pub fn load_opvar_sensitivity(
+        &self,
+        inst: *mut c_void,
+        model: *mut c_void,
+        param: u32,
+        dst: *mut f64,
+    ) {
+        (self.load_opvar_sensitivity)(inst, model, param, dst)
+    }
+    pub fn load_higher_order_resist(&self, inst: *mut c_void, model: *mut c_void, dst: *mut f64) {
+        (self.load_higher_order_resist)(inst, model, dst)
+    }
+    pub fn load_higher_order_react(&self, inst: *mut c_void, model: *mut c_void, dst: *mut f64) {
+        (self.load_higher_order_react)(inst, model, dst)
+    }
+    pub fn eval_batch(
+        &self,
+        handle: *mut c_void,
+        inst_ptrs: *mut *mut c_void,
+        model: *mut c_void,
+        info: *mut OsdiSimInfo,
+        n: u32,
+        flags: *mut u32,
+    ) -> u32 {
+        (self.eval_batch)(handle, inst_ptrs, model, info, n, flags)
+    }
 }
 #[repr(C)]
 pub struct OsdiNature {
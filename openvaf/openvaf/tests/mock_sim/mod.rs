@@ -1,5 +1,9 @@
-use std::cell::UnsafeCell;
+use std::cell::{RefCell, UnsafeCell};
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr, CString};
 use std::mem::swap;
+use std::panic::catch_unwind;
+use std::path::{Path, PathBuf};
 use std::ptr;
 
 use anyhow::Result;
@@ -25,7 +29,76 @@ pub struct MockSimulation {
     pub state_1: Vec<f64>,
     pub state_2: Vec<f64>,
     pub noise_dense: Vec<f64>,
+    /// quantities of other instances that can be read with `$simprobe`
+    pub probes: HashMap<(String, String), f64>,
+    /// passed to the model as the `plusargs` string simulator parameter
+    pub plusargs: Option<CString>,
+    /// the time passed to the model
+    pub abstime: f64,
+    /// the reactive residual at the last accepted timepoint
+    prev_react: Vec<f64>,
 }
+
+thread_local! {
+    /// The probes of the simulation that is currently evaluated on this thread.
+    static PROBES: RefCell<HashMap<(String, String), f64>> = RefCell::default();
+}
+
+pub(crate) unsafe extern "C" fn osdi_simprobe(
+    _handle: *mut c_void,
+    inst: *const c_char,
+    name: *const c_char,
+    val: *mut f64,
+) -> i32 {
+    let probe = catch_unwind(|| {
+        let inst = CStr::from_ptr(inst).to_str().expect("all OSDI strings must be valid utf-8");
+        let name = CStr::from_ptr(name).to_str().expect("all OSDI strings must be valid utf-8");
+        PROBES.with(|probes| probes.borrow().get(&(inst.to_owned(), name.to_owned())).copied())
+    });
+    match probe {
+        Ok(Some(probe)) => {
+            *val = probe;
+            0
+        }
+        _ => 1,
+    }
+}
+
+thread_local! {
+    /// The directory that relative paths of files opened by the model are resolved against.
+    static FILE_DIR: RefCell<Option<PathBuf>> = RefCell::default();
+    /// The path returned by the last call to `osdi_file_path`.
+    static FILE_PATH: RefCell<CString> = RefCell::default();
+}
+
+/// Resolves the relative paths of files opened (by `$fopen` or `$table_model`) on this thread
+/// against `dir` (or the working directory if `dir` is `None`).
+pub fn set_file_dir(dir: Option<&Path>) {
+    FILE_DIR.with(|file_dir| *file_dir.borrow_mut() = dir.map(Path::to_path_buf));
+}
+
+pub(crate) unsafe extern "C" fn osdi_file_path(
+    _handle: *mut c_void,
+    path: *const c_char,
+    _mode: *const c_char,
+) -> *mut c_char {
+    let path = catch_unwind(|| {
+        let path = CStr::from_ptr(path).to_str().expect("all OSDI strings must be valid utf-8");
+        let path = FILE_DIR.with(|dir| match &*dir.borrow() {
+            Some(dir) => dir.join(path),
+            None => PathBuf::from(path),
+        });
+        let path = CString::new(path.to_str().unwrap()).unwrap();
+        // the string must stay valid until the call returns
+        FILE_PATH.with(|slot| {
+            let ptr = path.as_ptr() as *mut c_char;
+            *slot.borrow_mut() = path;
+            ptr
+        })
+    });
+    path.unwrap_or(ptr::null_mut())
+}
+
 impl MockSimulation {
     fn new() -> MockSimulation {
         MockSimulation {
@@ -47,6 +120,10 @@ impl MockSimulation {
             state_1: Vec::new(),
             state_2: Vec::new(),
             noise_dense: Vec::new(),
+            probes: HashMap::new(),
+            plusargs: None,
+            abstime: 0.0,
+            prev_react: Vec::new(),
         }
     }
 
@@ -82,6 +159,10 @@ impl MockSimulation {
         self.solve[i] = voltage
     }
 
+    pub fn set_probe(&mut self, inst: &str, name: &str, val: f64) {
+        self.probes.insert((inst.to_owned(), name.to_owned()), val);
+    }
+
     pub fn read_residual(&self, node: &str) -> (f64, f64) {
         let i = self.nodes.get_index_of(node).unwrap();
         (self.residual_resist[i], self.residual_react[i])
@@ -112,6 +193,41 @@ impl MockSimulation {
         }
     }
 
+    /// Performs a Newton update of the unknowns after the first `fixed` nodes with the
+    /// backward Euler residual and returns the largest change of an unknown. The
+    /// operating point is solved with an infinite `step`.
+    fn newton_update(&mut self, fixed: usize, step: f64) -> f64 {
+        let n = self.nodes.len() - fixed;
+        let mut jacobian = vec![vec![0.0; n]; n];
+        // the entries are keyed by (unknown, equation)
+        for (i, &(column, row)) in self.jacobian_info.iter().enumerate() {
+            let (row, column) = (row as usize, column as usize);
+            if row < fixed || column < fixed {
+                continue;
+            }
+            let (resist, react) = unsafe {
+                (self.jacobian_resist[i].get().read(), self.jacobian_react[i].get().read())
+            };
+            jacobian[row - fixed][column - fixed] += resist + react / step;
+        }
+        let rhs: Vec<_> = (fixed..self.nodes.len())
+            .map(|i| {
+                let react = if step.is_finite() {
+                    (self.residual_react[i] - self.prev_react[i]) / step
+                } else {
+                    0.0
+                };
+                -(self.residual_resist[i] + react)
+            })
+            .collect();
+
+        let update = solve_dense(jacobian, rhs);
+        for (val, delta) in zip(&mut self.solve[fixed..], &update) {
+            *val += delta;
+        }
+        update.iter().fold(0.0, |max, delta| delta.abs().max(max))
+    }
+
     pub(crate) fn next_iter(&mut self) {
         self.solve.fill(0.0);
         swap(&mut self.state_1, &mut self.state_2);
@@ -178,6 +294,7 @@ impl OsdiInstance {
         sim.state_1.resize(self.descriptor.num_states as usize, 0.0);
         sim.state_2.resize(self.descriptor.num_states as usize, 0.0);
         sim.noise_dense.resize(self.descriptor.num_noise_src as usize, 0.0);
+        sim.prev_react.resize(sim.nodes.len(), 0.0);
         Ok(sim)
     }
 
@@ -196,6 +313,77 @@ impl OsdiInstance {
         self.descriptor.load_noise(self.data, model.data, freq, sim.noise_dense.as_mut_ptr())
     }
 
+    /// Returns the excitation (real and imaginary part) of each node during the AC analysis
+    /// `analysis`.
+    pub fn load_ac_stim(
+        &self,
+        model: &OsdiModel,
+        sim: &MockSimulation,
+        analysis: &str,
+    ) -> Vec<(f64, f64)> {
+        let mut re = vec![0.0; sim.nodes.len()];
+        let mut im = vec![0.0; sim.nodes.len()];
+        let analysis =
+            self.descriptor.ac_stim_names().iter().position(|name| name.to_str() == Ok(analysis));
+        if let Some(analysis) = analysis {
+            self.descriptor.load_ac_stim(
+                self.data,
+                model.data,
+                analysis as u32,
+                re.as_mut_ptr(),
+                im.as_mut_ptr(),
+            );
+        }
+        zip(re, im).collect()
+    }
+
+    /// Returns the derivative of the residual (resistive and reactive part) of each node and
+    /// the derivative of each opvar by the parameter with the OSDI id `param`.
+    pub fn load_param_sensitivity(
+        &self,
+        model: &OsdiModel,
+        sim: &MockSimulation,
+        param: u32,
+    ) -> (Vec<(f64, f64)>, Vec<f64>) {
+        let mut resist = vec![0.0; sim.nodes.len()];
+        let mut react = vec![0.0; sim.nodes.len()];
+        let mut op_vars = vec![0.0; self.descriptor.num_opvars as usize];
+        let pos = self.descriptor.sensitivity_params().iter().position(|&it| it == param);
+        if let Some(pos) = pos {
+            let pos = pos as u32;
+            self.descriptor.load_param_sensitivity_resist(
+                self.data,
+                model.data,
+                pos,
+                resist.as_mut_ptr(),
+            );
+            self.descriptor.load_param_sensitivity_react(
+                self.data,
+                model.data,
+                pos,
+                react.as_mut_ptr(),
+            );
+            self.descriptor.load_opvar_sensitivity(
+                self.data,
+                model.data,
+                pos,
+                op_vars.as_mut_ptr(),
+            );
+        }
+        (zip(resist, react).collect(), op_vars)
+    }
+
+    /// Returns the Taylor coefficients (resistive and reactive part) in the order of
+    /// `OsdiDescriptor::higher_order_entries`.
+    pub fn load_higher_order(&self, model: &OsdiModel) -> Vec<(f64, f64)> {
+        let num_entries = self.descriptor.num_higher_order_entries as usize;
+        let mut resist = vec![0.0; num_entries];
+        let mut react = vec![0.0; num_entries];
+        self.descriptor.load_higher_order_resist(self.data, model.data, resist.as_mut_ptr());
+        self.descriptor.load_higher_order_react(self.data, model.data, react.as_mut_ptr());
+        zip(resist, react).collect()
+    }
+
     pub fn load_dae(&self, model: &OsdiModel, sim: &mut MockSimulation) {
         self.descriptor.load_residual_resist(
             self.data,
@@ -218,12 +406,193 @@ impl OsdiInstance {
         self.descriptor.load_jacobian_resist(self.data, model.data);
         self.descriptor.load_jacobian_react(self.data, model.data, 1.0);
     }
+    /// Solves the operating point. The terminal voltages are fixed (as if they were
+    /// driven by ideal voltage sources) and the internal unknowns are solved for.
+    pub fn solve_op(&self, model: &OsdiModel, sim: &mut MockSimulation) -> EvalRetFlags {
+        self.solve_op_with(model, sim, EvalFlags::ANALYSIS_DC)
+    }
+
+    /// Solves the initial operating point of a transient analysis, see [`Self::solve_op`].
+    pub fn solve_tran_op(&self, model: &OsdiModel, sim: &mut MockSimulation) -> EvalRetFlags {
+        self.solve_op_with(model, sim, EvalFlags::ANALYSIS_TRAN | EvalFlags::ANALYSIS_IC)
+    }
+
+    fn solve_op_with(
+        &self,
+        model: &OsdiModel,
+        sim: &mut MockSimulation,
+        flags: EvalFlags,
+    ) -> EvalRetFlags {
+        let flags = self.newton(model, sim, flags, f64::INFINITY);
+        self.accept(model, sim);
+        flags
+    }
+
+    /// Advances a transient simulation to `time` with a backward Euler step from the last
+    /// accepted timepoint and accepts the result unless `eval` rejected the timestep. The
+    /// terminal voltages are fixed.
+    pub fn tran_step(
+        &self,
+        model: &OsdiModel,
+        sim: &mut MockSimulation,
+        time: f64,
+        step: f64,
+    ) -> EvalRetFlags {
+        sim.abstime = time;
+        let flags = self.newton(model, sim, EvalFlags::ANALYSIS_TRAN, step);
+        if flags.contains(EvalRetFlags::EVAL_RET_FLAG_REJECT) {
+            self.descriptor.reject_timestep(self.data, model.data);
+        } else {
+            self.accept(model, sim);
+        }
+        flags
+    }
+
+    fn accept(&self, model: &OsdiModel, sim: &mut MockSimulation) {
+        sim.prev_react.clone_from(&sim.residual_react);
+        sim.state_1.clone_from(&sim.state_2);
+        self.descriptor.accept_timestep(self.data, model.data);
+    }
+
+    fn newton(
+        &self,
+        model: &OsdiModel,
+        sim: &mut MockSimulation,
+        flags: EvalFlags,
+        step: f64,
+    ) -> EvalRetFlags {
+        let fixed = self.descriptor.num_terminals as usize + 1;
+        for _ in 0..50 {
+            sim.clear();
+            let ret_flags = self.eval(model, sim, flags);
+            self.load_dae(model, sim);
+            if sim.newton_update(fixed, step) < 1e-12 {
+                // the residual and states belong to the converged solution
+                sim.clear();
+                let ret_flags = self.eval(model, sim, flags);
+                self.load_dae(model, sim);
+                return ret_flags;
+            }
+            if ret_flags.contains(EvalRetFlags::EVAL_RET_FLAG_FATAL) {
+                return ret_flags;
+            }
+        }
+        panic!("newton iteration did not converge at t={}", sim.abstime)
+    }
+
+    /// Solves the small signal response (real and imaginary part of each node) at the angular
+    /// frequency `omega` to a unit excitation of the terminal `terminal` using the Jacobian of
+    /// the last call to `eval`. All other terminals are grounded. As required by the OSDI
+    /// descriptor the rows of delayed unknowns are phase shifted by `exp(-j*omega*td)`.
+    pub fn solve_ac(&self, sim: &MockSimulation, omega: f64, terminal: &str) -> Vec<(f64, f64)> {
+        let fixed = self.descriptor.num_terminals as usize + 1;
+        let n = sim.nodes.len();
+        let mut re = vec![vec![0.0; n]; n];
+        let mut im = vec![vec![0.0; n]; n];
+        // the entries are keyed by (unknown, equation)
+        for (i, &(column, row)) in sim.jacobian_info.iter().enumerate() {
+            let (row, column) = (row as usize, column as usize);
+            unsafe {
+                re[row][column] += sim.jacobian_resist[i].get().read();
+                im[row][column] += omega * sim.jacobian_react[i].get().read();
+            }
+        }
+        for delay in self.descriptor.delays() {
+            if delay.unknown == u32::MAX {
+                continue;
+            }
+            let row = self.node_mapping()[delay.unknown as usize].get() as usize;
+            let td = unsafe {
+                self.data.cast::<u8>().add(delay.delay_offset as usize).cast::<f64>().read()
+            };
+            let (cos, sin) = ((omega * td).cos(), (omega * td).sin());
+            for column in (0..n).filter(|&column| column != row) {
+                let (g, b) = (re[row][column], im[row][column]);
+                re[row][column] = g * cos + b * sin;
+                im[row][column] = b * cos - g * sin;
+            }
+        }
+
+        // [Re -Im; Im Re] [x_re; x_im] = [b_re; b_im] for the internal unknowns
+        let excited = sim.nodes.get_index_of(terminal).unwrap();
+        let m = n - fixed;
+        let mut a = vec![vec![0.0; 2 * m]; 2 * m];
+        let mut b = vec![0.0; 2 * m];
+        for i in 0..m {
+            for k in 0..m {
+                a[i][k] = re[i + fixed][k + fixed];
+                a[i][k + m] = -im[i + fixed][k + fixed];
+                a[i + m][k] = im[i + fixed][k + fixed];
+                a[i + m][k + m] = re[i + fixed][k + fixed];
+            }
+            b[i] = -re[i + fixed][excited];
+            b[i + m] = -im[i + fixed][excited];
+        }
+        let x = solve_dense(a, b);
+        (0..n)
+            .map(|i| match i.checked_sub(fixed) {
+                Some(i) => (x[i], x[i + m]),
+                None if i == excited => (1.0, 0.0),
+                None => (0.0, 0.0),
+            })
+            .collect()
+    }
+
+    /// The largest timestep requested with `$bound_step` during the last call to `eval`
+    pub fn bound_step(&self) -> f64 {
+        let offset = self.descriptor.bound_step_offset;
+        if offset == u32::MAX {
+            return f64::INFINITY;
+        }
+        unsafe { self.data.cast::<u8>().add(offset as usize).cast::<f64>().read() }
+    }
+
     pub fn eval(
         &self,
         model: &OsdiModel,
         sim: &mut MockSimulation,
-        mut flags: EvalFlags,
+        flags: EvalFlags,
     ) -> EvalRetFlags {
+        let flags = sim.with_sim_info(flags, |sim_info| {
+            self.descriptor.eval(b"foo\0".as_ptr() as *mut c_void, self.data, model.data, sim_info)
+        });
+        // the upper bits carry the order of a discontinuity
+        EvalRetFlags::from_bits_retain(flags)
+    }
+
+    /// Evaluates `instances` (which must all belong to `model`) with a single call to
+    /// `eval_batch`. All instances read their inputs from `sim`. Returns the combined flags
+    /// and the flags of every instance.
+    pub fn eval_batch(
+        instances: &[&OsdiInstance],
+        model: &OsdiModel,
+        sim: &mut MockSimulation,
+        flags: EvalFlags,
+    ) -> (EvalRetFlags, Vec<EvalRetFlags>) {
+        let descriptor = model.descriptor;
+        let mut inst_ptrs: Vec<_> = instances.iter().map(|inst| inst.data).collect();
+        let mut inst_flags = vec![0u32; instances.len()];
+        let flags = sim.with_sim_info(flags, |sim_info| {
+            descriptor.eval_batch(
+                b"foo\0".as_ptr() as *mut c_void,
+                inst_ptrs.as_mut_ptr(),
+                model.data,
+                sim_info,
+                inst_ptrs.len() as u32,
+                inst_flags.as_mut_ptr(),
+            )
+        });
+        let inst_flags = inst_flags.into_iter().map(EvalRetFlags::from_bits_retain).collect();
+        (EvalRetFlags::from_bits_retain(flags), inst_flags)
+    }
+}
+
+impl MockSimulation {
+    fn with_sim_info(
+        &mut self,
+        mut flags: EvalFlags,
+        eval: impl FnOnce(&mut OsdiSimInfo) -> u32,
+    ) -> u32 {
         // always calculate everything
         flags |= EvalFlags::CALC_RESIST_JACOBIAN
             | EvalFlags::CALC_RESIST_RESIDUAL
@@ -231,27 +600,53 @@ impl OsdiInstance {
             | EvalFlags::CALC_REACT_JACOBIAN
             | EvalFlags::CALC_REACT_RESIDUAL
             | EvalFlags::CALC_REACT_LIM_RHS
-            | EvalFlags::CALC_NOISE;
+            | EvalFlags::CALC_NOISE
+            | EvalFlags::CALC_AC_STIM
+            | EvalFlags::CALC_PARAM_SENSITIVITY
+            | EvalFlags::CALC_HIGHER_ORDER;
+        let mut names_str = vec![ptr::null_mut()];
+        let mut vals_str = Vec::new();
+        if let Some(plusargs) = &self.plusargs {
+            names_str.insert(0, b"plusargs\0".as_ptr() as *mut c_char);
+            vals_str.push(plusargs.as_ptr() as *mut c_char);
+        }
         let sim_params = OsdiSimParas {
             names: &mut ptr::null_mut(),
             vals: ptr::null_mut(),
-            names_str: &mut ptr::null_mut(),
-            vals_str: ptr::null_mut(),
+            names_str: names_str.as_mut_ptr(),
+            vals_str: vals_str.as_mut_ptr(),
         };
         let mut sim_info = OsdiSimInfo {
             paras: sim_params,
-            abstime: 0.0,
-            prev_solve: sim.solve.as_ptr() as *mut f64,
-            prev_state: sim.state_1.as_mut_ptr(),
-            next_state: sim.state_2.as_mut_ptr(),
+            abstime: self.abstime,
+            prev_solve: self.solve.as_ptr() as *mut f64,
+            prev_state: self.state_1.as_mut_ptr(),
+            next_state: self.state_2.as_mut_ptr(),
             flags: flags.bits(),
         };
-        let flags = self.descriptor.eval(
-            b"foo\0".as_ptr() as *mut c_void,
-            self.data,
-            model.data,
-            &mut sim_info,
-        );
-        EvalRetFlags::from_bits(flags).unwrap()
+        PROBES.with(|probes| probes.replace(self.probes.clone()));
+        eval(&mut sim_info)
+    }
+}
+
+/// Solves `a x = b` with gaussian elimination (partial pivoting)
+fn solve_dense(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Vec<f64> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs())).unwrap();
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        for row in col + 1..n {
+            let fac = a[row][col] / a[col][col];
+            for k in col..n {
+                a[row][k] -= fac * a[col][k];
+            }
+            b[row] -= fac * b[col];
+        }
+    }
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * b[k]).sum();
+        b[row] = (b[row] - sum) / a[row][row];
     }
+    b
 }
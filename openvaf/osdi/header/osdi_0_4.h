@@ -45,11 +45,17 @@
 #define ANALYSIS_IC 16384
 #define ANALYSIS_STATIC 32768
 #define ANALYSIS_NODESET 65536
+#define CALC_AC_STIM 131072
+#define CALC_PARAM_SENSITIVITY 262144
+#define CALC_HIGHER_ORDER 524288
 
 #define EVAL_RET_FLAG_LIM 1
 #define EVAL_RET_FLAG_FATAL 2
 #define EVAL_RET_FLAG_FINISH 4
 #define EVAL_RET_FLAG_STOP 8
+#define EVAL_RET_FLAG_REJECT 16
+#define EVAL_RET_FLAG_DISCONTINUITY 32
+#define EVAL_RET_DISCONTINUITY_ORDER_SHIFT 16
 
 
 #define LOG_LVL_MASK 7
@@ -79,9 +85,14 @@
 #define NOISE_TYPE_WHITE 0
 #define NOISE_TYPE_FLICKER 1
 #define NOISE_TYPE_TABLE 2
+#define NOISE_TYPE_TABLE_LOG 3
 
 #define MODULEFLAG_ABSTIME 1
 
+#define NODE_FLAG_ALIAS 1
+#define NODE_FLAG_PORT_ALIAS 2
+#define NODE_FLAG_DELAY 4
+
 typedef struct OsdiLimFunction {
   char *name;
   uint32_t num_args;
@@ -130,6 +141,15 @@ typedef struct OsdiJacobianEntry {
   uint32_t flags;
 }OsdiJacobianEntry;
 
+typedef struct OsdiHigherOrderEntry {
+  uint32_t residual;
+  uint32_t order;
+  uint32_t unknown_1;
+  uint32_t unknown_2;
+  uint32_t unknown_3;
+  uint32_t flags;
+}OsdiHigherOrderEntry;
+
 typedef struct OsdiNode {
   char *name;
   char *units;
@@ -160,6 +180,25 @@ typedef struct OsdiNatureRef {
   uint32_t index; 
 }OsdiNatureRef;
 
+typedef struct OsdiNoiseTable {
+  uint32_t num_points;
+  double *freq;
+  double *pwr;
+}OsdiNoiseTable;
+
+typedef struct OsdiDelayHistory {
+  uint32_t len;
+  double *time;
+  double *val;
+}OsdiDelayHistory;
+
+typedef struct OsdiDelay {
+  uint32_t unknown;
+  uint32_t input_offset;
+  uint32_t delay_offset;
+  uint32_t history_offset;
+}OsdiDelay;
+
 typedef struct OsdiDescriptor {
   char *name;
 
@@ -229,6 +268,33 @@ typedef struct OsdiDescriptor {
   uint32_t *noise_source_type;
   void (*load_noise_params)(void *inst, void *model, double *power, double *exponent);
   uint32_t module_flags;
+  OsdiNoiseTable *noise_tables;
+  uint32_t num_delays;
+  OsdiDelay *delays;
+  uint32_t num_persistent_states;
+  void (*accept_timestep)(void *inst, void *model);
+  void (*reject_timestep)(void *inst, void *model);
+  uint32_t random_seed_offset;
+  uint32_t *node_flags;
+  char **node_alias;
+  uint32_t num_ac_stim;
+  char **ac_stim_names;
+  void (*load_ac_stim)(void *inst, void *model, uint32_t analysis, double *re,
+                       double *im);
+  uint32_t num_sensitivity_params;
+  uint32_t *sensitivity_params;
+  void (*load_param_sensitivity_resist)(void *inst, void *model, uint32_t param,
+                                        double *dst);
+  void (*load_param_sensitivity_react)(void *inst, void *model, uint32_t param,
+                                       double *dst);
+  void (*load_opvar_sensitivity)(void *inst, void *model, uint32_t param,
+                                 double *dst);
+  uint32_t num_higher_order_entries;
+  OsdiHigherOrderEntry *higher_order_entries;
+  void (*load_higher_order_resist)(void *inst, void *model, double *dst);
+  void (*load_higher_order_react)(void *inst, void *model, double *dst);
+  uint32_t (*eval_batch)(void *handle, void **inst_ptrs, void *model,
+                         OsdiSimInfo *info, uint32_t n, uint32_t *flags);
 }OsdiDescriptor;
 
 typedef struct OsdiNature {
@@ -1,9 +1,10 @@
 use std::iter;
 use std::ptr::NonNull;
 
-use hir::CompilationDB;
+use hir::builtin::{TableExtrapolation, TableInterpolation};
+use hir::{CompilationDB, Type};
 use hir_lower::fmt::{DisplayKind, FmtArg, FmtArgKind};
-use hir_lower::{CallBackKind, HirInterner, RetFlag};
+use hir_lower::{CallBackKind, HirInterner, RetFlag, TableSource};
 use lasso::Rodeo;
 use llvm_sys::core::{
     LLVMAddIncoming, LLVMAppendBasicBlockInContext, LLVMBuildAdd, LLVMBuildArrayMalloc,
@@ -236,6 +237,55 @@ pub fn general_callbacks<'ll>(
                         num_state: 0,
                     })
                 }
+                CallBackKind::SimProbe => {
+                    let fun = builder
+                        .cx
+                        .get_func_by_name("simprobe")
+                        .expect("stdlib function simprobe is missing");
+                    let fun_ty = builder
+                        .cx
+                        .ty_func(&[ptr_ty, ptr_ty, ptr_ty, ptr_ty], builder.cx.ty_double());
+                    CallbackFun::Prebuilt(BuiltCallbackFun {
+                        fun_ty,
+                        fun,
+                        state: vec![handle, ret_flags].into_boxed_slice(),
+                        num_state: 0,
+                    })
+                }
+                CallBackKind::SimProbeOpt => {
+                    let fun = builder
+                        .cx
+                        .get_func_by_name("simprobe_opt")
+                        .expect("stdlib function simprobe_opt is missing");
+                    let fun_ty = builder.cx.ty_func(
+                        &[ptr_ty, ptr_ty, ptr_ty, builder.cx.ty_double()],
+                        builder.cx.ty_double(),
+                    );
+                    CallbackFun::Prebuilt(BuiltCallbackFun {
+                        fun_ty,
+                        fun,
+                        state: vec![handle].into_boxed_slice(),
+                        num_state: 0,
+                    })
+                }
+                CallBackKind::TestPlusArgs | CallBackKind::PlusArg => {
+                    let (name, ret_ty) = if *call == CallBackKind::TestPlusArgs {
+                        ("osdi_test_plusargs", builder.cx.ty_int())
+                    } else {
+                        ("osdi_plusarg", ptr_ty)
+                    };
+                    let fun = builder
+                        .cx
+                        .get_func_by_name(name)
+                        .unwrap_or_else(|| panic!("stdlib function {name} is missing"));
+                    let fun_ty = builder.cx.ty_func(&[ptr_ty, ptr_ty], ret_ty);
+                    CallbackFun::Prebuilt(BuiltCallbackFun {
+                        fun_ty,
+                        fun,
+                        state: vec![simparam].into_boxed_slice(),
+                        num_state: 0,
+                    })
+                }
                 // If these derivative were non zero they would have been removed
                 CallBackKind::Derivative(_) | CallBackKind::NodeDerivative(_) => {
                     let zero = builder.cx.const_real(0.0);
@@ -247,15 +297,22 @@ pub fn general_callbacks<'ll>(
                 | CallBackKind::CollapseHint(_, _)
                 | CallBackKind::BuiltinLimit { .. }
                 | CallBackKind::StoreLimit(_)
+                | CallBackKind::StoreTranState(_)
+                | CallBackKind::StorePersistentState(_)
                 | CallBackKind::LimDiscontinuity
                 | CallBackKind::Analysis
+                | CallBackKind::AbsDelay(_)
+                | CallBackKind::AbsDelayWeight(_)
                 | CallBackKind::NoiseTable(_)
                 | CallBackKind::WhiteNoise { .. }
                 | CallBackKind::FlickerNoise { .. }
+                | CallBackKind::AcStim { .. }
+                | CallBackKind::TableModel { .. }
                 | CallBackKind::TimeDerivative => return None,
 
                 CallBackKind::Print { kind, arg_tys } => {
-                    let (fun, fun_ty) = print_callback(builder.cx, *kind, arg_tys);
+                    let (fun, fun_ty) =
+                        print_callback(builder.cx, *kind, arg_tys, PrintTarget::Log);
                     CallbackFun::Prebuilt(BuiltCallbackFun {
                         fun_ty,
                         fun,
@@ -263,6 +320,83 @@ pub fn general_callbacks<'ll>(
                         num_state: 0,
                     })
                 }
+                CallBackKind::FilePrint { arg_tys } => {
+                    let (fun, fun_ty) = print_callback(
+                        builder.cx,
+                        DisplayKind::Display,
+                        arg_tys,
+                        PrintTarget::File,
+                    );
+                    CallbackFun::Prebuilt(BuiltCallbackFun {
+                        fun_ty,
+                        fun,
+                        state: Box::new([handle]),
+                        num_state: 0,
+                    })
+                }
+                CallBackKind::FileOpen => {
+                    let fun = builder
+                        .cx
+                        .get_func_by_name("osdi_fopen")
+                        .expect("stdlib function osdi_fopen is missing");
+                    let fun_ty = builder.cx.ty_func(&[ptr_ty, ptr_ty, ptr_ty], builder.cx.ty_int());
+                    CallbackFun::Prebuilt(BuiltCallbackFun {
+                        fun_ty,
+                        fun,
+                        state: Box::new([handle]),
+                        num_state: 0,
+                    })
+                }
+                CallBackKind::FileClose => {
+                    let fun = builder
+                        .cx
+                        .get_func_by_name("osdi_fclose")
+                        .expect("stdlib function osdi_fclose is missing");
+                    let fun_ty =
+                        builder.cx.ty_func(&[ptr_ty, builder.cx.ty_int()], builder.cx.ty_void());
+                    CallbackFun::Prebuilt(BuiltCallbackFun {
+                        fun_ty,
+                        fun,
+                        state: Box::new([handle]),
+                        num_state: 0,
+                    })
+                }
+                CallBackKind::RandomNextSeed(dist) => {
+                    let fun = builder
+                        .cx
+                        .get_func_by_name("osdi_random_next_seed")
+                        .expect("stdlib function osdi_random_next_seed is missing");
+                    let (ty_int, ty_double) = (builder.cx.ty_int(), builder.cx.ty_double());
+                    let fun_ty =
+                        builder.cx.ty_func(&[ty_int, ty_int, ty_double, ty_double], ty_int);
+                    CallbackFun::Prebuilt(BuiltCallbackFun {
+                        fun_ty,
+                        fun,
+                        state: Box::new([builder.cx.const_unsigned_int(*dist as u32)]),
+                        num_state: 0,
+                    })
+                }
+                // the string slots are provided by the function that owns the strings
+                CallBackKind::FormatString { .. } | CallBackKind::ScanValue { .. } => {
+                    string_callback(builder.cx, call, handle, builder.cx.const_null_ptr()).unwrap()
+                }
+                CallBackKind::ScanCount => {
+                    let fun = builder
+                        .cx
+                        .get_func_by_name("osdi_sscanf")
+                        .expect("stdlib function osdi_sscanf is missing");
+                    let fun_ty = builder.cx.ty_func(&[ptr_ty, ptr_ty], builder.cx.ty_int());
+                    CallbackFun::Prebuilt(BuiltCallbackFun {
+                        fun_ty,
+                        fun,
+                        state: Box::new([]),
+                        num_state: 0,
+                    })
+                }
+                // functions without instance data (like setup_model) use no seeds
+                CallBackKind::Random { .. } | CallBackKind::RandomStream { .. } => {
+                    random_callback(builder.cx, call, builder.cx.const_null_ptr()).unwrap()
+                }
                 CallBackKind::SetRetFlag(flag) => {
                     let fun = if *flag == RetFlag::Abort {
                         // Fatal
@@ -282,10 +416,26 @@ pub fn general_callbacks<'ll>(
                             .cx
                             .get_func_by_name("set_ret_flag_stop")
                             .expect("stdlib function set_ret_flag_stop is missing")
+                    } else if *flag == RetFlag::Reject {
+                        // Reject
+                        builder
+                            .cx
+                            .get_func_by_name("set_ret_flag_reject")
+                            .expect("stdlib function set_ret_flag_reject is missing")
+                    } else if *flag == RetFlag::Discontinuity {
+                        // Discontinuity, the order is passed as an argument
+                        builder
+                            .cx
+                            .get_func_by_name("set_ret_flag_discontinuity")
+                            .expect("stdlib function set_ret_flag_discontinuity is missing")
                     } else {
                         panic!("Unsupported RetFlag encountered.");
                     };
-                    let fun_ty = builder.cx.ty_func(&[ptr_ty], builder.cx.ty_void());
+                    let fun_ty = if *flag == RetFlag::Discontinuity {
+                        builder.cx.ty_func(&[ptr_ty, builder.cx.ty_int()], builder.cx.ty_void())
+                    } else {
+                        builder.cx.ty_func(&[ptr_ty], builder.cx.ty_void())
+                    };
                     CallbackFun::Prebuilt(BuiltCallbackFun {
                         fun_ty,
                         fun,
@@ -298,6 +448,184 @@ pub fn general_callbacks<'ll>(
         })
         .collect()
 }
+/// Builds the callbacks for drawing random numbers. `state` points to the random state in the
+/// instance data (or is null).
+pub fn random_callback<'ll>(
+    cx: &CodegenCx<'_, 'll>,
+    kind: &CallBackKind,
+    state: &'ll llvm_sys::LLVMValue,
+) -> Option<CallbackFun<'ll>> {
+    let (name, dist, global) = match *kind {
+        CallBackKind::Random { dist, global } => ("osdi_random", dist, global),
+        CallBackKind::RandomStream { dist, global } => ("osdi_random_stream", dist, global),
+        _ => return None,
+    };
+    let fun =
+        cx.get_func_by_name(name).unwrap_or_else(|| panic!("stdlib function {name} is missing"));
+    let (ty_int, ty_double) = (cx.ty_int(), cx.ty_double());
+    let mut args = vec![cx.ty_ptr(), ty_int, ty_int];
+    if matches!(kind, CallBackKind::Random { .. }) {
+        args.push(ty_int);
+    }
+    args.extend([ty_double, ty_double]);
+    let fun_ty = cx.ty_func(&args, ty_double);
+    Some(CallbackFun::Prebuilt(BuiltCallbackFun {
+        fun_ty,
+        fun,
+        state: Box::new([
+            state,
+            cx.const_unsigned_int(dist as u32),
+            cx.const_unsigned_int(global as u32),
+        ]),
+        num_state: 0,
+    }))
+}
+
+/// Builds the callbacks that create strings (`$swrite`, `$sformat` and `$sscanf`). `slot` points
+/// to the string slot that owns the created string (or is null).
+pub fn string_callback<'ll>(
+    cx: &CodegenCx<'_, 'll>,
+    kind: &CallBackKind,
+    handle: &'ll llvm_sys::LLVMValue,
+    slot: &'ll llvm_sys::LLVMValue,
+) -> Option<CallbackFun<'ll>> {
+    let (ptr_ty, int_ty) = (cx.ty_ptr(), cx.ty_int());
+    let (fun, fun_ty, state) = match kind {
+        CallBackKind::FormatString { arg_tys, .. } => {
+            let (fun, fun_ty) =
+                print_callback(cx, DisplayKind::Display, arg_tys, PrintTarget::String);
+            (fun, fun_ty, vec![handle, slot])
+        }
+        CallBackKind::ScanValue { arg, ty, .. } => {
+            let arg = cx.const_unsigned_int(*arg);
+            let (name, fun_ty, state) = match ty {
+                Type::Integer => (
+                    "osdi_sscanf_int",
+                    cx.ty_func(&[int_ty, ptr_ty, ptr_ty, int_ty], int_ty),
+                    vec![arg],
+                ),
+                Type::Real => (
+                    "osdi_sscanf_real",
+                    cx.ty_func(&[int_ty, ptr_ty, ptr_ty, cx.ty_double()], cx.ty_double()),
+                    vec![arg],
+                ),
+                Type::String => (
+                    "osdi_sscanf_str",
+                    cx.ty_func(&[ptr_ty, int_ty, ptr_ty, ptr_ty, ptr_ty], ptr_ty),
+                    vec![slot, arg],
+                ),
+                ty => unreachable!("invalid $sscanf output type {ty:?}"),
+            };
+            let fun = cx
+                .get_func_by_name(name)
+                .unwrap_or_else(|| panic!("stdlib function {name} is missing"));
+            (fun, fun_ty, state)
+        }
+        _ => return None,
+    };
+    Some(CallbackFun::Prebuilt(BuiltCallbackFun {
+        fun_ty,
+        fun,
+        state: state.into_boxed_slice(),
+        num_state: 0,
+    }))
+}
+
+/// Builds the callback that evaluates (a partial derivative of) a `$table_model`. `table`
+/// is the table loaded by setup_model.
+pub fn table_callback<'ll>(
+    cx: &CodegenCx<'_, 'll>,
+    kind: &CallBackKind,
+    handle: &'ll llvm_sys::LLVMValue,
+    table: &'ll llvm_sys::LLVMValue,
+) -> Option<CallbackFun<'ll>> {
+    let CallBackKind::TableModel { derivative, .. } = kind else {
+        return None;
+    };
+    let fun =
+        cx.get_func_by_name("osdi_table_eval").expect("stdlib function osdi_table_eval is missing");
+    let ptr_ty = cx.ty_ptr();
+    let fun_ty = cx.ty_variadic_func(&[ptr_ty, ptr_ty, ptr_ty], cx.ty_double());
+    let derivative: Vec<_> = derivative.iter().map(|&order| cx.const_u8(order)).collect();
+    let derivative = cx.const_arr_ptr(cx.ty_c_bool(), &derivative);
+    Some(CallbackFun::Prebuilt(BuiltCallbackFun {
+        fun_ty,
+        fun,
+        state: Box::new([handle, table, derivative]),
+        num_state: 0,
+    }))
+}
+
+/// Loads all tables of `$table_model` and stores them in the model data.
+///
+/// # Safety
+/// Must be called with the builder positioned in a block without terminator.
+pub unsafe fn load_tables<'ll>(
+    cx: &CodegenCx<'_, 'll>,
+    model_data: &OsdiModelData<'ll>,
+    inst_data: &OsdiInstanceData<'ll>,
+    handle: &'ll llvm_sys::LLVMValue,
+    model: &'ll llvm_sys::LLVMValue,
+    builder: &mir_llvm::Builder<'_, '_, 'll>,
+) {
+    if model_data.tables.is_empty() {
+        return;
+    }
+    let fun =
+        cx.get_func_by_name("osdi_table_load").expect("stdlib function osdi_table_load is missing");
+    let (ptr_ty, int_ty) = (cx.ty_ptr(), cx.ty_int());
+    let fun_ty = cx.ty_func(&[ptr_ty, ptr_ty, ptr_ty, int_ty, ptr_ty, int_ty], ptr_ty);
+    let c_str = |bytes: &[u8]| {
+        let chars: Vec<_> =
+            bytes.iter().chain(std::iter::once(&0)).map(|&c| cx.const_u8(c)).collect();
+        cx.const_arr_ptr(cx.ty_char(), &chars)
+    };
+
+    for table in &model_data.tables {
+        let dims = table.control.len();
+        let ctrl: Vec<u8> = table
+            .control
+            .iter()
+            .flat_map(|ctrl| {
+                let interpolation = match ctrl.interpolation {
+                    TableInterpolation::Ignore => b'I',
+                    TableInterpolation::Discrete => b'D',
+                    TableInterpolation::Linear => b'1',
+                    TableInterpolation::Quadratic => b'2',
+                    TableInterpolation::CubicSpline => b'3',
+                };
+                let extrapolation = |extrapolation| match extrapolation {
+                    TableExtrapolation::Clamp => b'C',
+                    TableExtrapolation::Linear => b'L',
+                    TableExtrapolation::Error => b'E',
+                };
+                [interpolation, extrapolation(ctrl.lower), extrapolation(ctrl.upper)]
+            })
+            .collect();
+
+        let (path, data, len) = match &table.src {
+            TableSource::File(path) => (c_str(path.as_bytes()), cx.const_null_ptr(), 0),
+            TableSource::Inline(samples) => {
+                let samples: Vec<_> =
+                    samples.iter().map(|&sample| cx.const_real(sample.into())).collect();
+                let len = samples.len() / (dims + 1);
+                (cx.const_null_ptr(), cx.const_arr_ptr(cx.ty_double(), &samples), len)
+            }
+        };
+        let args = [
+            handle,
+            path,
+            data,
+            cx.const_unsigned_int(len as u32),
+            c_str(&ctrl),
+            cx.const_unsigned_int(dims as u32),
+        ];
+        let val = builder.call(fun_ty, fun, &args);
+        let dst = model_data.table_ptr(inst_data, table, model, builder.llbuilder);
+        builder.store(dst, val);
+    }
+}
+
 /* This was very useful for debugging
 fn print_module_ir(cx: &CodegenCx, message: &str) {
     unsafe {
@@ -330,14 +658,37 @@ fn print_module_ir(cx: &CodegenCx, message: &str) {
     }
 }*/
 
+/// Where the message formatted by [`print_callback`] is written to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PrintTarget {
+    Log,
+    File,
+    /// The message is stored in a string slot and returned
+    String,
+}
+
 fn print_callback<'ll>(
     cx: &CodegenCx<'_, 'll>,
     kind: hir_lower::fmt::DisplayKind,
     arg_tys: &[FmtArg],
+    target: PrintTarget,
 ) -> (&'ll llvm_sys::LLVMValue, &'ll llvm_sys::LLVMType) {
+    // file output receives the file descriptor (and string output the string slot) between the
+    // handle and the format literal
     let mut args = vec![cx.ty_ptr(), cx.ty_ptr()];
+    let (fmt_pos, ret_ty) = match target {
+        PrintTarget::Log => (1, cx.ty_void()),
+        PrintTarget::File => {
+            args.insert(1, cx.ty_int());
+            (2, cx.ty_void())
+        }
+        PrintTarget::String => {
+            args.insert(1, cx.ty_ptr());
+            (2, cx.ty_ptr())
+        }
+    };
     args.extend(arg_tys.iter().map(|arg| lltype(&arg.ty, cx)));
-    let fun_ty = cx.ty_func(&args, cx.ty_void());
+    let fun_ty = cx.ty_func(&args, ret_ty);
     let name = cx.local_callback_name();
     let fun = cx.declare_int_fn(&name, fun_ty);
 
@@ -374,12 +725,10 @@ fn print_callback<'ll>(
 
         LLVMPositionBuilderAtEnd(llbuilder, entry_bb);
         let handle = LLVMGetParam(NonNull::from(fun).as_ptr(), 0);
-        let fmt_lit = LLVMGetParam(NonNull::from(fun).as_ptr(), 1);
-        let mut args = vec![
-            cx.const_null_ptr(),
-            cx.const_usize(0),
-            &*LLVMGetParam(NonNull::from(fun).as_ptr(), 1),
-        ];
+        // the file descriptor or string slot
+        let dst = LLVMGetParam(NonNull::from(fun).as_ptr(), 1);
+        let fmt_lit = LLVMGetParam(NonNull::from(fun).as_ptr(), fmt_pos);
+        let mut args = vec![cx.const_null_ptr(), cx.const_usize(0), &*fmt_lit];
 
         let exp_table = cx.get_declared_value("EXP").expect("constant EXP missing from stdlib");
         let exp_table_ty = cx.ty_array(cx.ty_double(), 11);
@@ -394,7 +743,7 @@ fn print_callback<'ll>(
         let mut free = Vec::new();
 
         for (i, arg) in arg_tys.iter().enumerate() {
-            let val = LLVMGetParam(NonNull::from(fun).as_ptr(), i as u32 + 2);
+            let val = LLVMGetParam(NonNull::from(fun).as_ptr(), i as u32 + fmt_pos + 1);
             match arg.kind {
                 FmtArgKind::Binary => {
                     let mut val_array = [val];
@@ -565,25 +914,50 @@ fn print_callback<'ll>(
         let mut incoming_blocks = [write_bb, err_bb];
         LLVMAddIncoming(msg, incoming_values.as_mut_ptr(), incoming_blocks.as_mut_ptr(), 2);
 
-        let fun_ptr = cx.get_declared_value("osdi_log").expect("symbol osdi_log is missing");
-        let fun_ty = cx.ty_func(&[cx.ty_ptr(), cx.ty_ptr(), cx.ty_int()], cx.ty_void());
-        let fun = LLVMBuildLoad2(
-            llbuilder,
-            NonNull::from(cx.ty_ptr()).as_ptr(),
-            NonNull::from(fun_ptr).as_ptr(),
-            UNNAMED,
-        );
-
-        // Fix for LLVMBuildCall2
-        let mut args = [handle, msg, flags];
-        LLVMBuildCall2(
-            llbuilder,
-            NonNull::from(fun_ty).as_ptr(),
-            fun,
-            args.as_mut_ptr(),
-            3,
-            UNNAMED,
-        );
+        if target != PrintTarget::Log {
+            let (name, dst_ty) = match target {
+                PrintTarget::File => ("osdi_fwrite", cx.ty_int()),
+                _ => ("osdi_swrite", cx.ty_ptr()),
+            };
+            let write_fun =
+                cx.get_func_by_name(name).unwrap_or_else(|| panic!("{name} missing from stdlib"));
+            let write_fun_ty = cx.ty_func(&[cx.ty_ptr(), dst_ty, cx.ty_ptr(), cx.ty_int()], ret_ty);
+            let mut args = [handle, dst, msg, flags];
+            let res = LLVMBuildCall2(
+                llbuilder,
+                NonNull::from(write_fun_ty).as_ptr(),
+                NonNull::from(write_fun).as_ptr(),
+                args.as_mut_ptr(),
+                4,
+                UNNAMED,
+            );
+            if target == PrintTarget::String {
+                // the string is returned instead of void
+                llvm_sys::core::LLVMBuildRet(llbuilder, res);
+                llvm_sys::core::LLVMDisposeBuilder(llbuilder);
+                return (fun, fun_ty);
+            }
+        } else {
+            let fun_ptr = cx.get_declared_value("osdi_log").expect("symbol osdi_log is missing");
+            let fun_ty = cx.ty_func(&[cx.ty_ptr(), cx.ty_ptr(), cx.ty_int()], cx.ty_void());
+            let fun = LLVMBuildLoad2(
+                llbuilder,
+                NonNull::from(cx.ty_ptr()).as_ptr(),
+                NonNull::from(fun_ptr).as_ptr(),
+                UNNAMED,
+            );
+
+            // Fix for LLVMBuildCall2
+            let mut args = [handle, msg, flags];
+            LLVMBuildCall2(
+                llbuilder,
+                NonNull::from(fun_ty).as_ptr(),
+                fun,
+                args.as_mut_ptr(),
+                3,
+                UNNAMED,
+            );
+        }
         llvm_sys::core::LLVMBuildRetVoid(llbuilder);
         llvm_sys::core::LLVMDisposeBuilder(llbuilder);
     }
@@ -1,13 +1,16 @@
 use core::ptr::NonNull;
 
-use hir_lower::{CallBackKind, CurrentKind, LimitState, ParamKind};
+use hir_lower::{CallBackKind, CurrentKind, Delay, ParamKind};
 use llvm_sys::core::{
-    LLVMAppendBasicBlockInContext, LLVMBuildAlloca, LLVMBuildAnd, LLVMBuildBr, LLVMBuildCall2,
-    LLVMBuildCondBr, LLVMBuildICmp, LLVMBuildInBoundsGEP2, LLVMBuildIntCast2, LLVMBuildLoad2,
-    LLVMBuildOr, LLVMBuildRet, LLVMBuildStore, LLVMCreateBuilderInContext, LLVMDisposeBuilder,
-    LLVMGetParam, LLVMPositionBuilderAtEnd,
+    LLVMAddIncoming, LLVMAppendBasicBlockInContext, LLVMBuildAdd, LLVMBuildAlloca, LLVMBuildAnd,
+    LLVMBuildBr, LLVMBuildCall2, LLVMBuildCondBr, LLVMBuildICmp, LLVMBuildInBoundsGEP2,
+    LLVMBuildIntCast2, LLVMBuildLShr, LLVMBuildLoad2, LLVMBuildMul, LLVMBuildOr, LLVMBuildPhi,
+    LLVMBuildRet, LLVMBuildSelect, LLVMBuildShl, LLVMBuildStore, LLVMBuildStructGEP2, LLVMBuildSub,
+    LLVMConstInt, LLVMCreateBuilderInContext, LLVMDisposeBuilder, LLVMGetInsertBlock, LLVMGetParam,
+    LLVMPositionBuilderAtEnd, LLVMTypeOf,
 };
-use llvm_sys::LLVMIntPredicate::{LLVMIntNE, LLVMIntULT};
+use llvm_sys::prelude::{LLVMBuilderRef, LLVMContextRef, LLVMValueRef};
+use llvm_sys::LLVMIntPredicate::{LLVMIntEQ, LLVMIntNE, LLVMIntULT};
 use log::info;
 use mir_llvm::{
     Builder, BuilderVal, BuiltCallbackFun, CallbackFun, InlineCallbackBuilder, MemLoc, UNNAMED,
@@ -16,15 +19,18 @@ use sim_back::SimUnknownKind;
 use typed_index_collections::TiVec;
 
 use crate::bitfield::{is_flag_set, is_flag_set_mem, is_flag_unset};
-use crate::compilation_unit::{general_callbacks, OsdiCompilationUnit};
+use crate::compilation_unit::{
+    general_callbacks, random_callback, string_callback, table_callback, OsdiCompilationUnit,
+};
 use crate::inst_data::OsdiInstanceParam;
 use crate::metadata::osdi_0_4::{
-    ANALYSIS_IC, CALC_NOISE, CALC_OP, CALC_REACT_JACOBIAN, CALC_REACT_LIM_RHS, CALC_REACT_RESIDUAL,
-    CALC_RESIST_JACOBIAN, CALC_RESIST_LIM_RHS, CALC_RESIST_RESIDUAL, ENABLE_LIM, EVAL_RET_FLAG_LIM,
-    INIT_LIM,
+    ANALYSIS_IC, CALC_AC_STIM, CALC_HIGHER_ORDER, CALC_NOISE, CALC_OP, CALC_PARAM_SENSITIVITY,
+    CALC_REACT_JACOBIAN, CALC_REACT_LIM_RHS, CALC_REACT_RESIDUAL, CALC_RESIST_JACOBIAN,
+    CALC_RESIST_LIM_RHS, CALC_RESIST_RESIDUAL, ENABLE_LIM, EVAL_RET_DISCONTINUITY_ORDER_SHIFT,
+    EVAL_RET_FLAG_DISCONTINUITY, EVAL_RET_FLAG_LIM, INIT_LIM,
 };
 use crate::metadata::OsdiLimFunction;
-use crate::OsdiLimId;
+use crate::{lltype, OsdiLimId};
 
 /*
 // Inline callback example
@@ -94,9 +100,25 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
         cx.declare_ext_fn(name, fun_ty)
     }
 
-    pub fn eval(&self) -> &'ll llvm_sys::LLVMValue {
-        let llfunc = self.eval_prototype();
-        let OsdiCompilationUnit { inst_data, model_data, cx, module, .. } = self;
+    /// The kernel shared by `eval` and `eval_batch`. Compared to `eval` it has two additional
+    /// arguments: the node voltages of the instance are read from `voltages[i * stride]`
+    /// (where `i` is the index of the unknown) instead of the solution vector of the simulator.
+    /// This allows `eval_batch` to gather the voltages of many instances into a structure of
+    /// arrays.
+    pub fn eval_kernel_prototype(&self) -> &'ll llvm_sys::LLVMValue {
+        let name = &format!("eval_kernel_{}", &self.module.sym);
+        let cx = &self.cx;
+
+        let ty_ptr = cx.ty_ptr();
+
+        let fun_ty =
+            cx.ty_func(&[ty_ptr, ty_ptr, ty_ptr, ty_ptr, ty_ptr, cx.ty_int()], cx.ty_int());
+        cx.declare_int_c_fn(name, fun_ty)
+    }
+
+    pub fn eval_kernel(&self) -> &'ll llvm_sys::LLVMValue {
+        let llfunc = self.eval_kernel_prototype();
+        let OsdiCompilationUnit { inst_data, model_data, cx, module, db, .. } = self;
 
         let func = module.eval;
         let intern = module.intern;
@@ -107,6 +129,8 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
         let instance = unsafe { &*llvm_sys::core::LLVMGetParam(NonNull::from(llfunc).as_ptr(), 1) };
         let model = unsafe { &*llvm_sys::core::LLVMGetParam(NonNull::from(llfunc).as_ptr(), 2) };
         let sim_info = unsafe { &*llvm_sys::core::LLVMGetParam(NonNull::from(llfunc).as_ptr(), 3) };
+        let voltages = unsafe { &*llvm_sys::core::LLVMGetParam(NonNull::from(llfunc).as_ptr(), 4) };
+        let stride = unsafe { &*llvm_sys::core::LLVMGetParam(NonNull::from(llfunc).as_ptr(), 5) };
         let sim_info_ty = self.tys.osdi_sim_info;
 
         // let simparam_ty = self.tys.osdi_sim_paras;
@@ -114,11 +138,6 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
 
         const ABSTIME_OFFSET: u32 = 1;
 
-        let prev_result = unsafe {
-            let ptr = builder.struct_gep(sim_info_ty, sim_info, 2);
-            builder.load(cx.ty_ptr(), ptr)
-        };
-
         let prev_state = unsafe {
             let ptr = builder.struct_gep(sim_info_ty, sim_info, 3);
             builder.load(cx.ty_ptr(), ptr)
@@ -140,7 +159,28 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
             .unknowns
             .indices()
             .map(|node| unsafe {
-                inst_data.read_node_voltage(cx, node, instance, prev_result, builder.llbuilder)
+                let idx = cx.const_unsigned_int(node.into());
+                let idx = LLVMBuildMul(
+                    builder.llbuilder,
+                    NonNull::from(idx).as_ptr(),
+                    NonNull::from(stride).as_ptr(),
+                    UNNAMED,
+                );
+                let mut gep_indices = [idx];
+                let ptr = LLVMBuildInBoundsGEP2(
+                    builder.llbuilder,
+                    NonNull::from(cx.ty_double()).as_ptr(),
+                    NonNull::from(voltages).as_ptr(),
+                    gep_indices.as_mut_ptr(),
+                    1,
+                    UNNAMED,
+                );
+                &*LLVMBuildLoad2(
+                    builder.llbuilder,
+                    NonNull::from(cx.ty_double()).as_ptr(),
+                    ptr,
+                    UNNAMED,
+                )
             })
             .collect();
 
@@ -153,8 +193,16 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
             }
         };
 
-        let state_idx: TiVec<LimitState, _> = (0..intern.lim_state.len())
-            .map(|i| unsafe { inst_data.read_state_idx(cx, i.into(), instance, builder.llbuilder) })
+        let state_idx: Vec<_> = (0..intern.num_states())
+            .map(|i| unsafe { inst_data.read_state_idx(cx, i, instance, builder.llbuilder) })
+            .collect();
+
+        let delay_history: TiVec<Delay, _> = intern
+            .delays
+            .keys()
+            .map(|delay| unsafe {
+                inst_data.read_delay_history(cx, delay, instance, builder.llbuilder)
+            })
             .collect();
 
         let true_ = cx.const_bool(true);
@@ -175,6 +223,14 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                                 .unwrap_or_else(|| model_data.param_loc(cx, param, model).unwrap())
                                 .into()
                         }
+                        ParamKind::ParamElement(param, pos) => {
+                            let elem_ty = lltype(param.ty(db).base_type(), cx);
+                            return inst_data
+                                .param_loc(cx, OsdiInstanceParam::User(param), instance)
+                                .unwrap_or_else(|| model_data.param_loc(cx, param, model).unwrap())
+                                .array_elem(pos, elem_ty, cx)
+                                .into();
+                        }
                         ParamKind::Voltage { hi, lo } => {
                             let hi = get_prev_solve(SimUnknownKind::KirchoffLaw(hi));
                             if let Some(lo) = lo {
@@ -247,7 +303,10 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                                 builder.llbuilder,
                             )
                             .unwrap(),
-                        ParamKind::HiddenState(_) => unreachable!(), // TODO  hidden state
+                        // replaced by the initial value (see `HirInterner::insert_var_init`)
+                        ParamKind::HiddenState(_) | ParamKind::HiddenStateElement(..) => {
+                            unreachable!("hidden state is replaced by the initial value")
+                        }
                         ParamKind::EnableIntegration => {
                             let flags = flags.read(builder.llbuilder);
                             let is_not_dc =
@@ -262,8 +321,12 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                             )
                         }
                         ParamKind::PrevState(state) => {
-                            let idx =
-                                inst_data.read_state_idx(cx, state, instance, builder.llbuilder);
+                            let idx = inst_data.read_state_idx(
+                                cx,
+                                state.into(),
+                                instance,
+                                builder.llbuilder,
+                            );
                             return MemLoc {
                                 ptr: prev_state,
                                 ptr_ty: cx.ty_double(),
@@ -273,8 +336,12 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                             .into();
                         }
                         ParamKind::NewState(state) => {
-                            let idx =
-                                inst_data.read_state_idx(cx, state, instance, builder.llbuilder);
+                            let idx = inst_data.read_state_idx(
+                                cx,
+                                state.into(),
+                                instance,
+                                builder.llbuilder,
+                            );
 
                             return MemLoc {
                                 ptr: next_state,
@@ -284,6 +351,26 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                             }
                             .into();
                         }
+                        ParamKind::PrevTranState(state) => {
+                            let idx = inst_data.read_state_idx(
+                                cx,
+                                intern.tran_state_slot(state),
+                                instance,
+                                builder.llbuilder,
+                            );
+                            return MemLoc {
+                                ptr: prev_state,
+                                ptr_ty: cx.ty_double(),
+                                ty: cx.ty_double(),
+                                indices: vec![idx].into_boxed_slice(),
+                            }
+                            .into();
+                        }
+                        ParamKind::PrevPersistentState(state) => {
+                            return inst_data
+                                .persistent_state_loc(cx, state, false, instance)
+                                .into();
+                        }
                         ParamKind::EnableLim => {
                             is_flag_set_mem(cx, ENABLE_LIM, &flags, builder.llbuilder)
                         }
@@ -322,10 +409,58 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                     CallbackFun::Prebuilt(BuiltCallbackFun {
                         fun_ty,
                         fun,
-                        state: Box::new([sim_info, state_idx[state]]),
+                        state: Box::new([sim_info, state_idx[usize::from(state)]]),
+                        num_state: 0,
+                    })
+                }
+                CallBackKind::StoreTranState(state) => {
+                    let fun = builder
+                        .cx
+                        .get_func_by_name("store_lim")
+                        .expect("stdlib function store_lim is missing");
+                    let fun_ty =
+                        cx.ty_func(&[cx.ty_ptr(), cx.ty_int(), cx.ty_double()], cx.ty_double());
+                    let idx = state_idx[intern.tran_state_slot(state)];
+                    CallbackFun::Prebuilt(BuiltCallbackFun {
+                        fun_ty,
+                        fun,
+                        state: Box::new([sim_info, idx]),
                         num_state: 0,
                     })
                 }
+                CallBackKind::StorePersistentState(state) => {
+                    let fun = builder
+                        .cx
+                        .get_func_by_name("store_persistent")
+                        .expect("stdlib function store_persistent is missing");
+                    let fun_ty = cx.ty_func(&[cx.ty_ptr(), cx.ty_double()], cx.ty_double());
+                    let dst = unsafe {
+                        inst_data.persistent_state_ptr(cx, state, true, instance, builder.llbuilder)
+                    };
+                    CallbackFun::Prebuilt(BuiltCallbackFun {
+                        fun_ty,
+                        fun,
+                        state: Box::new([dst]),
+                        num_state: 0,
+                    })
+                }
+                CallBackKind::Random { .. } | CallBackKind::RandomStream { .. } => {
+                    let state = unsafe { inst_data.random_state_ptr(instance, builder.llbuilder) };
+                    random_callback(cx, kind, state.unwrap()).unwrap()
+                }
+                CallBackKind::TableModel { table, .. } => {
+                    let table = unsafe {
+                        model_data.read_table(cx, inst_data, table, model, builder.llbuilder)
+                    };
+                    table_callback(cx, kind, handle, table).unwrap()
+                }
+                CallBackKind::FormatString { .. }
+                | CallBackKind::ScanValue { slot: Some(_), .. } => {
+                    let slot = kind.string_slot().unwrap();
+                    let slot =
+                        unsafe { inst_data.string_slot_ptr(cx, slot, instance, builder.llbuilder) };
+                    string_callback(cx, kind, handle, slot).unwrap()
+                }
                 CallBackKind::LimDiscontinuity => {
                     let fun = builder
                         .cx
@@ -352,6 +487,25 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                         num_state: 0,
                     })
                 }
+                CallBackKind::AbsDelay(delay) | CallBackKind::AbsDelayWeight(delay) => {
+                    let name = if matches!(kind, CallBackKind::AbsDelay(_)) {
+                        "absdelay_value"
+                    } else {
+                        "absdelay_weight"
+                    };
+                    let fun = builder
+                        .cx
+                        .get_func_by_name(name)
+                        .unwrap_or_else(|| panic!("stdlib function {name} is missing"));
+                    let fun_ty =
+                        cx.ty_func(&[cx.ty_ptr(), cx.ty_ptr(), cx.ty_double()], cx.ty_double());
+                    CallbackFun::Prebuilt(BuiltCallbackFun {
+                        fun_ty,
+                        fun,
+                        state: Box::new([sim_info, delay_history[delay]]),
+                        num_state: 0,
+                    })
+                }
                 _ => continue,
             };
             builder.callbacks[func] = Some(cb);
@@ -428,8 +582,45 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                 }
             };
             Self::build_store_results(&mut builder, llfunc, &flags, CALC_NOISE, &store_noise);
+            let store_ac_stim = |builder: &mut Builder<'_, '_, 'll>| {
+                for excitation in &inst_data.ac_excitations {
+                    for slot in excitation.slots() {
+                        inst_data.store_eval_output_slot(slot, instance, builder)
+                    }
+                }
+            };
+            Self::build_store_results(&mut builder, llfunc, &flags, CALC_AC_STIM, &store_ac_stim);
+            let store_param_sensitivities = |builder: &mut Builder<'_, '_, 'll>| {
+                for sensitivity in &inst_data.param_sensitivities {
+                    for eval_output in sensitivity.eval_outputs() {
+                        inst_data.store_eval_output(eval_output, instance, builder)
+                    }
+                }
+            };
+            Self::build_store_results(
+                &mut builder,
+                llfunc,
+                &flags,
+                CALC_PARAM_SENSITIVITY,
+                &store_param_sensitivities,
+            );
+            let store_higher_order = |builder: &mut Builder<'_, '_, 'll>| {
+                for entry in &inst_data.higher_order {
+                    for eval_output in entry.eval_outputs() {
+                        inst_data.store_eval_output(eval_output, instance, builder)
+                    }
+                }
+            };
+            Self::build_store_results(
+                &mut builder,
+                llfunc,
+                &flags,
+                CALC_HIGHER_ORDER,
+                &store_higher_order,
+            );
 
             inst_data.store_bound_step(instance, &builder);
+            inst_data.store_delays(instance, &builder);
 
             builder.ret();
         }
@@ -437,6 +628,294 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
         llfunc
     }
 
+    /// Generates `eval` which copies the node voltages of the instance from the solution vector
+    /// of the simulator and evaluates the instance with the [kernel](Self::eval_kernel).
+    /// The kernel is usually inlined, so the copy is optimized away.
+    pub fn eval(&self, kernel: &'ll llvm_sys::LLVMValue) -> &'ll llvm_sys::LLVMValue {
+        let cx = &self.cx;
+        let llfunc = self.eval_prototype();
+        let ty_double = NonNull::from(cx.ty_double()).as_ptr();
+        let int = |val: u32| NonNull::from(cx.const_unsigned_int(val)).as_ptr();
+        let num_unknowns = self.module.dae_system.unknowns.len() as u32;
+
+        unsafe {
+            let llfunc_ptr = NonNull::from(llfunc).as_ptr();
+            let llcx = NonNull::from(cx.llcx).as_ptr();
+            let entry_bb = LLVMAppendBasicBlockInContext(llcx, llfunc_ptr, UNNAMED);
+            let llbuilder = LLVMCreateBuilderInContext(llcx);
+            LLVMPositionBuilderAtEnd(llbuilder, entry_bb);
+
+            let handle = LLVMGetParam(llfunc_ptr, 0);
+            let instance = LLVMGetParam(llfunc_ptr, 1);
+            let model = LLVMGetParam(llfunc_ptr, 2);
+            let sim_info = LLVMGetParam(llfunc_ptr, 3);
+
+            let voltages_ty = cx.ty_array(cx.ty_double(), num_unknowns.max(1));
+            let voltages = LLVMBuildAlloca(llbuilder, NonNull::from(voltages_ty).as_ptr(), UNNAMED);
+            let prev_result = self.load_prev_result(llbuilder, sim_info);
+            for node in self.module.dae_system.unknowns.indices() {
+                let val = self.inst_data.read_node_voltage(
+                    cx,
+                    node,
+                    &*instance,
+                    &*prev_result,
+                    &*llbuilder,
+                );
+                let mut gep_indices = [int(node.into())];
+                let dst = LLVMBuildInBoundsGEP2(
+                    llbuilder,
+                    ty_double,
+                    voltages,
+                    gep_indices.as_mut_ptr(),
+                    1,
+                    UNNAMED,
+                );
+                LLVMBuildStore(llbuilder, NonNull::from(val).as_ptr(), dst);
+            }
+
+            let mut args = [handle, instance, model, sim_info, voltages, int(1)];
+            let ret = self.call_kernel(llbuilder, kernel, &mut args);
+            LLVMBuildRet(llbuilder, ret);
+            LLVMDisposeBuilder(llbuilder);
+        }
+
+        llfunc
+    }
+
+    pub fn eval_batch_prototype(&self) -> &'ll llvm_sys::LLVMValue {
+        let name = &format!("eval_batch_{}", &self.module.sym);
+        let cx = &self.cx;
+
+        let ty_ptr = cx.ty_ptr();
+
+        let fun_ty =
+            cx.ty_func(&[ty_ptr, ty_ptr, ty_ptr, ty_ptr, cx.ty_int(), ty_ptr], cx.ty_int());
+        cx.declare_ext_fn(name, fun_ty)
+    }
+
+    /// Generates `eval_batch` which evaluates the `n` instances in `inst_ptrs` that all belong
+    /// to the same model. The instances are processed in chunks of [`BATCH_LANES`]: first the
+    /// node voltages of the chunk are gathered into a structure of arrays
+    /// (`voltages[unknown * BATCH_LANES + lane]`), afterwards the [kernel](Self::eval_kernel)
+    /// is called for every lane of the chunk. The kernel lives in the same LLVM module, so the
+    /// optimizer can inline it, hoist the model and `sim_info` accesses out of the loop and
+    /// vectorize the loop over the lanes.
+    ///
+    /// The flags returned by each instance are written to `flags[i]` (unless `flags` is `NULL`).
+    /// The returned flags are the union of the flags of all instances and the discontinuity
+    /// order is the lowest order reported by any instance.
+    pub fn eval_batch(&self, kernel: &'ll llvm_sys::LLVMValue) -> &'ll llvm_sys::LLVMValue {
+        let cx = &self.cx;
+        let llfunc = self.eval_batch_prototype();
+        let ty_int = NonNull::from(cx.ty_int()).as_ptr();
+        let ty_ptr = NonNull::from(cx.ty_ptr()).as_ptr();
+        let ty_double = NonNull::from(cx.ty_double()).as_ptr();
+        let int = |val: u32| NonNull::from(cx.const_unsigned_int(val)).as_ptr();
+        // larger than any discontinuity order that fits into the return flags
+        let no_order = int(u16::MAX as u32);
+        let ret_flags_mask = int((1 << EVAL_RET_DISCONTINUITY_ORDER_SHIFT) - 1);
+        let num_unknowns = self.module.dae_system.unknowns.len() as u32;
+
+        unsafe {
+            let llfunc_ptr = NonNull::from(llfunc).as_ptr();
+            let llcx = NonNull::from(cx.llcx).as_ptr();
+            let entry_bb = LLVMAppendBasicBlockInContext(llcx, llfunc_ptr, UNNAMED);
+            let llbuilder = LLVMCreateBuilderInContext(llcx);
+            LLVMPositionBuilderAtEnd(llbuilder, entry_bb);
+
+            let handle = LLVMGetParam(llfunc_ptr, 0);
+            let inst_ptrs = LLVMGetParam(llfunc_ptr, 1);
+            let model = LLVMGetParam(llfunc_ptr, 2);
+            let sim_info = LLVMGetParam(llfunc_ptr, 3);
+            let n = LLVMGetParam(llfunc_ptr, 4);
+            let inst_flags_ptr = LLVMGetParam(llfunc_ptr, 5);
+
+            let voltages_ty = cx.ty_array(cx.ty_double(), num_unknowns.max(1) * BATCH_LANES);
+            let voltages = LLVMBuildAlloca(llbuilder, NonNull::from(voltages_ty).as_ptr(), UNNAMED);
+            let flags = LLVMBuildAlloca(llbuilder, ty_int, UNNAMED);
+            LLVMBuildStore(llbuilder, int(0), flags);
+            let order = LLVMBuildAlloca(llbuilder, ty_int, UNNAMED);
+            LLVMBuildStore(llbuilder, no_order, order);
+            // the flags of the instances are discarded if no array was provided
+            let discarded_flags = LLVMBuildAlloca(llbuilder, ty_int, UNNAMED);
+            let no_inst_flags = LLVMBuildICmp(
+                llbuilder,
+                LLVMIntEQ,
+                inst_flags_ptr,
+                NonNull::from(cx.const_null_ptr()).as_ptr(),
+                UNNAMED,
+            );
+            let prev_result = self.load_prev_result(llbuilder, sim_info);
+
+            let load_inst = |i| {
+                let mut gep_indices = [i];
+                let ptr = LLVMBuildInBoundsGEP2(
+                    llbuilder,
+                    ty_ptr,
+                    inst_ptrs,
+                    gep_indices.as_mut_ptr(),
+                    1,
+                    UNNAMED,
+                );
+                LLVMBuildLoad2(llbuilder, ty_ptr, ptr, UNNAMED)
+            };
+
+            build_loop(llcx, llfunc_ptr, llbuilder, n, BATCH_LANES, |chunk| {
+                let remaining = LLVMBuildSub(llbuilder, n, chunk, UNNAMED);
+                let is_partial =
+                    LLVMBuildICmp(llbuilder, LLVMIntULT, remaining, int(BATCH_LANES), UNNAMED);
+                let lanes =
+                    LLVMBuildSelect(llbuilder, is_partial, remaining, int(BATCH_LANES), UNNAMED);
+
+                // gather the node voltages of the chunk
+                build_loop(llcx, llfunc_ptr, llbuilder, lanes, 1, |lane| {
+                    let i = LLVMBuildAdd(llbuilder, chunk, lane, UNNAMED);
+                    let inst = load_inst(i);
+                    for node in self.module.dae_system.unknowns.indices() {
+                        let val = self.inst_data.read_node_voltage(
+                            cx,
+                            node,
+                            &*inst,
+                            &*prev_result,
+                            &*llbuilder,
+                        );
+                        let idx = u32::from(node) * BATCH_LANES;
+                        let idx = LLVMBuildAdd(llbuilder, int(idx), lane, UNNAMED);
+                        let mut gep_indices = [idx];
+                        let dst = LLVMBuildInBoundsGEP2(
+                            llbuilder,
+                            ty_double,
+                            voltages,
+                            gep_indices.as_mut_ptr(),
+                            1,
+                            UNNAMED,
+                        );
+                        LLVMBuildStore(llbuilder, NonNull::from(val).as_ptr(), dst);
+                    }
+                });
+
+                // evaluate the chunk
+                build_loop(llcx, llfunc_ptr, llbuilder, lanes, 1, |lane| {
+                    let i = LLVMBuildAdd(llbuilder, chunk, lane, UNNAMED);
+                    let inst = load_inst(i);
+                    let mut gep_indices = [lane];
+                    let lane_voltages = LLVMBuildInBoundsGEP2(
+                        llbuilder,
+                        ty_double,
+                        voltages,
+                        gep_indices.as_mut_ptr(),
+                        1,
+                        UNNAMED,
+                    );
+                    let mut args = [handle, inst, model, sim_info, lane_voltages, int(BATCH_LANES)];
+                    let inst_ret = self.call_kernel(llbuilder, kernel, &mut args);
+
+                    let mut gep_indices = [i];
+                    let inst_flags = LLVMBuildInBoundsGEP2(
+                        llbuilder,
+                        ty_int,
+                        inst_flags_ptr,
+                        gep_indices.as_mut_ptr(),
+                        1,
+                        UNNAMED,
+                    );
+                    let inst_flags = LLVMBuildSelect(
+                        llbuilder,
+                        no_inst_flags,
+                        discarded_flags,
+                        inst_flags,
+                        UNNAMED,
+                    );
+                    LLVMBuildStore(llbuilder, inst_ret, inst_flags);
+
+                    let inst_flags = LLVMBuildAnd(llbuilder, inst_ret, ret_flags_mask, UNNAMED);
+                    let prev_flags = LLVMBuildLoad2(llbuilder, ty_int, flags, UNNAMED);
+                    let next_flags = LLVMBuildOr(llbuilder, prev_flags, inst_flags, UNNAMED);
+                    LLVMBuildStore(llbuilder, next_flags, flags);
+
+                    let inst_discontinuity = LLVMBuildAnd(
+                        llbuilder,
+                        inst_ret,
+                        int(EVAL_RET_FLAG_DISCONTINUITY),
+                        UNNAMED,
+                    );
+                    let inst_discontinuity =
+                        LLVMBuildICmp(llbuilder, LLVMIntNE, inst_discontinuity, int(0), UNNAMED);
+                    let inst_order = LLVMBuildLShr(
+                        llbuilder,
+                        inst_ret,
+                        int(EVAL_RET_DISCONTINUITY_ORDER_SHIFT),
+                        UNNAMED,
+                    );
+                    let inst_order = LLVMBuildSelect(
+                        llbuilder,
+                        inst_discontinuity,
+                        inst_order,
+                        no_order,
+                        UNNAMED,
+                    );
+                    let prev_order = LLVMBuildLoad2(llbuilder, ty_int, order, UNNAMED);
+                    let is_lower =
+                        LLVMBuildICmp(llbuilder, LLVMIntULT, inst_order, prev_order, UNNAMED);
+                    let next_order =
+                        LLVMBuildSelect(llbuilder, is_lower, inst_order, prev_order, UNNAMED);
+                    LLVMBuildStore(llbuilder, next_order, order);
+                });
+            });
+
+            let flags = LLVMBuildLoad2(llbuilder, ty_int, flags, UNNAMED);
+            let order = LLVMBuildLoad2(llbuilder, ty_int, order, UNNAMED);
+            let discontinuity =
+                LLVMBuildAnd(llbuilder, flags, int(EVAL_RET_FLAG_DISCONTINUITY), UNNAMED);
+            let discontinuity = LLVMBuildICmp(llbuilder, LLVMIntNE, discontinuity, int(0), UNNAMED);
+            let order =
+                LLVMBuildShl(llbuilder, order, int(EVAL_RET_DISCONTINUITY_ORDER_SHIFT), UNNAMED);
+            let order = LLVMBuildSelect(llbuilder, discontinuity, order, int(0), UNNAMED);
+            let ret = LLVMBuildOr(llbuilder, flags, order, UNNAMED);
+            LLVMBuildRet(llbuilder, ret);
+
+            LLVMDisposeBuilder(llbuilder);
+        }
+
+        llfunc
+    }
+
+    /// Loads the pointer to the solution vector (`prev_solve`) from `sim_info`
+    unsafe fn load_prev_result(
+        &self,
+        llbuilder: LLVMBuilderRef,
+        sim_info: LLVMValueRef,
+    ) -> LLVMValueRef {
+        let ptr = LLVMBuildStructGEP2(
+            llbuilder,
+            NonNull::from(self.tys.osdi_sim_info).as_ptr(),
+            sim_info,
+            2,
+            UNNAMED,
+        );
+        LLVMBuildLoad2(llbuilder, NonNull::from(self.cx.ty_ptr()).as_ptr(), ptr, UNNAMED)
+    }
+
+    unsafe fn call_kernel(
+        &self,
+        llbuilder: LLVMBuilderRef,
+        kernel: &'ll llvm_sys::LLVMValue,
+        args: &mut [LLVMValueRef; 6],
+    ) -> LLVMValueRef {
+        let cx = &self.cx;
+        let ty_ptr = cx.ty_ptr();
+        let kernel_ty =
+            cx.ty_func(&[ty_ptr, ty_ptr, ty_ptr, ty_ptr, ty_ptr, cx.ty_int()], cx.ty_int());
+        LLVMBuildCall2(
+            llbuilder,
+            NonNull::from(kernel_ty).as_ptr(),
+            NonNull::from(kernel).as_ptr(),
+            args.as_mut_ptr(),
+            args.len() as u32,
+            UNNAMED,
+        )
+    }
+
     unsafe fn build_store_results(
         builder: &mut Builder<'_, '_, 'll>,
         llfunc: &'ll llvm_sys::LLVMValue,
@@ -611,3 +1090,40 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
         }
     }
 }
+
+/// The number of instances whose node voltages `eval_batch` gathers at once
+const BATCH_LANES: u32 = 32;
+
+/// Builds the loop `for i in (0..end).step_by(step) { body(i) }` at the current position of
+/// `llbuilder` and positions the builder after the loop.
+unsafe fn build_loop(
+    llcx: LLVMContextRef,
+    llfunc: LLVMValueRef,
+    llbuilder: LLVMBuilderRef,
+    end: LLVMValueRef,
+    step: u32,
+    body: impl FnOnce(LLVMValueRef),
+) {
+    let ty = LLVMTypeOf(end);
+    let pre_bb = LLVMGetInsertBlock(llbuilder);
+    let header_bb = LLVMAppendBasicBlockInContext(llcx, llfunc, UNNAMED);
+    let body_bb = LLVMAppendBasicBlockInContext(llcx, llfunc, UNNAMED);
+    let exit_bb = LLVMAppendBasicBlockInContext(llcx, llfunc, UNNAMED);
+    LLVMBuildBr(llbuilder, header_bb);
+
+    LLVMPositionBuilderAtEnd(llbuilder, header_bb);
+    let i = LLVMBuildPhi(llbuilder, ty, UNNAMED);
+    let in_range = LLVMBuildICmp(llbuilder, LLVMIntULT, i, end, UNNAMED);
+    LLVMBuildCondBr(llbuilder, in_range, body_bb, exit_bb);
+
+    LLVMPositionBuilderAtEnd(llbuilder, body_bb);
+    body(i);
+    let next_i = LLVMBuildAdd(llbuilder, i, LLVMConstInt(ty, step.into(), 0), UNNAMED);
+    let latch_bb = LLVMGetInsertBlock(llbuilder);
+    LLVMBuildBr(llbuilder, header_bb);
+
+    let mut incoming_blocks = [pre_bb, latch_bb];
+    let mut incoming_values = [LLVMConstInt(ty, 0, 0), next_i];
+    LLVMAddIncoming(i, incoming_values.as_mut_ptr(), incoming_blocks.as_mut_ptr(), 2);
+    LLVMPositionBuilderAtEnd(llbuilder, exit_bb);
+}
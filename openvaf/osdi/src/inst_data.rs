@@ -3,7 +3,7 @@ use core::ptr::NonNull;
 use std::hash::BuildHasherDefault;
 
 use hir::{CompilationDB, ParamSysFun, Parameter, Variable};
-use hir_lower::{HirInterner, LimitState, ParamKind, PlaceKind};
+use hir_lower::{CallBackKind, Delay, HirInterner, ParamKind, PersistentState, PlaceKind};
 use indexmap::IndexMap;
 use llvm_sys::core::{
     LLVMBuildFAdd,
@@ -15,6 +15,7 @@ use llvm_sys::core::{
     LLVMBuildStore,
     LLVMBuildStructGEP2,
     LLVMConstInt,
+    LLVMGetElementType,
     //    LLVMSetFastMath
 };
 use llvm_sys::target::{LLVMOffsetOfElement, LLVMTargetDataRef};
@@ -200,6 +201,112 @@ impl NoiseSource {
     }
 }
 
+/// The eval output slots of the excitation (real and imaginary part) of each unknown during
+/// one of the AC analyses that `ac_stim` sources are assigned to
+#[derive(Debug)]
+pub struct AcExcitation {
+    pub real: TiVec<SimUnknown, PackedOption<EvalOutputSlot>>,
+    pub imag: TiVec<SimUnknown, PackedOption<EvalOutputSlot>>,
+}
+
+impl AcExcitation {
+    pub fn new<'ll>(
+        excitation: &dae::AcExcitation,
+        slots: &mut TiMap<EvalOutputSlot, mir::Value, &'ll llvm_sys::LLVMType>,
+        ty_real: &'ll llvm_sys::LLVMType,
+        func: &Function,
+    ) -> AcExcitation {
+        let mut get_slot = |mut val| {
+            val = strip_optbarrier(func, val);
+            if val == F_ZERO {
+                None.into()
+            } else {
+                Some(slots.insert_full(val, ty_real).0).into()
+            }
+        };
+        AcExcitation {
+            real: excitation.real.iter().map(|&val| get_slot(val)).collect(),
+            imag: excitation.imag.iter().map(|&val| get_slot(val)).collect(),
+        }
+    }
+
+    pub fn slots(&self) -> impl Iterator<Item = EvalOutputSlot> + '_ {
+        self.real.iter().chain(&self.imag).filter_map(|slot| slot.expand())
+    }
+}
+
+/// The eval outputs of the derivatives by a parameter marked for sensitivity analysis
+#[derive(Debug)]
+pub struct ParamSensitivity {
+    pub resist: TiVec<SimUnknown, Option<EvalOutput>>,
+    pub react: TiVec<SimUnknown, Option<EvalOutput>>,
+    pub op_vars: Vec<Option<EvalOutput>>,
+}
+
+impl ParamSensitivity {
+    pub fn new<'ll>(
+        sensitivity: &dae::ParamSensitivity,
+        module: &OsdiModule<'_>,
+        slots: &mut TiMap<EvalOutputSlot, mir::Value, &'ll llvm_sys::LLVMType>,
+        ty_real: &'ll llvm_sys::LLVMType,
+    ) -> ParamSensitivity {
+        let mut get_output = |mut val| {
+            val = strip_optbarrier(module.eval, val);
+            if val == F_ZERO {
+                None
+            } else {
+                Some(EvalOutput::new(module, val, slots, false, ty_real))
+            }
+        };
+        ParamSensitivity {
+            resist: sensitivity.resist.iter().map(|&val| get_output(val)).collect(),
+            react: sensitivity.react.iter().map(|&val| get_output(val)).collect(),
+            op_vars: sensitivity.op_vars.iter().map(|&val| get_output(val)).collect(),
+        }
+    }
+
+    pub fn eval_outputs(&self) -> impl Iterator<Item = EvalOutput> + '_ {
+        self.resist.iter().chain(&self.react).chain(&self.op_vars).filter_map(|output| *output)
+    }
+}
+
+/// The eval outputs of a Taylor coefficient of order two or three of a residual
+#[derive(Clone, Copy, Debug)]
+pub struct HigherOrderEntry {
+    pub resist: Option<EvalOutput>,
+    pub react: Option<EvalOutput>,
+}
+
+impl HigherOrderEntry {
+    pub fn new<'ll>(
+        entry: &dae::HigherOrderEntry,
+        module: &OsdiModule<'_>,
+        slots: &mut TiMap<EvalOutputSlot, mir::Value, &'ll llvm_sys::LLVMType>,
+        ty_real: &'ll llvm_sys::LLVMType,
+    ) -> HigherOrderEntry {
+        let mut get_output = |mut val| {
+            val = strip_optbarrier(module.eval, val);
+            if val == F_ZERO {
+                None
+            } else {
+                Some(EvalOutput::new(module, val, slots, false, ty_real))
+            }
+        };
+        HigherOrderEntry { resist: get_output(entry.resist), react: get_output(entry.react) }
+    }
+
+    pub fn eval_outputs(&self) -> impl Iterator<Item = EvalOutput> {
+        self.resist.into_iter().chain(self.react)
+    }
+}
+
+/// The eval outputs that the simulator requires to record the history of an `absdelay`
+#[derive(Clone, Copy, Debug)]
+pub struct DelayLine {
+    pub input: EvalOutputSlot,
+    pub delay: EvalOutputSlot,
+}
+
 pub struct OsdiInstanceData<'ll> {
     /// llvm type for the instance data struct
     pub ty: &'ll llvm_sys::LLVMType,
@@ -219,9 +326,36 @@ pub struct OsdiInstanceData<'ll> {
 
     pub residual: TiVec<SimUnknown, Residual>,
     pub noise: Vec<NoiseSource>,
+    pub ac_excitations: Vec<AcExcitation>,
+    pub param_sensitivities: Vec<ParamSensitivity>,
+    pub higher_order: Vec<HigherOrderEntry>,
     pub opvars: IndexMap<Variable, EvalOutput, BuildHasherDefault<FxHasher>>,
     pub jacobian: TiVec<MatrixEntryId, MatrixEntry>,
     pub bound_step: Option<EvalOutputSlot>,
+    pub delays: TiVec<Delay, DelayLine>,
+    /// pointers to the `OsdiDelayHistory` of each delay, stored after the eval outputs
+    pub delay_history: Option<&'ll llvm_sys::LLVMType>,
+    /// the persistent states of analog operators, stored twice after the delay history:
+    /// first the values committed at the last accepted timepoint and then the values
+    /// written by the current `eval` call
+    pub persistent_states: Option<&'ll llvm_sys::LLVMType>,
+    /// the seeds set by the simulator (global and instance) followed by the state of the
+    /// corresponding random number streams, stored after the persistent states
+    pub random_state: Option<&'ll llvm_sys::LLVMType>,
+    /// the strings created by `$swrite`/`$sformat`/`$sscanf` (one pointer for each string
+    /// slot), stored after the random state
+    pub string_slots: Option<&'ll llvm_sys::LLVMType>,
+}
+
+/// The number of string slots used by the callbacks of `intern`.
+pub fn num_string_slots(intern: &HirInterner) -> u32 {
+    intern
+        .callbacks
+        .raw
+        .iter()
+        .filter_map(|kind| kind.string_slot())
+        .max()
+        .map_or(0, |slot| slot + 1)
 }
 
 impl<'ll> OsdiInstanceData<'ll> {
@@ -275,12 +409,65 @@ impl<'ll> OsdiInstanceData<'ll> {
             .iter()
             .map(|source| NoiseSource::new(source, module, &mut eval_outputs, ty_f64))
             .collect();
+        let ac_excitations = module
+            .dae_system
+            .ac_excitations
+            .iter()
+            .map(|excitation| AcExcitation::new(excitation, &mut eval_outputs, ty_f64, module.eval))
+            .collect();
+        let param_sensitivities = module
+            .dae_system
+            .param_sensitivities
+            .iter()
+            .map(|sensitivity| {
+                ParamSensitivity::new(sensitivity, module, &mut eval_outputs, ty_f64)
+            })
+            .collect();
+        let higher_order = module
+            .dae_system
+            .higher_order
+            .iter()
+            .map(|entry| HigherOrderEntry::new(entry, module, &mut eval_outputs, ty_f64))
+            .collect();
         let bound_step = module.intern.outputs.get(&PlaceKind::BoundStep).and_then(|val| {
             let mut val = val.expand()?;
             val = strip_optbarrier(module.eval, val);
             let slot = eval_outputs.insert_full(val, ty_f64).0;
             Some(slot)
         });
+        let mut delay_slot = |kind| {
+            let val = module.intern.outputs.get(&kind).and_then(|val| val.expand());
+            let val = val.map_or(F_ZERO, |val| strip_optbarrier(module.eval, val));
+            eval_outputs.insert_full(val, ty_f64).0
+        };
+        let delays: TiVec<Delay, _> = module
+            .intern
+            .delays
+            .keys()
+            .map(|delay| DelayLine {
+                input: delay_slot(PlaceKind::DelayInput(delay)),
+                delay: delay_slot(PlaceKind::DelayTime(delay)),
+            })
+            .collect();
+        let delay_history =
+            (!delays.is_empty()).then(|| cx.ty_array(cx.ty_ptr(), delays.len() as u32));
+
+        let num_persistent_states = module.intern.persistent_states.len() as u32;
+        let persistent_states =
+            (num_persistent_states != 0).then(|| cx.ty_array(ty_f64, num_persistent_states));
+
+        let uses_random = |intern: &HirInterner| {
+            intern.callbacks.raw.iter().any(|kind| {
+                matches!(kind, CallBackKind::Random { .. } | CallBackKind::RandomStream { .. })
+            })
+        };
+        let random_state = (uses_random(module.intern) || uses_random(&module.init.intern))
+            .then(|| cx.ty_array(ty_u32, 4));
+
+        let num_string_slots =
+            num_string_slots(module.intern).max(num_string_slots(&module.init.intern));
+        let string_slots =
+            (num_string_slots != 0).then(|| cx.ty_array(cx.ty_ptr(), num_string_slots));
 
         let param_given = bitfield::arr_ty(params.len() as u32, cx);
         let jacobian_ptr = cx.ty_array(cx.ty_ptr(), module.dae_system.jacobian.len() as u32);
@@ -293,7 +480,7 @@ impl<'ll> OsdiInstanceData<'ll> {
         let cache_slots: TiVec<_, _> =
             module.init.cache_slots.raw.values().map(|ty| lltype(ty, cx)).collect();
 
-        let state_idx = cx.ty_array(cx.ty_int(), module.intern.lim_state.len() as u32);
+        let state_idx = cx.ty_array(cx.ty_int(), module.intern.num_states() as u32);
         let static_fields: [_; NUM_CONST_FIELDS as usize] = [
             param_given,
             jacobian_ptr,
@@ -310,6 +497,11 @@ impl<'ll> OsdiInstanceData<'ll> {
             .chain(params.values().copied())
             .chain(cache_slots.iter().copied())
             .chain(eval_outputs.raw.values().copied())
+            .chain(delay_history)
+            .chain(persistent_states)
+            .chain(persistent_states)
+            .chain(random_state)
+            .chain(string_slots)
             .collect();
 
         let name = &module.sym;
@@ -329,9 +521,17 @@ impl<'ll> OsdiInstanceData<'ll> {
             cache_slots,
             residual,
             noise,
+            ac_excitations,
+            param_sensitivities,
+            higher_order,
             opvars,
             jacobian,
             bound_step,
+            delays,
+            delay_history,
+            persistent_states,
+            random_state,
+            string_slots,
         }
     }
 
@@ -350,6 +550,198 @@ impl<'ll> OsdiInstanceData<'ll> {
         Some(elem)
     }
 
+    pub unsafe fn store_delays(
+        &self,
+        ptr: &'ll llvm_sys::LLVMValue,
+        builder: &mir_llvm::Builder<'_, '_, 'll>,
+    ) {
+        for line in &self.delays {
+            self.store_eval_output_slot(line.input, ptr, builder);
+            self.store_eval_output_slot(line.delay, ptr, builder);
+        }
+    }
+
+    pub fn delay_elems(&self, delay: Delay) -> (u32, u32) {
+        let line = self.delays[delay];
+        (self.eval_output_slot_elem(line.input), self.eval_output_slot_elem(line.delay))
+    }
+
+    pub fn delay_history_elem(&self) -> Option<u32> {
+        self.delay_history?;
+        let elem = NUM_CONST_FIELDS
+            + self.params.len() as u32
+            + self.cache_slots.len() as u32
+            + self.eval_outputs.len() as u32;
+        Some(elem)
+    }
+
+    /// The field that holds the persistent states committed at the last accepted timepoint.
+    /// The pending states written during `eval` are stored in the next field.
+    pub fn persistent_states_elem(&self) -> Option<u32> {
+        self.persistent_states?;
+        let elem = NUM_CONST_FIELDS
+            + self.params.len() as u32
+            + self.cache_slots.len() as u32
+            + self.eval_outputs.len() as u32
+            + self.delay_history.is_some() as u32;
+        Some(elem)
+    }
+
+    pub fn persistent_state_loc(
+        &self,
+        cx: &CodegenCx<'_, 'll>,
+        state: PersistentState,
+        pending: bool,
+        ptr: &'ll llvm_sys::LLVMValue,
+    ) -> MemLoc<'ll> {
+        let elem = self.persistent_states_elem().unwrap() + pending as u32;
+        MemLoc {
+            ptr,
+            ptr_ty: self.ty,
+            ty: cx.ty_double(),
+            indices: vec![
+                cx.const_unsigned_int(0),
+                cx.const_unsigned_int(elem),
+                cx.const_unsigned_int(state.into()),
+            ]
+            .into_boxed_slice(),
+        }
+    }
+
+    pub unsafe fn persistent_state_ptr(
+        &self,
+        cx: &CodegenCx<'_, 'll>,
+        state: PersistentState,
+        pending: bool,
+        ptr: &'ll llvm_sys::LLVMValue,
+        llbuilder: &llvm_sys::LLVMBuilder,
+    ) -> &'ll llvm_sys::LLVMValue {
+        let builder_ptr = NonNull::from(llbuilder).as_ptr();
+        let arr_ty = self.persistent_states.unwrap();
+        let elem = self.persistent_states_elem().unwrap() + pending as u32;
+        let ptr = LLVMBuildStructGEP2(
+            builder_ptr,
+            NonNull::from(self.ty).as_ptr(),
+            NonNull::from(ptr).as_ptr(),
+            elem,
+            UNNAMED,
+        );
+
+        let zero = cx.const_int(0) as *const llvm_sys::LLVMValue as *mut _;
+        let state = cx.const_unsigned_int(state.into()) as *const llvm_sys::LLVMValue as *mut _;
+        let mut gep_indices: [llvm_sys::prelude::LLVMValueRef; 2] = [zero, state];
+        &*LLVMBuildGEP2(
+            builder_ptr,
+            NonNull::from(arr_ty).as_ptr(),
+            ptr,
+            gep_indices.as_mut_ptr(),
+            2,
+            UNNAMED,
+        )
+    }
+
+    pub fn random_state_elem(&self) -> Option<u32> {
+        self.random_state?;
+        let elem = NUM_CONST_FIELDS
+            + self.params.len() as u32
+            + self.cache_slots.len() as u32
+            + self.eval_outputs.len() as u32
+            + self.delay_history.is_some() as u32
+            + 2 * self.persistent_states.is_some() as u32;
+        Some(elem)
+    }
+
+    pub unsafe fn random_state_ptr(
+        &self,
+        ptr: &'ll llvm_sys::LLVMValue,
+        llbuilder: &llvm_sys::LLVMBuilder,
+    ) -> Option<&'ll llvm_sys::LLVMValue> {
+        let elem = self.random_state_elem()?;
+        let ptr = LLVMBuildStructGEP2(
+            NonNull::from(llbuilder).as_ptr(),
+            NonNull::from(self.ty).as_ptr(),
+            NonNull::from(ptr).as_ptr(),
+            elem,
+            UNNAMED,
+        );
+        Some(&*ptr)
+    }
+
+    pub fn string_slots_elem(&self) -> Option<u32> {
+        self.string_slots?;
+        let elem = NUM_CONST_FIELDS
+            + self.params.len() as u32
+            + self.cache_slots.len() as u32
+            + self.eval_outputs.len() as u32
+            + self.delay_history.is_some() as u32
+            + 2 * self.persistent_states.is_some() as u32
+            + self.random_state.is_some() as u32;
+        Some(elem)
+    }
+
+    pub unsafe fn string_slot_ptr(
+        &self,
+        cx: &CodegenCx<'_, 'll>,
+        slot: u32,
+        ptr: &'ll llvm_sys::LLVMValue,
+        llbuilder: &llvm_sys::LLVMBuilder,
+    ) -> &'ll llvm_sys::LLVMValue {
+        let builder_ptr = NonNull::from(llbuilder).as_ptr();
+        let arr_ty = self.string_slots.unwrap();
+        let elem = self.string_slots_elem().unwrap();
+        let ptr = LLVMBuildStructGEP2(
+            builder_ptr,
+            NonNull::from(self.ty).as_ptr(),
+            NonNull::from(ptr).as_ptr(),
+            elem,
+            UNNAMED,
+        );
+
+        let zero = cx.const_int(0) as *const llvm_sys::LLVMValue as *mut _;
+        let slot = cx.const_unsigned_int(slot) as *const llvm_sys::LLVMValue as *mut _;
+        let mut gep_indices: [llvm_sys::prelude::LLVMValueRef; 2] = [zero, slot];
+        &*LLVMBuildGEP2(
+            builder_ptr,
+            NonNull::from(arr_ty).as_ptr(),
+            ptr,
+            gep_indices.as_mut_ptr(),
+            2,
+            UNNAMED,
+        )
+    }
+
+    pub unsafe fn read_delay_history(
+        &self,
+        cx: &CodegenCx<'_, 'll>,
+        delay: Delay,
+        ptr: &'ll llvm_sys::LLVMValue,
+        llbuilder: &llvm_sys::LLVMBuilder,
+    ) -> &'ll llvm_sys::LLVMValue {
+        let builder_ptr = NonNull::from(llbuilder).as_ptr();
+        let arr_ty = self.delay_history.unwrap();
+        let elem = self.delay_history_elem().unwrap();
+        let ptr = LLVMBuildStructGEP2(
+            builder_ptr,
+            NonNull::from(self.ty).as_ptr(),
+            NonNull::from(ptr).as_ptr(),
+            elem,
+            UNNAMED,
+        );
+
+        let zero = cx.const_int(0) as *const llvm_sys::LLVMValue as *mut _;
+        let delay = cx.const_unsigned_int(delay.into()) as *const llvm_sys::LLVMValue as *mut _;
+        let mut gep_indices: [llvm_sys::prelude::LLVMValueRef; 2] = [zero, delay];
+        let ptr = LLVMBuildGEP2(
+            builder_ptr,
+            NonNull::from(arr_ty).as_ptr(),
+            ptr,
+            gep_indices.as_mut_ptr(),
+            2,
+            UNNAMED,
+        );
+        &*LLVMBuildLoad2(builder_ptr, NonNull::from(cx.ty_ptr()).as_ptr(), ptr, UNNAMED)
+    }
+
     pub unsafe fn param_ptr(
         &self,
         param: OsdiInstanceParam,
@@ -526,7 +918,7 @@ impl<'ll> OsdiInstanceData<'ll> {
             + u32::from(slot)
     }
 
-    unsafe fn load_eval_output_slot(
+    pub unsafe fn load_eval_output_slot(
         &self,
         llbuilder: &llvm_sys::LLVMBuilder,
         ptr: &'ll llvm_sys::LLVMValue,
@@ -683,7 +1075,7 @@ impl<'ll> OsdiInstanceData<'ll> {
     pub unsafe fn read_state_idx(
         &self,
         cx: &CodegenCx<'_, 'll>,
-        idx: LimitState,
+        idx: usize,
         ptr: &'ll llvm_sys::LLVMValue,
         llbuilder: &llvm_sys::LLVMBuilder,
     ) -> &'ll llvm_sys::LLVMValue {
@@ -696,7 +1088,7 @@ impl<'ll> OsdiInstanceData<'ll> {
 
         // Preparing indices for the next GEP
         let zero = cx.const_int(0) as *const llvm_sys::LLVMValue as *mut _;
-        let state = cx.const_unsigned_int(idx.into()) as *const llvm_sys::LLVMValue as *mut _;
+        let state = cx.const_unsigned_int(idx as u32) as *const llvm_sys::LLVMValue as *mut _;
         let mut gep_indices: [llvm_sys::prelude::LLVMValueRef; 2] = [zero, state];
         let gep_ptr = gep_indices.as_mut_ptr();
 
@@ -1189,11 +1581,23 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                         ),
                         cx.ty_double(),
                     ),
+                    ParamKind::ParamElement(param, pos) => {
+                        let (ptr, ty) = inst_data
+                            .param_ptr(OsdiInstanceParam::User(param), inst_ptr, llbuilder)
+                            .unwrap_or_else(|| {
+                                model_data.param_ptr(param, model_ptr, llbuilder).unwrap()
+                            });
+                        array_elem_ptr(cx, ptr, ty, pos, llbuilder)
+                    }
                     ParamKind::ParamSysFun(func) => inst_data
                         .param_ptr(OsdiInstanceParam::Builtin(func), inst_ptr, llbuilder)
                         .unwrap(),
 
-                    ParamKind::HiddenState(_) => todo!("hidden state"),
+                    // the hidden state of variables is replaced by their initial value
+                    // (see `HirInterner::insert_var_init`) and never stored
+                    ParamKind::HiddenState(_) | ParamKind::HiddenStateElement(..) => {
+                        unreachable!("hidden state is replaced by the initial value")
+                    }
 
                     ParamKind::Voltage { .. }
                     | ParamKind::Current(_)
@@ -1204,6 +1608,8 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                     | ParamKind::EnableLim
                     | ParamKind::PrevState(_)
                     | ParamKind::NewState(_)
+                    | ParamKind::PrevTranState(_)
+                    | ParamKind::PrevPersistentState(_)
                     | ParamKind::ImplicitUnknown(_) => unreachable!(),
                 }
             }
@@ -1272,11 +1678,23 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                         ),
                         cx.ty_double(),
                     ),
+                    ParamKind::ParamElement(param, pos) => {
+                        let (ptr, ty) = inst_data
+                            .param_ptr(OsdiInstanceParam::User(param), inst_ptr, llbuilder)
+                            .unwrap_or_else(|| {
+                                model_data.param_ptr(param, model_ptr, llbuilder).unwrap()
+                            });
+                        array_elem_ptr(cx, ptr, ty, pos, llbuilder)
+                    }
                     ParamKind::ParamSysFun(func) => inst_data
                         .param_ptr(OsdiInstanceParam::Builtin(func), inst_ptr, llbuilder)
                         .unwrap(),
 
-                    ParamKind::HiddenState(_) => todo!("hidden state"),
+                    // the hidden state of variables is replaced by their initial value
+                    // (see `HirInterner::insert_var_init`) and never stored
+                    ParamKind::HiddenState(_) | ParamKind::HiddenStateElement(..) => {
+                        unreachable!("hidden state is replaced by the initial value")
+                    }
 
                     ParamKind::Voltage { .. }
                     | ParamKind::Current(_)
@@ -1287,6 +1705,8 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                     | ParamKind::EnableLim
                     | ParamKind::PrevState(_)
                     | ParamKind::NewState(_)
+                    | ParamKind::PrevTranState(_)
+                    | ParamKind::PrevPersistentState(_)
                     | ParamKind::ImplicitUnknown(_) => unreachable!(),
                 }
             }
@@ -1294,3 +1714,25 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
         }
     }
 }
+
+/// Returns a pointer to the element `pos` of the array (of type `ty`) at `ptr`
+/// together with the type of the element.
+pub unsafe fn array_elem_ptr<'ll>(
+    cx: &CodegenCx<'_, 'll>,
+    ptr: &'ll llvm_sys::LLVMValue,
+    ty: &'ll llvm_sys::LLVMType,
+    pos: u32,
+    llbuilder: &llvm_sys::LLVMBuilder,
+) -> (&'ll llvm_sys::LLVMValue, &'ll llvm_sys::LLVMType) {
+    let mut indices = [cx.const_unsigned_int(0), cx.const_unsigned_int(pos)];
+    let ptr = &*LLVMBuildGEP2(
+        NonNull::from(llbuilder).as_ptr(),
+        NonNull::from(ty).as_ptr(),
+        NonNull::from(ptr).as_ptr(),
+        indices.as_mut_ptr() as *mut *mut _,
+        2,
+        UNNAMED,
+    );
+    let elem_ty = &*LLVMGetElementType(NonNull::from(ty).as_ptr());
+    (ptr, elem_ty)
+}
@@ -245,7 +245,9 @@ pub fn compile<'a>(
                 let tys = OsdiTys::new(&cx, NonNull::from(target_data_).as_ptr());
                 let cguint = OsdiCompilationUnit::new(&_db, module, &cx, &tys, true);
 
-                cguint.eval();
+                let kernel = cguint.eval_kernel();
+                cguint.eval(kernel);
+                cguint.eval_batch(kernel);
                 if dump_unopt_ir {
                     let mut unoptirs = unoptirs_clone.lock().unwrap();
                     unoptirs.insert((i, access), llmod.to_str().to_string());
@@ -360,26 +362,49 @@ pub fn compile<'a>(
             );
         }
 
-        let osdi_log =
-            cx.get_declared_value("osdi_log").expect("symbol osdi_log missing from std lib");
-        let val = cx.const_null_ptr();
-        unsafe {
-            llvm_sys::core::LLVMSetInitializer(
-                NonNull::from(osdi_log).as_ptr(),
-                NonNull::from(val).as_ptr(),
-            );
-            llvm_sys::core::LLVMSetLinkage(
-                NonNull::from(osdi_log).as_ptr(),
-                llvm_sys::LLVMLinkage::LLVMExternalLinkage,
-            );
-            llvm_sys::core::LLVMSetUnnamedAddress(
-                NonNull::from(osdi_log).as_ptr(),
-                llvm_sys::LLVMUnnamedAddr::LLVMNoUnnamedAddr,
-            );
-            llvm_sys::core::LLVMSetDLLStorageClass(
-                NonNull::from(osdi_log).as_ptr(),
-                llvm_sys::LLVMDLLStorageClass::LLVMDLLExportStorageClass,
-            );
+        // hooks the simulator may set after loading the library
+        for sym in ["osdi_log", "osdi_file_path", "osdi_simprobe"] {
+            let hook = cx
+                .get_declared_value(sym)
+                .unwrap_or_else(|| panic!("symbol {sym} missing from std lib"));
+            let val = cx.const_null_ptr();
+            unsafe {
+                llvm_sys::core::LLVMSetInitializer(
+                    NonNull::from(hook).as_ptr(),
+                    NonNull::from(val).as_ptr(),
+                );
+                llvm_sys::core::LLVMSetLinkage(
+                    NonNull::from(hook).as_ptr(),
+                    llvm_sys::LLVMLinkage::LLVMExternalLinkage,
+                );
+                llvm_sys::core::LLVMSetUnnamedAddress(
+                    NonNull::from(hook).as_ptr(),
+                    llvm_sys::LLVMUnnamedAddr::LLVMNoUnnamedAddr,
+                );
+                llvm_sys::core::LLVMSetDLLStorageClass(
+                    NonNull::from(hook).as_ptr(),
+                    llvm_sys::LLVMDLLStorageClass::LLVMDLLExportStorageClass,
+                );
+            }
+        }
+
+        // the tables of open files and loaded table models (and their locks) are shared by all
+        // functions of the library (but not exported), the std lib in the objects of the models
+        // only declares them
+        for name in ["osdi_files", "osdi_files_lock", "osdi_tables", "osdi_tables_lock"] {
+            let global = cx
+                .get_declared_value(name)
+                .unwrap_or_else(|| panic!("symbol {name} missing from std lib"));
+            unsafe {
+                let global = NonNull::from(global).as_ptr();
+                let ty = llvm_sys::core::LLVMGlobalGetValueType(global);
+                llvm_sys::core::LLVMSetInitializer(global, llvm_sys::core::LLVMConstNull(ty));
+                llvm_sys::core::LLVMSetLinkage(global, llvm_sys::LLVMLinkage::LLVMExternalLinkage);
+                llvm_sys::core::LLVMSetVisibility(
+                    global,
+                    llvm_sys::LLVMVisibility::LLVMHiddenVisibility,
+                );
+            }
         }
 
         debug_assert!(llmod.verify_and_print());
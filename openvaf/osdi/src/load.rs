@@ -2,16 +2,20 @@ use core::ffi::c_uint;
 use std::ptr::NonNull;
 
 use llvm_sys::core::{
-    LLVMAppendBasicBlockInContext, LLVMBuildCall2, LLVMBuildFAdd, LLVMBuildFDiv, LLVMBuildFMul,
-    LLVMBuildFSub, LLVMBuildGEP2, LLVMBuildRetVoid, LLVMBuildStore, LLVMCreateBuilderInContext,
+    LLVMAddCase, LLVMAppendBasicBlockInContext, LLVMBuildBr, LLVMBuildCall2, LLVMBuildFAdd,
+    LLVMBuildFDiv, LLVMBuildFMul, LLVMBuildFSub, LLVMBuildGEP2, LLVMBuildLoad2, LLVMBuildRetVoid,
+    LLVMBuildStore, LLVMBuildStructGEP2, LLVMBuildSwitch, LLVMCreateBuilderInContext,
     LLVMDisposeBuilder, LLVMGetParam, LLVMPositionBuilderAtEnd,
 };
 use mir_llvm::UNNAMED;
 use sim_back::dae::NoiseSourceKind;
 use stdx::iter::zip;
+use stdx::Ieee64;
 use typed_index_collections::TiVec;
 
 use crate::compilation_unit::OsdiCompilationUnit;
+use crate::inst_data::ParamSensitivity;
+
 #[derive(Debug, Clone, Copy)]
 pub enum JacobianLoadType {
     Tran,
@@ -120,7 +124,9 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
 
                         pwr
                     }
-                    NoiseSourceKind::NoiseTable { .. } => unimplemented!("noise tables"),
+                    NoiseSourceKind::NoiseTable { log, ref vals } => {
+                        self.noise_table_interp(vals, log, freq, &*llbuilder)
+                    }
                 };
 
                 // Multiply with squared factor because factor is in terms of signal, but
@@ -164,6 +170,43 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
         llfunc
     }
 
+    /// Builds a call to the stdlib function that interpolates a (constant) noise table
+    /// at the frequency `freq`.
+    unsafe fn noise_table_interp(
+        &self,
+        vals: &[(Ieee64, Ieee64)],
+        log: bool,
+        freq: &'ll llvm_sys::LLVMValue,
+        llbuilder: &llvm_sys::LLVMBuilder,
+    ) -> &'ll llvm_sys::LLVMValue {
+        let cx = self.cx;
+        let fun = cx
+            .get_func_by_name("noise_table_interp")
+            .expect("stdlib function noise_table_interp is missing");
+        let fun_ty = cx.ty_func(
+            &[cx.ty_ptr(), cx.ty_ptr(), cx.ty_int(), cx.ty_double(), cx.ty_c_bool()],
+            cx.ty_double(),
+        );
+        let freqs: Vec<_> = vals.iter().map(|(f, _)| cx.const_real((*f).into())).collect();
+        let pwrs: Vec<_> = vals.iter().map(|(_, pwr)| cx.const_real((*pwr).into())).collect();
+        let mut args = [
+            cx.const_arr_ptr(cx.ty_double(), &freqs),
+            cx.const_arr_ptr(cx.ty_double(), &pwrs),
+            cx.const_unsigned_int(vals.len() as u32),
+            freq,
+            cx.const_c_bool(log),
+        ]
+        .map(|arg| NonNull::from(arg).as_ptr());
+        &*LLVMBuildCall2(
+            NonNull::from(llbuilder).as_ptr(),
+            NonNull::from(fun_ty).as_ptr(),
+            NonNull::from(fun).as_ptr(),
+            args.as_mut_ptr(),
+            args.len() as u32,
+            UNNAMED,
+        )
+    }
+
     pub fn load_noise_params(&self) -> &'ll llvm_sys::LLVMValue {
         let OsdiCompilationUnit { cx, module, .. } = self;
         let void_ptr = cx.ty_ptr();
@@ -197,7 +240,9 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                     NoiseSourceKind::FlickerNoise { .. } => {
                         self.load_eval_output(eval_outputs.args[0], &*inst, &*model, &*llbuilder)
                     }
-                    NoiseSourceKind::NoiseTable { .. } => unimplemented!("noise tables"),
+                    // The table itself is exported in the descriptor (noise_tables),
+                    // only the (squared) factor is reported here.
+                    NoiseSourceKind::NoiseTable { .. } => cx.const_real(1.0),
                 };
 
                 // Multiply with squared factor because factor is in terms of signal, but
@@ -224,7 +269,7 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                     NoiseSourceKind::FlickerNoise { .. } => {
                         self.load_eval_output(eval_outputs.args[1], &*inst, &*model, &*llbuilder)
                     }
-                    NoiseSourceKind::NoiseTable { .. } => unimplemented!("noise tables"),
+                    NoiseSourceKind::NoiseTable { .. } => cx.const_real(0.0),
                 };
 
                 // Store power
@@ -303,6 +348,275 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
         llfunc
     }
 
+    /// Generates `load_ac_stim` which adds the excitation (real and imaginary part) created
+    /// by the `ac_stim` sources of the AC analysis `ac_stim_names[analysis]` to `re` and `im`.
+    pub fn load_ac_stim(&self) -> &'ll llvm_sys::LLVMValue {
+        let OsdiCompilationUnit { inst_data, cx, module, .. } = self;
+        let ptr_ty = cx.ty_ptr();
+        let fun_ty = cx.ty_func(&[ptr_ty, ptr_ty, cx.ty_int(), ptr_ty, ptr_ty], cx.ty_void());
+        let name = &format!("load_ac_stim_{}", module.sym);
+        let llfunc = cx.declare_int_c_fn(name, fun_ty);
+
+        unsafe {
+            let entry = LLVMAppendBasicBlockInContext(
+                NonNull::from(cx.llcx).as_ptr(),
+                NonNull::from(llfunc).as_ptr(),
+                UNNAMED,
+            );
+            let exit = LLVMAppendBasicBlockInContext(
+                NonNull::from(cx.llcx).as_ptr(),
+                NonNull::from(llfunc).as_ptr(),
+                UNNAMED,
+            );
+            let llbuilder = LLVMCreateBuilderInContext(NonNull::from(cx.llcx).as_ptr());
+
+            // get params
+            let inst = &*LLVMGetParam(NonNull::from(llfunc).as_ptr(), 0);
+            let analysis = LLVMGetParam(NonNull::from(llfunc).as_ptr(), 2);
+            let re = &*LLVMGetParam(NonNull::from(llfunc).as_ptr(), 3);
+            let im = &*LLVMGetParam(NonNull::from(llfunc).as_ptr(), 4);
+
+            LLVMPositionBuilderAtEnd(llbuilder, entry);
+            let switch =
+                LLVMBuildSwitch(llbuilder, analysis, exit, inst_data.ac_excitations.len() as u32);
+
+            // one case for each analysis, unknown analyses have no excitation
+            for (i, excitation) in inst_data.ac_excitations.iter().enumerate() {
+                let bb = LLVMAppendBasicBlockInContext(
+                    NonNull::from(cx.llcx).as_ptr(),
+                    NonNull::from(llfunc).as_ptr(),
+                    UNNAMED,
+                );
+                let case = NonNull::from(cx.const_unsigned_int(i as u32)).as_ptr();
+                LLVMAddCase(switch, case, bb);
+                LLVMPositionBuilderAtEnd(llbuilder, bb);
+                for node in module.dae_system.unknowns.indices() {
+                    for (slot, dst) in [(excitation.real[node], re), (excitation.imag[node], im)] {
+                        if let Some(slot) = slot.expand() {
+                            let val = inst_data.load_eval_output_slot(&*llbuilder, inst, slot);
+                            inst_data.store_contrib(cx, node, inst, dst, val, &*llbuilder, false);
+                        }
+                    }
+                }
+                LLVMBuildBr(llbuilder, exit);
+            }
+
+            LLVMPositionBuilderAtEnd(llbuilder, exit);
+            LLVMBuildRetVoid(llbuilder);
+            LLVMDisposeBuilder(llbuilder);
+        }
+
+        llfunc
+    }
+
+    /// Generates `load_param_sensitivity_resist` (`reactive = false`) or
+    /// `load_param_sensitivity_react` (`reactive = true`) which add the derivative of the
+    /// residual by the parameter `sensitivity_params[param]` to `dst`.
+    pub fn load_param_sensitivity(&self, reactive: bool) -> &'ll llvm_sys::LLVMValue {
+        let OsdiCompilationUnit { inst_data, cx, module, .. } = self;
+        let name = &format!(
+            "load_param_sensitivity_{}_{}",
+            if reactive { "react" } else { "resist" },
+            module.sym
+        );
+        self.build_param_sensitivity_fn(name, |sensitivity, inst, model, dst, llbuilder| unsafe {
+            let outputs = if reactive { &sensitivity.react } else { &sensitivity.resist };
+            for (node, output) in outputs.iter_enumerated() {
+                if let Some(output) = *output {
+                    let val = self.load_eval_output(output, inst, model, llbuilder);
+                    inst_data.store_contrib(cx, node, inst, dst, val, llbuilder, false);
+                }
+            }
+        })
+    }
+
+    /// Generates `load_opvar_sensitivity` which writes the derivative of each operating point
+    /// variable by the parameter `sensitivity_params[param]` to `dst`.
+    pub fn load_opvar_sensitivity(&self) -> &'ll llvm_sys::LLVMValue {
+        let OsdiCompilationUnit { cx, module, .. } = self;
+        let name = &format!("load_opvar_sensitivity_{}", module.sym);
+        self.build_param_sensitivity_fn(name, |sensitivity, inst, model, dst, llbuilder| unsafe {
+            for (i, output) in sensitivity.op_vars.iter().enumerate() {
+                let val = match *output {
+                    Some(output) => self.load_eval_output(output, inst, model, llbuilder),
+                    None => cx.const_real(0.0),
+                };
+                let index_val =
+                    cx.const_unsigned_int(i as u32) as *const llvm_sys::LLVMValue as *mut _;
+                let mut gep_indices: [llvm_sys::prelude::LLVMValueRef; 1] = [index_val];
+                let dst = LLVMBuildGEP2(
+                    NonNull::from(llbuilder).as_ptr(),
+                    NonNull::from(cx.ty_double()).as_ptr(),
+                    NonNull::from(dst).as_ptr(),
+                    gep_indices.as_mut_ptr(),
+                    1,
+                    UNNAMED,
+                );
+                LLVMBuildStore(NonNull::from(llbuilder).as_ptr(), NonNull::from(val).as_ptr(), dst);
+            }
+        })
+    }
+
+    /// Generates `load_higher_order_resist` (`reactive = false`) or `load_higher_order_react`
+    /// (`reactive = true`) which write the Taylor coefficient of `higher_order_entries[i]` to
+    /// `dst[i]`.
+    pub fn load_higher_order(&self, reactive: bool) -> &'ll llvm_sys::LLVMValue {
+        let OsdiCompilationUnit { inst_data, cx, module, .. } = self;
+        let ptr_ty = cx.ty_ptr();
+        let fun_ty = cx.ty_func(&[ptr_ty, ptr_ty, ptr_ty], cx.ty_void());
+        let name = &format!(
+            "load_higher_order_{}_{}",
+            if reactive { "react" } else { "resist" },
+            module.sym
+        );
+        let llfunc = cx.declare_int_c_fn(name, fun_ty);
+
+        unsafe {
+            let entry = LLVMAppendBasicBlockInContext(
+                NonNull::from(cx.llcx).as_ptr(),
+                NonNull::from(llfunc).as_ptr(),
+                UNNAMED,
+            );
+            let llbuilder = LLVMCreateBuilderInContext(NonNull::from(cx.llcx).as_ptr());
+
+            LLVMPositionBuilderAtEnd(llbuilder, entry);
+
+            // get params
+            let inst = &*LLVMGetParam(NonNull::from(llfunc).as_ptr(), 0);
+            let model = &*LLVMGetParam(NonNull::from(llfunc).as_ptr(), 1);
+            let dst = &*LLVMGetParam(NonNull::from(llfunc).as_ptr(), 2);
+
+            for (i, entry) in inst_data.higher_order.iter().enumerate() {
+                let output = if reactive { entry.react } else { entry.resist };
+                let val = match output {
+                    Some(output) => self.load_eval_output(output, inst, model, &*llbuilder),
+                    None => cx.const_real(0.0),
+                };
+                let index_val =
+                    cx.const_unsigned_int(i as u32) as *const llvm_sys::LLVMValue as *mut _;
+                let mut gep_indices: [llvm_sys::prelude::LLVMValueRef; 1] = [index_val];
+                let dst = LLVMBuildGEP2(
+                    llbuilder,
+                    NonNull::from(cx.ty_double()).as_ptr(),
+                    NonNull::from(dst).as_ptr(),
+                    gep_indices.as_mut_ptr(),
+                    1,
+                    UNNAMED,
+                );
+                LLVMBuildStore(llbuilder, NonNull::from(val).as_ptr(), dst);
+            }
+
+            LLVMBuildRetVoid(llbuilder);
+            LLVMDisposeBuilder(llbuilder);
+        }
+
+        llfunc
+    }
+
+    /// Builds a function `void name(void *inst, void *model, uint32_t param, double *dst)` that
+    /// calls `load` for the sensitivity with the index `param` and does nothing for unknown
+    /// indices.
+    fn build_param_sensitivity_fn(
+        &self,
+        name: &str,
+        load: impl Fn(
+            &ParamSensitivity,
+            &'ll llvm_sys::LLVMValue,
+            &'ll llvm_sys::LLVMValue,
+            &'ll llvm_sys::LLVMValue,
+            &llvm_sys::LLVMBuilder,
+        ),
+    ) -> &'ll llvm_sys::LLVMValue {
+        let OsdiCompilationUnit { inst_data, cx, .. } = self;
+        let ptr_ty = cx.ty_ptr();
+        let fun_ty = cx.ty_func(&[ptr_ty, ptr_ty, cx.ty_int(), ptr_ty], cx.ty_void());
+        let llfunc = cx.declare_int_c_fn(name, fun_ty);
+
+        unsafe {
+            let entry = LLVMAppendBasicBlockInContext(
+                NonNull::from(cx.llcx).as_ptr(),
+                NonNull::from(llfunc).as_ptr(),
+                UNNAMED,
+            );
+            let exit = LLVMAppendBasicBlockInContext(
+                NonNull::from(cx.llcx).as_ptr(),
+                NonNull::from(llfunc).as_ptr(),
+                UNNAMED,
+            );
+            let llbuilder = LLVMCreateBuilderInContext(NonNull::from(cx.llcx).as_ptr());
+
+            // get params
+            let inst = &*LLVMGetParam(NonNull::from(llfunc).as_ptr(), 0);
+            let model = &*LLVMGetParam(NonNull::from(llfunc).as_ptr(), 1);
+            let param = LLVMGetParam(NonNull::from(llfunc).as_ptr(), 2);
+            let dst = &*LLVMGetParam(NonNull::from(llfunc).as_ptr(), 3);
+
+            LLVMPositionBuilderAtEnd(llbuilder, entry);
+            let num_params = inst_data.param_sensitivities.len() as u32;
+            let switch = LLVMBuildSwitch(llbuilder, param, exit, num_params);
+
+            for (i, sensitivity) in inst_data.param_sensitivities.iter().enumerate() {
+                let bb = LLVMAppendBasicBlockInContext(
+                    NonNull::from(cx.llcx).as_ptr(),
+                    NonNull::from(llfunc).as_ptr(),
+                    UNNAMED,
+                );
+                let case = NonNull::from(cx.const_unsigned_int(i as u32)).as_ptr();
+                LLVMAddCase(switch, case, bb);
+                LLVMPositionBuilderAtEnd(llbuilder, bb);
+                load(sensitivity, inst, model, dst, &*llbuilder);
+                LLVMBuildBr(llbuilder, exit);
+            }
+
+            LLVMPositionBuilderAtEnd(llbuilder, exit);
+            LLVMBuildRetVoid(llbuilder);
+            LLVMDisposeBuilder(llbuilder);
+        }
+
+        llfunc
+    }
+
+    /// Generates `accept_timestep` (`accept = true`) which commits the persistent states
+    /// written by the last `eval` call or `reject_timestep` (`accept = false`) which discards
+    /// them by restoring the values committed at the last accepted timepoint.
+    pub fn commit_persistent_states(&self, accept: bool) -> &'ll llvm_sys::LLVMValue {
+        let OsdiCompilationUnit { inst_data, cx, module, .. } = self;
+        let ptr_ty = cx.ty_ptr();
+        let fun_ty = cx.ty_func(&[ptr_ty, ptr_ty], cx.ty_void());
+        let name = &format!("{}_timestep_{}", if accept { "accept" } else { "reject" }, module.sym);
+        let llfunc = cx.declare_int_c_fn(name, fun_ty);
+
+        unsafe {
+            let entry = LLVMAppendBasicBlockInContext(
+                NonNull::from(cx.llcx).as_ptr(),
+                NonNull::from(llfunc).as_ptr(),
+                UNNAMED,
+            );
+            let llbuilder = LLVMCreateBuilderInContext(NonNull::from(cx.llcx).as_ptr());
+
+            LLVMPositionBuilderAtEnd(llbuilder, entry);
+
+            if let (Some(arr_ty), Some(elem)) =
+                (inst_data.persistent_states, inst_data.persistent_states_elem())
+            {
+                // get params
+                let inst = LLVMGetParam(NonNull::from(llfunc).as_ptr(), 0);
+
+                let (src, dst) = if accept { (elem + 1, elem) } else { (elem, elem + 1) };
+                let inst_ty = NonNull::from(inst_data.ty).as_ptr();
+                let src = LLVMBuildStructGEP2(llbuilder, inst_ty, inst, src, UNNAMED);
+                let dst = LLVMBuildStructGEP2(llbuilder, inst_ty, inst, dst, UNNAMED);
+                let val = LLVMBuildLoad2(llbuilder, NonNull::from(arr_ty).as_ptr(), src, UNNAMED);
+                LLVMBuildStore(llbuilder, val, dst);
+            }
+
+            LLVMBuildRetVoid(llbuilder);
+            LLVMDisposeBuilder(llbuilder);
+        }
+
+        llfunc
+    }
+
     pub fn load_lim_rhs(&self, reactive: bool) -> &'ll llvm_sys::LLVMValue {
         let OsdiCompilationUnit { inst_data, cx, module, .. } = self;
         let void_ptr = cx.ty_ptr();
@@ -1,10 +1,10 @@
 use core::ptr::NonNull;
 use std::iter::once;
 
-use hir::{CompilationDB, ParamSysFun, Type};
+use hir::{CompilationDB, Nature, ParamSysFun, Type};
 use hir_def::db::HirDefDB;
 use hir_def::ndatable::NDATable;
-use hir_lower::{CurrentKind, ParamKind};
+use hir_lower::{CurrentKind, HirInterner, ImplicitEquationKind, NodeAlias, ParamKind};
 use lasso::{Rodeo, Spur};
 use llvm_sys::core::{
     LLVMConstArray2, LLVMConstInt, LLVMConstPtrToInt, LLVMGetArrayLength2, LLVMGetDataLayoutStr,
@@ -29,12 +29,13 @@ use crate::inst_data::{
 };
 use crate::load::JacobianLoadType;
 use crate::metadata::osdi_0_4::{
-    OsdiDescriptor, OsdiJacobianEntry, OsdiNatureRef, OsdiNode, OsdiNodePair, OsdiNoiseSource,
-    OsdiParamOpvar, OsdiTys, JACOBIAN_ENTRY_REACT, JACOBIAN_ENTRY_REACT_CONST,
-    JACOBIAN_ENTRY_RESIST, JACOBIAN_ENTRY_RESIST_CONST, MODULEFLAG_ABSTIME, NATREF_DISCIPLINE_FLOW,
-    NATREF_DISCIPLINE_POTENTIAL, NATREF_NONE, NOISE_TYPE_FLICKER, NOISE_TYPE_TABLE,
-    NOISE_TYPE_WHITE, PARA_KIND_INST, PARA_KIND_MODEL, PARA_KIND_OPVAR, PARA_TY_INT, PARA_TY_REAL,
-    PARA_TY_STR,
+    OsdiDelay, OsdiDescriptor, OsdiHigherOrderEntry, OsdiJacobianEntry, OsdiNatureRef, OsdiNode,
+    OsdiNodePair, OsdiNoiseSource, OsdiNoiseTable, OsdiParamOpvar, OsdiTys, JACOBIAN_ENTRY_REACT,
+    JACOBIAN_ENTRY_REACT_CONST, JACOBIAN_ENTRY_RESIST, JACOBIAN_ENTRY_RESIST_CONST,
+    MODULEFLAG_ABSTIME, NATREF_DISCIPLINE_FLOW, NATREF_DISCIPLINE_POTENTIAL, NATREF_NATURE,
+    NATREF_NONE, NODE_FLAG_ALIAS, NODE_FLAG_DELAY, NODE_FLAG_PORT_ALIAS, NOISE_TYPE_FLICKER,
+    NOISE_TYPE_TABLE, NOISE_TYPE_TABLE_LOG, NOISE_TYPE_WHITE, PARA_KIND_INST, PARA_KIND_MODEL,
+    PARA_KIND_OPVAR, PARA_TY_INT, PARA_TY_REAL, PARA_TY_STR,
 };
 use crate::ty_len;
 
@@ -223,7 +224,7 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
             .map(|(id, unknown)| {
                 let residual_nature_kind = module.dae_system.residual[id].nature_kind;
                 let (name, units, residual_units, is_flow, _, _) =
-                    sim_unknown_info(*unknown, residual_nature_kind, db);
+                    sim_unknown_info(*unknown, residual_nature_kind, module.intern, db);
                 let resist_residual_off =
                     inst_data.residual_off(id, false, target_data).unwrap_or(u32::MAX);
                 let react_residual_off =
@@ -306,6 +307,32 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
             .collect()
     }
 
+    pub fn higher_order_entries(&self) -> Vec<OsdiHigherOrderEntry> {
+        self.module
+            .dae_system
+            .higher_order
+            .iter()
+            .map(|entry| {
+                let mut flags = 0;
+                if entry.resist != F_ZERO {
+                    flags |= JACOBIAN_ENTRY_RESIST;
+                }
+                if entry.react != F_ZERO {
+                    flags |= JACOBIAN_ENTRY_REACT;
+                }
+                let unknown = |i: usize| entry.cols.get(i).map_or(u32::MAX, |&col| col.into());
+                OsdiHigherOrderEntry {
+                    residual: entry.row.into(),
+                    order: entry.cols.len() as u32,
+                    unknown_1: unknown(0),
+                    unknown_2: unknown(1),
+                    unknown_3: unknown(2),
+                    flags,
+                }
+            })
+            .collect()
+    }
+
     pub fn collapsible(&self) -> Vec<OsdiNodePair> {
         self.module
             .node_collapse
@@ -326,6 +353,113 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
             .collect()
     }
 
+    /// Noise tables of all noise sources, sources that are not
+    /// tables are represented by an empty table.
+    pub fn noise_tables(&self) -> Vec<OsdiNoiseTable> {
+        self.module
+            .dae_system
+            .noise_sources
+            .iter()
+            .map(|source| match source.kind {
+                NoiseSourceKind::NoiseTable { ref vals, .. } => OsdiNoiseTable {
+                    num_points: vals.len() as u32,
+                    freq: vals.iter().map(|(f, _)| (*f).into()).collect(),
+                    pwr: vals.iter().map(|(_, pwr)| (*pwr).into()).collect(),
+                },
+                _ => OsdiNoiseTable { num_points: 0, freq: Vec::new(), pwr: Vec::new() },
+            })
+            .collect()
+    }
+
+    /// The instance data offsets of the delay lines created for `absdelay`.
+    pub fn delays(&self, target_data: &LLVMTargetDataRef) -> Vec<OsdiDelay> {
+        let OsdiCompilationUnit { inst_data, module, cx, .. } = self;
+        let Some(history_elem) = inst_data.delay_history_elem() else {
+            return Vec::new();
+        };
+        let offset = |elem| unsafe {
+            LLVMOffsetOfElement(*target_data, NonNull::from(inst_data.ty).as_ptr(), elem) as u32
+        };
+        let history_offset = offset(history_elem);
+        // the history is an array of pointers
+        let history_stride =
+            unsafe { LLVMABISizeOfType(*target_data, NonNull::from(cx.ty_ptr()).as_ptr()) as u32 };
+        module
+            .intern
+            .delays
+            .iter_enumerated()
+            .map(|(delay, &equation)| {
+                let (input_elem, delay_elem) = inst_data.delay_elems(delay);
+                let unknown = module
+                    .dae_system
+                    .unknowns
+                    .index(&SimUnknownKind::Implicit(equation))
+                    .map_or(u32::MAX, u32::from);
+                OsdiDelay {
+                    unknown,
+                    input_offset: offset(input_elem),
+                    delay_offset: offset(delay_elem),
+                    history_offset: history_offset + history_stride * u32::from(delay),
+                }
+            })
+            .collect()
+    }
+
+    /// The `NODE_FLAG_*` flags and the alias target (empty if the node is not an alias)
+    /// of each node (including implicit unknowns).
+    pub fn node_aliases(&self) -> (Vec<u32>, Vec<String>) {
+        let module = &self.module;
+        module
+            .dae_system
+            .unknowns
+            .iter()
+            .map(|unknown| {
+                let alias = match *unknown {
+                    SimUnknownKind::KirchoffLaw(node) => module.intern.node_aliases.get(&node),
+                    SimUnknownKind::Implicit(equation)
+                        if matches!(
+                            module.intern.implicit_equations[equation],
+                            ImplicitEquationKind::Absdelay(_)
+                        ) =>
+                    {
+                        return (NODE_FLAG_DELAY, String::new())
+                    }
+                    _ => None,
+                };
+                match alias {
+                    Some(NodeAlias { target, port: false }) => (NODE_FLAG_ALIAS, target.clone()),
+                    Some(NodeAlias { target, port: true }) => {
+                        (NODE_FLAG_ALIAS | NODE_FLAG_PORT_ALIAS, target.clone())
+                    }
+                    None => (0, String::new()),
+                }
+            })
+            .unzip()
+    }
+
+    /// The OSDI parameter ids of the parameters marked for sensitivity analysis.
+    pub fn sensitivity_params(&self) -> Vec<u32> {
+        let OsdiCompilationUnit { inst_data, model_data, module, .. } = self;
+        module
+            .dae_system
+            .param_sensitivities
+            .iter()
+            .map(|sensitivity| {
+                let param = sensitivity.param;
+                if let Some(id) = inst_data.params.get_index_of(&OsdiInstanceParam::User(param)) {
+                    return id as u32;
+                }
+                let pos = model_data
+                    .params
+                    .keys()
+                    .filter(|param| !module.info.params[*param].is_instance)
+                    .position(|it| *it == param)
+                    .expect("sensitivity parameter is neither an instance nor a model parameter");
+                (inst_data.params.len() + pos) as u32
+            })
+            .collect()
+    }
+
     pub fn unknown_residual_natures(
         &self,
         db: &CompilationDB,
@@ -335,7 +469,7 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
         for (idx, &unknown) in self.module.dae_system.unknowns.iter_enumerated() {
             let residual_nature_kind = self.module.dae_system.residual[idx].nature_kind;
             let (_, _, _, _, unknown_natref, residual_natref) =
-                sim_unknown_info(unknown, residual_nature_kind, db);
+                sim_unknown_info(unknown, residual_nature_kind, self.module.intern, db);
             uvec.push(unknown_natref);
             rvec.push(residual_natref);
         }
@@ -370,6 +504,10 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                 LLVMOffsetOfElement(*target_data, NonNull::from(inst_data.ty).as_ptr(), elem) as u32
             });
 
+            let random_seed_offset = inst_data.random_state_elem().map_or(u32::MAX, |elem| {
+                LLVMOffsetOfElement(*target_data, NonNull::from(inst_data.ty).as_ptr(), elem) as u32
+            });
+
             let state_idx_off =
                 LLVMOffsetOfElement(*target_data, NonNull::from(inst_data.ty).as_ptr(), STATE_IDX)
                     as u32;
@@ -396,10 +534,23 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                     .map(|(src, eval_outputs)| match src.kind {
                         NoiseSourceKind::WhiteNoise { .. } => NOISE_TYPE_WHITE,
                         NoiseSourceKind::FlickerNoise { .. } => NOISE_TYPE_FLICKER,
-                        NoiseSourceKind::NoiseTable { .. } => NOISE_TYPE_TABLE,
+                        NoiseSourceKind::NoiseTable { log: false, .. } => NOISE_TYPE_TABLE,
+                        NoiseSourceKind::NoiseTable { log: true, .. } => NOISE_TYPE_TABLE_LOG,
                     })
                     .collect();
 
+            let noise_tables = self.noise_tables();
+            let delays = self.delays(target_data);
+            let (node_flags, node_alias) = self.node_aliases();
+            let ac_stim_names: Vec<_> = module
+                .dae_system
+                .ac_excitations
+                .iter()
+                .map(|excitation| cx.literals.resolve(&excitation.analysis).to_owned())
+                .collect();
+
+            let sensitivity_params = self.sensitivity_params();
+
             let (uvec, rvec) = self.unknown_residual_natures(db);
 
             let mut module_flags = 0u32;
@@ -447,7 +598,7 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                 load_jacobian_resist: self.load_jacobian(JacobianLoadType::Resist, false),
                 load_jacobian_react: self.load_jacobian(JacobianLoadType::React, false),
                 load_jacobian_tran: self.load_jacobian(JacobianLoadType::Tran, false),
-                num_states: self.module.intern.lim_state.len() as u32,
+                num_states: self.module.intern.num_states() as u32,
                 load_limit_rhs_resist: self.load_lim_rhs(false),
                 load_limit_rhs_react: self.load_lim_rhs(true),
                 given_flag_model: self.given_flag_model(),
@@ -466,6 +617,28 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                 noise_source_type,
                 load_noise_params: self.load_noise_params(),
                 module_flags,
+                noise_tables,
+                num_delays: delays.len() as u32,
+                delays,
+                num_persistent_states: module.intern.persistent_states.len() as u32,
+                accept_timestep: self.commit_persistent_states(true),
+                reject_timestep: self.commit_persistent_states(false),
+                random_seed_offset,
+                node_flags,
+                node_alias,
+                num_ac_stim: ac_stim_names.len() as u32,
+                ac_stim_names,
+                load_ac_stim: self.load_ac_stim(),
+                num_sensitivity_params: sensitivity_params.len() as u32,
+                sensitivity_params,
+                load_param_sensitivity_resist: self.load_param_sensitivity(false),
+                load_param_sensitivity_react: self.load_param_sensitivity(true),
+                load_opvar_sensitivity: self.load_opvar_sensitivity(),
+                num_higher_order_entries: module.dae_system.higher_order.len() as u32,
+                higher_order_entries: self.higher_order_entries(),
+                load_higher_order_resist: self.load_higher_order(false),
+                load_higher_order_react: self.load_higher_order(true),
+                eval_batch: self.eval_batch_prototype(),
             }
         }
     }
@@ -476,7 +649,7 @@ impl OsdiModule<'_> {
         for (idx, &unknown) in self.dae_system.unknowns.iter_enumerated() {
             let residual_nature_kind = self.dae_system.residual[idx].nature_kind;
             let (name, units, residual_units, _, _, _) =
-                sim_unknown_info(unknown, residual_nature_kind, db);
+                sim_unknown_info(unknown, residual_nature_kind, self.intern, db);
             intern.get_or_intern(&name);
             intern.get_or_intern(&units);
             intern.get_or_intern(&residual_units);
@@ -487,6 +660,7 @@ impl OsdiModule<'_> {
 fn sim_unknown_info(
     unknown: SimUnknownKind,
     residual_nature_kind: ResidualNatureKind,
+    hir_intern: &HirInterner,
     db: &CompilationDB,
 ) -> (String, String, String, bool, OsdiNatureRef, OsdiNatureRef) {
     let name;
@@ -522,7 +696,10 @@ fn sim_unknown_info(
             is_flow = true;
         }
         SimUnknownKind::Implicit(equ) => {
-            name = format!("implicit_equation_{}", u32::from(equ));
+            name = match hir_intern.implicit_unknowns.get(&equ) {
+                Some(unknown) => unknown.name.clone(),
+                None => format!("implicit_equation_{}", u32::from(equ)),
+            };
             discipline = None;
             is_flow = false;
         }
@@ -535,6 +712,23 @@ fn sim_unknown_info(
     // Get units
     let mut units = String::default();
     let mut residual_units = String::default();
+    if let SimUnknownKind::Implicit(equ) = unknown {
+        // Implicit unknowns reference their natures directly
+        let nature_ref = |nature: Nature| OsdiNatureRef {
+            ref_type: NATREF_NATURE,
+            index: nda_table.nature_name_map.get(&nature.name(db)).unwrap().into_raw(),
+        };
+        if let Some(unknown) = hir_intern.implicit_unknowns.get(&equ) {
+            if let Some(nature) = unknown.nature {
+                units = nature.units(db);
+                unknown_nature = nature_ref(nature);
+            }
+            if let Some(nature) = unknown.residual_nature {
+                residual_units = nature.units(db);
+                residual_nature = nature_ref(nature);
+            }
+        }
+    }
     if let Some(discipline) = discipline {
         // Discipline index
         let ndx = nda_table.discipline_name_map.get(&discipline.name(db)).unwrap().into_raw();
@@ -65,10 +65,16 @@ pub const ANALYSIS_TRAN: u32 = 8192;
 pub const ANALYSIS_IC: u32 = 16384;
 pub const ANALYSIS_STATIC: u32 = 32768;
 pub const ANALYSIS_NODESET: u32 = 65536;
+pub const CALC_AC_STIM: u32 = 131072;
+pub const CALC_PARAM_SENSITIVITY: u32 = 262144;
+pub const CALC_HIGHER_ORDER: u32 = 524288;
 pub const EVAL_RET_FLAG_LIM: u32 = 1;
 pub const EVAL_RET_FLAG_FATAL: u32 = 2;
 pub const EVAL_RET_FLAG_FINISH: u32 = 4;
 pub const EVAL_RET_FLAG_STOP: u32 = 8;
+pub const EVAL_RET_FLAG_REJECT: u32 = 16;
+pub const EVAL_RET_FLAG_DISCONTINUITY: u32 = 32;
+pub const EVAL_RET_DISCONTINUITY_ORDER_SHIFT: u32 = 16;
 pub const LOG_LVL_MASK: u32 = 7;
 pub const LOG_LVL_DEBUG: u32 = 0;
 pub const LOG_LVL_DISPLAY: u32 = 1;
@@ -91,7 +97,11 @@ pub const DOMAIN_CONTINUOUS: u32 = 2;
 pub const NOISE_TYPE_WHITE: u32 = 0;
 pub const NOISE_TYPE_FLICKER: u32 = 1;
 pub const NOISE_TYPE_TABLE: u32 = 2;
+pub const NOISE_TYPE_TABLE_LOG: u32 = 3;
 pub const MODULEFLAG_ABSTIME: u32 = 1;
+pub const NODE_FLAG_ALIAS: u32 = 1;
+pub const NODE_FLAG_PORT_ALIAS: u32 = 2;
+pub const NODE_FLAG_DELAY: u32 = 4;
 
 pub struct OsdiLimFunction<'ll> {
     pub name: String,
@@ -236,6 +246,41 @@ impl OsdiTyBuilder<'_, '_, '_> {
         self.osdi_jacobian_entry = Some(ty);
     }
 }
+pub struct OsdiHigherOrderEntry {
+    pub residual: u32,
+    pub order: u32,
+    pub unknown_1: u32,
+    pub unknown_2: u32,
+    pub unknown_3: u32,
+    pub flags: u32,
+}
+impl OsdiHigherOrderEntry {
+    pub fn to_ll_val<'ll>(
+        &self,
+        ctx: &CodegenCx<'_, 'll>,
+        tys: &'ll OsdiTys,
+    ) -> &'ll llvm_sys::LLVMValue {
+        let fields = [
+            ctx.const_unsigned_int(self.residual),
+            ctx.const_unsigned_int(self.order),
+            ctx.const_unsigned_int(self.unknown_1),
+            ctx.const_unsigned_int(self.unknown_2),
+            ctx.const_unsigned_int(self.unknown_3),
+            ctx.const_unsigned_int(self.flags),
+        ];
+        let ty = tys.osdi_higher_order_entry;
+        ctx.const_struct(ty, &fields)
+    }
+}
+impl OsdiTyBuilder<'_, '_, '_> {
+    fn osdi_higher_order_entry(&mut self) {
+        let ctx = self.ctx;
+        let fields =
+            [ctx.ty_int(), ctx.ty_int(), ctx.ty_int(), ctx.ty_int(), ctx.ty_int(), ctx.ty_int()];
+        let ty = ctx.ty_struct("OsdiHigherOrderEntry", &fields);
+        self.osdi_higher_order_entry = Some(ty);
+    }
+}
 pub struct OsdiNode {
     pub name: String,
     pub units: String,
@@ -365,6 +410,96 @@ impl OsdiTyBuilder<'_, '_, '_> {
         self.osdi_nature_ref = Some(ty);
     }
 }
+pub struct OsdiNoiseTable {
+    pub num_points: u32,
+    pub freq: Vec<f64>,
+    pub pwr: Vec<f64>,
+}
+impl OsdiNoiseTable {
+    pub fn to_ll_val<'ll>(
+        &self,
+        ctx: &CodegenCx<'_, 'll>,
+        tys: &'ll OsdiTys,
+    ) -> &'ll llvm_sys::LLVMValue {
+        let arr_1: Vec<_> = self.freq.iter().map(|it| ctx.const_real(*it)).collect();
+        let arr_2: Vec<_> = self.pwr.iter().map(|it| ctx.const_real(*it)).collect();
+        let fields = [
+            ctx.const_unsigned_int(self.num_points),
+            ctx.const_arr_ptr(ctx.ty_double(), &arr_1),
+            ctx.const_arr_ptr(ctx.ty_double(), &arr_2),
+        ];
+        let ty = tys.osdi_noise_table;
+        ctx.const_struct(ty, &fields)
+    }
+}
+impl OsdiTyBuilder<'_, '_, '_> {
+    fn osdi_noise_table(&mut self) {
+        let ctx = self.ctx;
+        let fields = [ctx.ty_int(), ctx.ty_ptr(), ctx.ty_ptr()];
+        let ty = ctx.ty_struct("OsdiNoiseTable", &fields);
+        self.osdi_noise_table = Some(ty);
+    }
+}
+pub struct OsdiDelayHistory {
+    pub len: u32,
+    pub time: Vec<f64>,
+    pub val: Vec<f64>,
+}
+impl OsdiDelayHistory {
+    pub fn to_ll_val<'ll>(
+        &self,
+        ctx: &CodegenCx<'_, 'll>,
+        tys: &'ll OsdiTys,
+    ) -> &'ll llvm_sys::LLVMValue {
+        let arr_1: Vec<_> = self.time.iter().map(|it| ctx.const_real(*it)).collect();
+        let arr_2: Vec<_> = self.val.iter().map(|it| ctx.const_real(*it)).collect();
+        let fields = [
+            ctx.const_unsigned_int(self.len),
+            ctx.const_arr_ptr(ctx.ty_double(), &arr_1),
+            ctx.const_arr_ptr(ctx.ty_double(), &arr_2),
+        ];
+        let ty = tys.osdi_delay_history;
+        ctx.const_struct(ty, &fields)
+    }
+}
+impl OsdiTyBuilder<'_, '_, '_> {
+    fn osdi_delay_history(&mut self) {
+        let ctx = self.ctx;
+        let fields = [ctx.ty_int(), ctx.ty_ptr(), ctx.ty_ptr()];
+        let ty = ctx.ty_struct("OsdiDelayHistory", &fields);
+        self.osdi_delay_history = Some(ty);
+    }
+}
+pub struct OsdiDelay {
+    pub unknown: u32,
+    pub input_offset: u32,
+    pub delay_offset: u32,
+    pub history_offset: u32,
+}
+impl OsdiDelay {
+    pub fn to_ll_val<'ll>(
+        &self,
+        ctx: &CodegenCx<'_, 'll>,
+        tys: &'ll OsdiTys,
+    ) -> &'ll llvm_sys::LLVMValue {
+        let fields = [
+            ctx.const_unsigned_int(self.unknown),
+            ctx.const_unsigned_int(self.input_offset),
+            ctx.const_unsigned_int(self.delay_offset),
+            ctx.const_unsigned_int(self.history_offset),
+        ];
+        let ty = tys.osdi_delay;
+        ctx.const_struct(ty, &fields)
+    }
+}
+impl OsdiTyBuilder<'_, '_, '_> {
+    fn osdi_delay(&mut self) {
+        let ctx = self.ctx;
+        let fields = [ctx.ty_int(), ctx.ty_int(), ctx.ty_int(), ctx.ty_int()];
+        let ty = ctx.ty_struct("OsdiDelay", &fields);
+        self.osdi_delay = Some(ty);
+    }
+}
 pub struct OsdiDescriptor<'ll> {
     pub name: String,
     pub num_nodes: u32,
@@ -417,6 +552,28 @@ pub struct OsdiDescriptor<'ll> {
     pub noise_source_type: Vec<u32>,
     pub load_noise_params: &'ll llvm_sys::LLVMValue,
     pub module_flags: u32,
+    pub noise_tables: Vec<OsdiNoiseTable>,
+    pub num_delays: u32,
+    pub delays: Vec<OsdiDelay>,
+    pub num_persistent_states: u32,
+    pub accept_timestep: &'ll llvm_sys::LLVMValue,
+    pub reject_timestep: &'ll llvm_sys::LLVMValue,
+    pub random_seed_offset: u32,
+    pub node_flags: Vec<u32>,
+    pub node_alias: Vec<String>,
+    pub num_ac_stim: u32,
+    pub ac_stim_names: Vec<String>,
+    pub load_ac_stim: &'ll llvm_sys::LLVMValue,
+    pub num_sensitivity_params: u32,
+    pub sensitivity_params: Vec<u32>,
+    pub load_param_sensitivity_resist: &'ll llvm_sys::LLVMValue,
+    pub load_param_sensitivity_react: &'ll llvm_sys::LLVMValue,
+    pub load_opvar_sensitivity: &'ll llvm_sys::LLVMValue,
+    pub num_higher_order_entries: u32,
+    pub higher_order_entries: Vec<OsdiHigherOrderEntry>,
+    pub load_higher_order_resist: &'ll llvm_sys::LLVMValue,
+    pub load_higher_order_react: &'ll llvm_sys::LLVMValue,
+    pub eval_batch: &'ll llvm_sys::LLVMValue,
 }
 impl<'ll> OsdiDescriptor<'ll> {
     pub fn to_ll_val(
@@ -434,6 +591,17 @@ impl<'ll> OsdiDescriptor<'ll> {
         let arr_47: Vec<_> = self.residual_nature.iter().map(|it| it.to_ll_val(ctx, tys)).collect();
         let arr_48: Vec<_> =
             self.noise_source_type.iter().map(|it| ctx.const_unsigned_int(*it)).collect();
+        let arr_51: Vec<_> = self.noise_tables.iter().map(|it| it.to_ll_val(ctx, tys)).collect();
+        let arr_53: Vec<_> = self.delays.iter().map(|it| it.to_ll_val(ctx, tys)).collect();
+        let arr_58: Vec<_> = self.node_flags.iter().map(|it| ctx.const_unsigned_int(*it)).collect();
+        let arr_59: Vec<_> =
+            self.node_alias.iter().map(|it| ctx.const_str_uninterned(it)).collect();
+        let arr_61: Vec<_> =
+            self.ac_stim_names.iter().map(|it| ctx.const_str_uninterned(it)).collect();
+        let arr_64: Vec<_> =
+            self.sensitivity_params.iter().map(|it| ctx.const_unsigned_int(*it)).collect();
+        let arr_69: Vec<_> =
+            self.higher_order_entries.iter().map(|it| it.to_ll_val(ctx, tys)).collect();
         let fields = [
             ctx.const_str_uninterned(&self.name),
             ctx.const_unsigned_int(self.num_nodes),
@@ -486,6 +654,28 @@ impl<'ll> OsdiDescriptor<'ll> {
             ctx.const_arr_ptr(ctx.ty_int(), &arr_48),
             self.load_noise_params,
             ctx.const_unsigned_int(self.module_flags),
+            ctx.const_arr_ptr(tys.osdi_noise_table, &arr_51),
+            ctx.const_unsigned_int(self.num_delays),
+            ctx.const_arr_ptr(tys.osdi_delay, &arr_53),
+            ctx.const_unsigned_int(self.num_persistent_states),
+            self.accept_timestep,
+            self.reject_timestep,
+            ctx.const_unsigned_int(self.random_seed_offset),
+            ctx.const_arr_ptr(ctx.ty_int(), &arr_58),
+            ctx.const_arr_ptr(ctx.ty_ptr(), &arr_59),
+            ctx.const_unsigned_int(self.num_ac_stim),
+            ctx.const_arr_ptr(ctx.ty_ptr(), &arr_61),
+            self.load_ac_stim,
+            ctx.const_unsigned_int(self.num_sensitivity_params),
+            ctx.const_arr_ptr(ctx.ty_int(), &arr_64),
+            self.load_param_sensitivity_resist,
+            self.load_param_sensitivity_react,
+            self.load_opvar_sensitivity,
+            ctx.const_unsigned_int(self.num_higher_order_entries),
+            ctx.const_arr_ptr(tys.osdi_higher_order_entry, &arr_69),
+            self.load_higher_order_resist,
+            self.load_higher_order_react,
+            self.eval_batch,
         ];
         let ty = tys.osdi_descriptor;
         ctx.const_struct(ty, &fields)
@@ -546,6 +736,28 @@ impl OsdiTyBuilder<'_, '_, '_> {
             ctx.ty_ptr(),
             ctx.ty_ptr(),
             ctx.ty_int(),
+            ctx.ty_ptr(),
+            ctx.ty_int(),
+            ctx.ty_ptr(),
+            ctx.ty_int(),
+            ctx.ty_ptr(),
+            ctx.ty_ptr(),
+            ctx.ty_int(),
+            ctx.ty_ptr(),
+            ctx.ty_ptr(),
+            ctx.ty_int(),
+            ctx.ty_ptr(),
+            ctx.ty_ptr(),
+            ctx.ty_int(),
+            ctx.ty_ptr(),
+            ctx.ty_ptr(),
+            ctx.ty_ptr(),
+            ctx.ty_ptr(),
+            ctx.ty_int(),
+            ctx.ty_ptr(),
+            ctx.ty_ptr(),
+            ctx.ty_ptr(),
+            ctx.ty_ptr(),
         ];
         let ty = ctx.ty_struct("OsdiDescriptor", &fields);
         self.osdi_descriptor = Some(ty);
@@ -730,10 +942,14 @@ pub struct OsdiTys<'ll> {
     pub osdi_init_info: &'ll llvm_sys::LLVMType,
     pub osdi_node_pair: &'ll llvm_sys::LLVMType,
     pub osdi_jacobian_entry: &'ll llvm_sys::LLVMType,
+    pub osdi_higher_order_entry: &'ll llvm_sys::LLVMType,
     pub osdi_node: &'ll llvm_sys::LLVMType,
     pub osdi_param_opvar: &'ll llvm_sys::LLVMType,
     pub osdi_noise_source: &'ll llvm_sys::LLVMType,
     pub osdi_nature_ref: &'ll llvm_sys::LLVMType,
+    pub osdi_noise_table: &'ll llvm_sys::LLVMType,
+    pub osdi_delay_history: &'ll llvm_sys::LLVMType,
+    pub osdi_delay: &'ll llvm_sys::LLVMType,
     pub osdi_descriptor: &'ll llvm_sys::LLVMType,
     pub osdi_nature: &'ll llvm_sys::LLVMType,
     pub osdi_discipline: &'ll llvm_sys::LLVMType,
@@ -753,10 +969,14 @@ impl<'ll> OsdiTys<'ll> {
             osdi_init_info: None,
             osdi_node_pair: None,
             osdi_jacobian_entry: None,
+            osdi_higher_order_entry: None,
             osdi_node: None,
             osdi_param_opvar: None,
             osdi_noise_source: None,
             osdi_nature_ref: None,
+            osdi_noise_table: None,
+            osdi_delay_history: None,
+            osdi_delay: None,
             osdi_descriptor: None,
             osdi_nature: None,
             osdi_discipline: None,
@@ -771,10 +991,14 @@ impl<'ll> OsdiTys<'ll> {
         builder.osdi_init_info();
         builder.osdi_node_pair();
         builder.osdi_jacobian_entry();
+        builder.osdi_higher_order_entry();
         builder.osdi_node();
         builder.osdi_param_opvar();
         builder.osdi_noise_source();
         builder.osdi_nature_ref();
+        builder.osdi_noise_table();
+        builder.osdi_delay_history();
+        builder.osdi_delay();
         builder.osdi_descriptor();
         builder.osdi_nature();
         builder.osdi_discipline();
@@ -794,10 +1018,14 @@ struct OsdiTyBuilder<'a, 'b, 'll> {
     osdi_init_info: Option<&'ll llvm_sys::LLVMType>,
     osdi_node_pair: Option<&'ll llvm_sys::LLVMType>,
     osdi_jacobian_entry: Option<&'ll llvm_sys::LLVMType>,
+    osdi_higher_order_entry: Option<&'ll llvm_sys::LLVMType>,
     osdi_node: Option<&'ll llvm_sys::LLVMType>,
     osdi_param_opvar: Option<&'ll llvm_sys::LLVMType>,
     osdi_noise_source: Option<&'ll llvm_sys::LLVMType>,
     osdi_nature_ref: Option<&'ll llvm_sys::LLVMType>,
+    osdi_noise_table: Option<&'ll llvm_sys::LLVMType>,
+    osdi_delay_history: Option<&'ll llvm_sys::LLVMType>,
+    osdi_delay: Option<&'ll llvm_sys::LLVMType>,
     osdi_descriptor: Option<&'ll llvm_sys::LLVMType>,
     osdi_nature: Option<&'ll llvm_sys::LLVMType>,
     osdi_discipline: Option<&'ll llvm_sys::LLVMType>,
@@ -815,10 +1043,14 @@ impl<'ll> OsdiTyBuilder<'_, '_, 'll> {
             osdi_init_info: self.osdi_init_info.unwrap(),
             osdi_node_pair: self.osdi_node_pair.unwrap(),
             osdi_jacobian_entry: self.osdi_jacobian_entry.unwrap(),
+            osdi_higher_order_entry: self.osdi_higher_order_entry.unwrap(),
             osdi_node: self.osdi_node.unwrap(),
             osdi_param_opvar: self.osdi_param_opvar.unwrap(),
             osdi_noise_source: self.osdi_noise_source.unwrap(),
             osdi_nature_ref: self.osdi_nature_ref.unwrap(),
+            osdi_noise_table: self.osdi_noise_table.unwrap(),
+            osdi_delay_history: self.osdi_delay_history.unwrap(),
+            osdi_delay: self.osdi_delay.unwrap(),
             osdi_descriptor: self.osdi_descriptor.unwrap(),
             osdi_nature: self.osdi_nature.unwrap(),
             osdi_discipline: self.osdi_discipline.unwrap(),
@@ -2,14 +2,15 @@ use core::ptr::NonNull;
 use std::hash::BuildHasherDefault;
 
 use hir::{CompilationDB, Parameter};
-use indexmap::IndexMap;
+use hir_lower::{CallBackKind, TableModel};
+use indexmap::{IndexMap, IndexSet};
 use llvm_sys::core::{LLVMBuildLoad2, LLVMBuildStore, LLVMBuildStructGEP2};
 use llvm_sys::LLVMValue as Value;
 use mir_llvm::{CodegenCx, MemLoc, UNNAMED};
 use rustc_hash::FxHasher;
 
 use crate::compilation_unit::OsdiModule;
-use crate::inst_data::{OsdiInstanceData, OsdiInstanceParam};
+use crate::inst_data::{num_string_slots, OsdiInstanceData, OsdiInstanceParam};
 use crate::{bitfield, lltype};
 
 const NUM_CONST_FIELDS: u32 = 1;
@@ -17,6 +18,11 @@ const NUM_CONST_FIELDS: u32 = 1;
 pub struct OsdiModelData<'ll> {
     pub param_given: &'ll llvm_sys::LLVMType,
     pub params: IndexMap<Parameter, &'ll llvm_sys::LLVMType, BuildHasherDefault<FxHasher>>,
+    /// The tables of `$table_model`, each stored as a pointer to the table loaded by setup_model
+    pub tables: IndexSet<TableModel, BuildHasherDefault<FxHasher>>,
+    /// The number of strings created by setup_model (see [`OsdiInstanceData::string_slots`]),
+    /// stored after the tables
+    pub num_string_slots: u32,
     pub ty: &'ll llvm_sys::LLVMType,
 }
 
@@ -39,15 +45,86 @@ impl<'ll> OsdiModelData<'ll> {
 
         let param_given = bitfield::arr_ty((inst_params.len() + params.len()) as u32, cx);
 
+        let callbacks = cgunit
+            .intern
+            .callbacks
+            .iter()
+            .chain(cgunit.init.intern.callbacks.iter())
+            .chain(cgunit.model_param_intern.callbacks.iter());
+        let tables = callbacks
+            .filter_map(|kind| match kind {
+                CallBackKind::TableModel { table, .. } => Some((**table).clone()),
+                _ => None,
+            })
+            .collect();
+
         let mut fields: Vec<_> = vec![param_given];
         fields.extend(params.values().copied());
         fields.extend(inst_params.values());
+        fields.extend((0..tables.len()).map(|_| cx.ty_ptr()));
+        let num_string_slots = num_string_slots(cgunit.model_param_intern);
+        fields.extend((0..num_string_slots).map(|_| cx.ty_ptr()));
 
         let name = &cgunit.sym;
         let name = format!("osdi_model_data_{name}");
         let ty = cx.ty_struct(&name, &fields);
 
-        OsdiModelData { param_given, params, ty }
+        OsdiModelData { param_given, params, tables, num_string_slots, ty }
+    }
+
+    pub unsafe fn table_ptr(
+        &self,
+        inst_data: &OsdiInstanceData<'ll>,
+        table: &TableModel,
+        ptr: &'ll llvm_sys::LLVMValue,
+        llbuilder: &llvm_sys::LLVMBuilder,
+    ) -> &'ll llvm_sys::LLVMValue {
+        let pos = self.tables.get_index_of(table).unwrap();
+        // tables are stored after the instance parameters
+        let elem = NUM_CONST_FIELDS + (self.params.len() + inst_data.params.len() + pos) as u32;
+        &*LLVMBuildStructGEP2(
+            NonNull::from(llbuilder).as_ptr(),
+            NonNull::from(self.ty).as_ptr(),
+            NonNull::from(ptr).as_ptr(),
+            elem,
+            UNNAMED,
+        )
+    }
+
+    pub unsafe fn string_slot_ptr(
+        &self,
+        inst_data: &OsdiInstanceData<'ll>,
+        slot: u32,
+        ptr: &'ll llvm_sys::LLVMValue,
+        llbuilder: &llvm_sys::LLVMBuilder,
+    ) -> &'ll llvm_sys::LLVMValue {
+        let elem = NUM_CONST_FIELDS
+            + (self.params.len() + inst_data.params.len() + self.tables.len()) as u32
+            + slot;
+        &*LLVMBuildStructGEP2(
+            NonNull::from(llbuilder).as_ptr(),
+            NonNull::from(self.ty).as_ptr(),
+            NonNull::from(ptr).as_ptr(),
+            elem,
+            UNNAMED,
+        )
+    }
+
+    pub unsafe fn read_table(
+        &self,
+        cx: &CodegenCx<'_, 'll>,
+        inst_data: &OsdiInstanceData<'ll>,
+        table: &TableModel,
+        ptr: &'ll llvm_sys::LLVMValue,
+        llbuilder: &llvm_sys::LLVMBuilder,
+    ) -> &'ll llvm_sys::LLVMValue {
+        let ptr = self.table_ptr(inst_data, table, ptr, llbuilder);
+        &*LLVMBuildLoad2(
+            NonNull::from(llbuilder).as_ptr(),
+            NonNull::from(cx.ty_ptr()).as_ptr(),
+            NonNull::from(ptr).as_ptr(),
+            UNNAMED,
+        )
     }
 
     pub fn nth_param_loc(
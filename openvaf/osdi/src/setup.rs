@@ -2,9 +2,9 @@ use core::ptr::NonNull;
 
 use hir_lower::{CallBackKind, ParamInfoKind, ParamKind, PlaceKind};
 use llvm_sys::core::{
-    LLVMAppendBasicBlockInContext, LLVMBuildBr, LLVMBuildCondBr, LLVMBuildRetVoid,
-    LLVMCreateBuilderInContext, LLVMDisposeBuilder, LLVMGetInsertBlock, LLVMGetLastInstruction,
-    LLVMGetParam, LLVMPositionBuilder, LLVMPositionBuilderAtEnd,
+    LLVMAppendBasicBlockInContext, LLVMBuildBr, LLVMBuildCondBr, LLVMBuildExtractValue,
+    LLVMBuildRetVoid, LLVMCreateBuilderInContext, LLVMDisposeBuilder, LLVMGetInsertBlock,
+    LLVMGetLastInstruction, LLVMGetParam, LLVMPositionBuilder, LLVMPositionBuilderAtEnd,
 };
 use llvm_sys::prelude::LLVMBasicBlockRef;
 use llvm_sys::LLVMIntPredicate::LLVMIntSLT;
@@ -15,8 +15,12 @@ use mir_llvm::{
 };
 use sim_back::SimUnknownKind;
 
-use crate::compilation_unit::{general_callbacks, OsdiCompilationUnit};
-use crate::inst_data::OsdiInstanceParam;
+use crate::compilation_unit::{
+    general_callbacks, load_tables, random_callback, string_callback, table_callback,
+    OsdiCompilationUnit,
+};
+use crate::inst_data::{array_elem_ptr, OsdiInstanceParam};
+use crate::lltype;
 
 use std::collections::HashMap;
 
@@ -138,7 +142,7 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
 
     pub fn setup_model(&self) -> &'ll llvm_sys::LLVMValue {
         let llfunc = self.setup_model_prototype();
-        let OsdiCompilationUnit { inst_data, model_data, tys, cx, .. } = self;
+        let OsdiCompilationUnit { inst_data, model_data, tys, cx, db, .. } = self;
 
         let func = &self.module.model_param_setup;
         let intern = &self.module.model_param_intern;
@@ -157,9 +161,18 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
         for (i, param) in model_data.params.keys().copied().enumerate() {
             let i = i as u32;
 
-            let dst = intern.params.unwrap_index(&ParamKind::Param(param));
-            let loc = unsafe { model_data.nth_param_loc(cx, i, &*model) };
-            builder.params[dst] = BuilderVal::Load(Box::new(loc));
+            if let Some(range) = param.array_range(db) {
+                let elem_ty = lltype(param.ty(db).base_type(), cx);
+                for pos in 0..range.num_elements() {
+                    let dst = intern.params.unwrap_index(&ParamKind::ParamElement(param, pos));
+                    let loc = unsafe { model_data.nth_param_loc(cx, i, &*model) };
+                    builder.params[dst] = loc.array_elem(pos, elem_ty, cx).into();
+                }
+            } else {
+                let dst = intern.params.unwrap_index(&ParamKind::Param(param));
+                let loc = unsafe { model_data.nth_param_loc(cx, i, &*model) };
+                builder.params[dst] = BuilderVal::Load(Box::new(loc));
+            }
 
             let dst = intern.params.unwrap_index(&ParamKind::ParamGiven { param });
             let is_given =
@@ -196,9 +209,18 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                 }
                 OsdiInstanceParam::User(param) => {
                     // Debug: Processing OsdiInstanceParam::User
-                    let dst = intern.params.unwrap_index(&ParamKind::Param(param));
-                    // Debug: Destination index for user param: dst
-                    builder.params[dst] = BuilderVal::Eager(val);
+                    if let Some(range) = param.array_range(db) {
+                        for pos in 0..range.num_elements() {
+                            let dst =
+                                intern.params.unwrap_index(&ParamKind::ParamElement(param, pos));
+                            let elem = unsafe { extract_elem(val, pos, builder.llbuilder) };
+                            builder.params[dst] = BuilderVal::Eager(elem);
+                        }
+                    } else {
+                        let dst = intern.params.unwrap_index(&ParamKind::Param(param));
+                        // Debug: Destination index for user param: dst
+                        builder.params[dst] = BuilderVal::Eager(val);
+                    }
                     let dst = intern.params.unwrap_index(&ParamKind::ParamGiven { param });
                     builder.params[dst] = BuilderVal::Eager(is_given);
                 }
@@ -253,6 +275,22 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
             }
         }
 
+        unsafe { load_tables(cx, model_data, inst_data, &*handle, &*model, &builder) };
+        for (call_id, call) in intern.callbacks.iter_enumerated() {
+            if let CallBackKind::TableModel { table, .. } = call {
+                let table = unsafe {
+                    model_data.read_table(cx, inst_data, table, &*model, builder.llbuilder)
+                };
+                builder.callbacks[call_id] = table_callback(cx, call, unsafe { &*handle }, table);
+            }
+            if let Some(slot) = call.string_slot() {
+                let slot = unsafe {
+                    model_data.string_slot_ptr(inst_data, slot, &*model, builder.llbuilder)
+                };
+                builder.callbacks[call_id] = string_callback(cx, call, unsafe { &*handle }, slot);
+            }
+        }
+
         unsafe {
             builder.build_consts();
             builder.build_func();
@@ -269,6 +307,23 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
 
         // store model parameters to reflect defaulted values
         for (i, param) in model_data.params.keys().enumerate() {
+            if let Some(range) = param.array_range(db) {
+                for pos in 0..range.num_elements() {
+                    let val =
+                        intern.outputs[&PlaceKind::ParamElement(*param, pos)].unwrap_unchecked();
+                    let inst = func.dfg.value_def(val).unwrap_inst();
+                    let bb = func.layout.inst_block(inst).unwrap();
+                    builder.select_bb_before_terminator(bb);
+                    unsafe {
+                        let val = builder.values[val].get(&builder);
+                        let (ptr, ty) =
+                            model_data.nth_param_ptr(i as u32, &*model, builder.llbuilder);
+                        let (ptr, _) = array_elem_ptr(cx, ptr, ty, pos, builder.llbuilder);
+                        builder.store(ptr, val);
+                    }
+                }
+                continue;
+            }
             let val = intern.outputs[&PlaceKind::Param(*param)].unwrap_unchecked();
             let inst = func.dfg.value_def(val).unwrap_inst();
             let bb = func.layout.inst_block(inst).unwrap();
@@ -303,6 +358,27 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                         }
                     }
                 }
+                OsdiInstanceParam::User(param) if param.array_range(db).is_some() => {
+                    let range = param.array_range(db).unwrap();
+                    for pos in 0..range.num_elements() {
+                        let val = intern.outputs[&PlaceKind::ParamElement(*param, pos)]
+                            .unwrap_unchecked();
+                        let inst = func.dfg.value_def(val).unwrap_inst();
+                        let bb = func.layout.inst_block(inst).unwrap();
+                        builder.select_bb_before_terminator(bb);
+                        unsafe {
+                            let val = builder.values[val].get(&builder);
+                            let (ptr, ty) = model_data.nth_inst_param_ptr(
+                                inst_data,
+                                i as u32,
+                                &*model,
+                                builder.llbuilder,
+                            );
+                            let (ptr, _) = array_elem_ptr(cx, ptr, ty, pos, builder.llbuilder);
+                            builder.store(ptr, val);
+                        }
+                    }
+                }
                 OsdiInstanceParam::User(param) => {
                     let val = intern.outputs[&PlaceKind::Param(*param)].unwrap_unchecked();
                     let inst = func.dfg.value_def(val).unwrap_inst();
@@ -357,7 +433,7 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
         // Debug: mark_collapsed output: (llfunc, fn_type)
         let llfunc = self.setup_instance_prototype();
         // Debug: setup_instance_prototype output: llfunc
-        let OsdiCompilationUnit { inst_data, model_data, tys, cx, module, .. } = self;
+        let OsdiCompilationUnit { inst_data, model_data, tys, cx, module, db, .. } = self;
 
         let func = &module.init.func;
         let intern = &module.init.intern;
@@ -420,8 +496,17 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                     }
                 }
                 OsdiInstanceParam::User(param) => {
-                    let dst = intern.params.unwrap_index(&ParamKind::Param(param));
-                    builder.params[dst] = BuilderVal::Eager(val);
+                    if let Some(range) = param.array_range(db) {
+                        for pos in 0..range.num_elements() {
+                            let dst =
+                                intern.params.unwrap_index(&ParamKind::ParamElement(param, pos));
+                            let elem = unsafe { extract_elem(val, pos, builder.llbuilder) };
+                            builder.params[dst] = BuilderVal::Eager(elem);
+                        }
+                    } else {
+                        let dst = intern.params.unwrap_index(&ParamKind::Param(param));
+                        builder.params[dst] = BuilderVal::Eager(val);
+                    }
                     // Store in instance structure to reflect defaulted value
                     unsafe {
                         inst_data.store_nth_param(i, instance, val, builder.llbuilder);
@@ -439,6 +524,15 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                 let loc = model_data.nth_param_loc(cx, i, model);
                 builder.params[dst] = BuilderVal::Load(Box::new(loc));
             }
+            if let Some(range) = param.array_range(db) {
+                let elem_ty = lltype(param.ty(db).base_type(), cx);
+                for pos in 0..range.num_elements() {
+                    if let Some(dst) = intern.params.index(&ParamKind::ParamElement(param, pos)) {
+                        let loc = model_data.nth_param_loc(cx, i, model);
+                        builder.params[dst] = loc.array_elem(pos, elem_ty, cx).into();
+                    }
+                }
+            }
 
             if let Some(dst) = intern.params.index(&ParamKind::ParamGiven { param }) {
                 let is_given =
@@ -472,6 +566,16 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
         unsafe { inst_data.store_temperature(&mut builder, instance, &*temperature) };
         unsafe { inst_data.store_connected_ports(&mut builder, instance, &*connected_terminals) };
 
+        // restart the random number streams from the seeds set by the simulator
+        let random_state = unsafe { inst_data.random_state_ptr(instance, builder.llbuilder) };
+        if let Some(random_state) = random_state {
+            let fun = cx
+                .get_func_by_name("osdi_random_init")
+                .expect("stdlib function osdi_random_init is missing");
+            let fun_ty = cx.ty_func(&[cx.ty_ptr()], cx.ty_void());
+            unsafe { builder.call(fun_ty, fun, &[random_state]) };
+        }
+
         // Debug: Setting up trivial callbacks
         let trivial_cb = cx.trivial_callbacks(&[]);
         // Debug: trivial_cb initialized
@@ -516,6 +620,22 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
                         CallbackFun::Prebuilt(trivial_cb.clone())
                     }
                 }
+                CallBackKind::Random { .. } | CallBackKind::RandomStream { .. } => {
+                    random_callback(cx, call, random_state.unwrap()).unwrap()
+                }
+                CallBackKind::TableModel { table, .. } => {
+                    let table = unsafe {
+                        model_data.read_table(cx, inst_data, table, model, builder.llbuilder)
+                    };
+                    table_callback(cx, call, unsafe { &*handle }, table).unwrap()
+                }
+                CallBackKind::FormatString { .. }
+                | CallBackKind::ScanValue { slot: Some(_), .. } => {
+                    let slot = call.string_slot().unwrap();
+                    let slot =
+                        unsafe { inst_data.string_slot_ptr(cx, slot, instance, builder.llbuilder) };
+                    string_callback(cx, call, unsafe { &*handle }, slot).unwrap()
+                }
                 CallBackKind::CollapseHint(node1, node2) => {
                     let node1 = module
                         .dae_system
@@ -637,3 +757,17 @@ impl<'ll> OsdiCompilationUnit<'_, '_, 'll> {
         llfunc
     }
 }
+
+/// Extracts the element `pos` from the array value `val`.
+unsafe fn extract_elem<'ll>(
+    val: &'ll llvm_sys::LLVMValue,
+    pos: u32,
+    llbuilder: &llvm_sys::LLVMBuilder,
+) -> &'ll llvm_sys::LLVMValue {
+    &*LLVMBuildExtractValue(
+        NonNull::from(llbuilder).as_ptr(),
+        NonNull::from(val).as_ptr(),
+        pos,
+        UNNAMED,
+    )
+}
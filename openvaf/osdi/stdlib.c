@@ -13,7 +13,19 @@ extern void *memcpy (void *__restrict __dest, const void *__restrict __src,
 extern void *malloc (size_t __size);
 extern void *realloc (void *__ptr, size_t __size);
 extern double log(double);
+extern double exp(double);
+extern double sqrt(double);
+extern double cos(double);
 extern int strcmp(const char*, const char*);
+extern void free(void *__ptr);
+typedef struct _IO_FILE FILE;
+extern FILE *fopen(const char *__filename, const char *__modes);
+extern int fclose(FILE *__stream);
+extern int fputs(const char *__s, FILE *__stream);
+extern char *fgets(char *__s, int __n, FILE *__stream);
+extern double strtod(const char *__nptr, char **__endptr);
+extern long strtol(const char *__nptr, char **__endptr, int __base);
+extern char *getenv(const char *__name);
 #define NULL ((void*)0)
 #else
 #include <math.h>
@@ -109,6 +121,45 @@ char *simparam_str(void *params_, void *handle, uint32_t *flags, char *name) {
   return "�";
 }
 
+// Called (if set by the simulator) to read the quantity name of the instance
+// inst for $simprobe. Stores the value in val and returns zero on success or
+// returns a non-zero value if the instance or the quantity does not exist.
+typedef int32_t (*osdi_simprobe_ptr)(void *handle, const char *inst,
+                                     const char *name, double *val);
+extern osdi_simprobe_ptr osdi_simprobe;
+
+static bool probe(void *handle, char *inst, char *name, double *val) {
+  return osdi_simprobe != NULL && osdi_simprobe(handle, inst, name, val) == 0;
+}
+
+double simprobe(void *handle, uint32_t *flags, char *inst, char *name) {
+  double val = 0.0;
+  if (probe(handle, inst, name, &val)) {
+    return val;
+  }
+  *flags |= EVAL_RET_FLAG_FATAL;
+  char *path = concat(inst, ".");
+  char *probe_name = path == NULL ? NULL : concat(path, name);
+  char *msg =
+      probe_name == NULL ? NULL : concat("unknown $simprobe ", probe_name);
+  if (msg == NULL) {
+    osdi_log(handle, "unknown $simprobe %s", LOG_LVL_FATAL | LOG_FMT_ERR);
+  } else {
+    osdi_log(handle, msg, LOG_LVL_FATAL);
+  }
+  free(path);
+  free(probe_name);
+  return 0.0;
+}
+
+double simprobe_opt(void *handle, char *inst, char *name, double default_val) {
+  double val = 0.0;
+  if (probe(handle, inst, name, &val)) {
+    return val;
+  }
+  return default_val;
+}
+
 void push_error(OsdiInitError **dst, uint32_t *len, uint32_t *cap,
                 OsdiInitError err) {
   if (*dst == NULL) {
@@ -184,12 +235,37 @@ void set_ret_flag_finish(int *flags) { *flags |= EVAL_RET_FLAG_FINISH; }
 
 void set_ret_flag_stop(int *flags) { *flags |= EVAL_RET_FLAG_STOP; }
 
+void set_ret_flag_reject(int *flags) { *flags |= EVAL_RET_FLAG_REJECT; }
+
+// the lowest order of all discontinuities reported during an eval call is kept
+void set_ret_flag_discontinuity(int *flags, int order) {
+  if (order < 0) {
+    return;
+  }
+  if (order > 0xffff) {
+    order = 0xffff;
+  }
+  unsigned int old = (unsigned int)*flags;
+  if ((old & EVAL_RET_FLAG_DISCONTINUITY) &&
+      (old >> EVAL_RET_DISCONTINUITY_ORDER_SHIFT) <= (unsigned int)order) {
+    return;
+  }
+  old &= (1u << EVAL_RET_DISCONTINUITY_ORDER_SHIFT) - 1;
+  *flags = (int)(old | EVAL_RET_FLAG_DISCONTINUITY |
+                 ((unsigned int)order << EVAL_RET_DISCONTINUITY_ORDER_SHIFT));
+}
+
 double store_lim(void *sim_info_, int idx, double val) {
   OsdiSimInfo *sim_info = (OsdiSimInfo *)sim_info_;
   sim_info->next_state[idx] = val;
   return val;
 }
 
+double store_persistent(double *dst, double val) {
+  *dst = val;
+  return val;
+}
+
 int analysis(void *sim_info_, char *name) {
   OsdiSimInfo *sim_info = (OsdiSimInfo *)sim_info_;
   uint32_t flags = sim_info->flags;
@@ -212,3 +288,1359 @@ double store_delay(void *sim_info_, double *dst, double val) {
 
   return *dst;
 }
+
+// Looks up the value of a delayed input at abstime - delay in the history
+// recorded by the simulator. If the delayed time lies after the last accepted
+// timepoint the result is interpolated between that timepoint and the current
+// input, *weight receives the weight of the current input in that case
+// (zero otherwise). Outside of transient analysis the delay line is
+// transparent (weight one).
+static double absdelay_lookup(OsdiSimInfo *sim_info, OsdiDelayHistory *hist,
+                              double delay, double *weight) {
+  *weight = 1.0;
+  if (!(sim_info->flags & ANALYSIS_TRAN) || hist == NULL || hist->len == 0 ||
+      delay <= 0.0) {
+    return 0.0;
+  }
+
+  double t = sim_info->abstime;
+  double target = t - delay;
+  uint32_t len = hist->len;
+  double t_last = hist->time[len - 1];
+  if (target >= t_last) {
+    if (t > t_last) {
+      *weight = (target - t_last) / (t - t_last);
+    }
+    return hist->val[len - 1];
+  }
+
+  *weight = 0.0;
+  if (target <= hist->time[0]) {
+    return hist->val[0];
+  }
+
+  // binary search for time[lo] <= target < time[hi]
+  uint32_t lo = 0;
+  uint32_t hi = len - 1;
+  while (hi - lo > 1) {
+    uint32_t mid = lo + (hi - lo) / 2;
+    if (hist->time[mid] <= target) {
+      lo = mid;
+    } else {
+      hi = mid;
+    }
+  }
+
+  double t0 = hist->time[lo], t1 = hist->time[hi];
+  double v0 = hist->val[lo], v1 = hist->val[hi];
+  return v0 + (v1 - v0) * (target - t0) / (t1 - t0);
+}
+
+double absdelay_value(void *sim_info_, void *hist, double delay) {
+  double weight;
+  return absdelay_lookup((OsdiSimInfo *)sim_info_, (OsdiDelayHistory *)hist,
+                         delay, &weight);
+}
+
+double absdelay_weight(void *sim_info_, void *hist, double delay) {
+  double weight;
+  absdelay_lookup((OsdiSimInfo *)sim_info_, (OsdiDelayHistory *)hist, delay,
+                  &weight);
+  return weight;
+}
+
+// Interpolates a noise table (frequencies in ascending order) at frequency f.
+// Outside of the table the first/last power is used. With log_log set the
+// interpolation is linear in the log-log domain (noise_table_log).
+double noise_table_interp(double *freq, double *pwr, uint32_t len, double f,
+                          bool log_log) {
+  if (len == 0) {
+    return 0.0;
+  }
+  if (f <= freq[0]) {
+    return pwr[0];
+  }
+  if (f >= freq[len - 1]) {
+    return pwr[len - 1];
+  }
+
+  // binary search for freq[lo] <= f < freq[hi]
+  uint32_t lo = 0;
+  uint32_t hi = len - 1;
+  while (hi - lo > 1) {
+    uint32_t mid = lo + (hi - lo) / 2;
+    if (freq[mid] <= f) {
+      lo = mid;
+    } else {
+      hi = mid;
+    }
+  }
+
+  double f0 = freq[lo], f1 = freq[hi];
+  double p0 = pwr[lo], p1 = pwr[hi];
+  if (log_log && f0 > 0.0 && p0 > 0.0 && p1 > 0.0) {
+    double t = (log(f) - log(f0)) / (log(f1) - log(f0));
+    return exp(log(p0) + t * (log(p1) - log(p0)));
+  }
+  return p0 + (p1 - p0) * (f - f0) / (f1 - f0);
+}
+
+// File I/O ($fopen, $fdisplay, $fwrite, $fstrobe, $fmonitor and $fclose)
+//
+// All open files are stored in a table that is shared by all instances of the
+// library. The descriptor returned by $fopen is 0x80000000 | index into that
+// table, the first three entries are reserved for stdin, stdout and stderr.
+// Writes to stdout and stderr are forwarded to osdi_log.
+
+// Called (if set by the simulator) before a file is opened. Returns the path
+// that is actually opened (only used until the call returns) or NULL to deny
+// access to the file.
+typedef char *(*osdi_file_path_ptr)(void *handle, const char *path,
+                                    const char *mode);
+extern osdi_file_path_ptr osdi_file_path;
+
+#define FD_BIT 0x80000000u
+#define FD_STDOUT (FD_BIT | 1)
+#define FD_STDERR (FD_BIT | 2)
+#define NUM_FILES 64
+
+typedef struct OsdiFile {
+  FILE *file;
+  char *path;
+  char *mode;
+  uint32_t refs;
+} OsdiFile;
+
+extern OsdiFile osdi_files[NUM_FILES];
+
+// The table of open files is shared by all models of the library but
+// simulators may evaluate instances from multiple threads. Every access to the
+// table is guarded by a spinlock (the critical sections are short and there is
+// no portable mutex in a freestanding environment). This file is compiled into
+// the object of every model, so the table and the lock are defined once by the
+// compiler (see osdi::compile) instead of here.
+extern volatile char osdi_files_lock;
+
+static void lock(volatile char *lock) {
+  while (__atomic_test_and_set(lock, __ATOMIC_ACQUIRE)) {
+  }
+}
+
+static void unlock(volatile char *lock) {
+  __atomic_clear(lock, __ATOMIC_RELEASE);
+}
+
+// Slots with a path but without a file are reserved by an osdi_fopen call that
+// is still opening the file.
+
+// must be called with osdi_files_lock held
+static OsdiFile *lookup_file(int32_t fd) {
+  uint32_t idx = (uint32_t)fd ^ FD_BIT;
+  if (idx < 3 || idx >= NUM_FILES || osdi_files[idx].file == NULL) {
+    return NULL;
+  }
+  return &osdi_files[idx];
+}
+
+static void file_err(void *handle, const char *msg, const char *path) {
+  char *err = concat(msg, path);
+  if (err == NULL) {
+    osdi_log(handle, (char *)msg, LOG_LVL_ERR | LOG_FMT_ERR);
+  } else {
+    osdi_log(handle, err, LOG_LVL_ERR);
+  }
+}
+
+int32_t osdi_fopen(void *handle, char *path, char *mode) {
+  if (osdi_file_path != NULL) {
+    char *redirected = osdi_file_path(handle, path, mode);
+    if (redirected == NULL) {
+      file_err(handle, "$fopen: access denied to ", path);
+      return 0;
+    }
+    path = redirected;
+  }
+
+  char *path_copy = concat(path, "");
+  char *mode_copy = concat(mode, "");
+  if (path_copy == NULL || mode_copy == NULL) {
+    free(path_copy);
+    free(mode_copy);
+    file_err(handle, "$fopen: failed to open ", path);
+    return 0;
+  }
+
+  // instances that open the same file share the descriptor, the file is
+  // closed once every instance has closed it
+  uint32_t idx;
+  for (;;) {
+    lock(&osdi_files_lock);
+    uint32_t free_idx = 0;
+    int pending = 0;
+    for (uint32_t i = 3; i < NUM_FILES; i++) {
+      if (osdi_files[i].path == NULL) {
+        if (free_idx == 0) {
+          free_idx = i;
+        }
+      } else if (!strcmp(osdi_files[i].path, path) &&
+                 !strcmp(osdi_files[i].mode, mode)) {
+        if (osdi_files[i].file == NULL) {
+          pending = 1;
+          break;
+        }
+        osdi_files[i].refs += 1;
+        unlock(&osdi_files_lock);
+        free(path_copy);
+        free(mode_copy);
+        return (int32_t)(FD_BIT | i);
+      }
+    }
+
+    if (pending) {
+      // another instance is opening the same file, wait until it is published
+      unlock(&osdi_files_lock);
+      continue;
+    }
+
+    if (free_idx == 0) {
+      unlock(&osdi_files_lock);
+      free(path_copy);
+      free(mode_copy);
+      file_err(handle, "$fopen: too many open files, failed to open ", path);
+      return 0;
+    }
+
+    // reserve the slot, the file is opened outside of the critical section
+    osdi_files[free_idx] = (OsdiFile){
+        .file = NULL,
+        .path = path_copy,
+        .mode = mode_copy,
+        .refs = 1,
+    };
+    idx = free_idx;
+    unlock(&osdi_files_lock);
+    break;
+  }
+
+  FILE *file = fopen(path, mode);
+
+  // publish the file or release the slot
+  lock(&osdi_files_lock);
+  if (file == NULL) {
+    osdi_files[idx] = (OsdiFile){
+        .file = NULL,
+        .path = NULL,
+        .mode = NULL,
+        .refs = 0,
+    };
+  } else {
+    osdi_files[idx].file = file;
+  }
+  unlock(&osdi_files_lock);
+
+  if (file == NULL) {
+    free(path_copy);
+    free(mode_copy);
+    file_err(handle, "$fopen: failed to open ", path);
+    return 0;
+  }
+  return (int32_t)(FD_BIT | idx);
+}
+
+// msg was allocated with malloc unless flags contains LOG_FMT_ERR
+void osdi_fwrite(void *handle, int32_t fd, char *msg, uint32_t flags) {
+  if ((uint32_t)fd == FD_STDOUT || fd == 1) {
+    osdi_log(handle, msg, flags);
+    return;
+  }
+  if ((uint32_t)fd == FD_STDERR) {
+    osdi_log(handle, msg, (flags & ~LOG_LVL_MASK) | LOG_LVL_ERR);
+    return;
+  }
+
+  if (flags & LOG_FMT_ERR) {
+    osdi_log(handle, msg, LOG_LVL_ERR | LOG_FMT_ERR);
+    return;
+  }
+
+  lock(&osdi_files_lock);
+  OsdiFile *file = lookup_file(fd);
+  if (file != NULL) {
+    fputs(msg, file->file);
+  }
+  unlock(&osdi_files_lock);
+  if (file == NULL) {
+    osdi_log(handle, "write to invalid file descriptor",
+             LOG_LVL_WARN | LOG_FMT_ERR);
+  }
+  free(msg);
+}
+
+void osdi_fclose(void *handle, int32_t fd) {
+  lock(&osdi_files_lock);
+  OsdiFile *file = lookup_file(fd);
+  if (file == NULL) {
+    unlock(&osdi_files_lock);
+    return;
+  }
+  file->refs -= 1;
+  if (file->refs != 0) {
+    unlock(&osdi_files_lock);
+    return;
+  }
+  fclose(file->file);
+  free(file->path);
+  free(file->mode);
+  *file = (OsdiFile){
+      .file = NULL,
+      .path = NULL,
+      .mode = NULL,
+      .refs = 0,
+  };
+  unlock(&osdi_files_lock);
+}
+
+// Random numbers ($random, $arandom, $rdist_* and $dist_*)
+//
+// All distributions are derived from the 32 bit linear congruential generator
+// of the Verilog reference implementation. The seeds written by the simulator
+// (global and instance seed) are mixed into every seed before a value is
+// drawn so that the same model code produces different (but reproducible)
+// values for every Monte-Carlo run/instance. The next two entries of the
+// random state hold the streams used by calls without an explicit seed.
+// Functions without access to the instance data pass NULL as the random state.
+
+// must match hir_lower::RandomDist
+#define DIST_RANDOM 0
+#define DIST_UNIFORM 1
+#define DIST_NORMAL 2
+#define DIST_EXPONENTIAL 3
+#define DIST_POISSON 4
+#define DIST_CHI_SQUARE 5
+#define DIST_T 6
+#define DIST_ERLANG 7
+
+#define RANDOM_SEED_GLOBAL 0
+#define RANDOM_SEED_INSTANCE 1
+#define RANDOM_STREAM_GLOBAL 2
+#define RANDOM_STREAM_INSTANCE 3
+
+#define PI 3.14159265358979323846
+
+static uint32_t rand_next(int32_t *seed) {
+  uint32_t s = (uint32_t)*seed * 69069u + 1u;
+  *seed = (int32_t)s;
+  return s;
+}
+
+// uniformly distributed in (0, 1)
+static double rand_unit(int32_t *seed) {
+  return ((double)rand_next(seed) + 0.5) / 4294967296.0;
+}
+
+static double rand_normal(int32_t *seed) {
+  double u1 = rand_unit(seed);
+  double u2 = rand_unit(seed);
+  return sqrt(-2.0 * log(u1)) * cos(2.0 * PI * u2);
+}
+
+static double rand_chi_square(int32_t *seed, double df) {
+  double res = 0.0;
+  for (int i = 0; i < (int)df; i++) {
+    double x = rand_normal(seed);
+    res += x * x;
+  }
+  return res;
+}
+
+static double rand_dist(uint32_t dist, int32_t *seed, double a, double b) {
+  switch (dist) {
+  case DIST_RANDOM:
+    return (double)(int32_t)rand_next(seed);
+  case DIST_UNIFORM:
+    return a + (b - a) * rand_unit(seed);
+  case DIST_NORMAL:
+    return a + b * rand_normal(seed);
+  case DIST_EXPONENTIAL:
+    return -a * log(rand_unit(seed));
+  case DIST_POISSON: {
+    double limit = exp(-a);
+    double prod = rand_unit(seed);
+    int32_t k = 0;
+    while (prod > limit) {
+      prod *= rand_unit(seed);
+      k++;
+    }
+    return (double)k;
+  }
+  case DIST_CHI_SQUARE:
+    return rand_chi_square(seed, a);
+  case DIST_T: {
+    double x = rand_normal(seed);
+    return x / sqrt(rand_chi_square(seed, a) / a);
+  }
+  case DIST_ERLANG: {
+    double prod = 1.0;
+    for (int i = 0; i < (int)a; i++) {
+      prod *= rand_unit(seed);
+    }
+    return -b / a * log(prod);
+  }
+  default:
+    return 0.0;
+  }
+}
+
+double osdi_random(int32_t *state, uint32_t dist, uint32_t global, int32_t seed,
+                   double a, double b) {
+  if (state != NULL) {
+    seed ^= state[global ? RANDOM_SEED_GLOBAL : RANDOM_SEED_INSTANCE];
+  }
+  return rand_dist(dist, &seed, a, b);
+}
+
+int32_t osdi_random_next_seed(uint32_t dist, int32_t seed, double a, double b) {
+  rand_dist(dist, &seed, a, b);
+  return seed;
+}
+
+double osdi_random_stream(int32_t *state, uint32_t dist, uint32_t global,
+                          double a, double b) {
+  if (state == NULL) {
+    int32_t seed = 0;
+    return rand_dist(dist, &seed, a, b);
+  }
+  int32_t *stream =
+      &state[global ? RANDOM_STREAM_GLOBAL : RANDOM_STREAM_INSTANCE];
+  return rand_dist(dist, stream, a, b);
+}
+
+void osdi_random_init(int32_t *state) {
+  state[RANDOM_STREAM_GLOBAL] = state[RANDOM_SEED_GLOBAL];
+  state[RANDOM_STREAM_INSTANCE] = state[RANDOM_SEED_INSTANCE];
+}
+
+// Interpolation of tables ($table_model)
+//
+// Every input of a table is controlled by three characters: the interpolation
+// (D, 1, 2 or 3) followed by the extrapolation below and above the table
+// (C, L or E). Ignored inputs are removed (together with their column) before
+// a table is loaded. The samples are sorted and grouped into a tree: the
+// children of a node are the sub-tables for each distinct value of the next
+// input. Interpolation happens recursively from the innermost input outwards.
+//
+// Tables are loaded by setup_model and cached (for the lifetime of the
+// library) so that repeated model setups do not read the same file again.
+// Tables that fail to load are not cached, so the next setup_model retries.
+// The cache is shared by all models: just like the table of open files, the
+// list and its lock are defined once by the compiler (see osdi::compile)
+// because this file is compiled into the object of every model. Loading a
+// table holds osdi_tables_lock. Loaded tables are never modified (except for
+// the extrapolation_err flag which is set atomically), so evaluating them does
+// not require the lock.
+
+typedef struct TableNode {
+  uint32_t len;
+  double *x;
+  // values of the samples (innermost input only)
+  double *y;
+  // sub-tables of the next input (all other inputs)
+  struct TableNode *children;
+  // maps the values to the second derivatives of the natural cubic spline
+  double *spline;
+} TableNode;
+
+typedef struct OsdiTable {
+  // cache key: the path of the file or the inline samples
+  char *path;
+  const double *data;
+  char *ctrl_src;
+  uint32_t dims;
+  // inputs that are not ignored and their control characters
+  uint32_t used;
+  uint32_t *inputs;
+  char *ctrl;
+  bool extrapolation_err;
+  TableNode root;
+  struct OsdiTable *next;
+} OsdiTable;
+
+extern OsdiTable *osdi_tables;
+extern volatile char osdi_tables_lock;
+
+static int table_cmp_rows(const double *a, const double *b, uint32_t inputs) {
+  for (uint32_t i = 0; i < inputs; i++) {
+    if (a[i] < b[i]) {
+      return -1;
+    }
+    if (a[i] > b[i]) {
+      return 1;
+    }
+  }
+  return 0;
+}
+
+// stable merge sort of the row indices
+static void table_sort(uint32_t *rows, uint32_t *buf, uint32_t len,
+                       const double *data, uint32_t width) {
+  if (len < 2) {
+    return;
+  }
+  uint32_t mid = len / 2;
+  table_sort(rows, buf, mid, data, width);
+  table_sort(rows + mid, buf, len - mid, data, width);
+  uint32_t i = 0, j = mid, k = 0;
+  while (i < mid && j < len) {
+    if (table_cmp_rows(&data[rows[j] * width], &data[rows[i] * width],
+                       width - 1) < 0) {
+      buf[k++] = rows[j++];
+    } else {
+      buf[k++] = rows[i++];
+    }
+  }
+  while (i < mid) {
+    buf[k++] = rows[i++];
+  }
+  while (j < len) {
+    buf[k++] = rows[j++];
+  }
+  memcpy(rows, buf, len * sizeof(uint32_t));
+}
+
+// solves the tridiagonal system of the natural cubic spline for every unit
+// vector so that the second derivatives are a linear combination of the values
+static double *table_spline(const double *x, uint32_t n) {
+  double *res = malloc(n * n * sizeof(double));
+  double *diag = malloc(n * sizeof(double));
+  double *rhs = malloc(n * sizeof(double));
+  if (res == NULL || diag == NULL || rhs == NULL) {
+    free(res);
+    free(diag);
+    free(rhs);
+    return NULL;
+  }
+  for (uint32_t col = 0; col < n; col++) {
+    for (uint32_t i = 0; i < n; i++) {
+      rhs[i] = 0.0;
+    }
+    for (uint32_t i = 1; i + 1 < n; i++) {
+      double h0 = x[i] - x[i - 1];
+      double h1 = x[i + 1] - x[i];
+      double y0 = i - 1 == col, y1 = i == col, y2 = i + 1 == col;
+      rhs[i] = 6.0 * ((y2 - y1) / h1 - (y1 - y0) / h0);
+      diag[i] = 2.0 * (h0 + h1);
+    }
+    // forward elimination (Thomas algorithm)
+    for (uint32_t i = 2; i + 1 < n; i++) {
+      double h = x[i] - x[i - 1];
+      double factor = h / diag[i - 1];
+      diag[i] -= factor * h;
+      rhs[i] -= factor * rhs[i - 1];
+    }
+    res[col] = 0.0;
+    res[(n - 1) * n + col] = 0.0;
+    double next = 0.0;
+    for (uint32_t i = n - 2; i > 0; i--) {
+      double h = x[i + 1] - x[i];
+      next = (rhs[i] - h * next) / diag[i];
+      res[i * n + col] = next;
+    }
+  }
+  free(diag);
+  free(rhs);
+  return res;
+}
+
+// builds the node of input dim from the sorted rows
+static bool table_build_node(OsdiTable *table, TableNode *node,
+                             const uint32_t *rows, uint32_t len,
+                             const double *data, uint32_t dim) {
+  uint32_t width = table->used + 1;
+  uint32_t groups = 0;
+  for (uint32_t i = 0; i < len; i++) {
+    if (i == 0 ||
+        data[rows[i] * width + dim] != data[rows[i - 1] * width + dim]) {
+      groups++;
+    }
+  }
+
+  bool leaf = dim + 1 == table->used;
+  node->len = groups;
+  node->x = malloc(groups * sizeof(double));
+  node->y = leaf ? malloc(groups * sizeof(double)) : NULL;
+  node->children = leaf ? NULL : malloc(groups * sizeof(TableNode));
+  node->spline = NULL;
+  if (node->children != NULL) {
+    // allows table_free_node to free partially built nodes
+    for (uint32_t group = 0; group < groups; group++) {
+      node->children[group] = (TableNode){.len = 0};
+    }
+  }
+  if (node->x == NULL || (leaf ? node->y == NULL : node->children == NULL)) {
+    return 0;
+  }
+
+  uint32_t start = 0;
+  for (uint32_t group = 0; group < groups; group++) {
+    double x = data[rows[start] * width + dim];
+    uint32_t end = start + 1;
+    while (end < len && data[rows[end] * width + dim] == x) {
+      end++;
+    }
+    node->x[group] = x;
+    if (leaf) {
+      // the first sample wins for duplicates
+      node->y[group] = data[rows[start] * width + table->used];
+    } else if (!table_build_node(table, &node->children[group], rows + start,
+                                 end - start, data, dim + 1)) {
+      return 0;
+    }
+    start = end;
+  }
+
+  if (table->ctrl[3 * dim] == '3' && groups > 2) {
+    node->spline = table_spline(node->x, groups);
+    if (node->spline == NULL) {
+      return 0;
+    }
+  }
+  return 1;
+}
+
+static void table_free_node(TableNode *node) {
+  if (node->children != NULL) {
+    for (uint32_t i = 0; i < node->len; i++) {
+      table_free_node(&node->children[i]);
+    }
+  }
+  free(node->x);
+  free(node->y);
+  free(node->children);
+  free(node->spline);
+}
+
+static void table_free(OsdiTable *table) {
+  table_free_node(&table->root);
+  free(table->path);
+  free(table->ctrl_src);
+  free(table->inputs);
+  free(table->ctrl);
+  free(table);
+}
+
+// reads a table file: every line that is not empty or a comment (#) contains
+// one sample (the inputs followed by the output)
+static double *table_read_file(void *handle, const char *path, uint32_t width,
+                               uint32_t *len) {
+  if (osdi_file_path != NULL) {
+    char *redirected = osdi_file_path(handle, path, "r");
+    if (redirected == NULL) {
+      file_err(handle, "$table_model: access denied to ", path);
+      return NULL;
+    }
+    path = redirected;
+  }
+
+  FILE *file = fopen(path, "r");
+  if (file == NULL) {
+    file_err(handle, "$table_model: failed to open ", path);
+    return NULL;
+  }
+
+  uint32_t cap = 64;
+  double *data = malloc(cap * width * sizeof(double));
+  char line[4096];
+  *len = 0;
+  while (data != NULL && fgets(line, sizeof(line), file) != NULL) {
+    char *pos = line;
+    while (*pos == ' ' || *pos == '\t' || *pos == ',') {
+      pos++;
+    }
+    if (*pos == '#' || *pos == '\n' || *pos == '\r' || *pos == 0) {
+      continue;
+    }
+    if (*len == cap) {
+      cap *= 2;
+      double *new_data = realloc(data, cap * width * sizeof(double));
+      if (new_data == NULL) {
+        free(data);
+        data = NULL;
+        break;
+      }
+      data = new_data;
+    }
+    for (uint32_t i = 0; i < width; i++) {
+      while (*pos == ' ' || *pos == '\t' || *pos == ',') {
+        pos++;
+      }
+      char *end;
+      data[*len * width + i] = strtod(pos, &end);
+      if (end == pos) {
+        file_err(handle, "$table_model: invalid sample in ", path);
+        free(data);
+        fclose(file);
+        return NULL;
+      }
+      pos = end;
+    }
+    *len += 1;
+  }
+  fclose(file);
+  return data;
+}
+
+static bool table_key_eq(const OsdiTable *table, const char *path,
+                         const double *data, const char *ctrl, uint32_t dims) {
+  if (table->dims != dims || strcmp(table->ctrl_src, ctrl)) {
+    return 0;
+  }
+  if (path != NULL) {
+    return table->path != NULL && !strcmp(table->path, path);
+  }
+  return table->data == data;
+}
+
+// builds the tree of a table from the len samples in data (which still
+// contain the columns of ignored inputs)
+static bool table_build(void *handle, OsdiTable *table, const double *samples,
+                        uint32_t len) {
+  // drop the columns of ignored inputs
+  uint32_t width = table->used + 1;
+  double *used_data = malloc(len * width * sizeof(double));
+  uint32_t *rows = malloc(len * sizeof(uint32_t));
+  uint32_t *buf = malloc(len * sizeof(uint32_t));
+  bool ok = len != 0 && used_data != NULL && rows != NULL && buf != NULL;
+  if (ok) {
+    for (uint32_t row = 0; row < len; row++) {
+      for (uint32_t i = 0; i < width; i++) {
+        used_data[row * width + i] =
+            samples[row * (table->dims + 1) + table->inputs[i]];
+      }
+      rows[row] = row;
+    }
+
+    if (table->used == 0) {
+      // all inputs are ignored, the table is a constant
+      table->root.len = 1;
+      table->root.y = malloc(sizeof(double));
+      ok = table->root.y != NULL;
+      if (ok) {
+        table->root.y[0] = used_data[table->used];
+      }
+    } else {
+      table_sort(rows, buf, len, used_data, width);
+      ok = table_build_node(table, &table->root, rows, len, used_data, 0);
+    }
+    if (!ok) {
+      osdi_log(handle, "$table_model: out of memory",
+               LOG_LVL_ERR | LOG_FMT_ERR);
+    }
+  }
+
+  free(used_data);
+  free(rows);
+  free(buf);
+  return ok;
+}
+
+// must be called with osdi_tables_lock held
+static OsdiTable *table_load(void *handle, const char *path,
+                             const double *data, uint32_t len,
+                             const char *ctrl, uint32_t dims) {
+  for (OsdiTable *table = osdi_tables; table != NULL; table = table->next) {
+    if (table_key_eq(table, path, data, ctrl, dims)) {
+      return table;
+    }
+  }
+
+  OsdiTable *table = malloc(sizeof(OsdiTable));
+  uint32_t *inputs = malloc((dims + 1) * sizeof(uint32_t));
+  char *path_copy = path == NULL ? NULL : concat(path, "");
+  char *ctrl_copy = concat(ctrl, "");
+  char *used_ctrl = concat(ctrl, "");
+  if (table == NULL || inputs == NULL || (path != NULL && path_copy == NULL) ||
+      ctrl_copy == NULL || used_ctrl == NULL) {
+    free(table);
+    free(inputs);
+    free(path_copy);
+    free(ctrl_copy);
+    free(used_ctrl);
+    return NULL;
+  }
+
+  *table = (OsdiTable){
+      .path = path_copy,
+      .data = data,
+      .ctrl_src = ctrl_copy,
+      .dims = dims,
+      .used = 0,
+      .inputs = inputs,
+      .ctrl = used_ctrl,
+      .extrapolation_err = 0,
+      .root = {.len = 0},
+      .next = NULL,
+  };
+
+  for (uint32_t i = 0; i < dims; i++) {
+    if (ctrl[3 * i] != 'I') {
+      memcpy(&used_ctrl[3 * table->used], &ctrl[3 * i], 3);
+      inputs[table->used++] = i;
+    }
+  }
+  // the output column
+  inputs[table->used] = dims;
+
+  bool ok;
+  if (path != NULL) {
+    double *samples = table_read_file(handle, path, dims + 1, &len);
+    ok = samples != NULL && table_build(handle, table, samples, len);
+    free(samples);
+  } else {
+    ok = table_build(handle, table, data, len);
+  }
+
+  if (!ok) {
+    // not cached so that the next model setup retries
+    table_free(table);
+    return NULL;
+  }
+  table->next = osdi_tables;
+  osdi_tables = table;
+  return table;
+}
+
+// Loads a table from the file at path or (if path is NULL) from the len inline
+// samples in data. ctrl contains three control characters for every input.
+// Tables that fail to load are NULL and evaluate to zero.
+void *osdi_table_load(void *handle, const char *path, const double *data,
+                      uint32_t len, const char *ctrl, uint32_t dims) {
+  lock(&osdi_tables_lock);
+  OsdiTable *table = table_load(handle, path, data, len, ctrl, dims);
+  unlock(&osdi_tables_lock);
+  return table;
+}
+
+static double table_eval_node(void *handle, OsdiTable *table,
+                              const TableNode *node, uint32_t dim,
+                              const double *x, const unsigned char *derivative);
+
+static double table_sample(void *handle, OsdiTable *table,
+                           const TableNode *node, uint32_t dim, uint32_t i,
+                           const double *x, const unsigned char *derivative) {
+  if (node->y != NULL) {
+    return node->y[i];
+  }
+  return table_eval_node(handle, table, &node->children[i], dim + 1, x,
+                         derivative);
+}
+
+#define SAMPLE(i) table_sample(handle, table, node, dim, i, x, derivative)
+
+// interpolates the derivative of the given order at xv (inside the table)
+static double table_interpolate(void *handle, OsdiTable *table,
+                                const TableNode *node, uint32_t dim,
+                                const double *x,
+                                const unsigned char *derivative, double xv,
+                                uint32_t order) {
+  uint32_t n = node->len;
+  char interpolation = table->ctrl[3 * dim];
+  if (interpolation == 'D') {
+    if (order != 0) {
+      return 0.0;
+    }
+    uint32_t best = 0;
+    for (uint32_t i = 1; i < n; i++) {
+      double dist = node->x[i] - xv;
+      double best_dist = node->x[best] - xv;
+      if (dist * dist < best_dist * best_dist) {
+        best = i;
+      }
+    }
+    return SAMPLE(best);
+  }
+
+  if (n == 1) {
+    return order == 0 ? SAMPLE(0) : 0.0;
+  }
+
+  // find the segment [x[j], x[j+1]] that contains xv
+  uint32_t lo = 0, hi = n - 1;
+  while (hi - lo > 1) {
+    uint32_t mid = (lo + hi) / 2;
+    if (node->x[mid] <= xv) {
+      lo = mid;
+    } else {
+      hi = mid;
+    }
+  }
+  uint32_t j = lo;
+  double h = node->x[j + 1] - node->x[j];
+
+  if (interpolation == '2' && n > 2) {
+    // Lagrange polynomial through the three closest samples
+    uint32_t start = j;
+    if (j + 2 >= n ||
+        (j > 0 && xv - node->x[j - 1] < node->x[j + 2] - xv)) {
+      start = j - 1;
+    }
+    const double *p = &node->x[start];
+    double res = 0.0;
+    for (uint32_t k = 0; k < 3; k++) {
+      double a = p[(k + 1) % 3], b = p[(k + 2) % 3];
+      double denom = (p[k] - a) * (p[k] - b);
+      double weight;
+      switch (order) {
+      case 0:
+        weight = (xv - a) * (xv - b);
+        break;
+      case 1:
+        weight = 2.0 * xv - a - b;
+        break;
+      case 2:
+        weight = 2.0;
+        break;
+      default:
+        return 0.0;
+      }
+      res += weight / denom * SAMPLE(start + k);
+    }
+    return res;
+  }
+
+  if (node->spline != NULL) {
+    // natural cubic spline, the second derivatives at the ends of the
+    // segment are linear combinations of all samples
+    double y0 = 0.0, y1 = 0.0, m0 = 0.0, m1 = 0.0;
+    for (uint32_t k = 0; k < n; k++) {
+      double y = SAMPLE(k);
+      if (k == j) {
+        y0 = y;
+      } else if (k == j + 1) {
+        y1 = y;
+      }
+      m0 += node->spline[j * n + k] * y;
+      m1 += node->spline[(j + 1) * n + k] * y;
+    }
+    double a = (node->x[j + 1] - xv) / h, b = 1.0 - a;
+    switch (order) {
+    case 0:
+      return a * y0 + b * y1 +
+             ((a * a * a - a) * m0 + (b * b * b - b) * m1) * h * h / 6.0;
+    case 1:
+      return (y1 - y0) / h - (3.0 * a * a - 1.0) / 6.0 * h * m0 +
+             (3.0 * b * b - 1.0) / 6.0 * h * m1;
+    case 2:
+      return a * m0 + b * m1;
+    case 3:
+      return (m1 - m0) / h;
+    default:
+      return 0.0;
+    }
+  }
+
+  // linear interpolation
+  switch (order) {
+  case 0: {
+    double t = (xv - node->x[j]) / h;
+    return (1.0 - t) * SAMPLE(j) + t * SAMPLE(j + 1);
+  }
+  case 1:
+    return (SAMPLE(j + 1) - SAMPLE(j)) / h;
+  default:
+    return 0.0;
+  }
+}
+
+static double table_eval_node(void *handle, OsdiTable *table,
+                              const TableNode *node, uint32_t dim,
+                              const double *x,
+                              const unsigned char *derivative) {
+  if (node->len == 0) {
+    return 0.0;
+  }
+  double xv = x[dim];
+  uint32_t order = derivative[dim];
+  double end;
+  char extrapolation;
+  if (xv < node->x[0]) {
+    end = node->x[0];
+    extrapolation = table->ctrl[3 * dim + 1];
+  } else if (xv > node->x[node->len - 1]) {
+    end = node->x[node->len - 1];
+    extrapolation = table->ctrl[3 * dim + 2];
+  } else {
+    return table_interpolate(handle, table, node, dim, x, derivative, xv,
+                             order);
+  }
+
+  switch (extrapolation) {
+  case 'L':
+    // continue with the slope at the end of the table
+    switch (order) {
+    case 0:
+      return table_interpolate(handle, table, node, dim, x, derivative, end,
+                               0) +
+             table_interpolate(handle, table, node, dim, x, derivative, end,
+                               1) *
+                 (xv - end);
+    case 1:
+      return table_interpolate(handle, table, node, dim, x, derivative, end,
+                               1);
+    default:
+      return 0.0;
+    }
+  case 'E':
+    // only the first extrapolation of a table is reported
+    if (!__atomic_exchange_n(&table->extrapolation_err, 1, __ATOMIC_RELAXED)) {
+      osdi_log(handle, "$table_model: input outside of the table",
+               LOG_LVL_ERR | LOG_FMT_ERR);
+    }
+    // fallthrough
+  default:
+    return order == 0 ? table_interpolate(handle, table, node, dim, x,
+                                          derivative, end, 0)
+                      : 0.0;
+  }
+}
+
+#undef SAMPLE
+
+// Evaluates the partial derivative (derivative contains the order for every
+// input) of a table. The inputs are passed as variadic double arguments.
+double osdi_table_eval(void *handle, void *table_,
+                       const unsigned char *derivative, ...) {
+  OsdiTable *table = table_;
+  if (table == NULL) {
+    return 0.0;
+  }
+
+  __builtin_va_list args;
+  __builtin_va_start(args, derivative);
+  double x[table->used + 1];
+  unsigned char used_derivative[table->used + 1];
+  uint32_t used = 0;
+  bool ignored_derivative = 0;
+  for (uint32_t i = 0; i < table->dims; i++) {
+    double val = __builtin_va_arg(args, double);
+    if (used < table->used && table->inputs[used] == i) {
+      x[used] = val;
+      used_derivative[used] = derivative[i];
+      used++;
+    } else if (derivative[i] != 0) {
+      ignored_derivative = 1;
+    }
+  }
+  __builtin_va_end(args);
+
+  if (ignored_derivative) {
+    return 0.0;
+  }
+  if (table->used == 0) {
+    return table->root.len == 0 ? 0.0 : table->root.y[0];
+  }
+  return table_eval_node(handle, table, &table->root, 0, x, used_derivative);
+}
+
+// Strings ($swrite, $sformat and $sscanf)
+//
+// Strings created at runtime are owned by a slot (in the instance or model
+// data) that belongs to the call that created them. The previous string of a
+// slot is freed when the call is evaluated again. Functions without string
+// slots pass NULL, their strings are never freed.
+
+static char *store_string(char **slot, char *str) {
+  if (slot != NULL) {
+    free(*slot);
+    *slot = str;
+  }
+  return str;
+}
+
+// msg was allocated with malloc unless flags contains LOG_FMT_ERR
+char *osdi_swrite(void *handle, char **slot, char *msg, uint32_t flags) {
+  if (flags & LOG_FMT_ERR) {
+    osdi_log(handle, msg, LOG_LVL_ERR | LOG_FMT_ERR);
+    msg = concat("", "");
+    if (msg == NULL) {
+      return "";
+    }
+  }
+  return store_string(slot, msg);
+}
+
+#define SCAN_NONE 0
+#define SCAN_INT 1
+#define SCAN_REAL 2
+#define SCAN_STR 3
+
+typedef struct ScanVal {
+  uint32_t kind;
+  int32_t i;
+  double r;
+  const char *s;
+  size_t len;
+} ScanVal;
+
+static bool scan_is_space(char c) {
+  return c == ' ' || c == '\t' || c == '\n' || c == '\r' || c == '\f' ||
+         c == '\v';
+}
+
+// copies at most max characters of the field at pos to buf (NUL terminated)
+#define SCAN_BUF_LEN 128
+static void scan_field(char *buf, const char *pos, size_t max) {
+  size_t len = 0;
+  while (pos[len] != 0 && len < max && len < SCAN_BUF_LEN - 1) {
+    buf[len] = pos[len];
+    len++;
+  }
+  buf[len] = 0;
+}
+
+// Scans str according to the (Verilog) format fmt and returns the number of
+// assigned outputs. The conversion of output arg is stored in dst.
+static int32_t scan(const char *str, const char *fmt, uint32_t arg,
+                    ScanVal *dst) {
+  int32_t assigned = 0;
+  const char *pos = str;
+  char buf[SCAN_BUF_LEN];
+  while (*fmt != 0) {
+    if (scan_is_space(*fmt)) {
+      while (scan_is_space(*fmt)) {
+        fmt++;
+      }
+      while (scan_is_space(*pos)) {
+        pos++;
+      }
+      continue;
+    }
+    if (*fmt != '%' || fmt[1] == '%') {
+      if (*fmt == '%') {
+        fmt++;
+      }
+      if (*pos != *fmt) {
+        break;
+      }
+      pos++;
+      fmt++;
+      continue;
+    }
+
+    fmt++;
+    bool suppress = *fmt == '*';
+    if (suppress) {
+      fmt++;
+    }
+    size_t max = 0;
+    while (*fmt >= '0' && *fmt <= '9') {
+      max = max * 10 + (size_t)(*fmt - '0');
+      fmt++;
+    }
+    if (max == 0) {
+      max = (size_t)-1;
+    }
+    char conv = *fmt;
+    if (conv == 0) {
+      break;
+    }
+    fmt++;
+    if (conv != 'c' && conv != 'C') {
+      while (scan_is_space(*pos)) {
+        pos++;
+      }
+    }
+    if (*pos == 0) {
+      break;
+    }
+
+    ScanVal val = {.kind = SCAN_NONE};
+    int base = 10;
+    switch (conv) {
+    case 'c':
+    case 'C':
+      val.kind = SCAN_INT;
+      val.i = (unsigned char)*pos;
+      pos++;
+      break;
+    case 's':
+    case 'S':
+      val.kind = SCAN_STR;
+      val.s = pos;
+      val.len = 0;
+      while (pos[val.len] != 0 && !scan_is_space(pos[val.len]) &&
+             val.len < max) {
+        val.len++;
+      }
+      pos += val.len;
+      break;
+    case 'b':
+    case 'B':
+      base = 2;
+      goto scan_int;
+    case 'o':
+    case 'O':
+      base = 8;
+      goto scan_int;
+    case 'h':
+    case 'H':
+    case 'x':
+    case 'X':
+      base = 16;
+      goto scan_int;
+    case 'd':
+    case 'D':
+    scan_int: {
+      scan_field(buf, pos, max);
+      char *end;
+      long i = strtol(buf, &end, base);
+      if (end != buf) {
+        val.kind = SCAN_INT;
+        val.i = (int32_t)i;
+        pos += end - buf;
+      }
+      break;
+    }
+    case 'e':
+    case 'E':
+    case 'f':
+    case 'F':
+    case 'g':
+    case 'G': {
+      scan_field(buf, pos, max);
+      char *end;
+      double r = strtod(buf, &end);
+      if (end != buf) {
+        val.kind = SCAN_REAL;
+        val.r = r;
+        pos += end - buf;
+      }
+      break;
+    }
+    default:
+      break;
+    }
+
+    if (val.kind == SCAN_NONE) {
+      break;
+    }
+    if (!suppress) {
+      if (dst != NULL && (uint32_t)assigned == arg) {
+        *dst = val;
+      }
+      assigned++;
+    }
+  }
+  return assigned;
+}
+
+int32_t osdi_sscanf(const char *str, const char *fmt) {
+  return scan(str, fmt, 0, NULL);
+}
+
+// The values converted by $sscanf are assigned to the output arg. If the
+// output is not assigned (or the conversion has the wrong type) the previous
+// value of the output is returned.
+
+int32_t osdi_sscanf_int(uint32_t arg, const char *str, const char *fmt,
+                        int32_t old) {
+  ScanVal val = {.kind = SCAN_NONE};
+  scan(str, fmt, arg, &val);
+  switch (val.kind) {
+  case SCAN_INT:
+    return val.i;
+  case SCAN_REAL:
+    return (int32_t)(val.r < 0 ? val.r - 0.5 : val.r + 0.5);
+  default:
+    return old;
+  }
+}
+
+double osdi_sscanf_real(uint32_t arg, const char *str, const char *fmt,
+                        double old) {
+  ScanVal val = {.kind = SCAN_NONE};
+  scan(str, fmt, arg, &val);
+  switch (val.kind) {
+  case SCAN_INT:
+    return val.i;
+  case SCAN_REAL:
+    return val.r;
+  default:
+    return old;
+  }
+}
+
+char *osdi_sscanf_str(char **slot, uint32_t arg, const char *str,
+                      const char *fmt, char *old) {
+  ScanVal val = {.kind = SCAN_NONE};
+  scan(str, fmt, arg, &val);
+  if (val.kind != SCAN_STR) {
+    return old;
+  }
+  char *res = malloc(val.len + 1);
+  if (res == NULL) {
+    return old;
+  }
+  memcpy(res, val.s, val.len);
+  res[val.len] = 0;
+  return store_string(slot, res);
+}
+
+// Plusargs ($test$plusargs and $value$plusargs)
+//
+// The plusargs are read from the string simulator parameter "plusargs" or, if
+// the simulator does not provide it, from the environment variable
+// OSDI_PLUSARGS. Both contain whitespace separated plusargs (+name or
+// +name=value), the leading + is optional.
+
+static const char *plusargs(void *params_) {
+  OsdiSimParas *params = params_;
+  for (int i = 0; params->names_str[i]; i++) {
+    if (strcmp(params->names_str[i], "plusargs") == 0) {
+      return params->vals_str[i];
+    }
+  }
+  return getenv("OSDI_PLUSARGS");
+}
+
+// Returns the first plusarg (without the leading +) that starts with the text
+// of fmt before the first conversion or NULL if there is no such plusarg.
+static const char *find_plusarg(void *params, const char *fmt) {
+  const char *pos = plusargs(params);
+  if (pos == NULL) {
+    return NULL;
+  }
+  for (;;) {
+    while (scan_is_space(*pos)) {
+      pos++;
+    }
+    if (*pos == 0) {
+      return NULL;
+    }
+    if (*pos == '+') {
+      pos++;
+    }
+    const char *arg = pos;
+    const char *prefix = fmt;
+    while (*prefix != 0 && *prefix != '%' && *prefix == *pos) {
+      prefix++;
+      pos++;
+    }
+    if (*prefix == 0 || *prefix == '%') {
+      return arg;
+    }
+    while (*pos != 0 && !scan_is_space(*pos)) {
+      pos++;
+    }
+  }
+}
+
+int32_t osdi_test_plusargs(void *params, char *fmt) {
+  return find_plusarg(params, fmt) != NULL;
+}
+
+// The plusarg is scanned with the complete format, the text before the
+// conversion matches the name of the plusarg.
+char *osdi_plusarg(void *params, char *fmt) {
+  const char *arg = find_plusarg(params, fmt);
+  return arg == NULL ? "" : (char *)arg;
+}
@@ -5,7 +5,7 @@ pub(super) use module::module;
 use module::MODULE_ITEM_OR_ATTR_RECOVERY;
 
 pub(super) const ITEM_RECOVERY_SET: TokenSet =
-    TokenSet::new(&[DISCIPLINE_KW, NATURE_KW, MODULE_KW, EOF]);
+    TokenSet::new(&[DISCIPLINE_KW, NATURE_KW, MODULE_KW, PARAMSET_KW, EOF]);
 
 const DISCIPLINE_RECOVERY_SET: TokenSet =
     ITEM_RECOVERY_SET.union(TokenSet::unique(ENDDISCIPLINE_KW));
@@ -54,6 +54,39 @@ pub(super) fn nature(p: &mut Parser, m: Marker) {
     m.complete(p, NATURE_DECL);
 }
 
+const PARAMSET_RECOVERY_SET: TokenSet = ITEM_RECOVERY_SET.union(TokenSet::unique(ENDPARAMSET_KW));
+const PARAMSET_ITEM_RECOVERY_SET: TokenSet =
+    PARAMSET_RECOVERY_SET.union(TokenSet::new(&[PARAMETER_KW, LOCALPARAM_KW, T![.], T!["(*"]]));
+
+pub(super) fn paramset(p: &mut Parser, m: Marker) {
+    p.bump(T![paramset]);
+    name_r(p, TokenSet::new(&[IDENT, T![;]]));
+    name_ref_r(p, TokenSet::unique(T![;]));
+    p.expect(T![;]);
+    while !p.at_ts(PARAMSET_RECOVERY_SET) {
+        let m = p.start();
+        attrs(p, PARAMSET_ITEM_RECOVERY_SET);
+        match p.current() {
+            PARAMETER_KW | LOCALPARAM_KW => parameter_decl(p, m),
+            T![.] => {
+                p.bump(T![.]);
+                name_ref_r(p, TokenSet::new(&[T![=], T![;]]));
+                p.expect(T![=]);
+                expr(p);
+                p.expect(T![;]);
+                m.complete(p, PARAM_SET_ASSIGN);
+            }
+            _ => {
+                m.abandon(p);
+                let err = p.unexpected_tokens_msg(vec![PARAMETER_KW, LOCALPARAM_KW, T![.]]);
+                p.err_recover(err, PARAMSET_ITEM_RECOVERY_SET);
+            }
+        }
+    }
+    p.expect(ENDPARAMSET_KW);
+    m.complete(p, PARAM_SET_DECL);
+}
+
 pub(super) fn decl_list(
     p: &mut Parser,
     terminator: SyntaxKind,
@@ -86,7 +119,10 @@ pub(super) fn var_decl(p: &mut Parser, m: Marker) {
 
 fn var(p: &mut Parser) -> bool {
     let m = p.start();
-    name_r(p, TokenSet::new(&[T![,], T![=], T![;]]));
+    name_r(p, TokenSet::new(&[T![,], T![=], T![;], T!['[']]));
+    if p.at(T!['[']) {
+        decl_range(p);
+    }
     if p.eat(T![=]) {
         expr(p);
     }
@@ -102,10 +138,24 @@ pub(super) fn parameter_decl(p: &mut Parser, m: Marker) {
     m.complete(p, PARAM_DECL);
 }
 
+/// Parses the `[msb:lsb]` range of an array (or node array) declaration.
+fn decl_range(p: &mut Parser) {
+    let m = p.start();
+    p.bump(T!['[']);
+    expr(p);
+    p.expect(T![:]);
+    expr(p);
+    p.expect(T![']']);
+    m.complete(p, RANGE);
+}
+
 const PARAM_RECOVER: TokenSet = MODULE_ITEM_OR_ATTR_RECOVERY.union(TokenSet::new(&[T![,], T![;]]));
 fn parameter(p: &mut Parser) -> bool {
     let m = p.start();
-    name_r(p, TokenSet::new(&[T![,], T![;]]));
+    name_r(p, TokenSet::new(&[T![,], T![;], T!['[']]));
+    if p.at(T!['[']) {
+        decl_range(p);
+    }
     p.expect(T![=]);
     expr(p);
     while !p.at_ts(PARAM_RECOVER) {
@@ -11,6 +11,7 @@ const MODULE_ITEM_RECOVERY: TokenSet = DIRECTION_TS.union(TokenSet::new(&[
     INTEGER_KW,
     PARAMETER_KW,
     LOCALPARAM_KW,
+    GENVAR_KW,
     ENDMODULE_KW,
     EOF,
 ]));
@@ -119,6 +120,9 @@ fn module_items(p: &mut Parser) {
             NET_TYPE => {
                 net_decl::<true>(p, m);
             }
+            IDENT if p.nth(1) == T![#] || (p.nth(1) == IDENT && p.nth(2) == T!['(']) => {
+                module_inst_decl(p, m);
+            }
             IDENT => {
                 net_decl::<false>(p, m);
             }
@@ -128,6 +132,9 @@ fn module_items(p: &mut Parser) {
             ALIASPARAM_KW => {
                 alias_parameter_decl(p, m);
             }
+            GENVAR_KW => {
+                genvar_decl(p, m);
+            }
             BRANCH_KW => {
                 branch_decl(p, m);
             }
@@ -168,7 +175,11 @@ fn net_decl<const NET_TYPE_FIRST: bool>(p: &mut Parser, m: Marker) {
             eat_name_ref(p);
         }
     } else {
-        name_ref_r(p, MODULE_ITEM_OR_ATTR_RECOVERY.union(TokenSet::unique(T![;])))
+        name_ref_r(p, MODULE_ITEM_OR_ATTR_RECOVERY.union(TokenSet::new(&[T![;], T!['[']])))
+    }
+
+    if p.at(T!['[']) {
+        decl_range(p);
     }
 
     net_dec_list(p);
@@ -176,6 +187,64 @@ fn net_decl<const NET_TYPE_FIRST: bool>(p: &mut Parser, m: Marker) {
     m.complete(p, NET_DECL);
 }
 
+fn genvar_decl(p: &mut Parser, m: Marker) {
+    p.bump(GENVAR_KW);
+    net_dec_list(p);
+    p.eat(T![;]);
+    m.complete(p, GENVAR_DECL);
+}
+
+fn module_inst_decl(p: &mut Parser, m: Marker) {
+    name_ref_r(p, MODULE_ITEM_OR_ATTR_RECOVERY.union(TokenSet::new(&[T![#], T![;]])));
+    if p.at(T![#]) {
+        let m = p.start();
+        p.bump(T![#]);
+        p.expect(T!['(']);
+        connection_list(p, PARAM_OVERRIDE);
+        m.complete(p, PARAM_OVERRIDES);
+    }
+    decl_list(p, T![;], module_inst, MODULE_ITEM_OR_ATTR_RECOVERY);
+    p.eat(T![;]);
+    m.complete(p, MODULE_INST_DECL);
+}
+
+fn module_inst(p: &mut Parser) -> bool {
+    let m = p.start();
+    name_r(p, TokenSet::new(&[T!['('], T![,], T![;]]));
+    if p.expect(T!['(']) {
+        connection_list(p, PORT_CONNECTION);
+    }
+    m.complete(p, MODULE_INST);
+    true
+}
+
+const CONNECTION_RECOVERY: TokenSet = TokenSet::new(&[T![;], T![')'], ENDMODULE_KW, EOF]);
+
+/// Parses the entries of a parameter override list `#(...)` or a port connection list
+/// `inst(...)` (after the opening parenthesis). Each entry is either an ordered expression
+/// or a named connection `.name(expr)`.
+fn connection_list(p: &mut Parser, kind: SyntaxKind) {
+    while !p.at_ts(CONNECTION_RECOVERY) {
+        let m = p.start();
+        if p.eat(T![.]) {
+            name_ref_r(p, TokenSet::new(&[T!['('], T![,], T![')']]));
+            p.expect(T!['(']);
+            if !p.at(T![')']) {
+                expr(p);
+            }
+            p.expect(T![')']);
+        } else if expr(p).is_none() {
+            m.abandon(p);
+            break;
+        }
+        m.complete(p, kind);
+        if !p.at(T![')']) && !p.expect(T![,]) {
+            break;
+        }
+    }
+    p.expect(T![')']);
+}
+
 fn net_dec_list(p: &mut Parser) {
     decl_list(p, T![;], decl_name, NET_RECOVERY);
 }
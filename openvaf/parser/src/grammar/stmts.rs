@@ -60,10 +60,19 @@ fn assign_or_expr(p: &mut Parser) -> bool {
 fn event_stmt(p: &mut Parser, m: Marker) {
     p.bump(T![@]);
     p.expect(T!['(']);
-    p.expect_ts_r(
-        TokenSet::new(&[INITIAL_STEP_KW, FINAL_STEP_KW]),
-        TokenSet::new(&[T![')'], T!['(']]),
-    );
+    if !p.at_ts(TokenSet::new(&[INITIAL_STEP_KW, FINAL_STEP_KW])) {
+        // monitored events like cross(..) or timer(..) are parsed as calls that
+        // are separated by the `or` identifier (validated later)
+        expr(p);
+        while p.eat(IDENT) {
+            expr(p);
+        }
+        p.expect(T![')']);
+        stmt_with_attrs(p);
+        m.complete(p, EVENT_STMT);
+        return;
+    }
+    p.bump_ts(TokenSet::new(&[INITIAL_STEP_KW, FINAL_STEP_KW]));
     if p.eat(T!['(']) {
         while !p.at_ts(TokenSet::new(&[T![')'], T![begin], ENDMODULE_KW])) {
             let mut succ = p.expect(STR_LIT);
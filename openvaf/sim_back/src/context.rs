@@ -41,7 +41,9 @@ impl<'a> Context<'a> {
                 | PlaceKind::ImplicitResidual { .. }
                 | PlaceKind::CollapseImplicitEquation(_)
                 | PlaceKind::IsVoltageSrc(_)
-                | PlaceKind::BoundStep => true,
+                | PlaceKind::BoundStep
+                | PlaceKind::DelayInput(_)
+                | PlaceKind::DelayTime(_) => true,
                 PlaceKind::Var(var) => module.op_vars.contains_key(&var),
                 _ => false,
             },
@@ -51,7 +53,8 @@ impl<'a> Context<'a> {
         .with_tagged_writes()
         .build(literals);
         // TODO hidden state
-        intern.insert_var_init(db, &mut func, literals);
+        intern.insert_var_init(db, module.module, &mut func, literals);
+        module.insert_frozen_params(&mut func, &intern, literals);
 
         Context {
             output_values: BitSet::new_empty(func.dfg.num_values()),
@@ -116,7 +119,13 @@ impl<'a> Context<'a> {
         } else {
             for (kind, val) in self.intern.outputs.iter() {
                 if matches!(kind, PlaceKind::Var(var) if self.module.op_vars.contains_key(var))
-                    || matches!(kind, PlaceKind::CollapseImplicitEquation(_) | PlaceKind::BoundStep)
+                    || matches!(
+                        kind,
+                        PlaceKind::CollapseImplicitEquation(_)
+                            | PlaceKind::BoundStep
+                            | PlaceKind::DelayInput(_)
+                            | PlaceKind::DelayTime(_)
+                    )
                 {
                     self.output_values.insert(val.unwrap_unchecked());
                 }
@@ -130,7 +139,7 @@ impl<'a> Context<'a> {
         self.op_dependent_insts.ensure(dfg.num_insts());
 
         for (cb, uses) in self.intern.callback_uses.iter_mut_enumerated() {
-            if self.intern.callbacks[cb].is_noise() {
+            if self.intern.callbacks[cb].is_small_signal_src() {
                 uses.retain(|&inst| {
                     if self.func.layout.inst_block(inst).is_none() {
                         return false;
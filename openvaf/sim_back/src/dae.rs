@@ -1,6 +1,8 @@
 use std::hash::BuildHasherDefault;
 
+use hir::Parameter;
 use indexmap::IndexSet;
+use lasso::Spur;
 use mir::{strip_optbarrier, Value, F_ZERO};
 use rustc_hash::FxHasher;
 use stdx::{impl_debug_display, impl_idx_from};
@@ -46,6 +48,13 @@ pub struct DaeSystem {
     pub small_signal_parameters: IndexSet<Value, BuildHasherDefault<FxHasher>>,
     /// noise
     pub noise_sources: Vec<NoiseSource>,
+    /// The excitations created by `ac_stim` (one for each AC analysis name)
+    pub ac_excitations: Vec<AcExcitation>,
+    /// The derivatives by the parameters marked for sensitivity analysis
+    pub param_sensitivities: Vec<ParamSensitivity>,
+    /// The Taylor coefficients of order two and above of the residuals
+    /// (see `ModuleInfo::derivative_order`)
+    pub higher_order: Vec<HigherOrderEntry>,
     /// model inputs (node pairs)
     pub model_inputs: Vec<(u32, u32)>,
     /// Jacobian entry counts
@@ -97,10 +106,27 @@ impl DaeSystem {
                 NoiseSourceKind::WhiteNoise { pwr } | NoiseSourceKind::FlickerNoise { pwr, .. } => {
                     pwr != F_ZERO
                 }
-                NoiseSourceKind::NoiseTable { .. } => true,
+                NoiseSourceKind::NoiseTable { ref vals, .. } => {
+                    vals.iter().any(|(_, pwr)| !pwr.is_zero())
+                }
             }
         });
 
+        self.ac_excitations.retain_mut(|excitation| {
+            excitation.map_vals(&mut sparsify);
+            excitation.real.iter().chain(&excitation.imag).any(|&val| val != F_ZERO)
+        });
+
+        for sensitivity in &mut self.param_sensitivities {
+            sensitivity.map_vals(&mut sparsify)
+        }
+
+        self.higher_order.retain_mut(|entry| {
+            entry.resist = sparsify(entry.resist);
+            entry.react = sparsify(entry.react);
+            entry.resist != F_ZERO || entry.react != F_ZERO
+        });
+
         self.jacobian.raw.retain_mut(|matrix_entry| {
             matrix_entry.resist = sparsify(matrix_entry.resist);
             matrix_entry.react = sparsify(matrix_entry.react);
@@ -221,6 +247,62 @@ impl Residual {
     }
 }
 
+/// The right hand side of the small signal system created by the `ac_stim` sources of an AC
+/// analysis. Just like the residual the excitation is added at the high node of a branch and
+/// subtracted at the low node. It is zero during all other analyses.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct AcExcitation {
+    /// The name of the AC analysis that this excitation belongs to
+    pub analysis: Spur,
+    /// The real part of the excitation of each unknown
+    pub real: TiVec<SimUnknown, Value>,
+    /// The imaginary part of the excitation of each unknown
+    pub imag: TiVec<SimUnknown, Value>,
+}
+
+impl AcExcitation {
+    pub fn map_vals(&mut self, mut f: impl FnMut(Value) -> Value) {
+        for val in self.real.iter_mut().chain(self.imag.iter_mut()) {
+            *val = f(*val);
+        }
+    }
+}
+
+/// The derivatives of the residual and of the operating point variables by a parameter that
+/// was marked for sensitivity analysis. The derivatives are only exact as long as no limiting
+/// is applied.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ParamSensitivity {
+    pub param: Parameter,
+    /// The derivative of the resistive residual of each unknown
+    pub resist: TiVec<SimUnknown, Value>,
+    /// The derivative of the reactive residual of each unknown
+    pub react: TiVec<SimUnknown, Value>,
+    /// The derivative of each operating point variable (in the order of `ModuleInfo::op_vars`),
+    /// zero for operating point variables that are not real
+    pub op_vars: Vec<Value>,
+}
+
+impl ParamSensitivity {
+    pub fn map_vals(&mut self, mut f: impl FnMut(Value) -> Value) {
+        for val in self.resist.iter_mut().chain(self.react.iter_mut()).chain(&mut self.op_vars) {
+            *val = f(*val);
+        }
+    }
+}
+
+/// A Taylor coefficient of the residual `row` for the product of the unknowns `cols`:
+/// the mixed derivative divided by the factorials of the multiplicity of each unknown
+/// (for example `1/2 d²I/dx²` or `d²I/dxdy`).
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct HigherOrderEntry {
+    pub row: SimUnknown,
+    /// The unknowns in ascending order, the length is the order of the derivative
+    pub cols: Vec<SimUnknown>,
+    pub resist: Value,
+    pub react: Value,
+}
+
 #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
 pub struct MatrixEntry {
     pub row: SimUnknown,
@@ -1,27 +1,32 @@
 use bitset::BitSet;
-use hir::{BranchWrite, CompilationDB, Node, ParamSysFun};
-use hir_lower::{CurrentKind, HirInterner, ImplicitEquation, ParamKind};
-use indexmap::IndexSet;
+use hir::{BranchWrite, CompilationDB, Node, ParamSysFun, Parameter, Type};
+use hir_lower::{CurrentKind, HirInterner, ImplicitEquation, ParamKind, PlaceKind};
+use indexmap::{IndexMap, IndexSet};
+use lasso::Spur;
 use mir::builder::InstBuilder;
 use mir::cursor::{Cursor, FuncCursor};
 use mir::{
     strip_optbarrier, Block, ControlFlowGraph, DominatorTree, Inst, KnownDerivatives, Unknown,
     Value, FALSE, F_ONE, F_ZERO, TRUE,
 };
-use mir_autodiff::auto_diff;
+use mir_autodiff::{auto_diff, higher_order_derivative, HigherOrderDerivatives};
 use rustc_hash::FxHasher;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::hash::BuildHasherDefault;
-use std::mem::replace;
+use std::iter::once;
+use std::mem::{replace, take};
 use std::vec;
 use typed_index_collections::TiVec;
 
 use crate::context::Context;
-use crate::dae::{DaeSystem, MatrixEntry, Residual, ResidualNatureKind, SimUnknown};
+use crate::dae::{
+    AcExcitation, DaeSystem, HigherOrderEntry, MatrixEntry, ParamSensitivity, Residual,
+    ResidualNatureKind, SimUnknown,
+};
 use crate::noise::NoiseSource;
 use crate::topology::{BranchInfo, Contribution};
 use crate::util::{add, is_op_dependent, update_optbarrier};
-use crate::SimUnknownKind;
+use crate::{ModuleInfo, SimUnknownKind};
 
 impl Residual {
     fn add(&mut self, cursor: &mut FuncCursor, negate: bool, mut val: Value) {
@@ -58,6 +63,7 @@ pub(super) struct Builder<'a> {
     pub(super) system: DaeSystem,
     pub(super) cursor: FuncCursor<'a>,
     pub(super) db: &'a CompilationDB,
+    pub(super) module: &'a ModuleInfo,
     pub(super) intern: &'a mut HirInterner,
     pub(super) cfg: &'a mut ControlFlowGraph,
     pub(super) dom_tree: &'a mut DominatorTree,
@@ -72,6 +78,7 @@ impl<'a> Builder<'a> {
             system: DaeSystem::default(),
             cursor: FuncCursor::new(&mut ctx.func).at_exit(),
             db: ctx.db,
+            module: ctx.module,
             intern: &mut ctx.intern,
             cfg: &mut ctx.cfg,
             dom_tree: &mut ctx.dom_tree,
@@ -88,24 +95,55 @@ impl<'a> Builder<'a> {
             builder.build_node(node)
         }
 
+        // the internal nodes of module instances become internal nodes of the flattened module
+        for inst in ctx.module.module.all_instances(builder.db) {
+            for node in inst.internal_nodes(builder.db) {
+                builder.build_node(node)
+            }
+        }
+
         builder
     }
 
     pub(super) fn finish(mut self) -> DaeSystem {
         let sim_unknown_reads = self.sim_unknown_reads();
-        let derivative_info = self.intern.unknowns(&self.cursor, true);
-        let extra_derivatives = self
+        let mut derivative_info = self.intern.unknowns(&self.cursor, true);
+        let sensitivity_params = self.sensitivity_params(&mut derivative_info);
+        let mut extra_derivatives = self
             .jacobian_derivatives(sim_unknown_reads.iter().map(|&(_, val)| val), &derivative_info);
+        extra_derivatives.extend(self.sensitivity_derivatives(&sensitivity_params));
+        let higher_order_unknowns =
+            self.higher_order_unknowns(&sim_unknown_reads, &derivative_info);
+        let higher_order_vals = self.higher_order_vals();
+        let higher_order_derivatives: Vec<_> = higher_order_unknowns.keys().copied().collect();
+        let higher_order = HigherOrderDerivatives {
+            vals: &higher_order_vals,
+            unknowns: &higher_order_derivatives,
+            order: self.module.derivative_order,
+        };
+        let higher_order = (self.module.derivative_order > 1).then_some(&higher_order);
         // TODO(pref): incrementially update dom_tree (for switch branches) instead
         self.dom_tree.compute(self.cursor.func, self.cfg, true, false, true);
-        let derivatives =
-            auto_diff(&mut *self.cursor.func, self.dom_tree, &derivative_info, &extra_derivatives);
+        let derivatives = auto_diff(
+            &mut *self.cursor.func,
+            self.dom_tree,
+            &derivative_info,
+            &extra_derivatives,
+            higher_order,
+        );
         drop(extra_derivatives);
         // auto_diff may in an unlikely case add extra bb at the end, ensure we are building everything at the end
         self.cursor.goto_exit();
 
         self.build_jacobian(&sim_unknown_reads, &derivative_info, &derivatives);
+        self.build_param_sensitivities(&sensitivity_params, &derivatives);
+        self.build_higher_order(&higher_order_unknowns, &derivatives);
         self.build_lim_rhs(&derivative_info, derivatives);
+        let num_unknowns = self.system.unknowns.len();
+        for excitation in &mut self.system.ac_excitations {
+            excitation.real.resize(num_unknowns, F_ZERO);
+            excitation.imag.resize(num_unknowns, F_ZERO);
+        }
         self.ensure_optbarriers();
 
         self.build_input_unknown_pairs();
@@ -401,6 +439,228 @@ impl<'a> Builder<'a> {
         res
     }
 
+    /// The parameters marked for sensitivity analysis and the unknowns that represent them
+    /// during automatic differentiation (`None` if the parameter is not used during evaluation).
+    fn sensitivity_params(
+        &self,
+        derivative_info: &mut KnownDerivatives,
+    ) -> Vec<(Parameter, Option<Unknown>)> {
+        self.module
+            .params
+            .iter()
+            .filter(|(_, info)| info.sensitivity)
+            .map(|(&param, _)| {
+                let unknown = self
+                    .intern
+                    .params
+                    .raw
+                    .get(&ParamKind::Param(param))
+                    .filter(|&&val| !self.cursor.func.dfg.value_dead(val))
+                    .map(|&val| derivative_info.unknowns.ensure(val).0);
+                (param, unknown)
+            })
+            .collect()
+    }
+
+    /// The values of the real operating point variables (in the order of `ModuleInfo::op_vars`).
+    fn op_var_vals(&self) -> Vec<Value> {
+        self.module
+            .op_vars
+            .keys()
+            .map(|&var| {
+                if var.ty(self.db) != Type::Real {
+                    return F_ZERO;
+                }
+                self.intern
+                    .outputs
+                    .get(&PlaceKind::Var(var))
+                    .and_then(|val| val.expand())
+                    .unwrap_or(F_ZERO)
+            })
+            .collect()
+    }
+
+    fn sensitivity_derivatives(
+        &self,
+        params: &[(Parameter, Option<Unknown>)],
+    ) -> Vec<(Value, Unknown)> {
+        let unknowns: Vec<_> = params.iter().filter_map(|&(_, unknown)| unknown).collect();
+        if unknowns.is_empty() {
+            return Vec::new();
+        }
+        let residuals =
+            self.system.residual.iter().flat_map(|residual| [residual.resist, residual.react]);
+        residuals
+            .chain(self.op_var_vals())
+            .filter(|&val| self.cursor.func.dfg.value_def(val).as_const().is_none())
+            .flat_map(|val| unknowns.iter().map(move |&unknown| (val, unknown)))
+            .collect()
+    }
+
+    fn build_param_sensitivities(
+        &mut self,
+        params: &[(Parameter, Option<Unknown>)],
+        derivatives: &HashMap<(Value, Unknown), Value, BuildHasherDefault<FxHasher>>,
+    ) {
+        let op_vars = self.op_var_vals();
+        for &(param, unknown) in params {
+            let ddx = |val| {
+                unknown
+                    .and_then(|unknown| derivatives.get(&(val, unknown)).copied())
+                    .unwrap_or(F_ZERO)
+            };
+            let sensitivity = ParamSensitivity {
+                param,
+                resist: self.system.residual.iter().map(|residual| ddx(residual.resist)).collect(),
+                react: self.system.residual.iter().map(|residual| ddx(residual.react)).collect(),
+                op_vars: op_vars.iter().map(|&val| ddx(val)).collect(),
+            };
+            self.system.param_sensitivities.push(sensitivity);
+        }
+    }
+
+    /// The unknowns (of automatic differentiation) by which the Taylor coefficients are
+    /// calculated and the simulation unknowns (with sign) they depend on. Just like for the
+    /// jacobian, limited values count as the voltage they limit.
+    fn higher_order_unknowns(
+        &self,
+        sim_unknown_reads: &[(ParamKind, Value)],
+        derivative_info: &KnownDerivatives,
+    ) -> IndexMap<Unknown, Vec<(SimUnknown, bool)>, BuildHasherDefault<FxHasher>> {
+        let mut res = IndexMap::default();
+        if self.module.derivative_order < 2 {
+            return res;
+        }
+        for &(kind, val) in sim_unknown_reads {
+            let cols = match kind {
+                ParamKind::Voltage { hi, lo } => {
+                    let mut cols = vec![(SimUnknownKind::KirchoffLaw(hi), false)];
+                    cols.extend(lo.map(|lo| (SimUnknownKind::KirchoffLaw(lo), true)));
+                    cols
+                }
+                ParamKind::ImplicitUnknown(equation) => {
+                    vec![(SimUnknownKind::Implicit(equation), false)]
+                }
+                ParamKind::Current(kind) => vec![(SimUnknownKind::Current(kind), false)],
+                _ => continue,
+            };
+            let cols: Vec<_> = cols
+                .into_iter()
+                .filter_map(|(kind, negate)| Some((self.system.unknowns.index(&kind)?, negate)))
+                .collect();
+            if cols.is_empty() {
+                continue;
+            }
+
+            let lim_vals = self.intern.lim_state.raw.get(&val).into_iter().flatten();
+            for (val, negate) in once((val, false)).chain(lim_vals.copied()) {
+                if self.cursor.func.dfg.value_dead(val) {
+                    continue;
+                }
+                if let Some(unknown) = derivative_info.unknowns.index(&val) {
+                    let dst: &mut Vec<_> = res.entry(unknown).or_default();
+                    dst.extend(cols.iter().map(|&(col, neg)| (col, neg != negate)));
+                }
+            }
+        }
+        res
+    }
+
+    /// The residuals for which Taylor coefficients are calculated. The small signal residuals
+    /// are always zero during large signal analysis and therefore ignored.
+    fn higher_order_vals(&self) -> Vec<Value> {
+        if self.module.derivative_order < 2 {
+            return Vec::new();
+        }
+        self.system
+            .residual
+            .iter()
+            .flat_map(|residual| [residual.resist, residual.react])
+            .filter(|&val| self.cursor.func.dfg.value_def(val).as_const().is_none())
+            .collect()
+    }
+
+    /// Builds the Taylor coefficients of order two up to `ModuleInfo::derivative_order`. The
+    /// derivatives by the simulation unknowns follow from the chain rule (sum over all ordered
+    /// combinations). Only one permutation of the simulation unknowns is stored per coefficient.
+    fn build_higher_order(
+        &mut self,
+        unknowns: &IndexMap<Unknown, Vec<(SimUnknown, bool)>, BuildHasherDefault<FxHasher>>,
+        derivatives: &HashMap<(Value, Unknown), Value, BuildHasherDefault<FxHasher>>,
+    ) {
+        let unknowns: Vec<_> = unknowns.iter().collect();
+        if unknowns.is_empty() {
+            return;
+        }
+        let mut coefficients: BTreeMap<(usize, Vec<SimUnknown>), (Value, Value)> = BTreeMap::new();
+        let mut sorted = Vec::new();
+        let mut cols = Vec::new();
+        for row in self.system.residual.keys() {
+            let residual = self.system.residual[row];
+            for order in 2..=self.module.derivative_order as usize {
+                // iterate all ordered tuples of unknowns
+                let mut tuple = vec![0; order];
+                loop {
+                    sorted.clear();
+                    sorted.extend(tuple.iter().map(|&i| *unknowns[i].0));
+                    sorted.sort_unstable();
+                    let resist = higher_order_derivative(derivatives, residual.resist, &sorted);
+                    let react = higher_order_derivative(derivatives, residual.react, &sorted);
+                    if resist != F_ZERO || react != F_ZERO {
+                        let mut col_tuple = vec![0; order];
+                        loop {
+                            cols.clear();
+                            let mut negate = false;
+                            for (&i, &j) in tuple.iter().zip(&col_tuple) {
+                                let (col, neg) = unknowns[i].1[j];
+                                cols.push(col);
+                                negate ^= neg;
+                            }
+                            if cols.windows(2).all(|cols| cols[0] <= cols[1]) {
+                                let dst = coefficients
+                                    .entry((order, cols.clone()))
+                                    .or_insert((F_ZERO, F_ZERO));
+                                add(&mut self.cursor, &mut dst.0, resist, negate);
+                                add(&mut self.cursor, &mut dst.1, react, negate);
+                            }
+                            if !next_tuple(&mut col_tuple, |pos| unknowns[tuple[pos]].1.len()) {
+                                break;
+                            }
+                        }
+                    }
+                    if !next_tuple(&mut tuple, |_| unknowns.len()) {
+                        break;
+                    }
+                }
+            }
+
+            for ((_, cols), (mut resist, mut react)) in take(&mut coefficients) {
+                // divide by the factorial of the multiplicity of each unknown
+                let mut factor = 1.0;
+                let mut multiplicity = 1.0;
+                for pair in cols.windows(2) {
+                    if pair[0] == pair[1] {
+                        multiplicity += 1.0;
+                        factor *= multiplicity;
+                    } else {
+                        multiplicity = 1.0;
+                    }
+                }
+                if factor != 1.0 {
+                    let factor = self.cursor.func.dfg.f64const(1.0 / factor);
+                    for val in [&mut resist, &mut react] {
+                        if *val != F_ZERO {
+                            *val = self.cursor.ins().fmul(factor, *val);
+                        }
+                    }
+                }
+                if resist != F_ZERO || react != F_ZERO {
+                    self.system.higher_order.push(HigherOrderEntry { row, cols, resist, react });
+                }
+            }
+        }
+    }
+
     pub(super) fn build_branch(&mut self, branch: BranchWrite, contributions: &BranchInfo) {
         let current = branch.into();
         // contributions.is_voltage_src is a Value that is used for choosing the branch type (voltage, current)
@@ -524,6 +784,7 @@ impl<'a> Builder<'a> {
             &mut self.cursor,
             false,
         );
+        self.add_ac_stim(contrib, SimUnknownKind::Implicit(eq), None);
     }
 
     fn mfactor_multiply(&mut self, mfactor: Value, srcfactor: Value) -> Value {
@@ -581,6 +842,7 @@ impl<'a> Builder<'a> {
             resist_small_signal: current_src.resist_small_signal,
             react_small_signal: current_src.react_small_signal,
             noise,
+            ac_stim: current_src.ac_stim.clone(),
         }
     }
 
@@ -603,6 +865,7 @@ impl<'a> Builder<'a> {
             resist_small_signal: voltage_src.resist_small_signal,
             react_small_signal: voltage_src.react_small_signal,
             noise,
+            ac_stim: voltage_src.ac_stim.clone(),
         }
     }
 
@@ -645,6 +908,20 @@ impl<'a> Builder<'a> {
             src
         });
         noise.extend(current_noise);
+        // AC stimuli are joined the same way as noise (but are not scaled)
+        let mut ac_stim = Vec::with_capacity(voltage_src.ac_stim.len() + current_src.ac_stim.len());
+        let voltage_ac_stim = voltage_src.ac_stim.iter().map(|src| {
+            let mut src = src.clone();
+            src.factor = select(src.factor, F_ZERO);
+            src
+        });
+        ac_stim.extend(voltage_ac_stim);
+        let current_ac_stim = current_src.ac_stim.iter().map(|src| {
+            let mut src = src.clone();
+            src.factor = select(F_ZERO, src.factor);
+            src
+        });
+        ac_stim.extend(current_ac_stim);
         // Build remaining phi commands
         let phi_resist = select(voltage_src.resist, current_src.resist);
         let phi_react = select(voltage_src.react, current_src.react);
@@ -674,6 +951,7 @@ impl<'a> Builder<'a> {
             resist_small_signal: phi_resist_ss,
             react_small_signal: phi_react_ss,
             noise,
+            ac_stim,
         }
     }
 
@@ -699,6 +977,58 @@ impl<'a> Builder<'a> {
         }))
     }
 
+    /// The index of the excitation of the AC analysis `analysis` (created on demand)
+    fn ac_excitation(&mut self, analysis: Spur) -> usize {
+        let pos = match self.system.ac_excitations.iter().position(|it| it.analysis == analysis) {
+            Some(pos) => pos,
+            None => {
+                self.system.ac_excitations.push(AcExcitation {
+                    analysis,
+                    real: TiVec::new(),
+                    imag: TiVec::new(),
+                });
+                self.system.ac_excitations.len() - 1
+            }
+        };
+        let num_unknowns = self.system.unknowns.len();
+        let excitation = &mut self.system.ac_excitations[pos];
+        excitation.real.resize(num_unknowns, F_ZERO);
+        excitation.imag.resize(num_unknowns, F_ZERO);
+        pos
+    }
+
+    fn add_ac_stim(
+        &mut self,
+        contrib: &Contribution,
+        hi: SimUnknownKind,
+        lo: Option<SimUnknownKind>,
+    ) {
+        let hi = self.ensure_unknown(hi);
+        let lo = lo.map(|lo| self.ensure_unknown(lo));
+        for src in &contrib.ac_stim {
+            // factor * mag * exp(j * phase)
+            let mut mag = strip_optbarrier(&self.cursor, src.mag);
+            let factor = strip_optbarrier(&self.cursor, src.factor);
+            if factor != F_ONE {
+                mag = self.cursor.ins().fmul(factor, mag);
+            }
+            let (real, imag) = match strip_optbarrier(&self.cursor, src.phase) {
+                F_ZERO => (mag, F_ZERO),
+                phase => {
+                    let cos = self.cursor.ins().cos(phase);
+                    let sin = self.cursor.ins().sin(phase);
+                    (self.cursor.ins().fmul(mag, cos), self.cursor.ins().fmul(mag, sin))
+                }
+            };
+            let pos = self.ac_excitation(src.analysis);
+            let excitation = &mut self.system.ac_excitations[pos];
+            for (unknown, negate) in once((hi, false)).chain(lo.map(|lo| (lo, true))) {
+                add(&mut self.cursor, &mut excitation.real[unknown], real, negate);
+                add(&mut self.cursor, &mut excitation.imag[unknown], imag, negate);
+            }
+        }
+    }
+
     fn add_kirchoff_law(&mut self, contrib: &Contribution, dst: BranchWrite) {
         let (hi, lo) = dst.nodes(self.db);
         let hi = SimUnknownKind::KirchoffLaw(hi);
@@ -709,6 +1039,7 @@ impl<'a> Builder<'a> {
         }
         // self.add_noise(contrib, hi, lo, true);
         self.add_noise(contrib, hi, lo);
+        self.add_ac_stim(contrib, hi, lo);
     }
 
     fn add_source_equation(
@@ -724,6 +1055,7 @@ impl<'a> Builder<'a> {
         residual.nature_kind = nature_kind;
         // self.add_noise(contrib, SimUnknownKind::Current(dst.into()), None, false);
         self.add_noise(contrib, SimUnknownKind::Current(dst.into()), None);
+        self.add_ac_stim(contrib, SimUnknownKind::Current(dst.into()), None);
 
         let (hi, lo) = dst.nodes(self.db);
         let hi = SimUnknownKind::KirchoffLaw(hi);
@@ -765,11 +1097,52 @@ impl<'a> Builder<'a> {
             noise_src.map_vals(|val| ensure_optbarrier(val, false));
         }
 
+        for excitation in &mut self.system.ac_excitations {
+            for (unknown, kind) in self.system.unknowns.iter_enumerated() {
+                let is_kirchoff = matches!(kind, SimUnknownKind::KirchoffLaw(_));
+                excitation.real[unknown] = ensure_optbarrier(excitation.real[unknown], is_kirchoff);
+                excitation.imag[unknown] = ensure_optbarrier(excitation.imag[unknown], is_kirchoff);
+            }
+        }
+
+        for sensitivity in &mut self.system.param_sensitivities {
+            for (unknown, kind) in self.system.unknowns.iter_enumerated() {
+                let is_kirchoff = matches!(kind, SimUnknownKind::KirchoffLaw(_));
+                sensitivity.resist[unknown] =
+                    ensure_optbarrier(sensitivity.resist[unknown], is_kirchoff);
+                sensitivity.react[unknown] =
+                    ensure_optbarrier(sensitivity.react[unknown], is_kirchoff);
+            }
+            for val in &mut sensitivity.op_vars {
+                *val = ensure_optbarrier(*val, false);
+            }
+        }
+
         for entry in &mut self.system.jacobian {
             let is_kirchoff =
                 matches!(self.system.unknowns[entry.row], SimUnknownKind::KirchoffLaw(_));
             entry.resist = ensure_optbarrier(entry.resist, is_kirchoff);
             entry.react = ensure_optbarrier(entry.react, is_kirchoff);
         }
+
+        for entry in &mut self.system.higher_order {
+            let is_kirchoff =
+                matches!(self.system.unknowns[entry.row], SimUnknownKind::KirchoffLaw(_));
+            entry.resist = ensure_optbarrier(entry.resist, is_kirchoff);
+            entry.react = ensure_optbarrier(entry.react, is_kirchoff);
+        }
+    }
+}
+
+/// Advances `tuple` to the next tuple in lexicographic order where the element at `pos` is
+/// smaller than `len(pos)`. Returns `false` once all tuples have been visited.
+fn next_tuple(tuple: &mut [usize], len: impl Fn(usize) -> usize) -> bool {
+    for pos in (0..tuple.len()).rev() {
+        tuple[pos] += 1;
+        if tuple[pos] < len(pos) {
+            return true;
+        }
+        tuple[pos] = 0;
     }
+    false
 }
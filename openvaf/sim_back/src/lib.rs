@@ -3,7 +3,7 @@ use hir_lower::{CurrentKind, HirInterner, ImplicitEquation, ParamKind};
 use lasso::Rodeo;
 use mir::Function;
 use mir_opt::{simplify_cfg, sparse_conditional_constant_propagation};
-pub use module_info::{collect_modules, ModuleInfo};
+pub use module_info::{collect_modules, FreezeError, FrozenParam, ModuleInfo};
 use stdx::impl_debug_display;
 
 use crate::context::{Context, OptimiziationStage};
@@ -211,6 +211,8 @@ impl<'a> CompiledModule<'a> {
             .collect();
         // Add initialization of instance parameters
         init.intern.insert_param_init(db, &mut init.func, literals, false, true, &inst_params);
+        // the defaults of the remaining parameters may depend on frozen parameters
+        module.insert_frozen_params(&mut init.func, &init.intern, literals);
 
         // Model setup MIR
         let mut model_param_setup = Function::default();
@@ -224,6 +226,7 @@ impl<'a> CompiledModule<'a> {
             true,
             &model_params,
         );
+        module.insert_frozen_params(&mut model_param_setup, &model_param_intern, literals);
         cx.cfg.compute(&model_param_setup);
         simplify_cfg(&mut model_param_setup, &mut cx.cfg);
         sparse_conditional_constant_propagation(&mut model_param_setup, &cx.cfg);
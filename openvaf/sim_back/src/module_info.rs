@@ -1,15 +1,19 @@
+use std::cmp::Ordering;
 use std::hash::BuildHasherDefault;
 
 use ahash::AHashSet;
 use hir::diagnostics::{BaseDB, ConsoleSink, Diagnostic, FileId, Label, LabelStyle, Report};
 use hir::{
-    CompilationDB, CompilationUnit, DiagnosticSink, Module, ParamSysFun, Parameter,
-    ResolvedAliasParameter, ScopeDef, Variable,
+    BodyRef, CompilationDB, CompilationUnit, ConstraintValue, DiagnosticSink, Expr, ExprId,
+    Literal, Module, ParamSysFun, Parameter, Ref, ResolvedAliasParameter, ScopeDef, Type, Variable,
 };
+use hir_lower::{HirInterner, ParamKind};
 use indexmap::IndexMap;
+use lasso::Rodeo;
+use mir::{Function, TRUE};
 use rustc_hash::FxHasher;
 use smol_str::SmolStr;
-use syntax::ast::{self, Expr};
+use syntax::ast::{self, BinaryOp, ConstraintKind, UnaryOp};
 use syntax::sourcemap::FileSpan;
 use syntax::AstNode;
 
@@ -48,6 +52,12 @@ pub struct ModuleInfo {
     pub params: IndexMap<Parameter, ParamInfo, BuildHasherDefault<FxHasher>>,
     pub sys_fun_alias: IndexMap<ParamSysFun, Vec<SmolStr>, BuildHasherDefault<FxHasher>>,
     pub op_vars: IndexMap<Variable, OpVar, BuildHasherDefault<FxHasher>>,
+    /// The highest order of the derivatives of the residuals by the unknowns. Orders above one
+    /// generate the Taylor coefficients in `DaeSystem::higher_order`.
+    pub derivative_order: u32,
+    /// Parameters that are replaced with a constant at compile time (see
+    /// [`ModuleInfo::freeze_param`]). These are not part of `params`.
+    pub frozen_params: IndexMap<Parameter, FrozenParam, BuildHasherDefault<FxHasher>>,
 }
 
 impl ModuleInfo {
@@ -90,6 +100,11 @@ impl ModuleInfo {
                         continue;
                     }
 
+                    // operating point variables are scalars
+                    if var.array_range(db).is_some() {
+                        continue;
+                    }
+
                     // check that we are not in a block
                     let name_len = name.len();
                     let path = declarations.to_path(name);
@@ -172,6 +187,16 @@ impl ModuleInfo {
                         }
                     };
 
+                    // (* sensitivity *) requests derivatives by this parameter
+                    let sensitivity = match param.get_attr(db, &ast, "sensitivity") {
+                        Some(_) if param.ty(db) == Type::Real => true,
+                        Some(attr) => {
+                            add_diagnostic(attr.clone(), &IllegalSensitivity { attr });
+                            false
+                        }
+                        None => false,
+                    };
+
                     params.insert(
                         param,
                         ParamInfo {
@@ -181,6 +206,7 @@ impl ModuleInfo {
                             description: desc,
                             group,
                             is_instance,
+                            sensitivity,
                         },
                     );
                 }
@@ -198,8 +224,220 @@ impl ModuleInfo {
             }
         }
 
-        ModuleInfo { module, params, op_vars, sys_fun_alias }
+        ModuleInfo {
+            module,
+            params,
+            op_vars,
+            sys_fun_alias,
+            derivative_order: 1,
+            frozen_params: IndexMap::default(),
+        }
+    }
+
+    /// Marks the real parameter `name` (or the parameter aliased by `name`) for sensitivity
+    /// analysis. Returns `false` if the module has no such parameter.
+    pub fn mark_sensitivity(&mut self, db: &CompilationDB, name: &str) -> bool {
+        let param = self
+            .params
+            .iter_mut()
+            .find(|(_, info)| info.name == name || info.alias.iter().any(|alias| alias == name));
+        match param {
+            Some((param, info)) if param.ty(db) == Type::Real => {
+                info.sensitivity = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Replaces the parameter `name` (or the parameter aliased by `name`) with the constant
+    /// `value`. The parameter is removed from `params` so simulators can no longer set it and
+    /// all code that only depends on it is constant folded. `value` is parsed according to the
+    /// type of the parameter. Values outside of the `from`/`exclude` range of the parameter are
+    /// rejected with [`FreezeError::OutOfBounds`]. Bounds may only refer to parameters that have
+    /// already been frozen, otherwise [`FreezeError::UnknownBounds`] is returned.
+    pub fn freeze_param(
+        &mut self,
+        db: &CompilationDB,
+        name: &str,
+        value: &str,
+    ) -> Result<(), FreezeError> {
+        let (param, _) = self
+            .params
+            .iter()
+            .find(|(_, info)| info.name == name || info.alias.iter().any(|alias| alias == name))
+            .ok_or(FreezeError::UnknownParam)?;
+        let param = *param;
+        let ty = param.ty(db);
+        let value = match ty {
+            Type::Real => value.parse().ok().map(FrozenParam::Real),
+            Type::Integer => value.parse().ok().map(FrozenParam::Integer),
+            Type::String => {
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .unwrap_or(value);
+                Some(FrozenParam::String(value.into()))
+            }
+            _ => None,
+        };
+        let value = value.ok_or(FreezeError::InvalidValue(ty))?;
+        match self.frozen_in_bounds(db, param, &value) {
+            Some(true) => (),
+            Some(false) => return Err(FreezeError::OutOfBounds),
+            None => return Err(FreezeError::UnknownBounds),
+        }
+        self.params.shift_remove(&param);
+        self.frozen_params.insert(param, value);
+        Ok(())
     }
+
+    /// Returns whether `value` satisfies the `from` and `exclude` constraints of `param`.
+    /// Returns `None` if a bound is not constant.
+    fn frozen_in_bounds(
+        &self,
+        db: &CompilationDB,
+        param: Parameter,
+        value: &FrozenParam,
+    ) -> Option<bool> {
+        // the ranges of paramset parameters select the paramset instead of bounding the value
+        if param.selects_paramset(db) {
+            return Some(true);
+        }
+        let body = param.init(db);
+        let body = body.borrow();
+        let mut from = None;
+        let mut excluded = false;
+        for bound in param.bounds(db).iter() {
+            let matches = match bound.val {
+                ConstraintValue::Value(expr) => {
+                    value.compare(&self.const_eval(body, expr)?)? == Ordering::Equal
+                }
+                ConstraintValue::Range(range) => {
+                    let start = self.const_eval(body, range.start)?.compare(value)?;
+                    let end = value.compare(&self.const_eval(body, range.end)?)?;
+                    let in_bound = |ord, inclusive| {
+                        ord == Ordering::Less || (inclusive && ord == Ordering::Equal)
+                    };
+                    in_bound(start, range.start_inclusive) && in_bound(end, range.end_inclusive)
+                }
+            };
+            match bound.kind {
+                ConstraintKind::From => *from.get_or_insert(false) |= matches,
+                ConstraintKind::Exclude => excluded |= matches,
+            }
+        }
+        Some(from.unwrap_or(true) && !excluded)
+    }
+
+    /// Evaluates the bound `expr` of a parameter. Only literals, frozen parameters and
+    /// arithmetic are supported.
+    fn const_eval(&self, body: BodyRef<'_>, expr: ExprId) -> Option<FrozenParam> {
+        let val = match body.get_expr(expr) {
+            Expr::Literal(Literal::Int(val)) => FrozenParam::Integer(*val),
+            Expr::Literal(Literal::Float(val)) => FrozenParam::Real((*val).into()),
+            Expr::Literal(Literal::Inf) => FrozenParam::Real(f64::INFINITY),
+            Expr::Literal(Literal::String(val)) => FrozenParam::String(val.as_ref().into()),
+            Expr::Read(Ref::Parameter(param)) => self.frozen_params.get(&param)?.clone(),
+            Expr::UnaryOp { expr, op: UnaryOp::Identity } => self.const_eval(body, expr)?,
+            Expr::UnaryOp { expr, op: UnaryOp::Neg } => match self.const_eval(body, expr)? {
+                FrozenParam::Real(val) => FrozenParam::Real(-val),
+                FrozenParam::Integer(val) => FrozenParam::Integer(val.checked_neg()?),
+                FrozenParam::String(_) => return None,
+            },
+            Expr::BinaryOp { lhs, rhs, op } => {
+                match (self.const_eval(body, lhs)?, self.const_eval(body, rhs)?) {
+                    (FrozenParam::Integer(lhs), FrozenParam::Integer(rhs)) => {
+                        FrozenParam::Integer(match op {
+                            BinaryOp::Addition => lhs.checked_add(rhs)?,
+                            BinaryOp::Subtraction => lhs.checked_sub(rhs)?,
+                            BinaryOp::Multiplication => lhs.checked_mul(rhs)?,
+                            BinaryOp::Division => lhs.checked_div(rhs)?,
+                            _ => return None,
+                        })
+                    }
+                    (lhs, rhs) => {
+                        let (lhs, rhs) = (lhs.as_real()?, rhs.as_real()?);
+                        FrozenParam::Real(match op {
+                            BinaryOp::Addition => lhs + rhs,
+                            BinaryOp::Subtraction => lhs - rhs,
+                            BinaryOp::Multiplication => lhs * rhs,
+                            BinaryOp::Division => lhs / rhs,
+                            BinaryOp::Power => lhs.powf(rhs),
+                            _ => return None,
+                        })
+                    }
+                }
+            }
+            _ => return None,
+        };
+        Some(val)
+    }
+
+    /// Replaces all reads of frozen parameters (and their `$param_given`) in `func` with
+    /// constants.
+    pub(crate) fn insert_frozen_params(
+        &self,
+        func: &mut Function,
+        intern: &HirInterner,
+        literals: &mut Rodeo,
+    ) {
+        for (kind, &val) in intern.params.iter() {
+            let frozen_val = match *kind {
+                ParamKind::Param(param) => match self.frozen_params.get(&param) {
+                    Some(&FrozenParam::Real(frozen)) => func.dfg.fconst(frozen.into()),
+                    Some(&FrozenParam::Integer(frozen)) => func.dfg.iconst(frozen),
+                    Some(FrozenParam::String(frozen)) => {
+                        func.dfg.sconst(literals.get_or_intern(frozen))
+                    }
+                    None => continue,
+                },
+                // frozen parameters are always given
+                ParamKind::ParamGiven { param } if self.frozen_params.contains_key(&param) => TRUE,
+                _ => continue,
+            };
+            func.dfg.replace_uses(val, frozen_val);
+        }
+    }
+}
+
+/// The constant value of a parameter frozen with [`ModuleInfo::freeze_param`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrozenParam {
+    Real(f64),
+    Integer(i32),
+    String(SmolStr),
+}
+
+impl FrozenParam {
+    fn as_real(&self) -> Option<f64> {
+        match *self {
+            FrozenParam::Real(val) => Some(val),
+            FrozenParam::Integer(val) => Some(val.into()),
+            FrozenParam::String(_) => None,
+        }
+    }
+
+    /// Compares two values, integers are converted to real if the other value is real
+    fn compare(&self, other: &FrozenParam) -> Option<Ordering> {
+        match (self, other) {
+            (FrozenParam::Integer(lhs), FrozenParam::Integer(rhs)) => Some(lhs.cmp(rhs)),
+            (FrozenParam::String(lhs), FrozenParam::String(rhs)) => Some(lhs.cmp(rhs)),
+            _ => self.as_real()?.partial_cmp(&other.as_real()?),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FreezeError {
+    /// The module has no parameter with the requested name
+    UnknownParam,
+    /// The value can not be parsed as the type of the parameter (or the parameter is an array)
+    InvalidValue(Type),
+    /// The value violates the `from`/`exclude` constraints of the parameter
+    OutOfBounds,
+    /// The bounds of the parameter depend on parameters that are not frozen
+    UnknownBounds,
 }
 
 struct IllegalAttr {
@@ -225,8 +463,28 @@ impl Diagnostic for IllegalAttr {
     }
 }
 
+struct IllegalSensitivity {
+    attr: ast::Attr,
+}
+
+impl Diagnostic for IllegalSensitivity {
+    fn build_report(&self, root_file: FileId, db: &dyn BaseDB) -> Report {
+        let FileSpan { range, file } = db
+            .parse(root_file)
+            .to_file_span(self.attr.syntax().text_range(), &db.sourcemap(root_file));
+        Report::warning()
+            .with_message("'sensitivity' attribute is ignored; only real parameters are supported")
+            .with_labels(vec![Label {
+                style: LabelStyle::Primary,
+                file_id: file,
+                range: range.into(),
+                message: "not a real parameter".to_owned(),
+            }])
+    }
+}
+
 struct UnknownType<'a> {
-    expr: Expr,
+    expr: ast::Expr,
     found: &'a str,
 }
 
@@ -258,6 +516,9 @@ pub struct ParamInfo {
     pub description: String,
     pub group: String,
     pub is_instance: bool,
+    /// Derivatives of the residual and the operating point variables by this parameter are
+    /// calculated (see `DaeSystem::param_sensitivities`)
+    pub sensitivity: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -1,8 +1,10 @@
 use hir::diagnostics::sink::Buffer;
 use hir::diagnostics::ConsoleSink;
-use hir::CompilationDB;
+use hir::{CompilationDB, Type};
 use indoc::indoc;
 
+use super::FreezeError;
+
 #[test]
 fn invalid_attr() {
     let src = indoc! {r#"
@@ -95,6 +97,7 @@ fn parameters() {
                     description: "hmm",
                     group: "foo",
                     is_instance: true,
+                    sensitivity: false,
                 },
             ),
             (
@@ -106,6 +109,7 @@ fn parameters() {
                     description: "hmm",
                     group: "foo",
                     is_instance: true,
+                    sensitivity: false,
                 },
             ),
             (
@@ -117,6 +121,7 @@ fn parameters() {
                     description: "",
                     group: "",
                     is_instance: false,
+                    sensitivity: false,
                 },
             ),
         ]
@@ -171,3 +176,98 @@ fn opvars() {
     "#]]
     .assert_debug_eq(&params);
 }
+
+#[test]
+fn freeze_params() {
+    let src = indoc! {r#"
+        module test;
+            parameter real foo=2.0;
+            parameter integer version=1;
+            parameter string mode="fast";
+            aliasparam alias=foo;
+        endmodule
+    "#};
+    let db = CompilationDB::new_virtual(src).unwrap();
+    let mut modules = super::collect_modules(&db, false, &mut ConsoleSink::new(&db)).unwrap();
+    let module = &mut modules[0];
+    assert_eq!(module.freeze_param(&db, "alias", "3.5"), Ok(()));
+    assert_eq!(module.freeze_param(&db, "version", "2"), Ok(()));
+    assert_eq!(module.freeze_param(&db, "mode", "\"slow\""), Ok(()));
+    assert_eq!(module.freeze_param(&db, "foo", "1.0"), Err(FreezeError::UnknownParam));
+    assert_eq!(module.freeze_param(&db, "missing", "1"), Err(FreezeError::UnknownParam));
+
+    assert!(module.params.is_empty());
+    let frozen: Vec<_> =
+        module.frozen_params.iter().map(|(param, val)| (param.name(&db), val)).collect();
+    expect_test::expect![[r#"
+        [
+            (
+                "foo",
+                Real(
+                    3.5,
+                ),
+            ),
+            (
+                "version",
+                Integer(
+                    2,
+                ),
+            ),
+            (
+                "mode",
+                String(
+                    "slow",
+                ),
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(&frozen);
+}
+
+#[test]
+fn freeze_invalid_value() {
+    let src = indoc! {r#"
+        module test;
+            parameter integer version=1;
+        endmodule
+    "#};
+    let db = CompilationDB::new_virtual(src).unwrap();
+    let mut modules = super::collect_modules(&db, false, &mut ConsoleSink::new(&db)).unwrap();
+    let module = &mut modules[0];
+    assert_eq!(
+        module.freeze_param(&db, "version", "1.5"),
+        Err(FreezeError::InvalidValue(Type::Integer))
+    );
+    // the parameter is kept if the value is invalid
+    assert!(module.frozen_params.is_empty());
+    assert_eq!(module.params.len(), 1);
+}
+
+#[test]
+fn freeze_out_of_bounds() {
+    let src = indoc! {r#"
+        module test;
+            parameter real r=1.0 from (0:inf) exclude 5.0;
+            parameter real rmax=2.0 from [r:10.0];
+            parameter integer version=1 from [1:3];
+            parameter string mode="fast" exclude "slow";
+        endmodule
+    "#};
+    let db = CompilationDB::new_virtual(src).unwrap();
+    let mut modules = super::collect_modules(&db, false, &mut ConsoleSink::new(&db)).unwrap();
+    let module = &mut modules[0];
+    assert_eq!(module.freeze_param(&db, "r", "0"), Err(FreezeError::OutOfBounds));
+    assert_eq!(module.freeze_param(&db, "r", "5"), Err(FreezeError::OutOfBounds));
+    assert_eq!(module.freeze_param(&db, "version", "4"), Err(FreezeError::OutOfBounds));
+    assert_eq!(module.freeze_param(&db, "mode", "slow"), Err(FreezeError::OutOfBounds));
+    // the bounds of rmax depend on r
+    assert_eq!(module.freeze_param(&db, "rmax", "3.0"), Err(FreezeError::UnknownBounds));
+    assert!(module.frozen_params.is_empty());
+
+    assert_eq!(module.freeze_param(&db, "r", "4.0"), Ok(()));
+    assert_eq!(module.freeze_param(&db, "rmax", "3.0"), Err(FreezeError::OutOfBounds));
+    assert_eq!(module.freeze_param(&db, "rmax", "4.0"), Ok(()));
+    assert_eq!(module.freeze_param(&db, "version", "3"), Ok(()));
+    assert_eq!(module.freeze_param(&db, "mode", "medium"), Ok(()));
+    assert!(module.params.is_empty());
+}
@@ -64,6 +64,7 @@ pub(crate) struct Contribution {
     pub resist_small_signal: Value,
     pub react_small_signal: Value,
     pub noise: Vec<Noise>,
+    pub ac_stim: Vec<AcStim>,
 }
 
 impl Contribution {
@@ -73,6 +74,27 @@ impl Contribution {
             && self.resist_small_signal == F_ZERO
             && self.react_small_signal == F_ZERO
             && self.noise.is_empty()
+            && self.ac_stim.is_empty()
+    }
+
+    /// Adds the small signal source (noise or `ac_stim`) called by `inst` scaled by `factor`.
+    pub fn add_small_signal_src(
+        &mut self,
+        inst: Inst,
+        cb: &CallBackKind,
+        factor: Value,
+        ssa_builder: &mut SSAVariableBuilder,
+        func: &mut Function,
+    ) {
+        if let CallBackKind::AcStim { name, .. } = *cb {
+            let [mag, phase] = [0, 1].map(|i| {
+                let arg = func.dfg.instr_args(inst)[i];
+                ssa_builder.define_at_exit(func, F_ZERO, arg, inst)
+            });
+            self.ac_stim.push(AcStim { analysis: name, mag, phase, factor })
+        } else {
+            self.noise.push(Noise::new(inst, cb, factor, ssa_builder, func))
+        }
     }
 }
 
@@ -85,6 +107,7 @@ impl Default for Contribution {
             resist_small_signal: F_ZERO,
             react_small_signal: F_ZERO,
             noise: Vec::new(),
+            ac_stim: Vec::new(),
         }
     }
 }
@@ -146,6 +169,16 @@ impl Noise {
     }
 }
 
+/// A contribution of an `ac_stim` source: `factor * mag * exp(j * phase)` during the AC
+/// analysis `analysis`.
+#[derive(Debug, Clone)]
+pub struct AcStim {
+    pub analysis: Spur,
+    pub mag: Value,
+    pub phase: Value,
+    pub factor: Value,
+}
+
 /// An intermediat representation the toplology of a circuit. It represents circuit
 /// topology as a set of contributions to branches and implicit equations. These contributions
 /// are divided into resistive/reactive voltage/current
@@ -262,6 +295,7 @@ impl Topology {
                                 resist_small_signal: F_ZERO,
                                 react_small_signal: F_ZERO,
                                 noise: Vec::new(),
+                                ac_stim: Vec::new(),
                             },
                             current_src: Contribution {
                                 unknown: current,
@@ -270,6 +304,7 @@ impl Topology {
                                 resist_small_signal: F_ZERO,
                                 react_small_signal: F_ZERO,
                                 noise: Vec::new(),
+                                ac_stim: Vec::new(),
                             },
                         };
                         branches.insert_full(branch, contrib);
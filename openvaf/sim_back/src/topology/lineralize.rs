@@ -14,7 +14,7 @@ use mir::{
 };
 use typed_indexmap::TiSet;
 
-use crate::topology::{Contribution, Noise};
+use crate::topology::Contribution;
 use crate::util::{add, update_optbarrier};
 
 #[derive(Debug)]
@@ -44,7 +44,7 @@ impl<'a> super::Builder<'a> {
         for (operator_inst, evaluation) in analog_operators {
             let arg0 = self.func.dfg.instr_args(operator_inst)[0];
             let cb = self.func.dfg.func_ref(operator_inst).unwrap();
-            let is_noise = intern.callbacks[cb].is_noise();
+            let is_small_signal_src = intern.callbacks[cb].is_small_signal_src();
             match evaluation {
                 Evaluation::Dead => {
                     cov_mark::hit!(dead_noise);
@@ -59,19 +59,18 @@ impl<'a> super::Builder<'a> {
                         let inst = self.func.dfg.value_def(resistive_contribute).inst().unwrap();
                         let contribute = self.topology.as_contribution(*contribute).unwrap();
                         let contribute = self.topology.get_mut(contribute);
-                        if is_noise {
+                        if is_small_signal_src {
                             dimension = FuncCursor::new(self.func)
                                 .after_inst(inst)
                                 .ins()
                                 .ensure_optbarrier(dimension);
-                            let noise = Noise::new(
+                            contribute.add_small_signal_src(
                                 operator_inst,
                                 cb,
                                 dimension,
                                 &mut ssa_builder,
                                 self.func,
                             );
-                            contribute.noise.push(noise)
                         } else {
                             update_optbarrier(
                                 self.func,
@@ -85,12 +84,17 @@ impl<'a> super::Builder<'a> {
                     }
                 }
                 Evaluation::Equation => {
-                    let eq = if is_noise {
+                    let eq = if let CallBackKind::AcStim { .. } = intern.callbacks[cb] {
+                        ImplicitEquationKind::AcStimSrc
+                    } else if is_small_signal_src {
                         ImplicitEquationKind::NoiseSrc
                     } else {
                         ImplicitEquationKind::Ddt
                     };
                     let eq = intern.implicit_equations.push_and_get_key(eq);
+                    if let Some(unknown) = intern.operator_unknowns.get(&operator_inst).cloned() {
+                        intern.set_implicit_unknown(eq, unknown);
+                    }
                     let eq_val =
                         intern.ensure_param(&mut self.func, ParamKind::ImplicitUnknown(eq));
                     let res = self.func.dfg.first_result(operator_inst);
@@ -106,20 +110,21 @@ impl<'a> super::Builder<'a> {
                     }
 
                     let neg_eq_val = FuncCursor::new(self.func).at_exit().ins().fneg(eq_val);
-                    let contributions = if is_noise {
+                    let contributions = if is_small_signal_src {
                         self.topology.small_signal_vals.insert(eq_val);
-                        Contribution {
+                        let mut contribution = Contribution {
                             unknown: Some(eq_val),
                             resist: neg_eq_val,
-                            noise: vec![Noise::new(
-                                operator_inst,
-                                &intern.callbacks[cb],
-                                F_ONE,
-                                &mut ssa_builder,
-                                self.func,
-                            )],
                             ..Contribution::default()
-                        }
+                        };
+                        contribution.add_small_signal_src(
+                            operator_inst,
+                            &intern.callbacks[cb],
+                            F_ONE,
+                            &mut ssa_builder,
+                            self.func,
+                        );
+                        contribution
                     } else {
                         let arg0 =
                             ssa_builder.define_at_exit(self.func, F_ZERO, arg0, operator_inst);
@@ -179,7 +184,8 @@ impl<'a> super::Builder<'a> {
                 }
                 CallBackKind::WhiteNoise { .. }
                 | CallBackKind::FlickerNoise { .. }
-                | CallBackKind::NoiseTable(_) => {
+                | CallBackKind::NoiseTable(_)
+                | CallBackKind::AcStim { .. } => {
                     for inst in take(uses) {
                         analog_operators.push((
                             inst,
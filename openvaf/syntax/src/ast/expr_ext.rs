@@ -366,6 +366,12 @@ impl ast::SelectExpr {
     }
 }
 
+impl ast::IndexExpr {
+    pub fn index(&self) -> Option<ast::Expr> {
+        support::children(self.syntax()).nth(1)
+    }
+}
+
 pub enum AsssigmentOp {
     /// a variable assignment stmt
     /// lhs must be an identifier (example `I = V(a,c)/R;`)
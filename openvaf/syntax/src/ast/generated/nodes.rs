@@ -147,6 +147,8 @@ impl EventStmt {
     pub fn final_step_token(&self) -> Option<SyntaxToken> {
         support::token(&self.syntax, T![final_step])
     }
+    pub fn exprs(&self) -> AstChildren<Expr> { support::children(&self.syntax) }
+    pub fn ident_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![ident]) }
     pub fn r_paren_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![')']) }
     pub fn stmt(&self) -> Option<Stmt> { support::child(&self.syntax) }
 }
@@ -284,6 +286,15 @@ impl PortFlow {
     pub fn r_angle_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![>]) }
 }
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IndexExpr {
+    pub(crate) syntax: SyntaxNode,
+}
+impl IndexExpr {
+    pub fn base(&self) -> Option<Expr> { support::child(&self.syntax) }
+    pub fn l_brack_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T!['[']) }
+    pub fn r_brack_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![']']) }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ArgList {
     pub(crate) syntax: SyntaxNode,
 }
@@ -402,6 +413,17 @@ impl NetDecl {
     pub fn net_type_token(&self) -> Option<SyntaxToken> {
         support::token(&self.syntax, T![net_type])
     }
+    pub fn range(&self) -> Option<Range> { support::child(&self.syntax) }
+    pub fn names(&self) -> AstChildren<Name> { support::children(&self.syntax) }
+    pub fn semicolon_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![;]) }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GenvarDecl {
+    pub(crate) syntax: SyntaxNode,
+}
+impl ast::AttrsOwner for GenvarDecl {}
+impl GenvarDecl {
+    pub fn genvar_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![genvar]) }
     pub fn names(&self) -> AstChildren<Name> { support::children(&self.syntax) }
     pub fn semicolon_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![;]) }
 }
@@ -482,6 +504,7 @@ pub struct Var {
 }
 impl Var {
     pub fn name(&self) -> Option<Name> { support::child(&self.syntax) }
+    pub fn range(&self) -> Option<Range> { support::child(&self.syntax) }
     pub fn eq_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![=]) }
     pub fn default(&self) -> Option<Expr> { support::child(&self.syntax) }
 }
@@ -491,6 +514,7 @@ pub struct Param {
 }
 impl Param {
     pub fn name(&self) -> Option<Name> { support::child(&self.syntax) }
+    pub fn range(&self) -> Option<Range> { support::child(&self.syntax) }
     pub fn eq_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![=]) }
     pub fn default(&self) -> Option<Expr> { support::child(&self.syntax) }
     pub fn constraints(&self) -> AstChildren<Constraint> { support::children(&self.syntax) }
@@ -536,6 +560,88 @@ impl FunctionArg {
     pub fn semicolon_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![;]) }
 }
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModuleInstDecl {
+    pub(crate) syntax: SyntaxNode,
+}
+impl ast::AttrsOwner for ModuleInstDecl {}
+impl ModuleInstDecl {
+    pub fn module(&self) -> Option<NameRef> { support::child(&self.syntax) }
+    pub fn param_overrides(&self) -> Option<ParamOverrides> { support::child(&self.syntax) }
+    pub fn module_insts(&self) -> AstChildren<ModuleInst> { support::children(&self.syntax) }
+    pub fn semicolon_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![;]) }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParamOverrides {
+    pub(crate) syntax: SyntaxNode,
+}
+impl ParamOverrides {
+    pub fn pound_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![#]) }
+    pub fn l_paren_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T!['(']) }
+    pub fn overrides(&self) -> AstChildren<ParamOverride> { support::children(&self.syntax) }
+    pub fn r_paren_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![')']) }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModuleInst {
+    pub(crate) syntax: SyntaxNode,
+}
+impl ModuleInst {
+    pub fn name(&self) -> Option<Name> { support::child(&self.syntax) }
+    pub fn l_paren_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T!['(']) }
+    pub fn connections(&self) -> AstChildren<PortConnection> { support::children(&self.syntax) }
+    pub fn r_paren_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![')']) }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParamOverride {
+    pub(crate) syntax: SyntaxNode,
+}
+impl ParamOverride {
+    pub fn dot_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![.]) }
+    pub fn name_ref(&self) -> Option<NameRef> { support::child(&self.syntax) }
+    pub fn l_paren_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T!['(']) }
+    pub fn expr(&self) -> Option<Expr> { support::child(&self.syntax) }
+    pub fn r_paren_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![')']) }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PortConnection {
+    pub(crate) syntax: SyntaxNode,
+}
+impl PortConnection {
+    pub fn dot_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![.]) }
+    pub fn name_ref(&self) -> Option<NameRef> { support::child(&self.syntax) }
+    pub fn l_paren_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T!['(']) }
+    pub fn expr(&self) -> Option<Expr> { support::child(&self.syntax) }
+    pub fn r_paren_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![')']) }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParamSetDecl {
+    pub(crate) syntax: SyntaxNode,
+}
+impl ast::AttrsOwner for ParamSetDecl {}
+impl ParamSetDecl {
+    pub fn paramset_token(&self) -> Option<SyntaxToken> {
+        support::token(&self.syntax, T![paramset])
+    }
+    pub fn name(&self) -> Option<Name> { support::child(&self.syntax) }
+    pub fn module(&self) -> Option<NameRef> { support::child(&self.syntax) }
+    pub fn semicolon_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![;]) }
+    pub fn param_set_items(&self) -> AstChildren<ParamSetItem> { support::children(&self.syntax) }
+    pub fn endparamset_token(&self) -> Option<SyntaxToken> {
+        support::token(&self.syntax, T![endparamset])
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParamSetAssign {
+    pub(crate) syntax: SyntaxNode,
+}
+impl ast::AttrsOwner for ParamSetAssign {}
+impl ParamSetAssign {
+    pub fn dot_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![.]) }
+    pub fn name_ref(&self) -> Option<NameRef> { support::child(&self.syntax) }
+    pub fn eq_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![=]) }
+    pub fn expr(&self) -> Option<Expr> { support::child(&self.syntax) }
+    pub fn semicolon_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![;]) }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Expr {
     PrefixExpr(PrefixExpr),
     BinExpr(BinExpr),
@@ -545,6 +651,7 @@ pub enum Expr {
     SelectExpr(SelectExpr),
     PathExpr(PathExpr),
     PortFlow(PortFlow),
+    IndexExpr(IndexExpr),
     Literal(Literal),
 }
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -577,6 +684,7 @@ pub enum Item {
     DisciplineDecl(DisciplineDecl),
     NatureDecl(NatureDecl),
     ModuleDecl(ModuleDecl),
+    ParamSetDecl(ParamSetDecl),
 }
 impl ast::AttrsOwner for Item {}
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -589,6 +697,13 @@ pub enum ModuleItem {
     VarDecl(VarDecl),
     ParamDecl(ParamDecl),
     AliasParam(AliasParam),
+    ModuleInstDecl(ModuleInstDecl),
+    GenvarDecl(GenvarDecl),
+}
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParamSetItem {
+    ParamDecl(ParamDecl),
+    ParamSetAssign(ParamSetAssign),
 }
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ModulePortKind {
@@ -927,6 +1042,17 @@ impl AstNode for PortFlow {
     }
     fn syntax(&self) -> &SyntaxNode { &self.syntax }
 }
+impl AstNode for IndexExpr {
+    fn can_cast(kind: SyntaxKind) -> bool { kind == INDEX_EXPR }
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        if Self::can_cast(syntax.kind()) {
+            Some(Self { syntax })
+        } else {
+            None
+        }
+    }
+    fn syntax(&self) -> &SyntaxNode { &self.syntax }
+}
 impl AstNode for ArgList {
     fn can_cast(kind: SyntaxKind) -> bool { kind == ARG_LIST }
     fn cast(syntax: SyntaxNode) -> Option<Self> {
@@ -1048,6 +1174,17 @@ impl AstNode for NetDecl {
     }
     fn syntax(&self) -> &SyntaxNode { &self.syntax }
 }
+impl AstNode for GenvarDecl {
+    fn can_cast(kind: SyntaxKind) -> bool { kind == GENVAR_DECL }
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        if Self::can_cast(syntax.kind()) {
+            Some(Self { syntax })
+        } else {
+            None
+        }
+    }
+    fn syntax(&self) -> &SyntaxNode { &self.syntax }
+}
 impl AstNode for AnalogBehaviour {
     fn can_cast(kind: SyntaxKind) -> bool { kind == ANALOG_BEHAVIOUR }
     fn cast(syntax: SyntaxNode) -> Option<Self> {
@@ -1180,6 +1317,83 @@ impl AstNode for FunctionArg {
     }
     fn syntax(&self) -> &SyntaxNode { &self.syntax }
 }
+impl AstNode for ModuleInstDecl {
+    fn can_cast(kind: SyntaxKind) -> bool { kind == MODULE_INST_DECL }
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        if Self::can_cast(syntax.kind()) {
+            Some(Self { syntax })
+        } else {
+            None
+        }
+    }
+    fn syntax(&self) -> &SyntaxNode { &self.syntax }
+}
+impl AstNode for ParamSetDecl {
+    fn can_cast(kind: SyntaxKind) -> bool { kind == PARAM_SET_DECL }
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        if Self::can_cast(syntax.kind()) {
+            Some(Self { syntax })
+        } else {
+            None
+        }
+    }
+    fn syntax(&self) -> &SyntaxNode { &self.syntax }
+}
+impl AstNode for ParamSetAssign {
+    fn can_cast(kind: SyntaxKind) -> bool { kind == PARAM_SET_ASSIGN }
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        if Self::can_cast(syntax.kind()) {
+            Some(Self { syntax })
+        } else {
+            None
+        }
+    }
+    fn syntax(&self) -> &SyntaxNode { &self.syntax }
+}
+impl AstNode for ParamOverrides {
+    fn can_cast(kind: SyntaxKind) -> bool { kind == PARAM_OVERRIDES }
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        if Self::can_cast(syntax.kind()) {
+            Some(Self { syntax })
+        } else {
+            None
+        }
+    }
+    fn syntax(&self) -> &SyntaxNode { &self.syntax }
+}
+impl AstNode for ModuleInst {
+    fn can_cast(kind: SyntaxKind) -> bool { kind == MODULE_INST }
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        if Self::can_cast(syntax.kind()) {
+            Some(Self { syntax })
+        } else {
+            None
+        }
+    }
+    fn syntax(&self) -> &SyntaxNode { &self.syntax }
+}
+impl AstNode for ParamOverride {
+    fn can_cast(kind: SyntaxKind) -> bool { kind == PARAM_OVERRIDE }
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        if Self::can_cast(syntax.kind()) {
+            Some(Self { syntax })
+        } else {
+            None
+        }
+    }
+    fn syntax(&self) -> &SyntaxNode { &self.syntax }
+}
+impl AstNode for PortConnection {
+    fn can_cast(kind: SyntaxKind) -> bool { kind == PORT_CONNECTION }
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        if Self::can_cast(syntax.kind()) {
+            Some(Self { syntax })
+        } else {
+            None
+        }
+    }
+    fn syntax(&self) -> &SyntaxNode { &self.syntax }
+}
 impl From<PrefixExpr> for Expr {
     fn from(node: PrefixExpr) -> Expr { Expr::PrefixExpr(node) }
 }
@@ -1204,6 +1418,9 @@ impl From<PathExpr> for Expr {
 impl From<PortFlow> for Expr {
     fn from(node: PortFlow) -> Expr { Expr::PortFlow(node) }
 }
+impl From<IndexExpr> for Expr {
+    fn from(node: IndexExpr) -> Expr { Expr::IndexExpr(node) }
+}
 impl From<Literal> for Expr {
     fn from(node: Literal) -> Expr { Expr::Literal(node) }
 }
@@ -1211,7 +1428,7 @@ impl AstNode for Expr {
     fn can_cast(kind: SyntaxKind) -> bool {
         match kind {
             PREFIX_EXPR | BIN_EXPR | PAREN_EXPR | ARRAY_EXPR | CALL | SELECT_EXPR | PATH_EXPR
-            | PORT_FLOW => true,
+            | PORT_FLOW | INDEX_EXPR => true,
             _ => Literal::can_cast(kind),
         }
     }
@@ -1225,6 +1442,7 @@ impl AstNode for Expr {
             SELECT_EXPR => Expr::SelectExpr(SelectExpr { syntax }),
             PATH_EXPR => Expr::PathExpr(PathExpr { syntax }),
             PORT_FLOW => Expr::PortFlow(PortFlow { syntax }),
+            INDEX_EXPR => Expr::IndexExpr(IndexExpr { syntax }),
             _ => Expr::Literal(Literal::cast(syntax)?),
         };
         Some(res)
@@ -1239,6 +1457,7 @@ impl AstNode for Expr {
             Expr::SelectExpr(it) => &it.syntax,
             Expr::PathExpr(it) => &it.syntax,
             Expr::PortFlow(it) => &it.syntax,
+            Expr::IndexExpr(it) => &it.syntax,
             Expr::Literal(it) => it.syntax(),
         }
     }
@@ -1376,10 +1595,13 @@ impl From<NatureDecl> for Item {
 impl From<ModuleDecl> for Item {
     fn from(node: ModuleDecl) -> Item { Item::ModuleDecl(node) }
 }
+impl From<ParamSetDecl> for Item {
+    fn from(node: ParamSetDecl) -> Item { Item::ParamSetDecl(node) }
+}
 impl AstNode for Item {
     fn can_cast(kind: SyntaxKind) -> bool {
         match kind {
-            DISCIPLINE_DECL | NATURE_DECL | MODULE_DECL => true,
+            DISCIPLINE_DECL | NATURE_DECL | MODULE_DECL | PARAM_SET_DECL => true,
             _ => false,
         }
     }
@@ -1388,6 +1610,7 @@ impl AstNode for Item {
             DISCIPLINE_DECL => Item::DisciplineDecl(DisciplineDecl { syntax }),
             NATURE_DECL => Item::NatureDecl(NatureDecl { syntax }),
             MODULE_DECL => Item::ModuleDecl(ModuleDecl { syntax }),
+            PARAM_SET_DECL => Item::ParamSetDecl(ParamSetDecl { syntax }),
             _ => return None,
         };
         Some(res)
@@ -1397,6 +1620,35 @@ impl AstNode for Item {
             Item::DisciplineDecl(it) => &it.syntax,
             Item::NatureDecl(it) => &it.syntax,
             Item::ModuleDecl(it) => &it.syntax,
+            Item::ParamSetDecl(it) => &it.syntax,
+        }
+    }
+}
+impl From<ParamDecl> for ParamSetItem {
+    fn from(node: ParamDecl) -> ParamSetItem { ParamSetItem::ParamDecl(node) }
+}
+impl From<ParamSetAssign> for ParamSetItem {
+    fn from(node: ParamSetAssign) -> ParamSetItem { ParamSetItem::ParamSetAssign(node) }
+}
+impl AstNode for ParamSetItem {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        match kind {
+            PARAM_DECL | PARAM_SET_ASSIGN => true,
+            _ => false,
+        }
+    }
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        let res = match syntax.kind() {
+            PARAM_DECL => ParamSetItem::ParamDecl(ParamDecl { syntax }),
+            PARAM_SET_ASSIGN => ParamSetItem::ParamSetAssign(ParamSetAssign { syntax }),
+            _ => return None,
+        };
+        Some(res)
+    }
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            ParamSetItem::ParamDecl(it) => &it.syntax,
+            ParamSetItem::ParamSetAssign(it) => &it.syntax,
         }
     }
 }
@@ -1424,11 +1676,17 @@ impl From<ParamDecl> for ModuleItem {
 impl From<AliasParam> for ModuleItem {
     fn from(node: AliasParam) -> ModuleItem { ModuleItem::AliasParam(node) }
 }
+impl From<ModuleInstDecl> for ModuleItem {
+    fn from(node: ModuleInstDecl) -> ModuleItem { ModuleItem::ModuleInstDecl(node) }
+}
+impl From<GenvarDecl> for ModuleItem {
+    fn from(node: GenvarDecl) -> ModuleItem { ModuleItem::GenvarDecl(node) }
+}
 impl AstNode for ModuleItem {
     fn can_cast(kind: SyntaxKind) -> bool {
         match kind {
             BODY_PORT_DECL | NET_DECL | ANALOG_BEHAVIOUR | FUNCTION | BRANCH_DECL | VAR_DECL
-            | PARAM_DECL | ALIAS_PARAM => true,
+            | PARAM_DECL | ALIAS_PARAM | MODULE_INST_DECL | GENVAR_DECL => true,
             _ => false,
         }
     }
@@ -1442,6 +1700,8 @@ impl AstNode for ModuleItem {
             VAR_DECL => ModuleItem::VarDecl(VarDecl { syntax }),
             PARAM_DECL => ModuleItem::ParamDecl(ParamDecl { syntax }),
             ALIAS_PARAM => ModuleItem::AliasParam(AliasParam { syntax }),
+            MODULE_INST_DECL => ModuleItem::ModuleInstDecl(ModuleInstDecl { syntax }),
+            GENVAR_DECL => ModuleItem::GenvarDecl(GenvarDecl { syntax }),
             _ => return None,
         };
         Some(res)
@@ -1456,6 +1716,8 @@ impl AstNode for ModuleItem {
             ModuleItem::VarDecl(it) => &it.syntax,
             ModuleItem::ParamDecl(it) => &it.syntax,
             ModuleItem::AliasParam(it) => &it.syntax,
+            ModuleItem::ModuleInstDecl(it) => &it.syntax,
+            ModuleItem::GenvarDecl(it) => &it.syntax,
         }
     }
 }
@@ -1577,6 +1839,11 @@ impl std::fmt::Display for Item {
         std::fmt::Display::fmt(self.syntax(), f)
     }
 }
+impl std::fmt::Display for ParamSetItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.syntax(), f)
+    }
+}
 impl std::fmt::Display for ModuleItem {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         std::fmt::Display::fmt(self.syntax(), f)
@@ -1742,6 +2009,11 @@ impl std::fmt::Display for PortFlow {
         std::fmt::Display::fmt(self.syntax(), f)
     }
 }
+impl std::fmt::Display for IndexExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.syntax(), f)
+    }
+}
 impl std::fmt::Display for ArgList {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         std::fmt::Display::fmt(self.syntax(), f)
@@ -1797,6 +2069,11 @@ impl std::fmt::Display for NetDecl {
         std::fmt::Display::fmt(self.syntax(), f)
     }
 }
+impl std::fmt::Display for GenvarDecl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.syntax(), f)
+    }
+}
 impl std::fmt::Display for AnalogBehaviour {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         std::fmt::Display::fmt(self.syntax(), f)
@@ -1857,3 +2134,38 @@ impl std::fmt::Display for FunctionArg {
         std::fmt::Display::fmt(self.syntax(), f)
     }
 }
+impl std::fmt::Display for ModuleInstDecl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.syntax(), f)
+    }
+}
+impl std::fmt::Display for ParamOverrides {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.syntax(), f)
+    }
+}
+impl std::fmt::Display for ModuleInst {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.syntax(), f)
+    }
+}
+impl std::fmt::Display for ParamOverride {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.syntax(), f)
+    }
+}
+impl std::fmt::Display for PortConnection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.syntax(), f)
+    }
+}
+impl std::fmt::Display for ParamSetDecl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.syntax(), f)
+    }
+}
+impl std::fmt::Display for ParamSetAssign {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.syntax(), f)
+    }
+}
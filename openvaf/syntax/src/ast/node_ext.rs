@@ -232,6 +232,14 @@ impl EventStmt {
     pub fn sim_phases(&self) -> AstChildTokens<StrLit> {
         support::child_token(self.syntax())
     }
+
+    /// The identifiers (`or`) that separate the monitored events of this event statement
+    pub fn event_separators(&self) -> impl Iterator<Item = SyntaxToken> {
+        self.syntax()
+            .children_with_tokens()
+            .filter_map(|e| e.into_token())
+            .filter(|token| token.kind() == IDENT)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
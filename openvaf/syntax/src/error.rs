@@ -102,6 +102,19 @@ pub enum SyntaxError {
         found: String,
         range: TextRange,
     },
+    IllegalEventSeparator {
+        found: String,
+        range: TextRange,
+    },
+    IllegalEvent {
+        range: TextRange,
+    },
+    EventArgCntMismatch {
+        name: String,
+        max: usize,
+        found: usize,
+        range: TextRange,
+    },
 
     RangeConstraintForNonNumericParameter {
         param: String,
@@ -135,6 +148,9 @@ impl_display! {
         MixedModuleHead{..} => "module header contains mix of port references and port declarations";
         IllegalBodyPorts{..} => "ports declared in module head and body";
         IllegalNetType{found,..} => "{} nets are currently not supported!",found;
+        IllegalEventSeparator{found,..} => "unexpected identifier '{}'; expected 'or'",found;
+        IllegalEvent{..} => "expected an event (cross, above or timer)";
+        EventArgCntMismatch{name,max,found,..} => "{} expects 1 to {} arguments; found {}",name,max,found;
         RangeConstraintForNonNumericParameter{param,..} => "non-numeric parameter '{}' has range bounds", param;
         PortNotDeclaredInModule{name,..} => "port '{name}' was not declared in the module head";
     }
@@ -48,7 +48,13 @@ impl<'a> SyntaxTreeBuilder<'a> {
         let span = self.tokens[self.token_pos].span;
         self.panic &= !matches!(
             kind,
-            T![;] | T![end] | T![endnature] | T![endmodule] | T![enddiscipline] | T![endfunction]
+            T![;]
+                | T![end]
+                | T![endnature]
+                | T![endmodule]
+                | T![enddiscipline]
+                | T![endfunction]
+                | T![endparamset]
         ) || self.err_depth != u32::MAX;
         self.do_token(kind, span);
     }
@@ -4,7 +4,7 @@ use tokens::SyntaxKind::NET_TYPE;
 
 use crate::ast::{
     self, support, ArgListOwner, AttrsOwner, BlockItem, ConstraintValue, Expr, FunctionItem,
-    LiteralKind, ModulePortKind, ModulePorts, Name, PathSegmentKind,
+    FunctionRef, LiteralKind, ModulePortKind, ModulePorts, Name, PathSegmentKind,
 };
 use crate::name::{kw, kw_comp};
 use crate::{match_ast, AstNode, AstPtr, SyntaxError, SyntaxNode, SyntaxNodePtr, T};
@@ -24,6 +24,7 @@ pub(crate) fn validate(root: &SyntaxNode, errors: &mut Vec<SyntaxError>) {
                 ast::Name(name) => validate_name(name,errors),
                 ast::ModuleDecl(module) => validate_module(module,errors),
                 ast::ParamDecl(param) => validate_param(param, errors),
+                ast::EventStmt(stmt) => validate_event_stmt(stmt, errors),
                 _ => validate_net_type_token(node,errors)
             }
         }
@@ -52,6 +53,56 @@ fn validate_param(param_decl: ast::ParamDecl, errors: &mut Vec<SyntaxError>) {
     }
 }
 
+fn validate_event_stmt(stmt: ast::EventStmt, errors: &mut Vec<SyntaxError>) {
+    for separator in stmt.event_separators() {
+        if separator.text() != kw::raw::or {
+            errors.push(SyntaxError::IllegalEventSeparator {
+                found: separator.text().to_owned(),
+                range: separator.text_range(),
+            })
+        }
+    }
+
+    for event in stmt.exprs() {
+        let call = match &event {
+            Expr::Call(call) => call,
+            _ => {
+                errors.push(SyntaxError::IllegalEvent { range: event.syntax().text_range() });
+                continue;
+            }
+        };
+        let name = match call.function_ref() {
+            Some(FunctionRef::Path(path)) if path.qualifier().is_none() => path.segment_token(),
+            _ => None,
+        };
+        let name = match name {
+            Some(name) => name,
+            None => {
+                errors.push(SyntaxError::IllegalEvent { range: event.syntax().text_range() });
+                continue;
+            }
+        };
+
+        let max = match name.text() {
+            kw::raw::cross => 4,
+            kw::raw::above | kw::raw::timer => 3,
+            _ => {
+                errors.push(SyntaxError::IllegalEvent { range: event.syntax().text_range() });
+                continue;
+            }
+        };
+        let found = call.arg_list().map_or(0, |args| args.args().count());
+        if found == 0 || found > max {
+            errors.push(SyntaxError::EventArgCntMismatch {
+                name: name.text().to_owned(),
+                max,
+                found,
+                range: event.syntax().text_range(),
+            })
+        }
+    }
+}
+
 fn validate_net_type_token(node: SyntaxNode, errors: &mut Vec<SyntaxError>) {
     if matches!(node.kind(), SyntaxKind::NET_DECL | SyntaxKind::PORT_DECL) {
         if let Some(token) = support::token(&node, NET_TYPE) {
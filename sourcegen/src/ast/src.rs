@@ -95,6 +95,9 @@ pub(crate) const KINDS_SRC: KindsSrc = KindsSrc {
         "initial",
         "final_step",
         "aliasparam",
+        "paramset",
+        "endparamset",
+        "genvar",
     ],
     literals: &["INT_NUMBER", "STD_REAL_NUMBER", "SI_REAL_NUMBER", "STR_LIT"],
     tokens: &["ERROR", "IDENT", "SYSFUN", "NET_TYPE", "WHITESPACE", "COMMENT"],
@@ -123,11 +126,20 @@ pub(crate) const KINDS_SRC: KindsSrc = KindsSrc {
         "FOR_STMT",
         "FUNCTION",
         "FUNCTION_ARG",
+        "GENVAR_DECL",
         "IF_STMT",
+        "INDEX_EXPR",
         "LITERAL",
         "MODULE_DECL",
         "MODULE_PORT",
         "MODULE_PORTS",
+        "MODULE_INST_DECL",
+        "MODULE_INST",
+        "PARAM_OVERRIDES",
+        "PARAM_OVERRIDE",
+        "PORT_CONNECTION",
+        "PARAM_SET_DECL",
+        "PARAM_SET_ASSIGN",
         "NAME",
         "NAME_REF",
         "SYS_FUN",
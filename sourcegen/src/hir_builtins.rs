@@ -25,34 +25,9 @@ const ANALOG_OPERATORS: [&str; 17] = [
     "transition",
 ];
 
-const UNSUPPORTED: [&str; 50] = [
-    "simprobe",
-    "analog_node_alias",
-    "analog_port_alias",
-    "test_plusargs",
-    "value_plusargs",
-    "zi_nd",
-    "zi_np",
-    "zi_zd",
-    "zi_zp",
-    "laplace_nd",
-    "laplace_np",
-    "laplace_zd",
-    "laplace_zp",
-    "last_crossing",
-    "slew",
-    "transition",
-    "fclose",
-    "fopen",
-    "fdisplay",
-    "fwrite",
-    "fstrobe",
-    "fmonitor",
+const UNSUPPORTED: [&str; 9] = [
     "fgets",
     "fscanf",
-    "swrite",
-    "sformat",
-    "sscanf",
     "rewind",
     "fseek",
     "ftell",
@@ -60,22 +35,6 @@ const UNSUPPORTED: [&str; 50] = [
     "ferror",
     "feof",
     "fdebug",
-    "dist_chi_square",
-    "dist_exponential",
-    "dist_poisson",
-    "dist_uniform",
-    "dist_erlang",
-    "dist_normal",
-    "dist_t",
-    "random",
-    "arandom",
-    "rdist_chi_square",
-    "rdist_exponential",
-    "rdist_poisson",
-    "rdist_uniform",
-    "rdist_erlang",
-    "rdist_normal",
-    "rdist_t",
 ];
 
 const ANALOG_OPERATORS_SYSFUN: [&str; 1] = ["$limit"];
@@ -114,7 +73,7 @@ const BUILTINS: [&str; 26] = [
 
 const PARAM_SYSFUNS: [&str; 6] = ["mfactor", "xposition", "yposition", "angle", "hflip", "vflip"];
 
-const SYSFUNS: [&str; 81] = [
+const SYSFUNS: [&str; 82] = [
     "$display",
     "$strobe",
     "$write",
@@ -193,7 +152,7 @@ const SYSFUNS: [&str; 81] = [
     "$port_connected",
     "$analog_node_alias",
     "$analog_port_alias",
-    // "$table_model",
+    "$table_model",
     "$test$plusargs",
     "$value$plusargs",
     "$bound_step",
@@ -58,18 +58,78 @@ pub fn stub_callbacks<'ll>(
                 CallBackKind::SimParam => sim_param_stub(cx),
                 CallBackKind::SimParamOpt => sim_param_opt_stub(cx),
                 CallBackKind::SimParamStr => sim_param_str_stub(cx),
+                // verilogae has no other instances to probe
+                CallBackKind::SimProbe => CallbackFun::Prebuilt(
+                    cx.const_callback(&[cx.ty_ptr(), cx.ty_ptr()], cx.const_real(0.0)),
+                ),
+                CallBackKind::SimProbeOpt => CallbackFun::Prebuilt(
+                    cx.const_return(&[cx.ty_ptr(), cx.ty_ptr(), cx.ty_double()], 2),
+                ),
+                // verilogae has no plusargs
+                CallBackKind::TestPlusArgs => {
+                    CallbackFun::Prebuilt(cx.const_callback(&[cx.ty_ptr()], cx.const_int(0)))
+                }
+                CallBackKind::PlusArg => CallbackFun::Prebuilt(
+                    cx.const_callback(&[cx.ty_ptr()], cx.const_str_uninterned("")),
+                ),
                 CallBackKind::Derivative(_)
                 | CallBackKind::NodeDerivative(_)
                 | CallBackKind::TimeDerivative
                 | CallBackKind::FlickerNoise { .. }
                 | CallBackKind::WhiteNoise { .. }
-                | CallBackKind::NoiseTable(_) => {
+                | CallBackKind::NoiseTable(_)
+                | CallBackKind::AbsDelay(_) => {
                     CallbackFun::Prebuilt(cx.const_callback(&[cx.ty_double()], cx.const_real(0.0)))
                 }
+                CallBackKind::AcStim { .. } => CallbackFun::Prebuilt(
+                    cx.const_callback(&[cx.ty_double(), cx.ty_double()], cx.const_real(0.0)),
+                ),
+                // without a history the delay line is transparent
+                CallBackKind::AbsDelayWeight(_) => {
+                    CallbackFun::Prebuilt(cx.const_callback(&[cx.ty_double()], cx.const_real(1.0)))
+                }
+                // without seeds from a simulator all random values are zero
+                CallBackKind::Random { .. } => CallbackFun::Prebuilt(cx.const_callback(
+                    &[cx.ty_int(), cx.ty_double(), cx.ty_double()],
+                    cx.const_real(0.0),
+                )),
+                CallBackKind::RandomNextSeed(_) => CallbackFun::Prebuilt(cx.const_callback(
+                    &[cx.ty_int(), cx.ty_double(), cx.ty_double()],
+                    cx.const_int(0),
+                )),
+                CallBackKind::RandomStream { .. } => CallbackFun::Prebuilt(
+                    cx.const_callback(&[cx.ty_double(), cx.ty_double()], cx.const_real(0.0)),
+                ),
+                // verilogae does not support string formatting and scanning: the format is
+                // returned unchanged and no outputs are assigned
+                CallBackKind::FormatString { arg_tys, .. } => {
+                    let mut args = vec![cx.ty_ptr()];
+                    args.extend(arg_tys.iter().map(|arg| lltype(&arg.ty, cx)));
+                    CallbackFun::Prebuilt(cx.const_return(&args, 0))
+                }
+                CallBackKind::ScanCount => CallbackFun::Prebuilt(
+                    cx.const_callback(&[cx.ty_ptr(), cx.ty_ptr()], cx.const_int(0)),
+                ),
+                CallBackKind::ScanValue { ty, .. } => CallbackFun::Prebuilt(
+                    cx.const_return(&[cx.ty_ptr(), cx.ty_ptr(), lltype(ty, cx)], 2),
+                ),
+                // verilogae does not support table models
+                CallBackKind::TableModel { table, .. } => CallbackFun::Prebuilt(cx.const_callback(
+                    &vec![cx.ty_double(); table.control.len()],
+                    cx.const_real(0.0),
+                )),
+                // verilogae does not support file output
+                CallBackKind::FileOpen => CallbackFun::Prebuilt(
+                    cx.const_callback(&[cx.ty_ptr(), cx.ty_ptr()], cx.const_int(0)),
+                ),
                 CallBackKind::Print { .. }
+                | CallBackKind::FilePrint { .. }
+                | CallBackKind::FileClose
                 | CallBackKind::ParamInfo(_, _)
                 | CallBackKind::BuiltinLimit { .. }
                 | CallBackKind::StoreLimit(_)
+                | CallBackKind::StoreTranState(_)
+                | CallBackKind::StorePersistentState(_)
                 | CallBackKind::LimDiscontinuity
                 | CallBackKind::CollapseHint(_, _)
                 | CallBackKind::SetRetFlag { .. } => return None,
@@ -363,9 +423,11 @@ impl CodegenCtx<'_, '_> {
 
                 let val = match kind {
                     ParamKind::Param(_)
+                    | ParamKind::ParamElement(..)
                     | ParamKind::Voltage { .. }
                     | ParamKind::Current(_)
-                    | ParamKind::HiddenState(_) => return BuilderVal::Undef,
+                    | ParamKind::HiddenState(_)
+                    | ParamKind::HiddenStateElement(..) => return BuilderVal::Undef,
                     ParamKind::Temperature => unsafe {
                         let temperature =
                             llvm_sys::core::LLVMGetParam(NonNull::from(llfun).as_ptr(), 8);
@@ -378,7 +440,9 @@ impl CodegenCtx<'_, '_> {
                     ParamKind::ImplicitUnknown(_)
                     | ParamKind::Abstime
                     | ParamKind::PrevState(_)
-                    | ParamKind::NewState(_) => codegen.builder.cx.const_real(0.0),
+                    | ParamKind::NewState(_)
+                    | ParamKind::PrevTranState(_)
+                    | ParamKind::PrevPersistentState(_) => codegen.builder.cx.const_real(0.0),
                     ParamKind::EnableIntegration | ParamKind::EnableLim => {
                         codegen.builder.cx.const_bool(false)
                     }
@@ -724,7 +788,9 @@ impl CodegenCtx<'_, '_> {
                     ParamKind::ImplicitUnknown(_)
                     | ParamKind::Abstime
                     | ParamKind::PrevState(_)
-                    | ParamKind::NewState(_) => builder.cx.const_real(0.0),
+                    | ParamKind::NewState(_)
+                    | ParamKind::PrevTranState(_)
+                    | ParamKind::PrevPersistentState(_) => builder.cx.const_real(0.0),
                     ParamKind::EnableIntegration | ParamKind::EnableLim => {
                         builder.cx.const_bool(false)
                     }